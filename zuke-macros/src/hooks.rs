@@ -7,6 +7,7 @@ use pest_derive::Parser;
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
 
 /// Parses a tag expression
 #[derive(Parser)]
@@ -81,12 +82,79 @@ pub enum Kind {
     Any,
 }
 
-/// get tag expr from attribute
-fn get_tag_expr(input: TokenStream) -> syn::Result<Option<syn::LitStr>> {
-    if syn::parse::<syn::parse::Nothing>(input.clone()).is_ok() {
-        Ok(None)
-    } else {
-        syn::parse::<syn::LitStr>(input).map(Some)
+/// Arguments to a hook attribute: an optional tag expression, plus optional `order = N`, and
+/// optional name filters such as `feature = "checkout.*"`
+struct HookArgs {
+    expr: Option<syn::LitStr>,
+    order: i32,
+    names: Vec<TokenStream2>,
+}
+
+/// Build a `NameFilter` for `field = "pattern"`. The pattern must match the whole name.
+fn name_filter(field: &syn::Ident, pattern: &syn::LitStr) -> syn::Result<TokenStream2> {
+    let variant = match field.to_string().as_str() {
+        "feature" => quote! { Feature },
+        "rule" => quote! { Rule },
+        "scenario" => quote! { Scenario },
+        "path" => quote! { Path },
+        _ => return Err(syn::Error::new(field.span(), "Unknown argument")),
+    };
+
+    let anchored = format!("^(?:{})$", pattern.value());
+    if let Err(e) = regex::Regex::new(&anchored) {
+        return Err(syn::Error::new(pattern.span(), e.to_string()));
+    }
+
+    Ok(quote! {
+        ::zuke::hooks::NameFilter {
+            field: ::zuke::hooks::NameField::#variant,
+            regex: ::zuke::reexport::regex::Regex::new(#anchored).unwrap(),
+        }
+    })
+}
+
+impl Parse for HookArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut expr = None;
+        let mut order = None;
+        let mut names = vec![];
+
+        while !input.is_empty() {
+            if input.peek(syn::LitStr) {
+                let s: syn::LitStr = input.parse()?;
+                if expr.is_some() {
+                    return Err(syn::Error::new(s.span(), "Redefinition of tag expression"));
+                }
+                expr = Some(s);
+            } else {
+                let ident: syn::Ident = input.parse()?;
+                input.parse::<syn::Token![=]>()?;
+                if ident == "order" {
+                    let negative = input.parse::<Option<syn::Token![-]>>()?.is_some();
+                    let value: syn::LitInt = input.parse()?;
+                    if order.is_some() {
+                        return Err(syn::Error::new(ident.span(), "Redefinition of order"));
+                    }
+                    let value: i32 = value.base10_parse()?;
+                    order = Some(if negative { -value } else { value });
+                } else if input.peek(syn::LitStr) {
+                    let pattern: syn::LitStr = input.parse()?;
+                    names.push(name_filter(&ident, &pattern)?);
+                } else {
+                    return Err(syn::Error::new(ident.span(), "Unknown argument"));
+                }
+            }
+
+            if !input.is_empty() {
+                input.parse::<syn::Token![,]>()?;
+            }
+        }
+
+        Ok(Self {
+            expr,
+            order: order.unwrap_or(0),
+            names,
+        })
     }
 }
 
@@ -97,13 +165,11 @@ pub fn register_before_after(
     before: bool,
     kind: Kind,
 ) -> TokenStream {
-    let expr = match get_tag_expr(args) {
-        Ok(s) => s,
-        Err(e) => return e.into_compile_error().into(),
-    };
+    let HookArgs { expr, order, names } = syn::parse_macro_input!(args as HookArgs);
 
     let func = syn::parse_macro_input!(input as syn::ItemFn);
     let func_name = &func.sig.ident;
+    let name = func_name.to_string();
     let func_call = quote! { #func_name(context) };
     let func_call = make_call(func_call, &func, false, true);
 
@@ -112,6 +178,8 @@ pub fn register_before_after(
         Some(s) => build_expr(s),
     };
 
+    let names = quote! { vec![#(#names),*] };
+
     let when = if before {
         quote! { ::zuke::hooks::BeforeAfter::Before }
     } else {
@@ -143,10 +211,13 @@ pub fn register_before_after(
             #(
                 inventory::submit! {
                     ::zuke::hooks::BeforeAfterHook {
+                        name: concat!(module_path!(), "::", #name),
                         when: #when,
                         kind: #kind,
                         func: |context| async move { #func_call }.boxed(),
                         expr: vec![#expr],
+                        order: #order,
+                        names: #names,
                     }
                 }
             )*
@@ -22,6 +22,28 @@ use step_args::*;
 ///     Ok(())
 /// }
 /// ```
+///
+/// Captures are converted with `str::parse`. `Option<T>` parameters are `None` when the capture
+/// group doesn't participate in the match, and `Vec<T>` parameters are split on a separator,
+/// which is "," unless given.
+///
+/// ```ignore
+/// #[given(regex, r"I have (?:(?P<count>\d+) )?widgets")]
+/// fn i_have_widgets(count: Option<u32>) {}
+///
+/// #[given("I have widgets named {names}", separator = " ")]
+/// fn i_have_named_widgets(names: Vec<String>) {}
+/// ```
+///
+/// Steps can be written in the team's language with `keyword = "..."`, which may be repeated. A
+/// pattern that starts with one of these is matched as if it didn't, since the feature file's
+/// language already decides which steps are "given" steps. `Context::run_step` also understands
+/// the aliases.
+///
+/// ```ignore
+/// #[given("Étant donné un compte de {amount} euros", keyword = "Étant donné", keyword = "Soit")]
+/// fn un_compte(amount: u32) {}
+/// ```
 #[proc_macro_attribute]
 pub fn given(args: TokenStream, input: TokenStream) -> TokenStream {
     let args = syn::parse_macro_input!(args as StepArgs);
@@ -29,7 +51,7 @@ pub fn given(args: TokenStream, input: TokenStream) -> TokenStream {
     implement_step(StepKeyword::Given, args, func)
 }
 
-/// Implement a "when" step
+/// Implement a "when" step. Takes the same arguments as [`macro@given`].
 #[proc_macro_attribute]
 pub fn when(args: TokenStream, input: TokenStream) -> TokenStream {
     let args = syn::parse_macro_input!(args as StepArgs);
@@ -37,7 +59,7 @@ pub fn when(args: TokenStream, input: TokenStream) -> TokenStream {
     implement_step(StepKeyword::When, args, func)
 }
 
-/// Implement a "then" step
+/// Implement a "then" step. Takes the same arguments as [`macro@given`].
 #[proc_macro_attribute]
 pub fn then(args: TokenStream, input: TokenStream) -> TokenStream {
     let args = syn::parse_macro_input!(args as StepArgs);
@@ -61,6 +83,42 @@ pub fn raw(args: TokenStream, input: TokenStream) -> TokenStream {
     implement_step(StepKeyword::Raw, args, func)
 }
 
+/// Implement steps as methods on a struct. Each method marked `#[given]`, `#[when]`, `#[then]`,
+/// `#[step]`, or `#[raw]` becomes a step.
+///
+/// The struct becomes a scenario-scoped fixture, created with `Default::default()` the first time
+/// one of its methods runs in a scenario. Methods taking `&self` or `&mut self` get that instance,
+/// so steps in the same scenario can share state through its fields. Such methods can't also
+/// take the context. Methods without `self` work like ordinary step functions.
+///
+/// # Examples
+///
+/// ```ignore
+/// #[derive(Default)]
+/// struct CheckoutSteps {
+///     items: Vec<String>,
+/// }
+///
+/// #[steps]
+/// impl CheckoutSteps {
+///     #[given("I add {item} to my cart")]
+///     async fn add_item(&mut self, item: String) {
+///         self.items.push(item);
+///     }
+///
+///     #[then("my cart has {count} items")]
+///     fn cart_has(&self, count: usize) -> anyhow::Result<()> {
+///         anyhow::ensure!(self.items.len() == count, "Cart has {} items", self.items.len());
+///         Ok(())
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn steps(_args: TokenStream, input: TokenStream) -> TokenStream {
+    let item = syn::parse_macro_input!(input as syn::ItemImpl);
+    implement_steps(item)
+}
+
 /// Register a reporter struct for command line use
 #[proc_macro_attribute]
 pub fn reporter(args: TokenStream, input: TokenStream) -> TokenStream {
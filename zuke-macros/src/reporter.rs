@@ -3,6 +3,8 @@ use quote::quote;
 
 pub fn register_reporter(name: &str, func: syn::ItemFn) -> TokenStream {
     let func_name = func.sig.ident.clone();
+    let output = format!("{}-output", name);
+    let output_help = format!("Output file for the {} reporter. Default is stdout.", name);
 
     (quote! {
         #func
@@ -11,7 +13,9 @@ pub fn register_reporter(name: &str, func: syn::ItemFn) -> TokenStream {
             use ::zuke::reexport::inventory;
             inventory::submit! {
                 ::zuke::reporter::ReporterEntry {
-                    name: #name.to_string(),
+                    name: #name,
+                    output: #output,
+                    output_help: #output_help,
                     func: #func_name,
                 }
             }
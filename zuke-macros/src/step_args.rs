@@ -27,9 +27,82 @@ pub struct StepArgs {
     pub pattern_span: Span,
     pub pattern: String,
     pub pattern_type: PatternType,
+    /// Separator for captures that are converted to `Vec<T>`
+    pub separator: String,
+    /// The step's keyword in other languages, from `keyword = "..."`
+    pub keywords: Vec<String>,
+}
+
+/// How a capture is converted into a function argument
+enum ArgKind {
+    /// Parsed with `str::parse`, or passed as-is if `is_ref`
+    Value { is_ref: bool },
+    /// `Option<T>`: `None` if the capture group didn't participate in the match
+    Option { is_ref: bool },
+    /// `Vec<T>`: the capture is split on the separator, and each item is trimmed
+    Vec { is_ref: bool },
+}
+
+impl ArgKind {
+    fn new(ty: &syn::Type) -> Self {
+        if let Some(inner) = generic_arg(ty, "Option") {
+            ArgKind::Option {
+                is_ref: is_ref(inner),
+            }
+        } else if let Some(inner) = generic_arg(ty, "Vec") {
+            ArgKind::Vec {
+                is_ref: is_ref(inner),
+            }
+        } else {
+            ArgKind::Value { is_ref: is_ref(ty) }
+        }
+    }
+}
+
+fn is_ref(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Reference(_))
+}
+
+/// If `ty` is `name<T>`, return `T`. Matches by name only, so `Option` and `std::option::Option`
+/// both work.
+fn generic_arg<'a>(ty: &'a syn::Type, name: &str) -> Option<&'a syn::Type> {
+    let path = match ty {
+        syn::Type::Path(p) if p.qself.is_none() => &p.path,
+        _ => return None,
+    };
+
+    let segment = path.segments.last()?;
+    if segment.ident != name {
+        return None;
+    }
+
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+            match args.args.first()? {
+                syn::GenericArgument::Type(t) => Some(t),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
 }
 
 impl StepArgs {
+    /// Remove a keyword alias from the start of the pattern, since the keyword is matched
+    /// separately. The longest alias wins, so "Etant donné que" is removed whole.
+    fn strip_keyword(&mut self) {
+        let mut aliases: Vec<&String> = self.keywords.iter().collect();
+        aliases.sort_by_key(|a| std::cmp::Reverse(a.len()));
+        for alias in aliases {
+            if let Some(rest) = self.pattern.strip_prefix(alias.as_str()) {
+                if alias.ends_with('\'') || rest.starts_with(char::is_whitespace) {
+                    self.pattern = rest.trim_start().to_string();
+                    return;
+                }
+            }
+        }
+    }
+
     fn expand_pattern(&mut self) -> Result<()> {
         if self.pattern_type == PatternType::Regex {
             return Ok(());
@@ -126,6 +199,8 @@ impl Parse for StepArgs {
         let mut pattern_span = None;
         let mut pattern = None;
         let mut pattern_type = PatternType::Expression;
+        let mut separator = String::from(",");
+        let mut keywords = vec![];
         let args = Punctuated::<syn::NestedMeta, syn::Token![,]>::parse_terminated(input)?;
 
         for arg in args {
@@ -147,6 +222,24 @@ impl Parse for StepArgs {
                         return Err(ParseError::new(p.span(), "Unknown flag"));
                     }
                 }
+                syn::NestedMeta::Meta(syn::Meta::NameValue(nv))
+                    if nv.path.is_ident("separator") =>
+                {
+                    // separator = "...": how to split captures for Vec<T> arguments
+                    match nv.lit {
+                        syn::Lit::Str(s) if !s.value().is_empty() => separator = s.value(),
+                        lit => return Err(ParseError::new(lit.span(), "Expected a separator")),
+                    }
+                }
+                syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) if nv.path.is_ident("keyword") => {
+                    // keyword = "...": the keyword in the team's language. May be repeated.
+                    match nv.lit {
+                        syn::Lit::Str(s) if !s.value().trim().is_empty() => {
+                            keywords.push(s.value().trim().to_string())
+                        }
+                        lit => return Err(ParseError::new(lit.span(), "Expected a keyword")),
+                    }
+                }
                 _ => return Err(ParseError::new(arg.span(), "Unexpected")),
             }
         }
@@ -162,13 +255,23 @@ impl Parse for StepArgs {
             pattern,
             pattern_type,
             pattern_span,
+            separator,
+            keywords,
         })
     }
 }
 
-pub fn generate_call(re: &Regex, func: &syn::ItemFn) -> proc_macro2::TokenStream {
+/// Generate the body of `StepImplementation::execute`. `callee` is what gets called with the
+/// arguments: the function's name, or a path to a method.
+pub fn generate_call(
+    re: &Regex,
+    func: &syn::ItemFn,
+    callee: proc_macro2::TokenStream,
+    separator: &str,
+) -> proc_macro2::TokenStream {
     let mut capture_names: HashSet<&str> = re.capture_names().flatten().collect();
     let func_name = &func.sig.ident;
+    let has_receiver = func.sig.receiver().is_some();
     // Find the arguments
     let mut func_args = vec![];
     for arg in func.sig.inputs.iter() {
@@ -192,8 +295,7 @@ pub fn generate_call(re: &Regex, func: &syn::ItemFn) -> proc_macro2::TokenStream
 
                 match &*ty.pat {
                     syn::Pat::Ident(p) => {
-                        let is_ref = matches!(&*ty.ty, syn::Type::Reference(_));
-                        func_args.push((p.ident.clone(), is_ref));
+                        func_args.push((p.ident.clone(), ArgKind::new(&ty.ty)));
                     }
                     _ => {
                         return quote_spanned! {arg.span()=>
@@ -207,16 +309,53 @@ pub fn generate_call(re: &Regex, func: &syn::ItemFn) -> proc_macro2::TokenStream
 
     // place the function call parameters
     let mut func_inputs = quote! {};
-    for (ident, is_ref) in func_args {
+    for (ident, kind) in func_args {
         let name = ident.to_string();
         if capture_names.take(name.as_str()).is_some() {
-            let parse = if is_ref {
-                quote! {}
-            } else {
-                quote! { .parse()? }
+            let input = match kind {
+                ArgKind::Value { is_ref: true } => {
+                    quote! { captures.name(#name).unwrap().as_str() }
+                }
+                ArgKind::Value { is_ref: false } => {
+                    quote! { captures.name(#name).unwrap().as_str().parse()? }
+                }
+                ArgKind::Option { is_ref: true } => {
+                    quote! { captures.name(#name).map(|m| m.as_str()) }
+                }
+                ArgKind::Option { is_ref: false } => quote! {
+                    match captures.name(#name) {
+                        ::std::option::Option::Some(m) => {
+                            ::std::option::Option::Some(m.as_str().parse()?)
+                        }
+                        ::std::option::Option::None => ::std::option::Option::None,
+                    }
+                },
+                ArgKind::Vec { is_ref } => {
+                    let parse = if is_ref {
+                        quote! { ::std::result::Result::<_, ::std::convert::Infallible>::Ok(s) }
+                    } else {
+                        quote! { s.parse() }
+                    };
+                    quote! {
+                        captures
+                            .name(#name)
+                            .map(|m| m.as_str())
+                            .unwrap_or("")
+                            .split(#separator)
+                            .map(str::trim)
+                            .filter(|s| !s.is_empty())
+                            .map(|s| #parse)
+                            .collect::<::std::result::Result<::std::vec::Vec<_>, _>>()?
+                    }
+                }
             };
 
-            func_inputs.extend(quote! { captures.name(#name).unwrap().as_str()#parse, });
+            func_inputs.extend(quote! { #input, });
+        } else if (name == "context" || name == "_context") && has_receiver {
+            // The fixture is borrowed from the context for the duration of the call
+            func_inputs.extend(quote_spanned! {ident.span()=>
+                compile_error!("Step methods that take `self` can't also take the context"),
+            });
         } else if name == "context" || name == "_context" {
             func_inputs.extend(quote! { &mut context, });
         } else {
@@ -227,7 +366,7 @@ pub fn generate_call(re: &Regex, func: &syn::ItemFn) -> proc_macro2::TokenStream
     }
 
     let mut func_call = quote! {
-        #func_name(#func_inputs)
+        #callee(#func_inputs)
     };
 
     // Anything left is a missing parameter. Append to func call so that the user gets them all
@@ -241,7 +380,29 @@ pub fn generate_call(re: &Regex, func: &syn::ItemFn) -> proc_macro2::TokenStream
     make_call(func_call, func, true, true)
 }
 
-pub fn implement_step(keyword: StepKeyword, mut args: StepArgs, func: syn::ItemFn) -> TokenStream {
+pub fn implement_step(keyword: StepKeyword, args: StepArgs, func: syn::ItemFn) -> TokenStream {
+    let func_name = &func.sig.ident;
+    let registration = match register_step(keyword, args, &func, quote! {}, quote! { #func_name }) {
+        Ok(r) => r,
+        Err(e) => return e.into(),
+    };
+
+    (quote! {
+        #func
+        #registration
+    })
+    .into()
+}
+
+/// Submit a step implementation to the inventory. `prelude` runs at the start of `execute`, and
+/// `callee` is called with the step's arguments. Returns a compile error on failure.
+fn register_step(
+    keyword: StepKeyword,
+    mut args: StepArgs,
+    func: &syn::ItemFn,
+    prelude: proc_macro2::TokenStream,
+    callee: proc_macro2::TokenStream,
+) -> std::result::Result<proc_macro2::TokenStream, proc_macro2::TokenStream> {
     // always normalized to English, capitalized
     let prefix = match keyword {
         StepKeyword::Given => "Given ",
@@ -251,30 +412,49 @@ pub fn implement_step(keyword: StepKeyword, mut args: StepArgs, func: syn::ItemF
         StepKeyword::Any => "(?:Given|When|Then) ",
     };
 
+    let step_type = match keyword {
+        StepKeyword::Given => {
+            quote! { ::std::option::Option::Some(::zuke::reexport::gherkin_rust::StepType::Given) }
+        }
+        StepKeyword::When => {
+            quote! { ::std::option::Option::Some(::zuke::reexport::gherkin_rust::StepType::When) }
+        }
+        StepKeyword::Then => {
+            quote! { ::std::option::Option::Some(::zuke::reexport::gherkin_rust::StepType::Then) }
+        }
+        StepKeyword::Raw | StepKeyword::Any => quote! { ::std::option::Option::None },
+    };
+
+    if !args.keywords.is_empty() {
+        match keyword {
+            StepKeyword::Given | StepKeyword::When | StepKeyword::Then => args.strip_keyword(),
+            StepKeyword::Any | StepKeyword::Raw => {
+                return Err(quote_spanned! {args.pattern_span=>
+                    compile_error!("Keyword aliases need #[given], #[when], or #[then]");
+                });
+            }
+        }
+    }
+    let aliases = args.keywords.clone();
+
     if let Err(e) = args.expand_pattern() {
-        return e.to_compile_error().into();
+        return Err(e.to_compile_error());
     }
 
     let final_pattern = format!("^(?i){}{}$", prefix, args.pattern);
     let re = match Regex::new(&final_pattern) {
         Ok(r) => r,
         Err(_) => {
-            return quote_spanned! {args.pattern_span=>
+            return Err(quote_spanned! {args.pattern_span=>
                 compile_error!("Pattern resulted in invalid regular expression");
-            }
-            .into();
+            });
         }
     };
 
     let pattern = re.as_str();
-    // Line and file name are available in nightly, so leave as an unimplemented feature for now.
-    let line: i32 = -1;
-    let filename = "<unavailable>";
-    let run_step = generate_call(&re, &func);
-
-    (quote! {
-        #func
+    let run_step = generate_call(&re, func, callee, &args.separator);
 
+    Ok(quote! {
         const _: () = {
             use ::zuke::reexport::inventory;
             inventory::submit! {
@@ -294,11 +474,22 @@ pub fn implement_step(keyword: StepKeyword, mut args: StepArgs, func: syn::ItemF
                         &self.location
                     }
 
+                    fn keyword(&self) -> ::std::option::Option<
+                        ::zuke::reexport::gherkin_rust::StepType
+                    > {
+                        #step_type
+                    }
+
+                    fn keyword_aliases(&self) -> &'static [&'static str] {
+                        &[#(#aliases),*]
+                    }
+
                     async fn execute(
                         &self,
                         mut context: &mut ::zuke::Context,
                         captures: &::zuke::reexport::regex::Captures,
                         ) -> ::anyhow::Result<()> {
+                        #prelude
                         #run_step
                     }
                 }
@@ -306,15 +497,123 @@ pub fn implement_step(keyword: StepKeyword, mut args: StepArgs, func: syn::ItemF
                 let step = ::std::boxed::Box::new(StepImpl {
                     regex: ::zuke::reexport::regex::Regex::new(#pattern).unwrap(),
                     location: ::zuke::Location {
-                        path: ::std::path::PathBuf::from(#filename),
-                        line: #line,
+                        path: ::std::path::PathBuf::from(::std::file!()),
+                        line: ::std::line!() as i32,
+                        module: ::std::module_path!(),
                     },
                 });
 
                 ::std::boxed::Box::leak(step) as &'static dyn ::zuke::StepImplementation
             }
         };
+    })
+}
+
+/// Which step macro an attribute is, if any
+fn step_keyword(attr: &syn::Attribute) -> Option<StepKeyword> {
+    let ident = &attr.path.segments.last()?.ident;
+    match ident.to_string().as_str() {
+        "given" => Some(StepKeyword::Given),
+        "when" => Some(StepKeyword::When),
+        "then" => Some(StepKeyword::Then),
+        "step" => Some(StepKeyword::Any),
+        "raw" => Some(StepKeyword::Raw),
+        _ => None,
+    }
+}
+
+/// Implement `#[steps]`: register each annotated method as a step, and make the type a
+/// scenario-scoped fixture so that methods taking `self` get the scenario's instance.
+pub fn implement_steps(mut item: syn::ItemImpl) -> TokenStream {
+    if let Some((_, path, _)) = &item.trait_ {
+        return quote_spanned! {path.span()=>
+            compile_error!("#[steps] must be used on an inherent impl, not a trait impl");
+        }
+        .into();
+    }
+    if !item.generics.params.is_empty() {
+        return quote_spanned! {item.generics.span()=>
+            compile_error!("#[steps] does not support generic types");
+        }
+        .into();
+    }
+
+    let ty = item.self_ty.clone();
+    let mut registrations = quote! {};
+
+    for impl_item in item.items.iter_mut() {
+        let method = match impl_item {
+            syn::ImplItem::Method(m) => m,
+            _ => continue,
+        };
+
+        // Pull the step attributes off of the method: they aren't valid inside an impl
+        let (step_attrs, attrs) = method
+            .attrs
+            .drain(..)
+            .partition::<Vec<_>, _>(|a| step_keyword(a).is_some());
+        method.attrs = attrs;
+
+        let func = syn::ItemFn {
+            attrs: vec![],
+            vis: method.vis.clone(),
+            sig: method.sig.clone(),
+            block: Box::new(method.block.clone()),
+        };
+        let name = &method.sig.ident;
+
+        let (prelude, callee) = match (method.sig.receiver(), method.sig.asyncness) {
+            (None, _) => (quote! {}, quote! { <#ty>::#name }),
+            // Async methods borrow the fixture from the context as usual
+            (Some(_), Some(_)) => (
+                quote! { context.use_fixture::<#ty>().await?; },
+                quote! { context.fixture_mut::<#ty>().await.#name },
+            ),
+            (Some(_), None) => {
+                let prelude = quote! {
+                    context.use_fixture::<#ty>().await?;
+                    // Blocking methods run on another thread, so the fixture's lifetime is
+                    // detached, as make_call does for the context. The thread holds its own
+                    // handle to the fixture set, so the fixture can't be torn down or dropped
+                    // until the method returns, even if this step is. Nothing else can reach the
+                    // fixture while the step runs: the method can't take the context.
+                    let __zuke_this = unsafe {
+                        &mut *(context.fixture_mut::<#ty>().await as *mut #ty)
+                    };
+                    let __zuke_fixtures = context.fixture_handle::<#ty>();
+                };
+                // Mentioning the handle moves it into the blocking closure
+                let callee = quote! { { let _ = &__zuke_fixtures; __zuke_this }.#name };
+                (prelude, callee)
+            }
+        };
+
+        for attr in step_attrs {
+            let keyword = step_keyword(&attr).unwrap();
+            let args = match attr.parse_args::<StepArgs>() {
+                Ok(a) => a,
+                Err(e) => return e.to_compile_error().into(),
+            };
+            match register_step(keyword, args, &func, prelude.clone(), callee.clone()) {
+                Ok(r) => registrations.extend(r),
+                Err(e) => return e.into(),
+            }
+        }
+    }
+
+    (quote! {
+        #item
+
+        #[::async_trait::async_trait]
+        impl ::zuke::Fixture for #ty {
+            async fn setup(
+                _context: &mut ::zuke::Context,
+            ) -> ::zuke::reexport::anyhow::Result<Self> {
+                ::std::result::Result::Ok(::std::default::Default::default())
+            }
+        }
 
+        #registrations
     })
     .into()
 }
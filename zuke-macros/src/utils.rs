@@ -15,8 +15,9 @@ pub fn make_call(
         None => func_call,
     };
 
-    // Handle return type. Assume that any return value is a Result that can be converted to
-    // anyhow::Result. (TODO: Handle explicit -> () )
+    // Handle return type. Assume that any return value is a Result whose error can be converted
+    // to StepError or anyhow::Error, preferring StepError so that custom errors can choose their
+    // own verdict. (TODO: Handle explicit -> () )
     let func_call = match func.sig.output {
         syn::ReturnType::Default => quote! {
             {
@@ -26,8 +27,16 @@ pub fn make_call(
         },
         _ => quote! {
             {
-                #func_call?;
-                ::std::result::Result::<(), ::zuke::reexport::anyhow::Error>::Ok(())
+                #[allow(unused_imports)]
+                use ::zuke::step::{ViaAnyhow as _, ViaStepError as _};
+                match #func_call {
+                    ::std::result::Result::Ok(_) => {
+                        ::std::result::Result::<(), ::zuke::reexport::anyhow::Error>::Ok(())
+                    }
+                    ::std::result::Result::Err(e) => ::std::result::Result::Err(
+                        (&::zuke::step::ConvertError::new(e)).into_anyhow()
+                    ),
+                }
             }
         },
     };
@@ -39,15 +48,13 @@ pub fn make_call(
             // In the async case, we have the option of cancelling
             if may_cancel {
                 quote! {
-                    let __zuke_flag = context.options().canceled.clone();
+                    let __zuke_interrupted = context.interrupted();
                     let fut = async move { #func_call };
-                    let canceled = __zuke_flag.wait();
                     use ::zuke::reexport::futures::{pin_mut, future::{Either, select}};
                     pin_mut!(fut);
-                    pin_mut!(canceled);
-                    match select(fut, canceled).await {
+                    match select(fut, __zuke_interrupted).await {
                         Either::Left((result, _)) => result,
-                        Either::Right(_) => Err(::zuke::StepError::cancel().into()),
+                        Either::Right((e, _)) => Err(e.into()),
                     }
                 }
             } else {
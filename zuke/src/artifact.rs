@@ -0,0 +1,118 @@
+//! Evidence collected from fixtures when something fails, such as screenshots
+//!
+//! A fixture that can show what went wrong, such as a WebDriver session, implements
+//! [`FailureArtifactProvider`] and returns itself from [`crate::Fixture::artifact_provider`].
+//! When a step fails, or a scenario fails outside of its steps, every active fixture that
+//! provides artifacts is asked for them, and they are kept on the failed component's
+//! [`crate::Outcome::artifacts`] for reporters to show.
+//!
+//! ```ignore
+//! #[async_trait]
+//! impl Fixture for Browser {
+//!     async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+//!         Browser::launch().await
+//!     }
+//!
+//!     fn artifact_provider(&self) -> Option<&dyn FailureArtifactProvider> {
+//!         Some(self)
+//!     }
+//! }
+//!
+//! #[async_trait]
+//! impl FailureArtifactProvider for Browser {
+//!     async fn failure_artifacts(&self, _context: &mut Context) -> anyhow::Result<Vec<Artifact>> {
+//!         Ok(vec![
+//!             Artifact::png("Screenshot", self.screenshot().await?),
+//!             Artifact::html("Page source", self.source().await?),
+//!         ])
+//!     }
+//! }
+//! ```
+
+use crate::context::Context;
+use async_trait::async_trait;
+use std::fmt;
+
+/// A file about a failure, such as a screenshot or a log
+#[derive(Clone)]
+pub struct Artifact {
+    /// What the artifact is, such as `Screenshot`
+    pub name: String,
+    /// The media type of the data, such as `image/png`
+    pub media_type: String,
+    /// The contents
+    pub data: Vec<u8>,
+}
+
+impl Artifact {
+    /// An artifact with any media type
+    pub fn new<N, M, D>(name: N, media_type: M, data: D) -> Self
+    where
+        N: Into<String>,
+        M: Into<String>,
+        D: Into<Vec<u8>>,
+    {
+        Self {
+            name: name.into(),
+            media_type: media_type.into(),
+            data: data.into(),
+        }
+    }
+
+    /// A plain text artifact, such as a log
+    pub fn text<N: Into<String>, T: Into<String>>(name: N, text: T) -> Self {
+        Self::new(name, "text/plain", text.into())
+    }
+
+    /// An HTML artifact, such as a page's source
+    pub fn html<N: Into<String>, T: Into<String>>(name: N, html: T) -> Self {
+        Self::new(name, "text/html", html.into())
+    }
+
+    /// A PNG image, such as a screenshot
+    pub fn png<N: Into<String>, D: Into<Vec<u8>>>(name: N, data: D) -> Self {
+        Self::new(name, "image/png", data)
+    }
+
+    /// A file extension for the media type, for reporters that save artifacts as files. `bin` if
+    /// the type isn't known.
+    pub fn extension(&self) -> &'static str {
+        let media_type = self.media_type.split(';').next().unwrap_or_default().trim();
+        match media_type {
+            "text/plain" => "txt",
+            "text/html" => "html",
+            "text/csv" => "csv",
+            "application/json" => "json",
+            "application/xml" | "text/xml" => "xml",
+            "image/png" => "png",
+            "image/jpeg" => "jpg",
+            "image/gif" => "gif",
+            "image/svg+xml" => "svg",
+            "video/webm" => "webm",
+            "video/mp4" => "mp4",
+            _ => "bin",
+        }
+    }
+}
+
+/// Leaves out the data, which may be large
+impl fmt::Debug for Artifact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Artifact")
+            .field("name", &self.name)
+            .field("media_type", &self.media_type)
+            .field("len", &self.data.len())
+            .finish()
+    }
+}
+
+/// A fixture that can collect artifacts when something fails. See the [module docs](self).
+#[async_trait]
+pub trait FailureArtifactProvider: Send + Sync {
+    /// Collect artifacts about a failure. This is called just after the step fails, before step
+    /// after hooks run, so the fixture still shows the failure. [`Context::outcome`] is the
+    /// failed step's or scenario's outcome.
+    ///
+    /// Errors here don't change the verdict; they become warnings on the outcome.
+    async fn failure_artifacts(&self, context: &mut Context) -> anyhow::Result<Vec<Artifact>>;
+}
@@ -0,0 +1,141 @@
+//! Assertions that fail with a structured diff, rather than an opaque message
+//!
+//! ```ignore
+//! #[then("the greeting is {expected}")]
+//! async fn greeting_is(context: &mut Context, expected: String) -> anyhow::Result<()> {
+//!     let greeting = context.fixture::<Greeting>().await;
+//!     zuke::assert_eq_diff!(greeting.text, expected);
+//!     Ok(())
+//! }
+//!
+//! #[then("the user list is unchanged")]
+//! async fn users_unchanged(context: &mut Context) -> anyhow::Result<()> {
+//!     let users = context.fixture::<Users>().await;
+//!     context.expect(&users.current).to_equal(&users.original)?;
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The [`Diff`] is kept as the step's [`crate::Outcome::reason`], so reporters can find it with
+//! [`crate::Outcome::diff`] and show it however suits them.
+
+use similar::{ChangeTag, TextDiff};
+use std::error::Error;
+use std::fmt;
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+/// Two values that should have been equal, but weren't. Values are kept in their pretty-printed
+/// (`{:#?}`) form, so that multi-line values can be compared line by line.
+#[derive(Debug, Clone)]
+pub struct Diff {
+    /// Describes what was being compared
+    pub message: String,
+    /// The expected value, pretty-printed
+    pub expected: String,
+    /// The actual value, pretty-printed
+    pub actual: String,
+}
+
+impl Diff {
+    /// Compare the debug representations of two values
+    pub fn new<E: fmt::Debug + ?Sized, A: fmt::Debug + ?Sized>(expected: &E, actual: &A) -> Self {
+        Self {
+            message: String::from("Values are not equal"),
+            expected: format!("{:#?}", expected),
+            actual: format!("{:#?}", actual),
+        }
+    }
+
+    /// Replace the default message
+    pub fn with_message<S: Into<String>>(mut self, message: S) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// The message, followed by a line-by-line diff. Lines only in the expected value start with
+    /// `-`, and lines only in the actual value start with `+`. With `color`, they are also red and
+    /// green.
+    pub fn render(&self, color: bool) -> String {
+        let mut out = format!("{}\n", self.message);
+        let legend = [("-", "expected", RED), ("+", "actual", GREEN)];
+        for (sign, name, code) in legend {
+            out.push_str(&paint(&format!("{} {}\n", sign, name), code, color));
+        }
+
+        let diff = TextDiff::from_lines(&self.expected, &self.actual);
+        for change in diff.iter_all_changes() {
+            let (sign, code) = match change.tag() {
+                ChangeTag::Delete => ("-", RED),
+                ChangeTag::Insert => ("+", GREEN),
+                ChangeTag::Equal => (" ", ""),
+            };
+            let mut line = format!("{} {}", sign, change.value());
+            if change.missing_newline() {
+                line.push('\n');
+            }
+            out.push_str(&paint(&line, code, color && !code.is_empty()));
+        }
+        out
+    }
+}
+
+fn paint(line: &str, code: &str, color: bool) -> String {
+    if color {
+        // Keep the newline outside of the color, so that indenting the result still works
+        let text = line.trim_end_matches('\n');
+        format!("{}{}{}{}", code, text, RESET, &line[text.len()..])
+    } else {
+        line.to_string()
+    }
+}
+
+impl fmt::Display for Diff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.render(false).trim_end())
+    }
+}
+
+impl Error for Diff {}
+
+/// A value to check, from [`expect`] or [`crate::Context::expect`]
+pub struct Expectation<T> {
+    actual: T,
+}
+
+/// Start a check on `actual`. See the [module docs](self).
+pub fn expect<T>(actual: T) -> Expectation<T> {
+    Expectation { actual }
+}
+
+impl<T: fmt::Debug> Expectation<T> {
+    /// Fail with a [`Diff`] unless the value equals `expected`
+    pub fn to_equal<U: fmt::Debug>(self, expected: U) -> Result<(), Diff>
+    where
+        T: PartialEq<U>,
+    {
+        if self.actual == expected {
+            Ok(())
+        } else {
+            Err(Diff::new(&expected, &self.actual))
+        }
+    }
+}
+
+/// Like `assert_eq!(actual, expected)`, but returns a [`Diff`] error from the step instead of
+/// panicking. An optional message can follow, with `format!` arguments.
+#[macro_export]
+macro_rules! assert_eq_diff {
+    ($actual:expr, $expected:expr $(,)?) => {{
+        if let ::std::result::Result::Err(diff) = $crate::assert::expect(&$actual).to_equal(&$expected) {
+            return ::std::result::Result::Err(diff.into());
+        }
+    }};
+    ($actual:expr, $expected:expr, $($arg:tt)+) => {{
+        if let ::std::result::Result::Err(diff) = $crate::assert::expect(&$actual).to_equal(&$expected) {
+            return ::std::result::Result::Err(diff.with_message(format!($($arg)+)).into());
+        }
+    }};
+}
@@ -0,0 +1,73 @@
+//! Controlling the time that steps and fixtures see
+//!
+//! Code that depends on the time is hard to test if it reads the real clock. Steps and fixtures
+//! that call [`crate::Context::now`] instead of `Utc::now()` can be tested with a [`MockClock`]:
+//! once a scenario uses one, the time stands still until a step moves it.
+//!
+//! ```ignore
+//! #[then("the session has expired")]
+//! async fn the_session_has_expired(context: &mut Context) -> anyhow::Result<()> {
+//!     let now = context.now().await;
+//!     let session = context.fixture::<Session>().await;
+//!     anyhow::ensure!(session.expires_at <= now, "Session expires at {}", session.expires_at);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! ```gherkin
+//! Scenario: Sessions expire
+//!     Given the time is "2021-06-01T12:00:00Z"
+//!     And a new session
+//!     When 3 hours pass
+//!     Then the session has expired
+//! ```
+//!
+//! Outcomes and reports keep using the real time. With the `fixtures` feature, the steps are in
+//! `fixtures::clock`.
+
+use crate::{Context, Fixture, Scope};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use parking_lot::Mutex;
+
+/// A clock for a scenario that only moves when told to. It starts at the real time when it is
+/// set up. See the [module docs](self).
+pub struct MockClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl MockClock {
+    /// A clock stopped at `now`
+    pub fn at(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+
+    /// The clock's time
+    pub fn now(&self) -> DateTime<Utc> {
+        *self.now.lock()
+    }
+
+    /// Set the clock. It may go backward.
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock() = now;
+    }
+
+    /// Move the clock forward by `duration`, or backward if it is negative, and return the new
+    /// time
+    pub fn advance(&self, duration: Duration) -> DateTime<Utc> {
+        let mut now = self.now.lock();
+        *now = *now + duration;
+        *now
+    }
+}
+
+#[async_trait]
+impl Fixture for MockClock {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self::at(Utc::now()))
+    }
+}
@@ -1,8 +1,12 @@
 //! Test components
 
+use crate::fixture::Scope;
+use crate::metadata::FeatureMetadata;
 use crate::options::TestOptions;
-use gherkin_rust::{Feature, Rule, Scenario, Step};
+use gherkin_rust::{Feature, Rule, Scenario, Step, StepType};
+use serde::{Serialize, Serializer};
 use std::fmt;
+use std::path::Path;
 use std::pin::Pin;
 use std::ptr;
 use std::sync::Arc;
@@ -12,9 +16,12 @@ use thiserror::Error;
 pub struct Component {
     options: Arc<TestOptions>,
     feature: Option<Pin<Arc<Feature>>>,
+    metadata: Arc<FeatureMetadata>,
     rule: *const Rule,
     scenario: *const Scenario,
     step: *const Step,
+    /// Keeps a step that isn't part of the feature alive. See [`Component::with_synthetic_step`].
+    synthetic: Option<Arc<Step>>,
     excluded: bool,
     included: bool,
 }
@@ -69,6 +76,122 @@ impl fmt::Display for ComponentKind {
     }
 }
 
+impl Serialize for ComponentKind {
+    /// As its `Display` form, e.g. `"scenario"`
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Where a step was defined. See [`Component::step_origin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StepOrigin {
+    /// A feature or rule's `Background`
+    Background,
+    /// The scenario itself
+    Scenario,
+}
+
+/// Add ` (N)` to `key` if `item` is the Nth of its siblings with its name, for N > 1
+fn push_ordinal<T>(key: &mut String, siblings: &[T], item: &T, name: impl Fn(&T) -> &str) {
+    let n = siblings
+        .iter()
+        .take_while(|s| !ptr::eq(*s, item))
+        .filter(|s| name(s) == name(item))
+        .count()
+        + 1;
+    if n > 1 {
+        key.push_str(&format!(" ({})", n));
+    }
+}
+
+/// The owners named by `@owner(...)` tags, in order. Tags that can't be parsed name no one.
+#[cfg(feature = "tags")]
+fn tag_owners<'a, I>(tags: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    use crate::tags::{parse_tags, TagArg};
+
+    parse_tags("owner", tags)
+        .into_iter()
+        .flatten()
+        .flat_map(|args| args.0)
+        .map(|arg| match arg {
+            TagArg::Text(name) => name,
+            TagArg::Number(n) => n.to_string(),
+        })
+        .filter(|o| !o.is_empty())
+        .collect()
+}
+
+/// Without the `tags` feature, `@owner` tags aren't read
+#[cfg(not(feature = "tags"))]
+fn tag_owners<'a, I>(_tags: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    vec![]
+}
+
+/// A stable identifier for a component. See [`Component::id`].
+#[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash)]
+pub struct ComponentId(pub u64);
+
+impl fmt::Display for ComponentId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl Serialize for ComponentId {
+    /// As its `Display` form, a hex string, since JSON can't hold every `u64`
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A serializable summary of a [`Component`]: where it is, what it's called, and its tags. See
+/// [`Component::info`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentInfo {
+    /// See [`Component::id`]
+    pub id: ComponentId,
+    /// See [`Component::key`]
+    pub key: String,
+    /// The type of component
+    pub kind: ComponentKind,
+    /// See [`Component::name`]
+    pub name: String,
+    /// The feature's name, if any
+    pub feature: Option<String>,
+    /// The rule's name, if any
+    pub rule: Option<String>,
+    /// The scenario's name, if any
+    pub scenario: Option<String>,
+    /// The step's keyword and text, if any
+    pub step: Option<String>,
+    /// The feature file, if known
+    pub path: Option<String>,
+    /// See [`Component::line`]
+    pub line: Option<usize>,
+    /// Tags, including inherited ones
+    pub tags: Vec<String>,
+}
+
+impl ComponentId {
+    /// 64-bit FNV-1a. We can't use `DefaultHasher`, because it isn't guaranteed to be the same
+    /// from one build to the next.
+    pub(crate) fn hash(bytes: &[u8]) -> Self {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for b in bytes {
+            hash ^= *b as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        Self(hash)
+    }
+}
+
 /// Errors that can occur when creating a new component
 #[derive(Error, Debug)]
 pub enum NewComponentError {
@@ -142,6 +265,197 @@ impl Component {
         }
     }
 
+    /// The path of the file this component was defined in, if known. Features from Markdown files
+    /// also give the line their block starts on, as in `README.md:11`.
+    pub fn path(&self) -> Option<&Path> {
+        self.feature()?.path.as_deref()
+    }
+
+    /// The line this component was defined on, if applicable.
+    pub fn line(&self) -> Option<usize> {
+        if let Some(s) = self.step() {
+            Some(s.position.line)
+        } else if let Some(s) = self.scenario() {
+            Some(s.position.line)
+        } else if let Some(r) = self.rule() {
+            Some(r.position.line)
+        } else {
+            self.feature().map(|f| f.position.line)
+        }
+    }
+
+    /// If this scenario was expanded from a scenario outline, the index of its row in the
+    /// examples table. An outline with a single example looks just like a plain scenario, and has
+    /// no index.
+    pub fn example_index(&self) -> Option<usize> {
+        let scenario = self.scenario()?;
+        let siblings = match self.rule() {
+            Some(r) => &r.scenarios,
+            None => &self.feature()?.scenarios,
+        };
+
+        let mut examples = siblings.iter().filter(|s| s.span == scenario.span);
+        let index = examples.clone().position(|s| ptr::eq(s, scenario))?;
+        if examples.nth(1).is_some() {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// The steps of this component's scenario, in the order they run: background steps, then the
+    /// scenario's own.
+    fn scenario_steps(&self) -> Option<impl Iterator<Item = (&Step, StepOrigin)>> {
+        let feature = self.feature()?;
+        let scenario = self.scenario()?;
+
+        let background = feature
+            .background
+            .iter()
+            .chain(self.rule().and_then(|r| r.background.as_ref()))
+            .flat_map(|bg| bg.steps.iter())
+            .map(|s| (s, StepOrigin::Background));
+        let steps = scenario.steps.iter().map(|s| (s, StepOrigin::Scenario));
+        Some(background.chain(steps))
+    }
+
+    /// The index of this step within its scenario, counting background steps. `None` for steps
+    /// that aren't in the scenario, such as those run with [`crate::Context::run_step`].
+    pub fn step_index(&self) -> Option<usize> {
+        let step = self.step()?;
+        self.scenario_steps()?.position(|(s, _)| ptr::eq(s, step))
+    }
+
+    /// The number of steps in this component's scenario, counting background steps. Together
+    /// with [`Self::step_index`], this gives progress such as "step 3/7".
+    pub fn step_count(&self) -> Option<usize> {
+        Some(self.scenario_steps()?.count())
+    }
+
+    /// The keyword that gave this step its type, in the feature's own language. That's the
+    /// step's own keyword, except for `And`, `But`, and `*`, which continue the steps before
+    /// them: for those, it's the keyword of the step that started the run, such as `Given`.
+    pub fn step_type_keyword(&self) -> Option<&str> {
+        let step = self.step()?;
+        let steps: Vec<&Step> = match self.scenario_steps() {
+            Some(steps) => steps.map(|(s, _)| s).collect(),
+            None => vec![],
+        };
+        let mut i = match steps.iter().position(|s| ptr::eq(*s, step)) {
+            Some(i) => i,
+            None => return Some(step.keyword.trim()),
+        };
+        while i > 0 && steps[i - 1].ty == step.ty {
+            i -= 1;
+        }
+        match steps[i].keyword.trim() {
+            // A run that starts with `*` has no keyword of its own to show
+            "*" => Some(match step.ty {
+                StepType::Given => "Given",
+                StepType::When => "When",
+                StepType::Then => "Then",
+            }),
+            keyword => Some(keyword),
+        }
+    }
+
+    /// Whether this step came from a background or from the scenario itself
+    pub fn step_origin(&self) -> Option<StepOrigin> {
+        let step = self.step()?;
+        self.scenario_steps()?
+            .find(|(s, _)| ptr::eq(*s, step))
+            .map(|(_, origin)| origin)
+    }
+
+    /// A stable, human readable name for this component, built from the feature path (or name,
+    /// if there is no path), the rule and scenario names, the example index, and the step index.
+    /// Unlike the component itself, keys are the same from one test run to the next as long as
+    /// the feature files don't change. A rule or scenario with the same name as an earlier one in
+    /// the same feature or rule gets a number, as in `login.feature::Log in (2)`, so keys are
+    /// unique among components of the same kind, as long as features have different paths (or
+    /// names, for features without a path). In a [`crate::SuiteRunner`], keys start with the
+    /// suite's name.
+    ///
+    /// The global component's key is empty.
+    pub fn key(&self) -> String {
+        let mut key = self.scenario_key();
+        if let Some(i) = self.step_index() {
+            key.push_str(&format!("::{}", i));
+        } else if let Some(step) = self.step() {
+            // A synthetic step
+            key.push_str(&format!("::{}", step.value));
+        }
+        key
+    }
+
+    /// As [`Self::key`], but a step's key is its scenario's
+    pub(crate) fn scenario_key(&self) -> String {
+        let mut key = match self.feature() {
+            None => return String::new(),
+            Some(f) => match &f.path {
+                Some(path) => path.display().to_string(),
+                None => f.name.clone(),
+            },
+        };
+        if let Some(suite) = &self.options.suite {
+            key.insert_str(0, &format!("{}::", suite));
+        }
+
+        if let Some(r) = self.rule() {
+            key.push_str("::");
+            key.push_str(&r.name);
+            push_ordinal(&mut key, &self.feature().unwrap().rules, r, |r| &r.name);
+        }
+
+        if let Some(s) = self.scenario() {
+            key.push_str("::");
+            key.push_str(&s.name);
+            let siblings = match self.rule() {
+                Some(r) => &r.scenarios,
+                None => &self.feature().unwrap().scenarios,
+            };
+            push_ordinal(&mut key, siblings, s, |s| &s.name);
+            if let Some(i) = self.example_index() {
+                key.push_str(&format!("#{}", i));
+            }
+        }
+
+        key
+    }
+
+    /// A deterministic ID for this component: a hash of its kind and [`Self::key`]. It is the same
+    /// from one test run to the next (and from one machine to the next), as long as the feature
+    /// files don't change. Useful for rerun files, baselines, sharding, and external reporting.
+    ///
+    /// IDs are unique wherever keys are: see [`Self::key`]. Two inline features with the same
+    /// name, and no path, share their IDs.
+    pub fn id(&self) -> ComponentId {
+        ComponentId::hash(format!("{}\0{}", self.kind(), self.key()).as_bytes())
+    }
+
+    /// A serializable summary of this component
+    pub fn info(&self) -> ComponentInfo {
+        ComponentInfo {
+            id: self.id(),
+            key: self.key(),
+            kind: self.kind(),
+            name: self.name().to_string(),
+            feature: self.feature().map(|f| f.name.clone()),
+            rule: self.rule().map(|r| r.name.clone()),
+            scenario: self.scenario().map(|s| s.name.clone()),
+            step: self.step().map(|s| format!("{} {}", s.keyword, s.value)),
+            path: self.path().map(|p| p.display().to_string()),
+            line: self.line(),
+            tags: self.tags().cloned().collect(),
+        }
+    }
+
+    /// Settings from the top of the feature file, such as its owner. Empty for the global
+    /// component, and for features that have none. See [`crate::metadata`].
+    pub fn metadata(&self) -> &FeatureMetadata {
+        &self.metadata
+    }
+
     /// The tags for the current component, not including tags inherited from the parent.
     pub fn tags_uninherited(&self) -> &[String] {
         if let Some(s) = self.scenario() {
@@ -180,6 +494,21 @@ impl Component {
         tags.into_iter()
     }
 
+    /// Who owns the component: the arguments of `@owner(...)` tags, such as
+    /// `@owner(team-payments)` or `@owner(team-a, team-b)`, including tags of the feature and rule,
+    /// followed by the `owner` setting at the top of the feature file. Without duplicates. The
+    /// tags are parsed as in [`crate::tags::args`], so an owner can be quoted.
+    pub fn owners(&self) -> Vec<String> {
+        let tagged = tag_owners(self.tags());
+        let mut owners: Vec<String> = vec![];
+        for owner in tagged.into_iter().chain(self.metadata.owner.clone()) {
+            if !owners.contains(&owner) {
+                owners.push(owner);
+            }
+        }
+        owners
+    }
+
     /// Is this component excluded by name?
     ///
     /// This component is de-selected, along with everything below it
@@ -195,14 +524,52 @@ impl Component {
         self.included
     }
 
+    /// Is `other` this component, or nested somewhere inside of it? For example, a feature
+    /// contains its rules, scenarios, and steps.
+    pub fn contains(&self, other: &Component) -> bool {
+        let same_feature = match (&self.feature, &other.feature) {
+            (None, _) => return true,
+            (Some(a), Some(b)) => ptr::eq::<Feature>(&**a, &**b),
+            (Some(_), None) => false,
+        };
+
+        same_feature
+            && (self.rule.is_null() || self.rule == other.rule)
+            && (self.scenario.is_null() || self.scenario == other.scenario)
+            && (self.step.is_null() || self.step == other.step)
+    }
+
+    /// Are `other` and this component within the same fixture scope? For example, everything in a
+    /// feature shares [`Scope::Feature`]. A scope coarser than this component is as wide as it can
+    /// be, so at [`Scope::Scenario`], a feature shares scope with everything inside of it.
+    pub fn shares_scope(&self, other: &Component, scope: Scope) -> bool {
+        let same_feature = match (&self.feature, &other.feature) {
+            (None, _) => true,
+            (Some(a), Some(b)) => ptr::eq::<Feature>(&**a, &**b),
+            (Some(_), None) => false,
+        };
+
+        match scope {
+            Scope::Global => true,
+            Scope::Feature => same_feature,
+            Scope::Scenario => {
+                same_feature
+                    && (self.scenario.is_null()
+                        || (self.rule == other.rule && self.scenario == other.scenario))
+            }
+        }
+    }
+
     /// Create a global-level component
     pub fn global(options: Arc<TestOptions>) -> Arc<Self> {
         Arc::new(Self {
             options,
             feature: None,
+            metadata: Arc::default(),
             rule: ptr::null(),
             scenario: ptr::null(),
             step: ptr::null(),
+            synthetic: None,
             included: false,
             excluded: false,
         })
@@ -210,14 +577,22 @@ impl Component {
 
     /// Create a feature level component from a global component
     pub fn with_feature(&self, feature: Feature) -> Arc<Self> {
+        self.with_feature_metadata(feature, FeatureMetadata::default())
+    }
+
+    /// As [`Self::with_feature`], with settings from the top of the feature file. See
+    /// [`crate::metadata`].
+    pub fn with_feature_metadata(&self, feature: Feature, metadata: FeatureMetadata) -> Arc<Self> {
         Arc::new(Self {
             options: self.options.clone(),
             included: self.options.includes(&feature.name),
             excluded: self.options.excludes(&feature.name),
             feature: Some(Arc::pin(feature)),
+            metadata: Arc::new(metadata),
             rule: ptr::null(),
             scenario: ptr::null(),
             step: ptr::null(),
+            synthetic: None,
         })
     }
 
@@ -233,9 +608,11 @@ impl Component {
                     included: self.included || self.options.includes(&rule.name),
                     excluded: self.excluded || self.options.excludes(&rule.name),
                     feature: self.feature.clone(),
+                    metadata: self.metadata.clone(),
                     rule,
                     scenario: ptr::null(),
                     step: ptr::null(),
+                    synthetic: None,
                 })
             })
             .collect())
@@ -254,15 +631,39 @@ impl Component {
 
         Ok(scenarios
             .map(|s| {
-                Arc::new(Self {
+                let mut component = Self {
                     options: self.options.clone(),
                     included: self.included || self.options.includes(&s.name),
                     excluded: self.excluded || self.options.excludes(&s.name),
                     feature: self.feature.clone(),
+                    metadata: self.metadata.clone(),
                     rule: self.rule,
                     scenario: s,
                     step: ptr::null(),
-                })
+                    synthetic: None,
+                };
+
+                // Out-of-shard scenarios are excluded rather than dropped, so they still show up
+                // in the counts.
+                if !component.options.in_shard(component.id()) {
+                    component.excluded = true;
+                }
+                // Likewise scenarios that aren't part of the profile
+                if !component.options.profile_settings.allows(component.tags()) {
+                    component.excluded = true;
+                }
+                // And scenarios that belong to someone other than `--owner`
+                let owners = &component.options.owners;
+                if !owners.is_empty()
+                    && !component
+                        .owners()
+                        .iter()
+                        .any(|o| owners.iter().any(|x| x == o))
+                {
+                    component.excluded = true;
+                }
+
+                Arc::new(component)
             })
             .collect())
     }
@@ -279,9 +680,11 @@ impl Component {
                     included: self.included,
                     excluded: self.excluded,
                     feature: self.feature.clone(),
+                    metadata: self.metadata.clone(),
                     rule: self.rule,
                     scenario: self.scenario,
                     step: s,
+                    synthetic: None,
                 })
             }));
         }
@@ -293,9 +696,11 @@ impl Component {
                     included: self.included,
                     excluded: self.excluded,
                     feature: self.feature.clone(),
+                    metadata: self.metadata.clone(),
                     rule: self.rule,
                     scenario: self.scenario,
                     step: s,
+                    synthetic: None,
                 })
             }));
         }
@@ -303,6 +708,26 @@ impl Component {
         Ok(steps)
     }
 
+    /// Create a step level component for a step that isn't in the feature, such as one run by
+    /// [`crate::Context::run_step`]. The step appears to belong to the same scenario as `self`.
+    pub fn with_synthetic_step(&self, step: Step) -> Result<Arc<Self>, NewComponentError> {
+        self.feature().ok_or(NewComponentError::NoFeature)?;
+        self.scenario().ok_or(NewComponentError::NoScenario)?;
+
+        let step = Arc::new(step);
+        Ok(Arc::new(Self {
+            options: self.options.clone(),
+            included: self.included,
+            excluded: self.excluded,
+            feature: self.feature.clone(),
+            metadata: self.metadata.clone(),
+            rule: self.rule,
+            scenario: self.scenario,
+            step: Arc::as_ptr(&step),
+            synthetic: Some(step),
+        }))
+    }
+
     /// Create step level components from a scenario component
     pub fn with_steps(&self) -> Result<Vec<Arc<Self>>, NewComponentError> {
         self.feature().ok_or(NewComponentError::NoFeature)?;
@@ -317,9 +742,11 @@ impl Component {
                     included: self.included,
                     excluded: self.excluded,
                     feature: self.feature.clone(),
+                    metadata: self.metadata.clone(),
                     rule: self.rule,
                     scenario: self.scenario,
                     step: s,
+                    synthetic: None,
                 })
             })
             .collect())
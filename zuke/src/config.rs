@@ -0,0 +1,130 @@
+//! Settings from a configuration file
+use anyhow::Context as _;
+use std::path::{Path, PathBuf};
+use toml::value::{Table, Value};
+
+/// The configuration file that is read if `--config` isn't given. It's fine if it doesn't exist.
+pub const DEFAULT_CONFIG: &str = "zuke.toml";
+
+/// Settings from a configuration file, `zuke.toml` unless `--config` says otherwise.
+///
+/// ```toml
+/// # Feature files or directories, relative to this file
+/// features = ["tests/features"]
+///
+/// # Command line options, by their long names. The command line and environment variables take
+/// # precedence.
+/// [options]
+/// reporter = ["plain", "timing"]
+/// timing-count = 5
+/// strict = true
+///
+/// # Anything else. Fixtures can read these with TestOptions::setting
+/// [settings]
+/// base-url = "http://localhost:8080"
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    /// The file these settings came from, if any
+    pub path: Option<PathBuf>,
+    /// Feature files or directories to add to the test run
+    pub features: Vec<PathBuf>,
+    /// Command line options, as long names and values. Lists become multiple entries.
+    pub options: Vec<(String, String)>,
+    /// Custom settings
+    pub settings: Table,
+}
+
+impl Config {
+    /// Read a configuration file
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read config file {}", path.display()))?;
+        let mut config = Self::parse(&text, path.parent().unwrap_or_else(|| Path::new("")))
+            .with_context(|| format!("Bad config file {}", path.display()))?;
+        config.path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// Read the configuration file given with `--config`, or else [`DEFAULT_CONFIG`] if it exists.
+    pub fn load_default(path: Option<&str>) -> anyhow::Result<Self> {
+        match path {
+            Some(p) => Self::load(p),
+            None if Path::new(DEFAULT_CONFIG).is_file() => Self::load(DEFAULT_CONFIG),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Parse configuration from a string. Feature paths are relative to `base`.
+    pub fn parse(text: &str, base: &Path) -> anyhow::Result<Self> {
+        let mut table: Table = toml::from_str(text)?;
+
+        let features = match table.remove("features") {
+            None => vec![],
+            Some(Value::String(s)) => vec![base.join(s)],
+            Some(Value::Array(items)) => items
+                .into_iter()
+                .map(|item| match item {
+                    Value::String(s) => Ok(base.join(s)),
+                    _ => anyhow::bail!("features must be a list of paths"),
+                })
+                .collect::<anyhow::Result<_>>()?,
+            Some(_) => anyhow::bail!("features must be a list of paths"),
+        };
+
+        let mut options = vec![];
+        match table.remove("options") {
+            None => (),
+            Some(Value::Table(t)) => {
+                for (name, value) in t {
+                    for value in option_values(&value)
+                        .with_context(|| format!("Bad value for option {:?}", name))?
+                    {
+                        options.push((name.clone(), value));
+                    }
+                }
+            }
+            Some(_) => anyhow::bail!("options must be a table"),
+        }
+
+        let settings = match table.remove("settings") {
+            None => Table::new(),
+            Some(Value::Table(t)) => t,
+            Some(_) => anyhow::bail!("settings must be a table"),
+        };
+
+        if let Some(key) = table.keys().next() {
+            anyhow::bail!("Unknown key {:?}", key);
+        }
+
+        Ok(Self {
+            path: None,
+            features,
+            options,
+            settings,
+        })
+    }
+}
+
+/// Command line values for a TOML value. Lists give one value per item.
+fn option_values(value: &Value) -> anyhow::Result<Vec<String>> {
+    Ok(match value {
+        Value::String(s) => vec![s.clone()],
+        Value::Integer(i) => vec![i.to_string()],
+        Value::Float(f) => vec![f.to_string()],
+        Value::Boolean(b) => vec![b.to_string()],
+        Value::Datetime(d) => vec![d.to_string()],
+        Value::Array(items) => {
+            let mut values = vec![];
+            for item in items {
+                match item {
+                    Value::Array(_) | Value::Table(_) => anyhow::bail!("Lists can't be nested"),
+                    _ => values.extend(option_values(item)?),
+                }
+            }
+            values
+        }
+        Value::Table(_) => anyhow::bail!("Expected a value or a list"),
+    })
+}
@@ -3,14 +3,35 @@
 //! A context is an outcome plus active fixtures. When the test component is done running, the
 //! fixtures will be jettisoned and the outcome will be passed along to reporters.
 
+use crate::assert::{expect, Expectation};
+use crate::clock::MockClock;
 use crate::component::{Component, ComponentKind, NewComponentError};
+use crate::event::Event;
 use crate::fixture::{Fixture, FixtureError, FixtureSet, Scope};
 use crate::options::TestOptions;
 use crate::outcome::Outcome;
+use crate::state::{GlobalState, StateError};
+use crate::step::StepError;
+use async_broadcast as broadcast;
+use async_std::channel;
 use async_std::task;
+use chrono::{DateTime, Utc};
+use futures::future::{self, BoxFuture, Either, FutureExt};
+use futures::stream::StreamExt;
 use gherkin_rust::{Feature, Rule, Scenario, Step};
-use std::any::TypeId;
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How deeply [`Context::run_step`] may nest, to catch steps that run themselves
+pub const MAX_STEP_DEPTH: usize = 32;
+
+/// The longest fixtures may take to collect failure artifacts, even if the scenario's deadline has
+/// already passed
+pub const ARTIFACT_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// The test context is a combination of the current test component (i.e., scenario, step, feature,
 /// etc.), the currently active test fixtures, and any other information needed to execute a test.
@@ -21,6 +42,13 @@ pub struct Context {
     global_fixtures: Option<Arc<FixtureSet>>, // an option for teardown
     feature_fixtures: Option<Arc<FixtureSet>>,
     scenario_fixtures: Option<Arc<FixtureSet>>, // only an arc to keep the borrow checker happy
+    events: Option<broadcast::Sender<Event>>,
+    deadline: Option<Instant>,
+    expansions: HashMap<String, String>,
+    saved: HashMap<String, String>,
+    captured_error: Option<anyhow::Error>,
+    state: Arc<GlobalState>,
+    step_depth: usize,
 }
 
 /// An "open" context is a context that can be used to derive other contexts. They are used by
@@ -32,6 +60,8 @@ pub struct Context {
 pub struct OpenContext {
     /// The closed context
     pub context: Context,
+    /// The scenario's outcome, set aside while a step is running
+    scenario_outcome: Option<Outcome>,
 }
 
 impl OpenContext {
@@ -48,7 +78,15 @@ impl OpenContext {
                 global_fixtures: Some(Arc::new(FixtureSet::new())),
                 feature_fixtures: None,
                 scenario_fixtures: None,
+                events: None,
+                deadline: None,
+                step_depth: 0,
+                expansions: HashMap::new(),
+                saved: HashMap::new(),
+                captured_error: None,
+                state: Arc::new(GlobalState::new()),
             },
+            scenario_outcome: None,
         }
     }
 
@@ -67,7 +105,15 @@ impl OpenContext {
                 global_fixtures: self.context.global_fixtures.clone(),
                 feature_fixtures: Some(Arc::new(FixtureSet::new())),
                 scenario_fixtures: None,
+                events: self.context.events.clone(),
+                deadline: None,
+                step_depth: 0,
+                expansions: self.context.expansions.clone(),
+                saved: self.context.saved.clone(),
+                captured_error: None,
+                state: self.context.state.clone(),
             },
+            scenario_outcome: None,
         }
     }
 
@@ -89,7 +135,15 @@ impl OpenContext {
                     global_fixtures: self.context.global_fixtures.clone(),
                     feature_fixtures: self.context.feature_fixtures.clone(),
                     scenario_fixtures: None,
+                    events: self.context.events.clone(),
+                    deadline: None,
+                    step_depth: 0,
+                    expansions: self.context.expansions.clone(),
+                    saved: self.context.saved.clone(),
+                    captured_error: None,
+                    state: self.context.state.clone(),
                 },
+                scenario_outcome: None,
             })
             .collect())
     }
@@ -112,21 +166,73 @@ impl OpenContext {
                     global_fixtures: self.context.global_fixtures.clone(),
                     feature_fixtures: self.context.feature_fixtures.clone(),
                     scenario_fixtures: Some(Arc::new(FixtureSet::new())),
+                    events: self.context.events.clone(),
+                    deadline: None,
+                    step_depth: 0,
+                    expansions: self.context.expansions.clone(),
+                    saved: self.context.saved.clone(),
+                    captured_error: None,
+                    state: self.context.state.clone(),
                 },
+                scenario_outcome: None,
             })
             .collect())
     }
 
+    /// Create a fresh context for running this scenario again. It shares global and feature
+    /// fixtures, but gets its own scenario fixtures and outcome.
+    pub fn with_iteration(&self) -> Self {
+        let component = self.context.component.clone();
+        Self {
+            context: Context {
+                options: self.context.options.clone(),
+                outcome: Outcome::undecided(component.clone()),
+                component,
+                global_fixtures: self.context.global_fixtures.clone(),
+                feature_fixtures: self.context.feature_fixtures.clone(),
+                scenario_fixtures: Some(Arc::new(FixtureSet::new())),
+                events: self.context.events.clone(),
+                deadline: None,
+                step_depth: 0,
+                expansions: self.context.expansions.clone(),
+                saved: self.context.saved.clone(),
+                captured_error: None,
+                state: self.context.state.clone(),
+            },
+            scenario_outcome: None,
+        }
+    }
+
+    /// Set where events generated while running this context (and contexts derived from it) are
+    /// sent.
+    pub fn set_events(&mut self, events: broadcast::Sender<Event>) {
+        self.context.events = Some(events);
+    }
+
     /// Sets the component and nothing else. For step execution where we mutate the context serially
     /// rather than derive new contexts.
     pub fn set_component(&mut self, component: Arc<Component>) {
         self.context.component = component;
     }
 
+    /// Begin executing a step. The step's outcome replaces the scenario's outcome on the context
+    /// until [`Self::end_step`] is called, so that step implementations and step hooks can see
+    /// and modify it.
+    pub fn begin_step(&mut self, outcome: Outcome) {
+        assert!(self.scenario_outcome.is_none(), "Step is already running");
+        self.context.component = outcome.component().clone();
+        let scenario = std::mem::replace(&mut self.context.outcome, outcome);
+        self.scenario_outcome = Some(scenario);
+    }
+
+    /// Finish executing a step, restoring the scenario's outcome. Returns the step's outcome.
+    pub fn end_step(&mut self) -> Outcome {
+        let scenario = self.scenario_outcome.take().expect("No step is running");
+        std::mem::replace(&mut self.context.outcome, scenario)
+    }
+
     /// Run the before hooks (fixtures).
     pub async fn before_hooks(&mut self) {
-        // TODO: better handling of multiple errors
-
         let fixture_sets = [
             self.context.global_fixtures.clone(),
             self.context.feature_fixtures.clone(),
@@ -137,15 +243,13 @@ impl OpenContext {
             if let Err(e) = fixtures.before(&mut self.context).await {
                 self.context
                     .outcome_mut()
-                    .set_err(e.context("Error in before hook"));
+                    .add_err(e.context("Error in before hook"));
             }
         }
     }
 
     /// Run the after hooks (fixtures)
     pub async fn after_hooks(&mut self) {
-        // TODO: better handling of multiple errors
-
         let fixture_sets = [
             self.context.scenario_fixtures.clone(),
             self.context.feature_fixtures.clone(),
@@ -156,7 +260,58 @@ impl OpenContext {
             if let Err(e) = fixtures.after(&mut self.context).await {
                 self.context
                     .outcome_mut()
-                    .set_err(e.context("Error in before hook"));
+                    .add_err(e.context("Error in after hook"));
+            }
+        }
+    }
+
+    /// Ask active fixtures for artifacts about a failure, and add them to the current outcome.
+    /// See [`crate::artifact`].
+    ///
+    /// Collection stops if the test run is canceled, or at the scenario's deadline. If that has
+    /// passed, as when a step timed out, fixtures get [`ARTIFACT_TIMEOUT`] instead.
+    pub async fn collect_failure_artifacts(&mut self) {
+        let fixture_sets = [
+            self.context.scenario_fixtures.clone(),
+            self.context.feature_fixtures.clone(),
+            self.context.global_fixtures.clone(),
+        ];
+        let now = Instant::now();
+        let until = match self.context.deadline {
+            Some(d) if d > now => d.min(now + ARTIFACT_TIMEOUT),
+            _ => now + ARTIFACT_TIMEOUT,
+        };
+        let canceled = self.context.options.canceled.clone();
+
+        for fixtures in fixture_sets.iter().flatten() {
+            let result = {
+                let collect = Box::pin(fixtures.failure_artifacts(&mut self.context));
+                let interrupted = future::select(
+                    canceled.wait().boxed(),
+                    task::sleep(until.saturating_duration_since(Instant::now())).boxed(),
+                );
+                match future::select(collect, interrupted).await {
+                    Either::Left((result, _)) => Some(result),
+                    Either::Right((Either::Left(_), _)) => return,
+                    Either::Right((Either::Right(_), _)) => None,
+                }
+            };
+
+            let outcome = self.context.outcome_mut();
+            match result {
+                Some((artifacts, errors)) => {
+                    outcome.artifacts.extend(artifacts);
+                    if let Err(e) = errors.into_result() {
+                        outcome.add_warning(e.context("Couldn't collect failure artifacts"));
+                    }
+                }
+                None => {
+                    outcome.add_warning(anyhow::anyhow!(
+                        "Gave up collecting failure artifacts after {:?}",
+                        until - now
+                    ));
+                    return;
+                }
             }
         }
     }
@@ -189,7 +344,7 @@ impl OpenContext {
                     .teardown(context)
                     .await;
                 if let Err(e) = result {
-                    context.outcome.set_err(e);
+                    context.outcome.add_err(e);
                 }
                 // No async drop, so we'll do this in the background
                 let _ = task::spawn_blocking(move || drop(f));
@@ -238,6 +393,30 @@ impl OpenContext {
         }
         outcome
     }
+
+    /// Give up on the context without tearing down fixtures, because the test run has been
+    /// aborted. The outcome is marked as canceled.
+    ///
+    /// Fixtures are still dropped on a background thread, but no teardown or after hooks run.
+    pub fn abandon(self) -> Outcome {
+        let Self { context, .. } = self;
+        let Context {
+            mut outcome,
+            global_fixtures,
+            feature_fixtures,
+            scenario_fixtures,
+            ..
+        } = context;
+
+        let _ = task::spawn_blocking(move || {
+            drop(scenario_fixtures);
+            drop(feature_fixtures);
+            drop(global_fixtures);
+        });
+
+        outcome.set_err(StepError::cancel_with_message("Test run aborted").into());
+        outcome
+    }
 }
 
 impl Context {
@@ -246,6 +425,371 @@ impl Context {
         &self.options
     }
 
+    /// Wait until the test run is canceled. Long-running steps can race against this to stop
+    /// early. (Async step implementations are already canceled automatically.)
+    pub async fn canceled(&self) {
+        self.options.canceled.wait().await
+    }
+
+    /// Return a [`crate::Verdict::Canceled`] error if the test run has been canceled. Blocking
+    /// steps that run for a long time can call this periodically with `?` to stop early.
+    pub fn check_canceled(&self) -> Result<(), StepError> {
+        if self.options.canceled.is_set() {
+            Err(StepError::cancel())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Store a value for the rest of the test run, replacing any value of the same type. Only
+    /// allowed in the global context, until the global before hooks finish. See
+    /// [`crate::state`].
+    pub fn set_state<T: Any + Send + Sync>(&self, value: T) -> Result<(), StateError> {
+        if self.kind() != ComponentKind::Global {
+            return Err(StateError::NotGlobal(type_name::<T>()));
+        }
+        self.state.set(value)
+    }
+
+    /// A value stored with [`Self::set_state`], if any
+    pub fn state<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.state.get()
+    }
+
+    /// Make global state read-only. Called by runners once the global before hooks have run.
+    pub fn seal_state(&self) {
+        self.state.seal();
+    }
+
+    /// Start a check on a value that fails with a structured [`crate::assert::Diff`]:
+    /// `context.expect(actual).to_equal(expected)?`
+    pub fn expect<T>(&self, actual: T) -> Expectation<T> {
+        expect(actual)
+    }
+
+    /// Replace `{name}` with `value` in the text of steps that run in this context, or contexts
+    /// derived from it. For example, a scenario fixture can set an expansion during setup for the
+    /// rest of the scenario's steps.
+    pub fn set_expansion<N: Into<String>, V: Into<String>>(&mut self, name: N, value: V) {
+        self.expansions.insert(name.into(), value.into());
+    }
+
+    /// Run another step by its text, such as `"Given a logged-in user"`, from inside a step
+    /// implementation. This allows composing high-level steps out of existing ones. The keyword is
+    /// optional; without one, the step has the same type as the current step. The step's outcome
+    /// is added to the current step's outcome as a child.
+    ///
+    /// Returns an error if the step doesn't pass. Its verdict is kept, so a pending or skipped
+    /// step makes the calling step pending or skipped as well.
+    ///
+    /// ```
+    /// use zuke::{given, Context};
+    ///
+    /// #[given("a logged-in admin")]
+    /// async fn a_logged_in_admin(context: &mut Context) -> anyhow::Result<()> {
+    ///     context.run_step(r#"Given a user named "admin""#).await?;
+    ///     context.run_step(r#"When "admin" logs in"#).await?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn run_step(&mut self, text: &str) -> anyhow::Result<()> {
+        let current = match self.step() {
+            Some(step) => step.clone(),
+            None => anyhow::bail!("run_step can only be used from a step"),
+        };
+        if self.step_depth >= MAX_STEP_DEPTH {
+            anyhow::bail!("Steps are nested more than {} deep", MAX_STEP_DEPTH);
+        }
+
+        let vocab = self.options().vocab.clone();
+        let (ty, keyword, value) = vocab.split_keyword(text, current.ty);
+        let step = Step {
+            keyword: keyword.to_string(),
+            ty,
+            value: value.to_string(),
+            docstring: None,
+            table: None,
+            span: current.span,
+            position: current.position,
+        };
+        let component = self.component.with_synthetic_step(step)?;
+
+        // Swap in the nested step, as OpenContext::begin_step does
+        let parent = std::mem::replace(&mut self.component, component.clone());
+        let parent_outcome = std::mem::replace(&mut self.outcome, Outcome::undecided(component));
+        self.step_depth += 1;
+
+        let result = vocab.execute(self).await;
+
+        self.step_depth -= 1;
+        self.component = parent;
+        let mut outcome = std::mem::replace(&mut self.outcome, parent_outcome);
+        match result {
+            Ok(()) if !outcome.is_undecided() => outcome.ended = Utc::now(),
+            result => {
+                outcome.set_result(result);
+            }
+        }
+
+        // The caller gets the original error, so that it can be downcast. The nested step's own
+        // outcome keeps its message.
+        let error = if outcome.passed() {
+            None
+        } else {
+            let reason = match outcome.reason.take() {
+                Some(r) => {
+                    outcome.reason = Some(anyhow::anyhow!("{:#}", r));
+                    r.context(format!("{} {}", keyword, value))
+                }
+                None => anyhow::anyhow!("{} {}: {}", keyword, value, outcome.verdict),
+            };
+            Some(StepError {
+                verdict: outcome.verdict,
+                reason: Some(reason),
+            })
+        };
+
+        self.outcome.add_child(Arc::new(outcome));
+        match error {
+            Some(e) => Err(e.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Save a value for later steps in this scenario. `{saved:name}` in later step text is
+    /// replaced by the value.
+    pub fn remember<N: Into<String>, V: Into<String>>(&mut self, name: N, value: V) {
+        self.saved.insert(name.into(), value.into());
+    }
+
+    /// Get a value saved by [`Context::remember`]
+    pub fn recall(&self, name: &str) -> Option<&str> {
+        self.saved.get(name).map(String::as_str)
+    }
+
+    /// Note a problem without stopping the step. Unlike [`crate::warn!`], the step carries on,
+    /// and can warn more than once. If it passes, it passes with warnings, and reporters show
+    /// each warning.
+    pub fn warn<M>(&mut self, message: M)
+    where
+        M: fmt::Display + fmt::Debug + Send + Sync + 'static,
+    {
+        self.outcome.add_warning(anyhow::Error::msg(message));
+    }
+
+    /// Every value saved by [`Context::remember`], as (name, value)
+    pub fn remembered(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.saved.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Keep an error for a later step to check, instead of failing this step, for testing that
+    /// something fails. Returns the value if `result` is `Ok`. Either way, this replaces any error
+    /// captured before, so a later step sees the result of the latest attempt.
+    ///
+    /// ```ignore
+    /// #[when("I withdraw {amount} dollars")]
+    /// async fn withdraw(context: &mut Context, amount: u32) {
+    ///     let result = context.fixture::<Bank>().await.withdraw(amount).await;
+    ///     context.capture_error(result);
+    /// }
+    ///
+    /// #[then("the withdrawal is refused for insufficient funds")]
+    /// fn refused(context: &mut Context) -> anyhow::Result<()> {
+    ///     match context.captured_error().and_then(|e| e.downcast_ref::<BankError>()) {
+    ///         Some(BankError::InsufficientFunds) => Ok(()),
+    ///         other => anyhow::bail!("Expected insufficient funds, got {:?}", other),
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// With the `fixtures` feature, any step can be captured this way with
+    /// `When {step}, expecting an error`. See `fixtures::errors`.
+    pub fn capture_error<T, E>(&mut self, result: Result<T, E>) -> Option<T>
+    where
+        E: Into<anyhow::Error>,
+    {
+        match result {
+            Ok(value) => {
+                self.captured_error = None;
+                Some(value)
+            }
+            Err(e) => {
+                self.captured_error = Some(e.into());
+                None
+            }
+        }
+    }
+
+    /// The error kept by [`Context::capture_error`] in this scenario, if the latest attempt failed
+    pub fn captured_error(&self) -> Option<&anyhow::Error> {
+        self.captured_error.as_ref()
+    }
+
+    /// Take the error kept by [`Context::capture_error`], so later steps don't see it
+    pub fn take_captured_error(&mut self) -> Option<anyhow::Error> {
+        self.captured_error.take()
+    }
+
+    /// The value of a placeholder, without braces
+    fn expansion(&self, name: &str) -> Option<&String> {
+        match name.strip_prefix("saved:") {
+            Some(name) => self.saved.get(name),
+            None => self.expansions.get(name),
+        }
+    }
+
+    /// Replace `{name}` placeholders set by [`Context::set_expansion`], and `{saved:name}`
+    /// placeholders set by [`Context::remember`]. Other text in braces is
+    /// left alone. Step text is expanded automatically, but steps may want to expand docstrings or
+    /// tables too.
+    pub fn expand(&self, text: &str) -> String {
+        if self.expansions.is_empty() && self.saved.is_empty() {
+            return text.to_string();
+        }
+
+        let mut expanded = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find('{') {
+            let (before, after) = rest.split_at(start);
+            expanded.push_str(before);
+
+            let value = after
+                .find('}')
+                .and_then(|end| Some((self.expansion(&after[1..end])?, end)));
+            match value {
+                Some((value, end)) => {
+                    expanded.push_str(value);
+                    rest = &after[end + 1..];
+                }
+                None => {
+                    expanded.push('{');
+                    rest = &after[1..];
+                }
+            }
+        }
+        expanded.push_str(rest);
+        expanded
+    }
+
+    /// Give the current scenario a deadline, `timeout` from now. An earlier deadline is kept. Async
+    /// steps still running at the deadline fail, and no steps start after it. Blocking steps that
+    /// run for a long time can call [`Context::check_deadline`] periodically to stop early.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        let deadline = Instant::now() + timeout;
+        self.deadline = Some(match self.deadline {
+            Some(d) => d.min(deadline),
+            None => deadline,
+        });
+    }
+
+    /// The current scenario's deadline, if any. See [`Context::set_timeout`].
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Return a [`crate::Verdict::Failed`] error if the scenario's deadline has passed.
+    pub fn check_deadline(&self) -> Result<(), StepError> {
+        match self.deadline {
+            Some(d) if Instant::now() >= d => Err(StepError::fail_with_message("Timed out")),
+            _ => Ok(()),
+        }
+    }
+
+    /// Retry `check` every `interval` until it succeeds, for things that become true eventually,
+    /// such as a message arriving or a cache expiring. If it still fails after `timeout`, or at
+    /// the scenario's deadline, the error is its last one, with the number of attempts. Stops
+    /// early if the test run is canceled.
+    ///
+    /// ```ignore
+    /// #[then(r#"the order "{id}" is shipped"#)]
+    /// async fn the_order_is_shipped(context: &mut Context, id: String) -> anyhow::Result<()> {
+    ///     let shop = context.fixture::<Shop>().await;
+    ///     context
+    ///         .eventually(Duration::from_secs(10), Duration::from_millis(250), || async {
+    ///             let status = shop.order_status(&id).await?;
+    ///             anyhow::ensure!(status == "shipped", "The order is {}", status);
+    ///             Ok(())
+    ///         })
+    ///         .await
+    /// }
+    /// ```
+    ///
+    /// To retry a whole step instead, see `Then within {secs}s ...` in `fixtures::eventually`.
+    pub async fn eventually<T, F, Fut>(
+        &self,
+        timeout: Duration,
+        interval: Duration,
+        mut check: F,
+    ) -> anyhow::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = anyhow::Result<T>>,
+    {
+        let started = Instant::now();
+        let timeout = match started.checked_add(timeout) {
+            Some(t) => t,
+            None => anyhow::bail!("Bad timeout {:?}: too long", timeout),
+        };
+        let deadline = match self.deadline {
+            Some(d) => d.min(timeout),
+            None => timeout,
+        };
+
+        let mut attempts = 0;
+        loop {
+            self.check_canceled()?;
+            attempts += 1;
+            let error = match check().await {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(error.context(format!(
+                    "Still failing after {} attempts in {:.1}s",
+                    attempts,
+                    (now - started).as_secs_f64()
+                )));
+            }
+            task::sleep(interval.min(deadline - now)).await;
+        }
+    }
+
+    /// The time as steps and fixtures should see it: the [`MockClock`]'s time if the scenario uses
+    /// one, or else the real time. Use this instead of `Utc::now()` in code that should be testable
+    /// with a mocked clock. See [`crate::clock`].
+    pub async fn now(&self) -> DateTime<Utc> {
+        match self.try_fixture::<MockClock>().await {
+            Some(clock) => clock.now(),
+            None => Utc::now(),
+        }
+    }
+
+    /// Resolves when an async step should stop early, because the test run was canceled or the
+    /// scenario's deadline passed. Used by step macros.
+    #[doc(hidden)]
+    pub fn interrupted(&self) -> BoxFuture<'static, StepError> {
+        let canceled = self.options.canceled.clone();
+        let deadline = self.deadline;
+        async move {
+            let canceled = canceled.wait().boxed();
+            let timed_out = async move {
+                match deadline {
+                    Some(d) => task::sleep(d.saturating_duration_since(Instant::now())).await,
+                    None => future::pending().await,
+                }
+            }
+            .boxed();
+
+            match future::select(canceled, timed_out).await {
+                Either::Left(_) => StepError::cancel(),
+                Either::Right(_) => StepError::fail_with_message("Timed out"),
+            }
+        }
+        .boxed()
+    }
+
     /// Attempt to get a fixture. If the fixture is not *already* in use, this returns `None`.
     ///
     /// This function is async because it is possible for the fixture to be in the process of being
@@ -319,6 +863,18 @@ impl Context {
         .expect(not_found)
     }
 
+    /// A handle that keeps the fixture set holding `T` alive, so that a blocking step can keep
+    /// using the fixture on another thread even if the step's future is dropped. Used by step
+    /// macros.
+    #[doc(hidden)]
+    pub fn fixture_handle<T: Fixture>(&self) -> impl Send + 'static {
+        match T::SCOPE {
+            Scope::Global => self.global_fixtures.clone(),
+            Scope::Feature => self.feature_fixtures.clone(),
+            Scope::Scenario => self.scenario_fixtures.clone(),
+        }
+    }
+
     /// Activate a fixture. This must be called before `get_fixture`, etc., will
     /// work.
     pub async fn use_fixture<T: Fixture>(&mut self) -> anyhow::Result<()> {
@@ -335,6 +891,24 @@ impl Context {
         }
     }
 
+    /// Use `fixture` in place of setting one up, as if by [`Self::use_fixture`]. For testing with
+    /// fakes; see [`crate::testkit`].
+    pub(crate) async fn insert_fixture<T: Fixture>(&self, fixture: T) -> anyhow::Result<()> {
+        let set = match T::SCOPE {
+            Scope::Global => self.global_fixtures.as_ref(),
+            Scope::Feature => self.feature_fixtures.as_ref(),
+            Scope::Scenario => self.scenario_fixtures.as_ref(),
+        };
+
+        match set {
+            Some(f) => {
+                f.insert(fixture).await;
+                Ok(())
+            }
+            None => Err(anyhow::anyhow!(FixtureError::WrongScope)),
+        }
+    }
+
     /// Current scope, as it pertains to fixtures. [`Self::kind`] is finer-grained and usually what you
     /// want.
     pub fn fixture_scope(&self) -> Scope {
@@ -392,6 +966,51 @@ impl Context {
         self.component.name()
     }
 
+    /// Receive events for this context's component, and everything else in the same fixture
+    /// scope, from now on. For example, a feature-scoped fixture can subscribe in `setup()` with
+    /// `context.subscribe_events(Self::SCOPE)` to see each step of its feature finish.
+    ///
+    /// Events are buffered, so a subscriber that falls behind doesn't hold up the test run. The
+    /// stream ends once the scope finishes.
+    pub fn subscribe_events(&self, scope: Scope) -> channel::Receiver<Event> {
+        let (send, recv) = channel::unbounded();
+        let mut events = match &self.events {
+            Some(events) => events.new_receiver(),
+            None => return recv,
+        };
+
+        let component = self.component.clone();
+        let scope_kind = match scope {
+            Scope::Global => ComponentKind::Global,
+            Scope::Feature => ComponentKind::Feature,
+            Scope::Scenario => ComponentKind::Scenario,
+        };
+        let last = scope_kind.min(component.kind());
+        task::spawn(async move {
+            while let Some(event) = events.next().await {
+                if !component.shares_scope(event.component(), scope) {
+                    continue;
+                }
+                // A repeated scenario's fixtures belong to one iteration
+                let done = match &event {
+                    Event::Finished(o) | Event::IterationFinished(o) => o.kind() == last,
+                    _ => false,
+                };
+                if send.send(event).await.is_err() || done {
+                    break;
+                }
+            }
+        });
+        recv
+    }
+
+    /// Send an event to reporters. Does nothing if there is nowhere to send it.
+    pub(crate) async fn broadcast(&self, event: Event) {
+        if let Some(events) = &self.events {
+            let _ = events.broadcast(event).await;
+        }
+    }
+
     /// The in-progress outcome
     pub fn outcome(&self) -> &Outcome {
         &self.outcome
@@ -1,6 +1,7 @@
 //! An event sent to reporters
 
-use crate::component::Component;
+use crate::component::{Component, ComponentKind};
+use crate::hooks::HookOutcome;
 use crate::outcome::Outcome;
 use std::sync::Arc;
 
@@ -11,4 +12,105 @@ pub enum Event {
     Started(Arc<Component>),
     /// A component has finished.
     Finished(Arc<Outcome>),
+    /// A before/after hook function has finished running for a component.
+    HookFinished(Arc<HookOutcome>),
+    /// One run of a repeated scenario has finished. The scenario's own [`Event::Finished`] comes
+    /// after the last one, with each iteration as a child.
+    IterationFinished(Arc<Outcome>),
+}
+
+impl Event {
+    /// The component the event is about
+    pub fn component(&self) -> &Arc<Component> {
+        match self {
+            Event::Started(component) => component,
+            Event::Finished(outcome) => outcome.component(),
+            Event::HookFinished(hook) => &hook.component,
+            Event::IterationFinished(outcome) => outcome.component(),
+        }
+    }
+}
+
+/// Which events a reporter needs. See [`crate::Reporter::events`]. Reporters get only the events
+/// that match, so a reporter that only needs the final result doesn't have to wade through every
+/// step.
+///
+/// ```
+/// # use zuke::{ComponentKind, EventFilter};
+/// // Only the final result, and each feature as it finishes
+/// let filter = EventFilter::none().finished(&[ComponentKind::Global, ComponentKind::Feature]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventFilter {
+    started: u8,
+    finished: u8,
+    hooks: bool,
+    iterations: bool,
+}
+
+fn kind_bit(kind: ComponentKind) -> u8 {
+    1 << kind as u8
+}
+
+impl Default for EventFilter {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl EventFilter {
+    /// Every event
+    pub fn all() -> Self {
+        Self {
+            started: u8::MAX,
+            finished: u8::MAX,
+            hooks: true,
+            iterations: true,
+        }
+    }
+
+    /// No events. Add some with [`Self::started`], [`Self::finished`], [`Self::hooks`], and
+    /// [`Self::iterations`].
+    pub fn none() -> Self {
+        Self {
+            started: 0,
+            finished: 0,
+            hooks: false,
+            iterations: false,
+        }
+    }
+
+    /// Also pass [`Event::Started`] for these kinds of component
+    pub fn started(mut self, kinds: &[ComponentKind]) -> Self {
+        self.started |= kinds.iter().copied().map(kind_bit).fold(0, |a, b| a | b);
+        self
+    }
+
+    /// Also pass [`Event::Finished`] for these kinds of component
+    pub fn finished(mut self, kinds: &[ComponentKind]) -> Self {
+        self.finished |= kinds.iter().copied().map(kind_bit).fold(0, |a, b| a | b);
+        self
+    }
+
+    /// Pass [`Event::HookFinished`], or not
+    pub fn hooks(mut self, hooks: bool) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
+    /// Pass [`Event::IterationFinished`], or not
+    pub fn iterations(mut self, iterations: bool) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Does the reporter want this event?
+    pub fn matches(&self, event: &Event) -> bool {
+        match event {
+            Event::Started(component) => self.started & kind_bit(component.kind()) != 0,
+            Event::Finished(outcome) => self.finished & kind_bit(outcome.kind()) != 0,
+            Event::HookFinished(_) => self.hooks,
+            Event::IterationFinished(_) => self.iterations,
+        }
+    }
 }
@@ -1,6 +1,8 @@
 //! Test fixtures
 
+use crate::artifact::{Artifact, FailureArtifactProvider};
 use crate::context::Context;
+use crate::outcome::MultiError;
 use crate::panic::PanicToError;
 use async_std::channel;
 use async_std::sync::{RwLock, RwLockUpgradableReadGuard};
@@ -77,16 +79,20 @@ pub trait Fixture: Any + Send + Sync + Sized + 'static {
         Ok(())
     }
 
-    /// Called when a feature or scenario begins. This function will not be called prior to fixture
-    /// setup, so a global-level fixture will start to receive these callbacks only after it has
-    /// first been set up: scenarios that finished prior will be missed. Similarly, a feature-level
-    /// fixture will never receive a "before-feature" hook, because it had not yet been created.
+    /// Called when a feature, scenario, or step begins. This function will not be called prior to
+    /// fixture setup, so a global-level fixture will start to receive these callbacks only after
+    /// it has first been set up: scenarios that finished prior will be missed. Similarly, a
+    /// feature-level fixture will never receive a "before-feature" hook, because it had not yet
+    /// been created.
     ///
     /// To receive this hook for _all_ scenarios, create a global fixture using
     /// `ZukeBuilder::use_fixture`.
     ///
     /// Returning an error from this function will cause the component to fail, and any scenarios
     /// inside to be skipped.
+    ///
+    /// While a step is running, [`Context::outcome`] refers to the step's outcome rather than
+    /// the scenario's.
     async fn before(&self, _context: &mut Context) -> anyhow::Result<()> {
         Ok(())
     }
@@ -98,6 +104,12 @@ pub trait Fixture: Any + Send + Sync + Sized + 'static {
     async fn after(&self, _context: &mut Context) -> anyhow::Result<()> {
         Ok(())
     }
+
+    /// Fixtures that can collect artifacts when something fails, such as a screenshot, return
+    /// `Some(self)` here. See [`crate::artifact`]. Default is `None`.
+    fn artifact_provider(&self) -> Option<&dyn FailureArtifactProvider> {
+        None
+    }
 }
 
 type FixtureFuncMut = for<'a> fn(
@@ -110,6 +122,9 @@ type FixtureFunc = for<'a> fn(
     &'a mut Context,
 ) -> BoxFuture<'a, anyhow::Result<()>>;
 
+type FixtureProviderFunc =
+    for<'a> fn(&'a (dyn Any + Send + Sync + 'static)) -> Option<&'a dyn FailureArtifactProvider>;
+
 type EntryCallbackFn =
     for<'a> fn(&'a FixtureEntry, &'a mut Context) -> BoxFuture<'a, anyhow::Result<()>>;
 
@@ -126,6 +141,7 @@ struct FixtureEntry {
     teardown: FixtureFuncMut,
     before: FixtureFunc,
     after: FixtureFunc,
+    artifact_provider: FixtureProviderFunc,
 }
 
 impl FixtureEntry {
@@ -154,11 +170,19 @@ impl FixtureEntry {
             f.after(c)
         }
 
+        fn artifact_provider<F: Fixture>(
+            f: &(dyn Any + Send + Sync + 'static),
+        ) -> Option<&dyn FailureArtifactProvider> {
+            let f: &F = f.downcast_ref().expect("Internal type error");
+            f.artifact_provider()
+        }
+
         Self {
             fixture: Box::new(fixture),
             teardown: teardown::<F>,
             before: before::<F>,
             after: after::<F>,
+            artifact_provider: artifact_provider::<F>,
         }
     }
 
@@ -181,6 +205,62 @@ impl FixtureEntry {
     async fn after(&self, context: &mut Context) -> anyhow::Result<()> {
         PanicToError::from((self.after)(&*self.fixture, context)).await
     }
+
+    async fn failure_artifacts(&self, context: &mut Context) -> anyhow::Result<Vec<Artifact>> {
+        match (self.artifact_provider)(&*self.fixture) {
+            Some(provider) => PanicToError::from(provider.failure_artifacts(context)).await,
+            None => Ok(vec![]),
+        }
+    }
+}
+
+type ReplacementFn = for<'a> fn(&'a mut Context) -> BoxFuture<'a, anyhow::Result<FixtureEntry>>;
+
+/// Fixture types that are set up as other types, such as a fake database in place of a real one.
+/// See [`crate::ZukeBuilder::replace_fixture`].
+#[derive(Default, Clone)]
+pub struct FixtureReplacements {
+    setups: HashMap<TypeId, ReplacementFn>,
+}
+
+impl fmt::Debug for FixtureReplacements {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<FixtureReplacements: {}>", self.setups.len())
+    }
+}
+
+impl FixtureReplacements {
+    /// When `T` is used, set up `R` instead and convert it. The result is still a `T`, so its
+    /// scope, hooks, and teardown are those of `T`.
+    pub fn replace<T, R>(&mut self)
+    where
+        T: Fixture + From<R>,
+        R: Fixture,
+    {
+        fn setup<'a, T, R>(context: &'a mut Context) -> BoxFuture<'a, anyhow::Result<FixtureEntry>>
+        where
+            T: Fixture + From<R>,
+            R: Fixture,
+        {
+            async move {
+                let replacement = R::setup(context).await?;
+                Ok(FixtureEntry::new(T::from(replacement)))
+            }
+            .boxed()
+        }
+
+        self.setups.insert(TypeId::of::<T>(), setup::<T, R>);
+    }
+
+    /// Add the replacements in `other`, which win over any here for the same type
+    pub(crate) fn extend(&mut self, other: FixtureReplacements) {
+        self.setups.extend(other.setups);
+    }
+
+    /// Is `T` replaced by another type?
+    pub fn is_replaced<T: Fixture>(&self) -> bool {
+        self.setups.contains_key(&TypeId::of::<T>())
+    }
 }
 
 enum FixtureState {
@@ -280,6 +360,14 @@ impl FixtureSet {
         &mut *self.fixtures.get()
     }
 
+    /// Use an existing fixture, rather than setting one up. It will be torn down like any other.
+    pub async fn insert<T: Fixture>(&self, fixture: T) {
+        let _lock = self.lock.write().await;
+        let fixtures = unsafe { self.get_hash_mut() };
+        let entry = FixtureEntry::new(fixture);
+        fixtures.insert(TypeId::of::<T>(), FixtureState::Ready(Box::pin(entry)));
+    }
+
     /// Activate a fixture.
     pub async fn activate<T: Fixture>(&self, context: &mut Context) -> anyhow::Result<()> {
         let lock = self.lock.upgradable_read().await;
@@ -326,7 +414,7 @@ impl FixtureSet {
     /// Tear down all fixtures in this scope.
     pub async fn teardown(&mut self, context: &mut Context) -> anyhow::Result<()> {
         // no locking required due to &mut self
-        let mut errors = vec![];
+        let mut errors = MultiError::new();
         let fixtures = self.fixtures.get_mut();
 
         for fixture in fixtures.values_mut() {
@@ -343,14 +431,7 @@ impl FixtureSet {
             }
         }
 
-        if errors.is_empty() {
-            Ok(())
-        } else if errors.len() == 1 {
-            Err(errors.drain(..).next().unwrap())
-        } else {
-            // todo pass them all along
-            Err(anyhow::anyhow!("Multiple errors in teardown"))
-        }
+        errors.into_result()
     }
 
     /// Call all before hooks in this scope
@@ -365,19 +446,57 @@ impl FixtureSet {
             .await
     }
 
+    /// Collect artifacts from every fixture in this scope that provides them. A fixture that
+    /// fails to provide them doesn't stop the others.
+    pub async fn failure_artifacts(&self, context: &mut Context) -> (Vec<Artifact>, MultiError) {
+        let mut artifacts = vec![];
+        let mut errors = MultiError::new();
+        let fixtures = unsafe { self.get_hash() }; // only use with lock held
+
+        let keys: Vec<TypeId> = {
+            let _lock = self.lock.read().await;
+            fixtures.keys().map(Clone::clone).collect()
+        };
+
+        for id in keys {
+            let fut = {
+                let _lock = self.lock.read().await;
+                match fixtures.get(&id).unwrap() {
+                    FixtureState::Ready(entry) => entry.failure_artifacts(context),
+                    _ => continue,
+                }
+            };
+
+            match fut.await {
+                Ok(a) => artifacts.extend(a),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        (artifacts, errors)
+    }
+
     async fn create_fixture<T: Fixture>(
         &self,
         context: &mut Context,
     ) -> anyhow::Result<FixtureEntry> {
-        let fixture = T::setup(context).await?;
-        Ok(FixtureEntry::new(fixture))
+        let replacement = context
+            .options()
+            .fixture_replacements
+            .setups
+            .get(&TypeId::of::<T>())
+            .copied();
+        match replacement {
+            Some(setup) => setup(context).await,
+            None => Ok(FixtureEntry::new(T::setup(context).await?)),
+        }
     }
 
     async fn for_each_fixture<F>(&self, callback: F, context: &mut Context) -> anyhow::Result<()>
     where
         F: for<'a> Fn(&'a FixtureEntry, &'a mut Context) -> BoxFuture<'a, anyhow::Result<()>>,
     {
-        let mut result = Ok(());
+        let mut errors = MultiError::new();
         let fixtures = unsafe { self.get_hash() }; // only use with lock held
 
         // we only promise that fixtures will see components after they have been set up. That
@@ -399,12 +518,11 @@ impl FixtureSet {
                 }
             };
 
-            // TODO: handle multiple errors better
             if let Err(e) = fut.await {
-                result = Err(e)
+                errors.push(e);
             }
         }
 
-        result
+        errors.into_result()
     }
 }
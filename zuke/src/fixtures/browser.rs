@@ -0,0 +1,422 @@
+//! A web browser driven over WebDriver, with steps for navigating and checking pages. Requires
+//! the `browser` feature.
+//!
+//! The WebDriver client runs on tokio, so the `browser` feature also turns on async-std's `tokio1`
+//! feature, which gives it a tokio runtime to run on.
+
+use crate::artifact::{Artifact, FailureArtifactProvider};
+use crate::flag::Flag;
+use crate::{given, then, when, Context, Fixture, Scope};
+use anyhow::Context as _;
+use async_std::task;
+use async_trait::async_trait;
+use fantoccini::elements::Element;
+use fantoccini::{Client, ClientBuilder, Locator};
+use futures::future::FutureExt;
+use serde_json::{Map, Value};
+use std::ops::Deref;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// The environment variable checked for the WebDriver URL if there is no `webdriver-url` setting
+pub const WEBDRIVER_URL_VAR: &str = "WEBDRIVER_URL";
+
+/// Where to find WebDriver if it isn't configured: `chromedriver --port=4444`, `geckodriver`, or
+/// Selenium's default
+pub const DEFAULT_WEBDRIVER_URL: &str = "http://localhost:4444";
+
+/// How long to wait for elements and text if `browser-timeout` isn't set
+pub const DEFAULT_BROWSER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often to check the page while waiting for text
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A WebDriver session. Used by [`Browser`] and [`FeatureBrowser`]; steps use whichever the
+/// `browser-scope` setting chooses.
+///
+/// Elements are found by CSS selector. Finding an element waits for it to appear, up to the
+/// `browser-timeout`, since pages tend to fill in after they load.
+pub struct BrowserSession {
+    client: Client,
+    base_url: Option<Url>,
+    timeout: Duration,
+    closed: Flag,
+}
+
+impl BrowserSession {
+    /// Start a session, configured from the settings. If the test run is aborted, so that
+    /// fixtures aren't torn down, the session is still closed, as best it can be.
+    pub async fn connect(context: &Context) -> anyhow::Result<Self> {
+        let options = context.options();
+        let webdriver = match options.setting_as::<String>("webdriver-url")? {
+            Some(url) => url,
+            None => std::env::var(WEBDRIVER_URL_VAR)
+                .unwrap_or_else(|_| String::from(DEFAULT_WEBDRIVER_URL)),
+        };
+        let capabilities = options
+            .setting_as::<Map<String, Value>>("webdriver-capabilities")?
+            .unwrap_or_default();
+        let timeout = match options.setting_as::<f64>("browser-timeout")? {
+            Some(secs) => Duration::try_from_secs_f64(secs).with_context(|| {
+                format!("Bad setting browser-timeout = {}: expected seconds", secs)
+            })?,
+            None => DEFAULT_BROWSER_TIMEOUT,
+        };
+        let base_url = match options.setting_as::<String>("base-url")? {
+            Some(url) => Some(Url::parse(&url).with_context(|| format!("Bad URL {:?}", url))?),
+            None => None,
+        };
+
+        let client = ClientBuilder::native()
+            .capabilities(capabilities)
+            .connect(&webdriver)
+            .await
+            .with_context(|| format!("Could not start a WebDriver session at {}", webdriver))?;
+
+        let closed = Flag::new();
+        let aborted = options.aborted.clone();
+        let (watched, done) = (client.clone(), closed.clone());
+        task::spawn(async move {
+            futures::select! {
+                () = aborted.wait().fuse() => {
+                    let _ = watched.close().await;
+                }
+                () = done.wait().fuse() => (),
+            }
+        });
+
+        Ok(Self {
+            client,
+            base_url,
+            timeout,
+            closed,
+        })
+    }
+
+    /// The WebDriver client, for anything the steps don't cover
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Resolve a URL against the `base-url` setting
+    pub fn url(&self, url: &str) -> anyhow::Result<Url> {
+        let parsed = match &self.base_url {
+            Some(base) => base.join(url),
+            None => Url::parse(url),
+        };
+        parsed.with_context(|| format!("Bad URL {:?}", url))
+    }
+
+    /// Load a page
+    pub async fn goto(&self, url: &str) -> anyhow::Result<()> {
+        let url = self.url(url)?;
+        self.client
+            .goto(url.as_str())
+            .await
+            .with_context(|| format!("Could not load {}", url))
+    }
+
+    /// Wait for an element to appear
+    pub async fn find(&self, locator: Locator<'_>) -> anyhow::Result<Element> {
+        let description = format!("{:?}", locator);
+        self.client
+            .wait()
+            .at_most(self.timeout)
+            .for_element(locator)
+            .await
+            .with_context(|| format!("No element {}", description))
+    }
+
+    /// Wait for an element matching a CSS selector to appear
+    pub async fn find_css(&self, selector: &str) -> anyhow::Result<Element> {
+        self.find(Locator::Css(selector)).await
+    }
+
+    /// Check `condition` until it holds, or the timeout passes. On timeout, the error is the
+    /// last one `condition` returned.
+    pub async fn eventually<'a, F, Fut>(&'a self, mut condition: F) -> anyhow::Result<()>
+    where
+        F: FnMut(&'a Self) -> Fut,
+        Fut: std::future::Future<Output = anyhow::Result<()>>,
+    {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            match condition(self).await {
+                Ok(()) => return Ok(()),
+                Err(e) if Instant::now() >= deadline => return Err(e),
+                Err(_) => task::sleep(POLL_INTERVAL).await,
+            }
+        }
+    }
+
+    /// End the session
+    pub async fn close(&self) -> anyhow::Result<()> {
+        if self.closed.is_set() {
+            return Ok(());
+        }
+        self.closed.set();
+        self.client
+            .clone()
+            .close()
+            .await
+            .context("Could not close the WebDriver session")
+    }
+}
+
+#[async_trait]
+impl FailureArtifactProvider for BrowserSession {
+    /// A screenshot and the page source, as they were when the step failed
+    async fn failure_artifacts(&self, _context: &mut Context) -> anyhow::Result<Vec<Artifact>> {
+        let screenshot = self.client.screenshot().await?;
+        let source = self.client.source().await?;
+        Ok(vec![
+            Artifact::png("Screenshot", screenshot),
+            Artifact::html("Page source", source),
+        ])
+    }
+}
+
+/// A browser for each scenario. Provides these steps, which find elements by CSS selector:
+///
+/// - `Given I am on {url}` or `When I go to {url}`: Load a page. Relative URLs are joined onto
+///   the `base-url` setting, as for the `Http` fixture.
+/// - `When I click "{selector}"`
+/// - `When I click the link "{text}"`
+/// - `When I fill in "{selector}" with "{value}"`
+/// - `When I fill in the form`: Fill in each field of a data table with two columns, a selector
+///   and a value.
+/// - `Then the page title is "{title}"`
+/// - `Then the page contains "{text}"`
+/// - `Then the element "{selector}" contains "{text}"`
+/// - `Then the URL ends with "{text}"`
+///
+/// `Then` steps wait, up to the `browser-timeout`, for the page to catch up. When a step fails,
+/// a screenshot and the page source are kept as [failure artifacts](crate::artifact).
+///
+/// Settings:
+///
+/// ```toml
+/// [settings]
+/// webdriver-url = "http://localhost:4444"   # or the WEBDRIVER_URL environment variable
+/// browser-scope = "feature"                 # share a browser between a feature's scenarios
+/// browser-timeout = 10                      # seconds; default 5
+///
+/// [settings.webdriver-capabilities]
+/// browserName = "chrome"
+/// "goog:chromeOptions" = { args = ["--headless"] }
+/// ```
+///
+/// The session is closed when the scenario ends.
+pub struct Browser(BrowserSession);
+
+/// As [`Browser`], but shared by the scenarios of a feature, for suites where starting a browser
+/// for each scenario is too slow. Steps use it when the `browser-scope` setting is `feature`.
+/// Scenarios see what the scenarios before them left behind, such as cookies.
+pub struct FeatureBrowser(BrowserSession);
+
+impl Deref for Browser {
+    type Target = BrowserSession;
+
+    fn deref(&self) -> &BrowserSession {
+        &self.0
+    }
+}
+
+impl Deref for FeatureBrowser {
+    type Target = BrowserSession;
+
+    fn deref(&self) -> &BrowserSession {
+        &self.0
+    }
+}
+
+#[async_trait]
+impl Fixture for Browser {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self(BrowserSession::connect(context).await?))
+    }
+
+    async fn teardown(&mut self, _context: &mut Context) -> anyhow::Result<()> {
+        self.0.close().await
+    }
+
+    fn artifact_provider(&self) -> Option<&dyn FailureArtifactProvider> {
+        Some(&self.0)
+    }
+}
+
+#[async_trait]
+impl Fixture for FeatureBrowser {
+    const SCOPE: Scope = Scope::Feature;
+
+    async fn setup(context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self(BrowserSession::connect(context).await?))
+    }
+
+    async fn teardown(&mut self, _context: &mut Context) -> anyhow::Result<()> {
+        self.0.close().await
+    }
+
+    fn artifact_provider(&self) -> Option<&dyn FailureArtifactProvider> {
+        Some(&self.0)
+    }
+}
+
+/// The browser for steps, at the scope chosen by the `browser-scope` setting
+async fn browser(context: &mut Context) -> anyhow::Result<&BrowserSession> {
+    let scope = context.options().setting_as::<String>("browser-scope")?;
+    match scope.as_deref() {
+        None | Some("scenario") => {
+            context.use_fixture::<Browser>().await?;
+            Ok(&context.fixture::<Browser>().await.0)
+        }
+        Some("feature") => {
+            context.use_fixture::<FeatureBrowser>().await?;
+            Ok(&context.fixture::<FeatureBrowser>().await.0)
+        }
+        Some(other) => anyhow::bail!(
+            "Bad setting browser-scope = {:?}: expected \"scenario\" or \"feature\"",
+            other
+        ),
+    }
+}
+
+#[given("I am on {url}")]
+async fn i_am_on(context: &mut Context, url: String) -> anyhow::Result<()> {
+    browser(context).await?.goto(&url).await
+}
+
+#[when("I go to {url}")]
+async fn i_go_to(context: &mut Context, url: String) -> anyhow::Result<()> {
+    browser(context).await?.goto(&url).await
+}
+
+#[when(r#"I click "{selector}""#)]
+async fn i_click(context: &mut Context, selector: String) -> anyhow::Result<()> {
+    let element = browser(context).await?.find_css(&selector).await?;
+    element
+        .click()
+        .await
+        .with_context(|| format!("Could not click {:?}", selector))
+}
+
+#[when(r#"I click the link "{text}""#)]
+async fn i_click_the_link(context: &mut Context, text: String) -> anyhow::Result<()> {
+    let element = browser(context)
+        .await?
+        .find(Locator::LinkText(&text))
+        .await?;
+    element
+        .click()
+        .await
+        .with_context(|| format!("Could not click the link {:?}", text))
+}
+
+async fn fill_in(browser: &BrowserSession, selector: &str, value: &str) -> anyhow::Result<()> {
+    let element = browser.find_css(selector).await?;
+    element.clear().await?;
+    element
+        .send_keys(value)
+        .await
+        .with_context(|| format!("Could not fill in {:?}", selector))
+}
+
+#[when(r#"I fill in "{selector}" with "{value}""#)]
+async fn i_fill_in(context: &mut Context, selector: String, value: String) -> anyhow::Result<()> {
+    fill_in(browser(context).await?, &selector, &value).await
+}
+
+#[when("I fill in the form")]
+async fn i_fill_in_the_form(context: &mut Context) -> anyhow::Result<()> {
+    let rows: Vec<Vec<String>> = match context.step().and_then(|s| s.table.as_ref()) {
+        Some(table) => table
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|cell| context.expand(cell)).collect())
+            .collect(),
+        None => anyhow::bail!("Expected a table of selectors and values"),
+    };
+
+    let browser = browser(context).await?;
+    for row in rows {
+        match row.as_slice() {
+            [selector, value] => fill_in(browser, selector, value).await?,
+            _ => anyhow::bail!("Expected a selector and a value, not {:?}", row),
+        }
+    }
+    Ok(())
+}
+
+#[then(r#"the page title is "{title}""#)]
+async fn the_page_title_is(context: &mut Context, title: String) -> anyhow::Result<()> {
+    let browser = browser(context).await?;
+    let title = title.as_str();
+    browser
+        .eventually(|b| async move {
+            let actual = b.client().title().await?;
+            anyhow::ensure!(
+                actual == title,
+                "Expected the title {:?}, got {:?}",
+                title,
+                actual
+            );
+            Ok(())
+        })
+        .await
+}
+
+#[then(r#"the page contains "{text}""#)]
+async fn the_page_contains(context: &mut Context, text: String) -> anyhow::Result<()> {
+    let browser = browser(context).await?;
+    let text = text.as_str();
+    browser
+        .eventually(|b| async move {
+            let body = b.find_css("body").await?.text().await?;
+            anyhow::ensure!(body.contains(text), "{:?} is not on the page", text);
+            Ok(())
+        })
+        .await
+}
+
+#[then(r#"the element "{selector}" contains "{text}""#)]
+async fn the_element_contains(
+    context: &mut Context,
+    selector: String,
+    text: String,
+) -> anyhow::Result<()> {
+    let browser = browser(context).await?;
+    let (selector, text) = (selector.as_str(), text.as_str());
+    browser
+        .eventually(|b| async move {
+            let actual = b.find_css(selector).await?.text().await?;
+            anyhow::ensure!(
+                actual.contains(text),
+                "Expected {:?} to contain {:?}, but it has {:?}",
+                selector,
+                text,
+                actual
+            );
+            Ok(())
+        })
+        .await
+}
+
+#[then(r#"the URL ends with "{text}""#)]
+async fn the_url_ends_with(context: &mut Context, text: String) -> anyhow::Result<()> {
+    let browser = browser(context).await?;
+    let text = text.as_str();
+    browser
+        .eventually(|b| async move {
+            let url = b.client().current_url().await?;
+            anyhow::ensure!(
+                url.as_str().ends_with(text),
+                "Expected the URL to end with {:?}, but it is {}",
+                text,
+                url
+            );
+            Ok(())
+        })
+        .await
+}
@@ -0,0 +1,69 @@
+//! Steps for controlling the time. See [`crate::clock`].
+//!
+//! - `Given the clock is stopped`: Stop the clock at the current time.
+//! - `Given the time is "{time}"`: Stop the clock at an RFC 3339 time, such as
+//!   `2021-06-01T12:00:00Z`.
+//! - `When {amount} {unit} pass`, such as `When 3 hours pass` or `When 1 day passes`: Move the
+//!   clock forward. The units are seconds, minutes, hours, days, and weeks.
+//! - `Then the time is "{time}"`
+//!
+//! Each of these uses the [`MockClock`](crate::MockClock), so [`Context::now`] sees the change.
+
+use crate::clock::MockClock;
+use crate::{given, then, when, Context};
+use anyhow::Context as _;
+use chrono::{DateTime, Duration, Utc};
+
+async fn clock(context: &mut Context) -> anyhow::Result<&MockClock> {
+    context.use_fixture::<MockClock>().await?;
+    Ok(context.fixture::<MockClock>().await)
+}
+
+fn parse_time(time: &str) -> anyhow::Result<DateTime<Utc>> {
+    let parsed = DateTime::parse_from_rfc3339(time)
+        .with_context(|| format!("{:?} is not an RFC 3339 time", time))?;
+    Ok(parsed.with_timezone(&Utc))
+}
+
+#[given("the clock is stopped")]
+async fn the_clock_is_stopped(context: &mut Context) -> anyhow::Result<()> {
+    clock(context).await?;
+    Ok(())
+}
+
+#[given(r#"the time is "{time}""#)]
+async fn the_time_is(context: &mut Context, time: String) -> anyhow::Result<()> {
+    let time = parse_time(&time)?;
+    clock(context).await?.set(time);
+    Ok(())
+}
+
+#[when(
+    regex,
+    r"(?P<amount>\d+) (?P<unit>seconds?|minutes?|hours?|days?|weeks?) pass(?:es)?"
+)]
+async fn time_passes(context: &mut Context, amount: i64, unit: String) -> anyhow::Result<()> {
+    let duration = match unit.trim_end_matches('s') {
+        "second" => Duration::seconds(amount),
+        "minute" => Duration::minutes(amount),
+        "hour" => Duration::hours(amount),
+        "day" => Duration::days(amount),
+        "week" => Duration::weeks(amount),
+        _ => anyhow::bail!("Unknown unit {:?}", unit),
+    };
+    clock(context).await?.advance(duration);
+    Ok(())
+}
+
+#[then(r#"the time is "{time}""#)]
+async fn then_the_time_is(context: &mut Context, time: String) -> anyhow::Result<()> {
+    let expected = parse_time(&time)?;
+    let actual = context.now().await;
+    anyhow::ensure!(
+        actual == expected,
+        "Expected the time to be {}, but it is {}",
+        expected.to_rfc3339(),
+        actual.to_rfc3339()
+    );
+    Ok(())
+}
@@ -0,0 +1,171 @@
+//! Running programs from steps, for black-box testing of command line tools
+
+use crate::{then, when, Context, Fixture, Scope};
+use anyhow::Context as _;
+use async_std::process;
+use async_trait::async_trait;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::Duration;
+
+/// The result of running a command
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    /// How the command exited
+    pub status: ExitStatus,
+    /// Everything the command wrote to stdout
+    pub stdout: String,
+    /// Everything the command wrote to stderr
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    /// The exit code, or `None` if the command was killed by a signal
+    pub fn code(&self) -> Option<i32> {
+        self.status.code()
+    }
+}
+
+/// Runs programs for a scenario and keeps the output of the last one. Provides these steps:
+///
+/// - ``When I run `program args...` ``: Arguments are split as in a POSIX shell, but there is
+///   no shell: pipes, redirection, and variables aren't available.
+/// - `Then the exit code is {code}`
+/// - `Then stdout contains "{text}"`
+/// - `Then stderr contains "{text}"`
+///
+/// A command that is still running when the scenario is canceled or times out is killed. Use
+/// [`Command::set_timeout`] to limit individual commands.
+#[derive(Default)]
+pub struct Command {
+    current_dir: Option<PathBuf>,
+    env: Vec<(OsString, OsString)>,
+    timeout: Option<Duration>,
+    output: Option<CommandOutput>,
+}
+
+impl Command {
+    /// Run later commands in `dir`. The default is Zuke's own working directory.
+    pub fn set_current_dir<P: AsRef<Path>>(&mut self, dir: P) {
+        self.current_dir = Some(dir.as_ref().to_path_buf());
+    }
+
+    /// Set an environment variable for later commands
+    pub fn set_env<K: AsRef<OsStr>, V: AsRef<OsStr>>(&mut self, key: K, value: V) {
+        self.env
+            .push((key.as_ref().to_os_string(), value.as_ref().to_os_string()));
+    }
+
+    /// Kill later commands that run longer than `timeout`, and fail the step that ran them.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// The output of the last command, if any
+    pub fn output(&self) -> Option<&CommandOutput> {
+        self.output.as_ref()
+    }
+
+    /// Run a program to completion and capture its output. `args` includes the program name. An
+    /// error means the program couldn't be run or timed out; a nonzero exit code is not an error.
+    pub async fn run<I, S>(&mut self, args: I) -> anyhow::Result<&CommandOutput>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.output = None;
+
+        let mut args = args.into_iter();
+        let program = match args.next() {
+            Some(p) => p.as_ref().to_os_string(),
+            None => anyhow::bail!("No command given"),
+        };
+
+        let mut command = process::Command::new(&program);
+        command
+            .args(args)
+            .stdin(process::Stdio::null())
+            .kill_on_drop(true);
+        if let Some(dir) = &self.current_dir {
+            command.current_dir(dir);
+        }
+        for (key, value) in &self.env {
+            command.env(key, value);
+        }
+
+        let output = command.output();
+        let output = match self.timeout {
+            Some(timeout) => async_std::future::timeout(timeout, output)
+                .await
+                .map_err(|_| anyhow::anyhow!("Command timed out after {:?}", timeout))?,
+            None => output.await,
+        };
+        let output =
+            output.with_context(|| format!("Could not run {}", program.to_string_lossy()))?;
+
+        Ok(self.output.insert(CommandOutput {
+            status: output.status,
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }))
+    }
+}
+
+#[async_trait]
+impl Fixture for Command {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self::default())
+    }
+}
+
+/// The output of the last command, for `Then` steps
+async fn last_output(context: &Context) -> anyhow::Result<&CommandOutput> {
+    match context.try_fixture::<Command>().await {
+        Some(command) => command.output().context("The last command didn't finish"),
+        None => anyhow::bail!("No command has been run"),
+    }
+}
+
+#[when("I run `{command}`")]
+async fn i_run(context: &mut Context, command: String) -> anyhow::Result<()> {
+    let args = shell_words::split(&command)?;
+    context.use_fixture::<Command>().await?;
+    context.fixture_mut::<Command>().await.run(args).await?;
+    Ok(())
+}
+
+#[then(r"the exit code is {code:-?\d+}")]
+async fn the_exit_code_is(context: &mut Context, code: i32) -> anyhow::Result<()> {
+    let output = last_output(context).await?;
+    if output.code() != Some(code) {
+        anyhow::bail!(
+            "Expected exit code {}, got {}\nstdout:\n{}\nstderr:\n{}",
+            code,
+            output.status,
+            output.stdout,
+            output.stderr,
+        );
+    }
+    Ok(())
+}
+
+#[then(r#"stdout contains "{text}""#)]
+async fn stdout_contains(context: &mut Context, text: String) -> anyhow::Result<()> {
+    let output = last_output(context).await?;
+    if !output.stdout.contains(&text) {
+        anyhow::bail!("{:?} not found in stdout:\n{}", text, output.stdout);
+    }
+    Ok(())
+}
+
+#[then(r#"stderr contains "{text}""#)]
+async fn stderr_contains(context: &mut Context, text: String) -> anyhow::Result<()> {
+    let output = last_output(context).await?;
+    if !output.stderr.contains(&text) {
+        anyhow::bail!("{:?} not found in stderr:\n{}", text, output.stderr);
+    }
+    Ok(())
+}
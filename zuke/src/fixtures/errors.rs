@@ -0,0 +1,77 @@
+//! Steps for testing that something fails. See [`Context::capture_error`].
+//!
+//! - `{step}, expecting an error`, with any keyword: Run the step, and keep its failure for later
+//!   steps instead of failing the scenario. Fails if the step passes. A step that is pending,
+//!   skipped, or canceled isn't captured, and neither is a step that doesn't match exactly one
+//!   implementation, such as a misspelled one.
+//! - `Then it fails mentioning "{text}"`: The captured error mentions `text`.
+//! - `Then it does not fail`: No error was captured, or the latest attempt passed.
+//!
+//! ```gherkin
+//! Scenario: Overdrawing
+//!     Given an account with 100 dollars
+//!     When I withdraw 500 dollars, expecting an error
+//!     Then it fails mentioning "insufficient funds"
+//! ```
+
+use crate::vocab::Error as VocabError;
+use crate::{step, then, Context, StepError, Verdict};
+
+#[step("{step}, expecting an error")]
+async fn expecting_an_error(context: &mut Context, step: String) -> anyhow::Result<()> {
+    let (children, verdict) = (context.outcome().children.len(), context.outcome().verdict);
+    let error = match context.run_step(&step).await {
+        Ok(()) => anyhow::bail!("Expected an error, but {:?} passed", step),
+        Err(e) => e,
+    };
+    match error.downcast_ref::<StepError>() {
+        Some(e) if e.verdict != Verdict::Failed => return Err(error),
+        // A step that couldn't be found or called didn't fail the way the scenario expects
+        Some(e) if e.reason.as_ref().map_or(false, is_vocab_error) => return Err(error),
+        _ => (),
+    }
+
+    // The failure is expected, so it doesn't count against this step
+    let outcome = context.outcome_mut();
+    outcome.children.truncate(children);
+    outcome.verdict = verdict;
+    context.capture_error(Err::<(), _>(error));
+    Ok(())
+}
+
+fn is_vocab_error(e: &anyhow::Error) -> bool {
+    matches!(
+        e.downcast_ref::<VocabError>(),
+        Some(
+            VocabError::NoMatch { .. }
+                | VocabError::MultipleMatches { .. }
+                | VocabError::KeywordMismatch { .. }
+                | VocabError::BadParameters
+        )
+    )
+}
+
+#[then(r#"it fails mentioning "{text}""#)]
+fn it_fails_mentioning(context: &mut Context, text: String) -> anyhow::Result<()> {
+    match context.captured_error() {
+        Some(e) => {
+            let message = format!("{:#}", e);
+            anyhow::ensure!(
+                message.contains(&text),
+                "{:?} not found in {:?}",
+                text,
+                message
+            );
+            Ok(())
+        }
+        None => anyhow::bail!("No error was captured"),
+    }
+}
+
+#[then("it does not fail")]
+fn it_does_not_fail(context: &mut Context) -> anyhow::Result<()> {
+    match context.captured_error() {
+        Some(e) => anyhow::bail!("Expected no error, but got: {:#}", e),
+        None => Ok(()),
+    }
+}
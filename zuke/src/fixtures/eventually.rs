@@ -0,0 +1,60 @@
+//! Retrying steps until they pass. See [`Context::eventually`].
+//!
+//! - `Then within {secs}s {step}`, such as `Then within 5s the order is shipped`: Run the step
+//!   until it passes, every 100 ms, for up to `secs` seconds. Only failures are retried; a step
+//!   that is pending, skipped, or canceled stops at once. On timeout, the step fails with the last
+//!   failure and the number of attempts.
+//!
+//! Only the last attempt is kept in the outcome.
+
+use crate::{then, Context, StepError, Verdict};
+use anyhow::Context as _;
+use async_std::task;
+use std::time::{Duration, Instant};
+
+/// How long to wait between attempts
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[then(r#"within {secs:\d+(?:\.\d+)?}s {step}"#)]
+async fn within(context: &mut Context, secs: f64, step: String) -> anyhow::Result<()> {
+    let started = Instant::now();
+    let timeout = Duration::try_from_secs_f64(secs)
+        .ok()
+        .and_then(|t| started.checked_add(t))
+        .with_context(|| format!("Bad timeout {} s: too long", secs))?;
+    let deadline = match context.deadline() {
+        Some(d) => d.min(timeout),
+        None => timeout,
+    };
+
+    let mut attempts = 0;
+    loop {
+        context.check_canceled()?;
+        attempts += 1;
+        let (children, verdict) = (context.outcome().children.len(), context.outcome().verdict);
+        let error = match context.run_step(&step).await {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+
+        let retry = match error.downcast_ref::<StepError>() {
+            Some(e) => e.verdict == Verdict::Failed,
+            None => true,
+        };
+        let now = Instant::now();
+        if !retry {
+            return Err(error);
+        } else if now >= deadline {
+            return Err(error.context(format!(
+                "Still failing after {} attempts in {:.1}s",
+                attempts,
+                (now - started).as_secs_f64()
+            )));
+        }
+
+        let outcome = context.outcome_mut();
+        outcome.children.truncate(children);
+        outcome.verdict = verdict;
+        task::sleep(POLL_INTERVAL.min(deadline - now)).await;
+    }
+}
@@ -0,0 +1,102 @@
+//! Steps for setting up and checking files. Relative paths are in the scenario's [`TempDir`].
+//!
+//! - `Given a file "{path}" containing`, with the contents as a docstring
+//! - `Given an empty file "{path}"`
+//! - `Given a directory "{path}"`
+//! - `Then the file "{path}" exists`
+//! - `Then the file "{path}" does not exist`
+//! - `Then the file "{path}" contains "{text}"`
+//! - `Then the file "{path}" contains`, with the expected text as a docstring
+//!
+//! Missing parent directories are created as needed.
+
+use crate::fixtures::TempDir;
+use crate::{given, then, Context};
+use anyhow::Context as _;
+use std::path::PathBuf;
+
+/// Resolve a path from a step, relative to the scenario's temporary directory
+async fn resolve(context: &mut Context, path: &str) -> anyhow::Result<PathBuf> {
+    context.use_fixture::<TempDir>().await?;
+    Ok(context.fixture::<TempDir>().await.join(path))
+}
+
+/// The step's docstring, with expansions
+fn docstring(context: &Context) -> anyhow::Result<String> {
+    match context.step().and_then(|s| s.docstring.as_ref()) {
+        Some(d) => Ok(context.expand(d)),
+        None => anyhow::bail!("Expected a docstring"),
+    }
+}
+
+/// Create a file and any missing parent directories
+fn write_file(path: PathBuf, contents: &str) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Could not create {}", parent.display()))?;
+    }
+    std::fs::write(&path, contents).with_context(|| format!("Could not write {}", path.display()))
+}
+
+/// Read a file that should exist
+fn read_file(path: PathBuf) -> anyhow::Result<String> {
+    std::fs::read_to_string(&path).with_context(|| format!("Could not read {}", path.display()))
+}
+
+#[given(r#"a file "{path}" containing"#)]
+async fn a_file_containing(context: &mut Context, path: String) -> anyhow::Result<()> {
+    let contents = docstring(context)?;
+    write_file(resolve(context, &path).await?, &contents)
+}
+
+#[given(r#"an empty file "{path}""#)]
+async fn an_empty_file(context: &mut Context, path: String) -> anyhow::Result<()> {
+    write_file(resolve(context, &path).await?, "")
+}
+
+#[given(r#"a directory "{path}""#)]
+async fn a_directory(context: &mut Context, path: String) -> anyhow::Result<()> {
+    let path = resolve(context, &path).await?;
+    std::fs::create_dir_all(&path).with_context(|| format!("Could not create {}", path.display()))
+}
+
+#[then(r#"the file "{path}" exists"#)]
+async fn the_file_exists(context: &mut Context, path: String) -> anyhow::Result<()> {
+    let resolved = resolve(context, &path).await?;
+    if !resolved.exists() {
+        anyhow::bail!("{} does not exist", path);
+    }
+    Ok(())
+}
+
+#[then(r#"the file "{path}" does not exist"#)]
+async fn the_file_does_not_exist(context: &mut Context, path: String) -> anyhow::Result<()> {
+    let resolved = resolve(context, &path).await?;
+    if resolved.exists() {
+        anyhow::bail!("{} exists", path);
+    }
+    Ok(())
+}
+
+#[then(r#"the file "{path}" contains "{text}""#)]
+async fn the_file_contains_text(
+    context: &mut Context,
+    path: String,
+    text: String,
+) -> anyhow::Result<()> {
+    let contents = read_file(resolve(context, &path).await?)?;
+    if !contents.contains(&text) {
+        anyhow::bail!("{:?} not found in {}:\n{}", text, path, contents);
+    }
+    Ok(())
+}
+
+#[then(r#"the file "{path}" contains"#)]
+async fn the_file_contains(context: &mut Context, path: String) -> anyhow::Result<()> {
+    let text = docstring(context)?;
+    let contents = read_file(resolve(context, &path).await?)?;
+    if !contents.contains(&text) {
+        anyhow::bail!("{:?} not found in {}:\n{}", text, path, contents);
+    }
+    Ok(())
+}
@@ -0,0 +1,202 @@
+//! An HTTP client for API testing. Requires the `http` feature.
+
+use crate::{then, when, Context, Fixture, Scope};
+use anyhow::Context as _;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::str::FromStr;
+use surf::http::Method;
+use surf::Url;
+
+/// A response received by [`Http`]
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    /// The status code
+    pub status: u16,
+    /// Response headers, by lowercase name
+    pub headers: Vec<(String, String)>,
+    /// The body, decoded as UTF-8
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// The first value of a header, if present
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Parse the body as JSON
+    pub fn json(&self) -> anyhow::Result<Value> {
+        serde_json::from_str(&self.body).context("The response body is not JSON")
+    }
+}
+
+/// An HTTP client for a scenario that keeps the last response. Provides these steps:
+///
+/// - `When I GET {url}`, and likewise for `POST`, `PUT`, `PATCH`, `DELETE`, and `HEAD`. A
+///   docstring, if given, is sent as the request body.
+/// - `Then the response status is {code}`
+/// - `Then the response JSON at {pointer} equals {value}`: `pointer` is a [JSON pointer][1] such
+///   as `/items/0/name`, and `value` is JSON, such as `"widget"` or `42`.
+///
+/// Relative URLs are joined onto the `base-url` setting from the config file, if any:
+///
+/// ```toml
+/// [settings]
+/// base-url = "http://localhost:8080/api/"
+/// ```
+///
+/// [1]: https://datatracker.ietf.org/doc/html/rfc6901
+pub struct Http {
+    client: surf::Client,
+    base_url: Option<Url>,
+    headers: Vec<(String, String)>,
+    response: Option<HttpResponse>,
+}
+
+impl Http {
+    /// Join relative URLs onto `url`
+    pub fn set_base_url(&mut self, url: &str) -> anyhow::Result<()> {
+        self.base_url = Some(Url::parse(url).with_context(|| format!("Bad URL {:?}", url))?);
+        Ok(())
+    }
+
+    /// Send a header with every later request
+    pub fn set_header<N: Into<String>, V: Into<String>>(&mut self, name: N, value: V) {
+        self.headers.push((name.into(), value.into()));
+    }
+
+    /// The last response, if any
+    pub fn response(&self) -> Option<&HttpResponse> {
+        self.response.as_ref()
+    }
+
+    /// Resolve a URL against the base URL
+    pub fn url(&self, url: &str) -> anyhow::Result<Url> {
+        let parsed = match &self.base_url {
+            Some(base) => base.join(url),
+            None => Url::parse(url),
+        };
+        parsed.with_context(|| format!("Bad URL {:?}", url))
+    }
+
+    /// Send a request and wait for the response. An error status is not an error here; use
+    /// [`HttpResponse::status`].
+    pub async fn request(
+        &mut self,
+        method: Method,
+        url: &str,
+        body: Option<String>,
+    ) -> anyhow::Result<&HttpResponse> {
+        self.response = None;
+
+        let url = self.url(url)?;
+        let mut request = self.client.request(method, url.clone());
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        if let Some(body) = body {
+            request = request.body_string(body);
+        }
+
+        let mut response = request
+            .await
+            .map_err(|e| e.into_inner())
+            .with_context(|| format!("{} {} failed", method, url))?;
+        let body = response
+            .body_string()
+            .await
+            .map_err(|e| e.into_inner())
+            .with_context(|| format!("Could not read the response to {} {}", method, url))?;
+        let headers = response
+            .iter()
+            .flat_map(|(name, values)| {
+                values
+                    .iter()
+                    .map(move |v| (name.as_str().to_lowercase(), v.as_str().to_string()))
+            })
+            .collect();
+
+        Ok(self.response.insert(HttpResponse {
+            status: response.status() as u16,
+            headers,
+            body,
+        }))
+    }
+}
+
+#[async_trait]
+impl Fixture for Http {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(context: &mut Context) -> anyhow::Result<Self> {
+        let mut http = Self {
+            client: surf::Client::new(),
+            base_url: None,
+            headers: vec![],
+            response: None,
+        };
+        if let Some(url) = context.options().setting_as::<String>("base-url")? {
+            http.set_base_url(&url)?;
+        }
+        Ok(http)
+    }
+}
+
+/// The last response, for `Then` steps
+async fn last_response(context: &Context) -> anyhow::Result<&HttpResponse> {
+    match context.try_fixture::<Http>().await {
+        Some(http) => http.response().context("The last request didn't finish"),
+        None => anyhow::bail!("No request has been sent"),
+    }
+}
+
+#[when(r"I {method:GET|POST|PUT|PATCH|DELETE|HEAD} {url}")]
+async fn i_send(context: &mut Context, method: String, url: String) -> anyhow::Result<()> {
+    let method = Method::from_str(&method).map_err(|e| e.into_inner())?;
+    let body = context
+        .step()
+        .and_then(|s| s.docstring.as_ref())
+        .map(|d| context.expand(d));
+    context.use_fixture::<Http>().await?;
+    context
+        .fixture_mut::<Http>()
+        .await
+        .request(method, &url, body)
+        .await?;
+    Ok(())
+}
+
+#[then(r"the response status is {code:\d+}")]
+async fn the_response_status_is(context: &mut Context, code: u16) -> anyhow::Result<()> {
+    let response = last_response(context).await?;
+    if response.status != code {
+        anyhow::bail!(
+            "Expected status {}, got {}\n{}",
+            code,
+            response.status,
+            response.body
+        );
+    }
+    Ok(())
+}
+
+#[then(r"the response JSON at {pointer:\S*} equals {value}")]
+async fn the_response_json_equals(
+    context: &mut Context,
+    pointer: String,
+    value: String,
+) -> anyhow::Result<()> {
+    let expected: Value =
+        serde_json::from_str(&value).with_context(|| format!("{} is not JSON", value))?;
+    let response = last_response(context).await?;
+    let json = response.json()?;
+    match json.pointer(&pointer) {
+        Some(actual) if *actual == expected => Ok(()),
+        Some(actual) => anyhow::bail!("Expected {} at {:?}, got {}", expected, pointer, actual),
+        None => anyhow::bail!("Nothing at {:?} in {}", pointer, json),
+    }
+}
@@ -0,0 +1,434 @@
+//! A Kafka message broker, with steps for publishing messages and waiting for them. Requires the
+//! `messaging` feature.
+
+use crate::{given, then, when, Context, Fixture, Scope};
+use anyhow::Context as _;
+use async_trait::async_trait;
+use futures::future::try_join_all;
+use regex::Regex;
+use rskafka::client::partition::{Compression, OffsetAt, PartitionClient, UnknownTopicHandling};
+use rskafka::client::{Client, ClientBuilder};
+use rskafka::record::Record;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The environment variable checked for broker addresses, separated by commas, if there is no
+/// `kafka-brokers` setting
+pub const KAFKA_BROKERS_VAR: &str = "KAFKA_BROKERS";
+
+/// Where to find Kafka if it isn't configured
+pub const DEFAULT_KAFKA_BROKER: &str = "localhost:9092";
+
+/// How long each fetch waits for new messages while a step is waiting
+const FETCH_WAIT_MS: u64 = 200;
+
+/// The most each fetch returns from one partition
+const FETCH_MAX_BYTES: i32 = 1024 * 1024;
+
+/// A message read from a topic
+#[derive(Debug, Clone)]
+pub struct Message {
+    /// The topic it was read from
+    pub topic: String,
+    /// The partition it was read from
+    pub partition: i32,
+    /// Its offset within the partition
+    pub offset: i64,
+    /// Its key, if any
+    pub key: Option<Vec<u8>>,
+    /// Its payload. Empty for a tombstone.
+    pub payload: Vec<u8>,
+}
+
+impl Message {
+    /// The payload as text, replacing anything that isn't UTF-8
+    pub fn text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.payload)
+    }
+}
+
+/// A topic's partitions, and clients for them
+type Partitions = Vec<(i32, Arc<PartitionClient>)>;
+
+/// A connection to Kafka, shared by every scenario. Steps use [`Consumer`] to read only messages
+/// published during the scenario; see that for the steps.
+///
+/// Broker addresses come from the `kafka-brokers` setting, or else `KAFKA_BROKERS`:
+///
+/// ```toml
+/// [settings]
+/// kafka-brokers = ["localhost:9092"]
+/// kafka-topics = ["orders", "invoices"]   # see Consumer
+/// ```
+#[derive(Clone)]
+pub struct Broker {
+    client: Arc<Client>,
+    partitions: Arc<async_std::sync::Mutex<HashMap<String, Partitions>>>,
+}
+
+impl Broker {
+    /// Connect to Kafka through any of `brokers`, such as `localhost:9092`
+    pub async fn connect(brokers: Vec<String>) -> anyhow::Result<Self> {
+        let description = brokers.join(", ");
+        let client = ClientBuilder::new(brokers)
+            .build()
+            .await
+            .with_context(|| format!("Could not connect to Kafka at {}", description))?;
+        Ok(Self {
+            client: Arc::new(client),
+            partitions: Default::default(),
+        })
+    }
+
+    /// The client, for anything the steps don't cover
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Clients for each of a topic's partitions. A topic that doesn't exist yet is assumed to
+    /// have one partition, as when Kafka creates topics on first use. That guess isn't kept, so
+    /// the topic's real partitions are used once it exists.
+    async fn partitions(&self, topic: &str) -> anyhow::Result<Partitions> {
+        let mut cache = self.partitions.lock().await;
+        if let Some(partitions) = cache.get(topic) {
+            return Ok(partitions.clone());
+        }
+
+        let topics = self.client.list_topics().await?;
+        let (ids, exists): (Vec<i32>, bool) = match topics.into_iter().find(|t| t.name == topic) {
+            Some(t) => (t.partitions.into_iter().collect(), true),
+            None => (vec![0], false),
+        };
+        let mut partitions = vec![];
+        for id in ids {
+            let client = self
+                .client
+                .partition_client(topic, id, UnknownTopicHandling::Retry)
+                .await
+                .with_context(|| format!("Could not use partition {} of {:?}", id, topic))?;
+            partitions.push((id, Arc::new(client)));
+        }
+        if exists {
+            cache.insert(topic.to_string(), partitions.clone());
+        }
+        Ok(partitions)
+    }
+
+    /// Publish a message to the topic's first partition, and return its offset
+    pub async fn publish(
+        &self,
+        topic: &str,
+        key: Option<Vec<u8>>,
+        payload: Vec<u8>,
+    ) -> anyhow::Result<i64> {
+        let partitions = self.partitions(topic).await?;
+        let (_, partition) = partitions.first().context("The topic has no partitions")?;
+        let record = Record {
+            key,
+            value: Some(payload),
+            headers: BTreeMap::new(),
+            timestamp: chrono::Utc::now(),
+        };
+        let offsets = partition
+            .produce(vec![record], Compression::NoCompression)
+            .await
+            .with_context(|| format!("Could not publish to {:?}", topic))?;
+        offsets
+            .first()
+            .copied()
+            .context("Kafka didn't return an offset")
+    }
+
+    /// The offset of the next message in each of a topic's partitions
+    pub async fn latest_offsets(&self, topic: &str) -> anyhow::Result<HashMap<i32, i64>> {
+        let mut offsets = HashMap::new();
+        for (id, partition) in self.partitions(topic).await? {
+            offsets.insert(id, partition.get_offset(OffsetAt::Latest).await?);
+        }
+        Ok(offsets)
+    }
+
+    /// Read messages from each partition, starting at `offsets`, and move the offsets past them.
+    /// Waits a little while if there are none yet.
+    pub async fn fetch(
+        &self,
+        topic: &str,
+        offsets: &mut HashMap<i32, i64>,
+    ) -> anyhow::Result<Vec<Message>> {
+        self.fetch_within(topic, offsets, Duration::from_millis(FETCH_WAIT_MS))
+            .await
+    }
+
+    /// As [`Self::fetch`], but waits no longer than `wait`. The partitions are read at once, so
+    /// the wait isn't repeated for each of them.
+    async fn fetch_within(
+        &self,
+        topic: &str,
+        offsets: &mut HashMap<i32, i64>,
+        wait: Duration,
+    ) -> anyhow::Result<Vec<Message>> {
+        let wait_ms = wait.as_millis().min(FETCH_WAIT_MS as u128) as i32;
+        let partitions = self.partitions(topic).await?;
+        let fetches = partitions.iter().map(|(id, partition)| {
+            let offset = *offsets.get(id).unwrap_or(&0);
+            async move {
+                let (records, _high_watermark) = partition
+                    .fetch_records(offset, 1..FETCH_MAX_BYTES, wait_ms)
+                    .await
+                    .with_context(|| format!("Could not read from {:?}", topic))?;
+                Ok::<_, anyhow::Error>((*id, records))
+            }
+        });
+
+        let mut messages = vec![];
+        for (id, records) in try_join_all(fetches).await? {
+            let offset = offsets.entry(id).or_insert(0);
+            for r in records {
+                *offset = (*offset).max(r.offset + 1);
+                messages.push(Message {
+                    topic: topic.to_string(),
+                    partition: id,
+                    offset: r.offset,
+                    key: r.record.key,
+                    payload: r.record.value.unwrap_or_default(),
+                });
+            }
+        }
+        Ok(messages)
+    }
+}
+
+#[async_trait]
+impl Fixture for Broker {
+    const SCOPE: Scope = Scope::Global;
+
+    async fn setup(context: &mut Context) -> anyhow::Result<Self> {
+        let brokers = match context
+            .options()
+            .setting_as::<Vec<String>>("kafka-brokers")?
+        {
+            Some(brokers) => brokers,
+            None => match std::env::var(KAFKA_BROKERS_VAR) {
+                Ok(var) => var.split(',').map(|b| b.trim().to_string()).collect(),
+                Err(_) => vec![String::from(DEFAULT_KAFKA_BROKER)],
+            },
+        };
+        Self::connect(brokers).await
+    }
+}
+
+/// Reads messages for a scenario, so that scenarios don't see each other's messages. Provides
+/// these steps:
+///
+/// - `Given I am listening on topic "{topic}"`: Only messages published from now on count.
+/// - `When I publish "{payload}" to topic "{topic}"`, or `When I publish to topic "{topic}"`
+///   with the payload in a docstring.
+/// - `Then a message matching "{pattern}" is received on topic "{topic}" within {secs}s`:
+///   `pattern` is a regular expression, which only needs to match part of the payload.
+/// - `Then no message matching "{pattern}" is received on topic "{topic}" within {secs}s`
+///
+/// A scenario only sees messages published after it started listening on the topic. It starts
+/// listening to the topics in the `kafka-topics` setting when the fixture is set up, and to any
+/// other topic the first time a step uses it. Topics the system under test publishes to should be
+/// listed, or listened on explicitly, so that messages published before a step names the topic
+/// aren't missed.
+///
+/// Messages are published to the topic's first partition, and read from every partition.
+pub struct Consumer {
+    broker: Broker,
+    offsets: HashMap<String, HashMap<i32, i64>>,
+    received: HashMap<String, Vec<Message>>,
+}
+
+impl Consumer {
+    /// Only count messages on `topic` published from now on, unless already listening
+    pub async fn listen(&mut self, topic: &str) -> anyhow::Result<()> {
+        if !self.offsets.contains_key(topic) {
+            let offsets = self.broker.latest_offsets(topic).await?;
+            self.offsets.insert(topic.to_string(), offsets);
+        }
+        Ok(())
+    }
+
+    /// Messages received on `topic` during the scenario so far, without waiting for more
+    pub fn received(&self, topic: &str) -> &[Message] {
+        self.received
+            .get(topic)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Read any new messages on `topic`, waiting a little while if there are none
+    pub async fn poll(&mut self, topic: &str) -> anyhow::Result<&[Message]> {
+        self.poll_within(topic, Duration::from_millis(FETCH_WAIT_MS))
+            .await
+    }
+
+    /// As [`Self::poll`], but waits no longer than `wait`
+    async fn poll_within(&mut self, topic: &str, wait: Duration) -> anyhow::Result<&[Message]> {
+        self.listen(topic).await?;
+        let offsets = self.offsets.get_mut(topic).expect("Listening to the topic");
+        let messages = self.broker.fetch_within(topic, offsets, wait).await?;
+        self.received
+            .entry(topic.to_string())
+            .or_default()
+            .extend(messages);
+        Ok(self.received(topic))
+    }
+
+    /// Wait up to `timeout` for a message on `topic` that satisfies `predicate`, including
+    /// messages already received
+    pub async fn wait_for<P>(
+        &mut self,
+        topic: &str,
+        timeout: Duration,
+        mut predicate: P,
+    ) -> anyhow::Result<Option<Message>>
+    where
+        P: FnMut(&Message) -> bool,
+    {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let messages = self.poll_within(topic, remaining).await?;
+            if let Some(m) = messages.iter().find(|m| predicate(m)) {
+                return Ok(Some(m.clone()));
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Fixture for Consumer {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(context: &mut Context) -> anyhow::Result<Self> {
+        context.use_fixture::<Broker>().await?;
+        let broker = context.fixture::<Broker>().await.clone();
+        let mut consumer = Self {
+            broker,
+            offsets: HashMap::new(),
+            received: HashMap::new(),
+        };
+        let topics = context
+            .options()
+            .setting_as::<Vec<String>>("kafka-topics")?
+            .unwrap_or_default();
+        for topic in topics {
+            consumer.listen(&topic).await?;
+        }
+        Ok(consumer)
+    }
+}
+
+async fn consumer(context: &mut Context) -> anyhow::Result<&mut Consumer> {
+    context.use_fixture::<Consumer>().await?;
+    Ok(context.fixture_mut::<Consumer>().await)
+}
+
+async fn publish(context: &mut Context, topic: &str, payload: String) -> anyhow::Result<()> {
+    let consumer = consumer(context).await?;
+    // Listen first, so the scenario sees its own message
+    consumer.listen(topic).await?;
+    consumer
+        .broker
+        .publish(topic, None, payload.into_bytes())
+        .await?;
+    Ok(())
+}
+
+fn pattern(pattern: &str) -> anyhow::Result<Regex> {
+    Regex::new(pattern).with_context(|| format!("Bad regular expression {:?}", pattern))
+}
+
+fn timeout(secs: f64) -> anyhow::Result<Duration> {
+    Duration::try_from_secs_f64(secs).with_context(|| format!("Bad timeout {} s: too long", secs))
+}
+
+fn describe(messages: &[Message]) -> String {
+    if messages.is_empty() {
+        return String::from("No messages were received");
+    }
+    let mut text = String::from("Received:");
+    for m in messages {
+        text.push_str(&format!("\n  {}", m.text()));
+    }
+    text
+}
+
+#[given(r#"I am listening on topic "{topic}""#)]
+async fn i_am_listening(context: &mut Context, topic: String) -> anyhow::Result<()> {
+    consumer(context).await?.listen(&topic).await
+}
+
+#[when(r#"I publish "{payload}" to topic "{topic}""#)]
+async fn i_publish(context: &mut Context, payload: String, topic: String) -> anyhow::Result<()> {
+    publish(context, &topic, payload).await
+}
+
+#[when(r#"I publish to topic "{topic}""#)]
+async fn i_publish_docstring(context: &mut Context, topic: String) -> anyhow::Result<()> {
+    let payload = match context.step().and_then(|s| s.docstring.as_ref()) {
+        Some(d) => context.expand(d),
+        None => anyhow::bail!("Expected the payload in a docstring"),
+    };
+    publish(context, &topic, payload).await
+}
+
+#[then(
+    r#"a message matching "{regex}" is received on topic "{topic}" within {secs:\d+(?:\.\d+)?}s"#
+)]
+async fn a_message_is_received(
+    context: &mut Context,
+    regex: String,
+    topic: String,
+    secs: f64,
+) -> anyhow::Result<()> {
+    let regex = pattern(&regex)?;
+    let timeout = timeout(secs)?;
+    let consumer = consumer(context).await?;
+    match consumer
+        .wait_for(&topic, timeout, |m| regex.is_match(&m.text()))
+        .await?
+    {
+        Some(_) => Ok(()),
+        None => anyhow::bail!(
+            "No message matching {:?} on {:?} within {} s\n{}",
+            regex.as_str(),
+            topic,
+            secs,
+            describe(consumer.received(&topic))
+        ),
+    }
+}
+
+#[then(
+    r#"no message matching "{regex}" is received on topic "{topic}" within {secs:\d+(?:\.\d+)?}s"#
+)]
+async fn no_message_is_received(
+    context: &mut Context,
+    regex: String,
+    topic: String,
+    secs: f64,
+) -> anyhow::Result<()> {
+    let regex = pattern(&regex)?;
+    let timeout = timeout(secs)?;
+    let consumer = consumer(context).await?;
+    match consumer
+        .wait_for(&topic, timeout, |m| regex.is_match(&m.text()))
+        .await?
+    {
+        None => Ok(()),
+        Some(m) => anyhow::bail!(
+            "Unexpected message on {:?} at offset {}: {}",
+            topic,
+            m.offset,
+            m.text()
+        ),
+    }
+}
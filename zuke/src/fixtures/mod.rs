@@ -0,0 +1,32 @@
+#![warn(missing_docs)]
+
+//! Ready-made fixtures and steps for common test needs
+
+#[cfg(feature = "browser")]
+pub mod browser;
+pub mod clock;
+pub mod command;
+pub mod errors;
+pub mod eventually;
+pub mod fs;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "messaging")]
+pub mod messaging;
+pub mod random;
+pub mod remember;
+#[cfg(feature = "sql")]
+pub mod sql;
+pub mod tempdir;
+
+#[cfg(feature = "browser")]
+pub use browser::{Browser, BrowserSession, FeatureBrowser};
+pub use command::{Command, CommandOutput};
+#[cfg(feature = "http")]
+pub use http::{Http, HttpResponse};
+#[cfg(feature = "messaging")]
+pub use messaging::{Broker, Consumer, Message};
+pub use random::Rand;
+#[cfg(feature = "sql")]
+pub use sql::Database;
+pub use tempdir::TempDir;
@@ -0,0 +1,155 @@
+//! Random test data that can be reproduced. See [`Rand`].
+//!
+//! Each scenario gets its own generator, seeded from the run's `--seed` and the scenario's
+//! [key](crate::Component::key), so a scenario sees the same data when the run is repeated with the
+//! same seed, no matter which other scenarios run or in what order. The seed is in the run info
+//! that reporters show, such as the plain reporter's `-v` summary.
+//!
+//! These steps save what they generate with [`Context::remember`], for later steps to use as
+//! `{saved:name}`:
+//!
+//! - `I pick a random name as "{name}"`
+//! - `I pick a random email as "{name}"`
+//! - `I pick a random UUID as "{name}"`
+//! - `I pick a random number from {low} to {high} as "{name}"`, inclusive
+//!
+//! ```gherkin
+//! Scenario: Signing up
+//!     Given I pick a random email as "email"
+//!     When I sign up as "{saved:email}"
+//!     Then I get a welcome message at "{saved:email}"
+//! ```
+
+use crate::component::ComponentId;
+use crate::{step, Context, Fixture, Scope};
+use async_trait::async_trait;
+use parking_lot::{Mutex, MutexGuard};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+const FIRST_NAMES: &[&str] = &[
+    "Alice", "Bob", "Carol", "Dave", "Erin", "Frank", "Grace", "Heidi", "Ivan", "Judy", "Mallory",
+    "Niaj", "Olivia", "Peggy", "Rupert", "Sybil", "Trent", "Victor", "Walter", "Yuki",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Anderson", "Baker", "Chen", "Dubois", "Evans", "Fischer", "Garcia", "Hughes", "Ivanova",
+    "Jones", "Kim", "Lopez", "Murphy", "Nguyen", "Okafor", "Patel", "Rossi", "Silva", "Tanaka",
+    "Weber",
+];
+
+/// A random number generator for a scenario. See the [module docs](self).
+pub struct Rand {
+    seed: u64,
+    rng: Mutex<StdRng>,
+}
+
+impl Rand {
+    /// A generator with a particular seed
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    /// This generator's seed. It is derived from the run's seed, not equal to it.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The generator itself, for anything not covered here
+    pub fn rng(&self) -> MutexGuard<'_, StdRng> {
+        self.rng.lock()
+    }
+
+    /// A number from `low` to `high`, inclusive. Errors if `low` is more than `high`.
+    pub fn number(&self, low: i64, high: i64) -> anyhow::Result<i64> {
+        anyhow::ensure!(low <= high, "{} is more than {}", low, high);
+        Ok(self.rng().gen_range(low..=high))
+    }
+
+    /// A person's full name, such as `Grace Okafor`
+    pub fn name(&self) -> String {
+        let mut rng = self.rng();
+        let first = FIRST_NAMES.choose(&mut *rng).unwrap();
+        let last = LAST_NAMES.choose(&mut *rng).unwrap();
+        format!("{} {}", first, last)
+    }
+
+    /// An email address at `example.com`, such as `grace.okafor.4821@example.com`. The number
+    /// makes collisions unlikely.
+    pub fn email(&self) -> String {
+        let name = self.name().to_lowercase().replace(' ', ".");
+        let number: i64 = self.rng().gen_range(1000..=9999);
+        format!("{}.{}@example.com", name, number)
+    }
+
+    /// A random (version 4) UUID, such as `0b7c9a6e-5f1d-4c3a-9e2b-7d4f8a1c6e30`
+    pub fn uuid(&self) -> String {
+        let mut bytes: [u8; 16] = self.rng().gen();
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+        let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..32]
+        )
+    }
+}
+
+#[async_trait]
+impl Fixture for Rand {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(context: &mut Context) -> anyhow::Result<Self> {
+        // The scenario's key, even when a step is the first to use this
+        let name = context.component().scenario_key();
+        let seed = context.options().seed;
+        let hash = ComponentId::hash(format!("{}\0{}", seed, name).as_bytes());
+        Ok(Self::with_seed(hash.0))
+    }
+}
+
+async fn rand(context: &mut Context) -> anyhow::Result<&Rand> {
+    context.use_fixture::<Rand>().await?;
+    Ok(context.fixture::<Rand>().await)
+}
+
+#[step(r#"I pick a random name as "{name}""#)]
+async fn random_name(context: &mut Context, name: String) -> anyhow::Result<()> {
+    let value = rand(context).await?.name();
+    context.remember(name, value);
+    Ok(())
+}
+
+#[step(r#"I pick a random email as "{name}""#)]
+async fn random_email(context: &mut Context, name: String) -> anyhow::Result<()> {
+    let value = rand(context).await?.email();
+    context.remember(name, value);
+    Ok(())
+}
+
+#[step(r#"I pick a random UUID as "{name}""#)]
+async fn random_uuid(context: &mut Context, name: String) -> anyhow::Result<()> {
+    let value = rand(context).await?.uuid();
+    context.remember(name, value);
+    Ok(())
+}
+
+#[step(r#"I pick a random number from {low:-?\d+} to {high:-?\d+} as "{name}""#)]
+async fn random_number(
+    context: &mut Context,
+    low: i64,
+    high: i64,
+    name: String,
+) -> anyhow::Result<()> {
+    let value = rand(context).await?.number(low, high)?;
+    context.remember(name, value.to_string());
+    Ok(())
+}
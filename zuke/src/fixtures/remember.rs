@@ -0,0 +1,33 @@
+//! Steps for passing values between steps. See [`Context::remember`].
+//!
+//! - `I remember "{value}" as "{name}"`, with any keyword
+//! - `Then the remembered value "{name}" is "{expected}"`
+//!
+//! Later steps can use `{saved:name}` in their text:
+//!
+//! ```gherkin
+//! Scenario: Renaming a user
+//!     Given I remember "alice" as "user"
+//!     When I rename "{saved:user}" to "bob"
+//!     Then there is no user named "{saved:user}"
+//! ```
+
+use crate::{step, then, Context};
+
+#[step(r#"I remember "{value}" as "{name}""#)]
+fn i_remember(context: &mut Context, value: String, name: String) {
+    context.remember(name, value);
+}
+
+#[then(r#"the remembered value "{name}" is "{expected}""#)]
+fn the_remembered_value_is(
+    context: &mut Context,
+    name: String,
+    expected: String,
+) -> anyhow::Result<()> {
+    match context.recall(&name) {
+        Some(value) if value == expected => Ok(()),
+        Some(value) => anyhow::bail!("{:?} is {:?}, not {:?}", name, value, expected),
+        None => anyhow::bail!("Nothing was remembered as {:?}", name),
+    }
+}
@@ -0,0 +1,302 @@
+//! A SQL database connection, with steps for seeding and checking tables. Requires the `sql`
+//! feature.
+
+use crate::{given, then, when, Context, Fixture, Scope};
+use anyhow::Context as _;
+use async_trait::async_trait;
+use sqlx::any::{AnyPool, AnyPoolOptions, AnyRow};
+use sqlx::{Column, Executor, Row};
+
+/// The environment variable checked for a database URL if there is no `database-url` setting
+pub const DATABASE_URL_VAR: &str = "DATABASE_URL";
+
+/// The results of a query, as text. `NULL` is `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryTable {
+    /// Column names
+    pub columns: Vec<String>,
+    /// Row values
+    pub rows: Vec<Vec<Option<String>>>,
+}
+
+/// A connection pool for the test database. Provides these steps, which take [data tables][1]
+/// with column names in the first row. A cell that reads `NULL` is SQL `NULL`.
+///
+/// - `Given the table {table} contains`: Insert the rows.
+/// - `When I execute the SQL`: Run the statements in the docstring, as one script.
+/// - `Then the query "{sql}" returns`: Compare the results in order.
+/// - `Then the table {table} contains exactly`: Compare the given columns of every row,
+///   ignoring order.
+///
+/// On a mismatch, the step fails with the cells that differ, and rows that are missing or
+/// unexpected.
+///
+/// The database URL comes from the `database-url` setting or else `DATABASE_URL`:
+///
+/// ```toml
+/// [settings]
+/// database-url = "postgres://zuke@localhost/test"
+/// ```
+///
+/// An in-memory SQLite database, such as `sqlite::memory:`, is kept on one connection, since each
+/// connection would otherwise have a database of its own.
+///
+/// [1]: https://cucumber.io/docs/gherkin/reference/#data-tables
+pub struct Database {
+    pool: AnyPool,
+}
+
+impl Database {
+    /// Connect to a database
+    pub async fn connect(url: &str) -> anyhow::Result<Self> {
+        let mut options = AnyPoolOptions::new();
+        if is_sqlite_memory(url) {
+            options = options
+                .max_connections(1)
+                .min_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None);
+        }
+        let pool = options
+            .connect(url)
+            .await
+            .context("Could not connect to the database")?;
+        Ok(Self { pool })
+    }
+
+    /// The connection pool, for anything the steps don't cover
+    pub fn pool(&self) -> &AnyPool {
+        &self.pool
+    }
+
+    /// Run one statement and return the number of rows affected
+    pub async fn execute(&self, sql: &str) -> anyhow::Result<u64> {
+        let result = sqlx::query(sql)
+            .execute(&self.pool)
+            .await
+            .with_context(|| format!("Failed to execute {:?}", sql))?;
+        Ok(result.rows_affected())
+    }
+
+    /// Run several statements, separated by semicolons, and return the number of rows affected.
+    /// The database splits them, so semicolons in strings and comments are left alone.
+    pub async fn execute_script(&self, sql: &str) -> anyhow::Result<u64> {
+        let result = self
+            .pool
+            .execute(sql)
+            .await
+            .with_context(|| format!("Failed to execute {:?}", sql))?;
+        Ok(result.rows_affected())
+    }
+
+    /// Run a query and return the results as text
+    pub async fn query(&self, sql: &str) -> anyhow::Result<QueryTable> {
+        let rows = sqlx::query(sql)
+            .fetch_all(&self.pool)
+            .await
+            .with_context(|| format!("Failed to query {:?}", sql))?;
+
+        let columns = match rows.first() {
+            Some(row) => row.columns().iter().map(|c| c.name().to_string()).collect(),
+            None => vec![],
+        };
+        let rows = rows
+            .iter()
+            .map(|row| (0..row.len()).map(|i| cell_text(row, i)).collect())
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(QueryTable { columns, rows })
+    }
+
+    /// Insert rows into a table. Values are passed as SQL string literals, so that the database
+    /// converts them to the column's type.
+    pub async fn insert(
+        &self,
+        table: &str,
+        columns: &[String],
+        rows: &[Vec<Option<String>>],
+    ) -> anyhow::Result<()> {
+        for row in rows {
+            let values: Vec<_> = row.iter().map(|v| literal(v.as_deref())).collect();
+            let sql = format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                table,
+                columns.join(", "),
+                values.join(", ")
+            );
+            self.execute(&sql).await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Fixture for Database {
+    const SCOPE: Scope = Scope::Global;
+
+    async fn setup(context: &mut Context) -> anyhow::Result<Self> {
+        let url = match context.options().setting_as::<String>("database-url")? {
+            Some(url) => url,
+            None => std::env::var(DATABASE_URL_VAR)
+                .context("Set database-url in the config file, or DATABASE_URL")?,
+        };
+        Self::connect(&url).await
+    }
+
+    async fn teardown(&mut self, _context: &mut Context) -> anyhow::Result<()> {
+        self.pool.close().await;
+        Ok(())
+    }
+}
+
+/// Whether `url` is for an in-memory SQLite database
+fn is_sqlite_memory(url: &str) -> bool {
+    url.starts_with("sqlite:") && (url.contains(":memory:") || url.contains("mode=memory"))
+}
+
+/// Read a cell as text, whatever its type
+fn cell_text(row: &AnyRow, i: usize) -> anyhow::Result<Option<String>> {
+    if let Ok(v) = row.try_get::<Option<String>, _>(i) {
+        return Ok(v);
+    }
+    if let Ok(v) = row.try_get::<Option<i64>, _>(i) {
+        return Ok(v.map(|v| v.to_string()));
+    }
+    if let Ok(v) = row.try_get::<Option<f64>, _>(i) {
+        return Ok(v.map(|v| v.to_string()));
+    }
+    if let Ok(v) = row.try_get::<Option<bool>, _>(i) {
+        return Ok(v.map(|v| v.to_string()));
+    }
+    anyhow::bail!("Can't read column {} as text", row.columns()[i].name())
+}
+
+/// A value as a SQL literal
+fn literal(value: Option<&str>) -> String {
+    match value {
+        Some(v) => format!("'{}'", v.replace('\'', "''")),
+        None => "NULL".to_string(),
+    }
+}
+
+/// Split a data table into column names and values
+fn data_table(context: &Context) -> anyhow::Result<QueryTable> {
+    let table = match context.step().and_then(|s| s.table.as_ref()) {
+        Some(t) => t,
+        None => anyhow::bail!("Expected a data table"),
+    };
+
+    // Column names are never NULL, so a column can be named that
+    let (header, rows) = match table.rows.split_first() {
+        Some(split) => split,
+        None => anyhow::bail!("The data table needs a row of column names"),
+    };
+    let columns = header.iter().map(|cell| context.expand(cell)).collect();
+    let rows = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| match cell.as_str() {
+                    "NULL" => None,
+                    s => Some(context.expand(s)),
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(QueryTable { columns, rows })
+}
+
+/// Describe how `actual` differs from `expected`, or `None` if they match. Rows are matched up
+/// by position.
+pub fn diff_tables(expected: &QueryTable, actual: &QueryTable) -> Option<String> {
+    let show = |v: &Option<String>| match v {
+        Some(v) => format!("{:?}", v),
+        None => "NULL".to_string(),
+    };
+
+    let mut lines = vec![];
+    if expected.columns.len() != actual.columns.len() && !actual.rows.is_empty() {
+        lines.push(format!(
+            "Expected columns {:?}, got {:?}",
+            expected.columns, actual.columns
+        ));
+    }
+
+    for (i, (e, a)) in expected.rows.iter().zip(&actual.rows).enumerate() {
+        for (j, (e, a)) in e.iter().zip(a).enumerate() {
+            if e != a {
+                lines.push(format!(
+                    "Row {}, column {}: expected {}, got {}",
+                    i + 1,
+                    expected.columns.get(j).map(String::as_str).unwrap_or("?"),
+                    show(e),
+                    show(a)
+                ));
+            }
+        }
+    }
+    for (i, row) in expected.rows.iter().enumerate().skip(actual.rows.len()) {
+        let row: Vec<_> = row.iter().map(show).collect();
+        lines.push(format!("Row {} is missing: {}", i + 1, row.join(", ")));
+    }
+    for (i, row) in actual.rows.iter().enumerate().skip(expected.rows.len()) {
+        let row: Vec<_> = row.iter().map(show).collect();
+        lines.push(format!("Row {} is unexpected: {}", i + 1, row.join(", ")));
+    }
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+/// Get the database, connecting if needed
+async fn database(context: &mut Context) -> anyhow::Result<&Database> {
+    context.use_fixture::<Database>().await?;
+    Ok(context.fixture::<Database>().await)
+}
+
+#[given(r"the table {table:\w+} contains")]
+async fn the_table_contains(context: &mut Context, table: String) -> anyhow::Result<()> {
+    let data = data_table(context)?;
+    database(context)
+        .await?
+        .insert(&table, &data.columns, &data.rows)
+        .await
+}
+
+#[when("I execute the SQL")]
+async fn i_execute_the_sql(context: &mut Context) -> anyhow::Result<()> {
+    let sql = match context.step().and_then(|s| s.docstring.as_ref()) {
+        Some(d) => context.expand(d),
+        None => anyhow::bail!("Expected a docstring"),
+    };
+    database(context).await?.execute_script(&sql).await?;
+    Ok(())
+}
+
+#[then(r#"the query "{sql}" returns"#)]
+async fn the_query_returns(context: &mut Context, sql: String) -> anyhow::Result<()> {
+    let expected = data_table(context)?;
+    let actual = database(context).await?.query(&sql).await?;
+    match diff_tables(&expected, &actual) {
+        Some(diff) => anyhow::bail!("The query returned different results:\n{}", diff),
+        None => Ok(()),
+    }
+}
+
+#[then(r"the table {table:\w+} contains exactly")]
+async fn the_table_contains_exactly(context: &mut Context, table: String) -> anyhow::Result<()> {
+    let mut expected = data_table(context)?;
+    let sql = format!("SELECT {} FROM {}", expected.columns.join(", "), table);
+    let mut actual = database(context).await?.query(&sql).await?;
+    expected.rows.sort();
+    actual.rows.sort();
+    actual.columns = expected.columns.clone();
+    match diff_tables(&expected, &actual) {
+        Some(diff) => anyhow::bail!("The table {} has different rows:\n{}", table, diff),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,71 @@
+//! A scratch directory for each scenario
+
+use crate::{Context, Fixture, Scope};
+use anyhow::Context as _;
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+
+/// A fresh, empty directory for each scenario, deleted when the scenario ends.
+///
+/// While the fixture is in use, `{tempdir}` in step text is replaced by the directory's path. The
+/// working directory of the process is not changed, since scenarios may run concurrently; join
+/// relative paths onto [`TempDir::path`] instead.
+///
+/// ```
+/// use zuke::{given, Context};
+/// use zuke::fixtures::TempDir;
+///
+/// #[given("a file named {string}")]
+/// async fn a_file(context: &mut Context, name: String) -> anyhow::Result<()> {
+///     context.use_fixture::<TempDir>().await?;
+///     let path = context.fixture::<TempDir>().await.join(name);
+///     std::fs::write(path, "")?;
+///     Ok(())
+/// }
+/// ```
+pub struct TempDir {
+    dir: Option<tempfile::TempDir>,
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// The path to the directory
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Join a relative path onto the directory
+    pub fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.path.join(path)
+    }
+}
+
+#[async_trait]
+impl Fixture for TempDir {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(context: &mut Context) -> anyhow::Result<Self> {
+        let dir = tempfile::Builder::new()
+            .prefix("zuke-")
+            .tempdir()
+            .context("Could not create a temporary directory")?;
+        let path = dir.path().to_path_buf();
+        context.set_expansion("tempdir", path.to_string_lossy());
+        Ok(Self {
+            dir: Some(dir),
+            path,
+        })
+    }
+
+    async fn teardown(&mut self, _context: &mut Context) -> anyhow::Result<()> {
+        if let Some(dir) = self.dir.take() {
+            dir.close().with_context(|| {
+                format!(
+                    "Could not remove temporary directory {}",
+                    self.path.display()
+                )
+            })?;
+        }
+        Ok(())
+    }
+}
@@ -30,6 +30,11 @@ impl Flag {
         let _ = self.recv.recv().await;
     }
 
+    /// Check whether the flag has been set, without waiting
+    pub fn is_set(&self) -> bool {
+        self.send.lock().unwrap().is_none()
+    }
+
     /// Set the flag
     pub fn set(&self) {
         // close the channel
@@ -0,0 +1,364 @@
+//! Running Zuke as a `cargo test` target. See [`crate::main!`].
+use crate::extra_options;
+use crate::flag::Flag;
+use crate::options::TestOptions;
+use crate::parser::find_files;
+use crate::reporter::Collect;
+use crate::top::{CancelMethod, ZukeBuilder};
+use async_std::task::block_on;
+use clap::{App, Arg};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// Where to look for features if none are given
+pub const DEFAULT_FEATURE_PATH: &str = "tests/features";
+
+/// How often `--watch` checks for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Options that `cargo test` may pass along for libtest, that take a value
+const LIBTEST_OPTIONS: &[&str] = &["--test-threads", "--color", "--format", "--logfile", "-Z"];
+
+/// Flags that `cargo test` may pass along for libtest. `--quiet` isn't one: it's passed on.
+const LIBTEST_FLAGS: &[&str] = &[
+    "--nocapture",
+    "--show-output",
+    "--ignored",
+    "--include-ignored",
+    "--exact",
+    "--test",
+    "--bench",
+    "--report-time",
+    "--ensure-time",
+    "--shuffle",
+];
+
+#[extra_options]
+fn harness_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("watch")
+            .long("watch")
+            .help("After the tests finish, re-run features when they change"),
+    )
+}
+
+/// Set up a `[[test]]` target with `harness = false`. Use `cargo test -- <zuke args>` to pass
+/// arguments to Zuke. Test name filters given to `cargo test` select components by name, as with
+/// `--name`. Exits with 0 if the test run passed, or 101 if it failed, like libtest.
+///
+/// ```ignore
+/// // tests/features.rs
+/// zuke::main!();
+/// ```
+///
+/// This is equivalent to [`run_with`] with features from `tests/features`. Features can also be
+/// given explicitly:
+///
+/// ```ignore
+/// zuke::main!("tests/features", "tests/more_features");
+/// ```
+#[macro_export]
+macro_rules! main {
+    () => {
+        $crate::main!($crate::harness::DEFAULT_FEATURE_PATH);
+    };
+    ($($path:expr),+ $(,)?) => {
+        fn main() {
+            $crate::harness::run_with(|| {
+                let mut builder = $crate::Zuke::builder();
+                $( builder.feature_path($path); )+
+                builder
+            })
+        }
+    };
+}
+
+/// Run the tests from `builder` with arguments from `cargo test`, then exit the process. `--watch`
+/// isn't supported, since the builder can't be reused; see [`run_with`].
+pub fn run(builder: ZukeBuilder) -> ! {
+    let args = harness_args();
+    let (_, failed) = run_once(builder, args);
+    std::process::exit(if failed { 101 } else { 0 })
+}
+
+/// As [`run`], but with a function to make a new builder for each test run. Used by
+/// [`crate::main!`].
+///
+/// With `--watch`, this waits for feature files to change after the tests finish, then runs the
+/// features that changed. Ctrl+C stops watching. If the test binary itself is rebuilt, for
+/// example by `cargo test --no-run` in another terminal, it restarts. Changes to Rust sources
+/// can't be picked up without a rebuild, so those only print a reminder.
+pub fn run_with<F: FnMut() -> ZukeBuilder>(mut make: F) -> ! {
+    let args = harness_args();
+    let builder = make();
+    let mut paths = builder.feature_paths();
+    let (options, mut failed) = run_once(builder, args.clone());
+
+    let options = match options {
+        Some(o) if o.opts.is_present("watch") => o,
+        _ => std::process::exit(if failed { 101 } else { 0 }),
+    };
+
+    // Ctrl+C cancels the first run's flag. Later runs share it, so that Ctrl+C stops both the
+    // current run and the watch.
+    let stop = options.canceled.clone();
+    paths.extend(options.config.features.iter().cloned());
+    let sources = std::env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from);
+    let mut watcher = Watcher::new(paths, sources);
+
+    while !stop.is_set() {
+        eprintln!("\nWatching for changes. Press Ctrl+C to stop.");
+        let changed = match watcher.wait(&stop) {
+            Some(changed) => changed,
+            None => break,
+        };
+
+        let mut builder = make();
+        builder.cancel_method(CancelMethod::Shared(stop.clone()));
+        builder.only_features(changed);
+        failed = run_once(builder, args.clone()).1;
+    }
+
+    std::process::exit(if failed { 101 } else { 0 })
+}
+
+/// Arguments from `cargo test`, or exit if there's nothing to do
+fn harness_args() -> Vec<OsString> {
+    match libtest_args(std::env::args_os()) {
+        Some(args) => args,
+        // `cargo test -- --list`. There are no tests to list until we parse features.
+        None => std::process::exit(0),
+    }
+}
+
+/// Run the tests once. Returns the options used, if the tests could be built, and whether the
+/// tests failed. Exits the process on usage errors.
+fn run_once(mut builder: ZukeBuilder, args: Vec<OsString>) -> (Option<Arc<TestOptions>>, bool) {
+    if !builder.has_reporters() {
+        builder.command_line_reporter();
+    }
+    let (collect, outcome) = Collect::new();
+    builder.reporter(collect);
+
+    let zuke = match builder.build_with_app_from(App::new("Zuke"), args) {
+        Ok(zuke) => zuke,
+        Err(e) => match e.downcast::<clap::Error>() {
+            // Prints help, version, or usage errors
+            Ok(e) => e.exit(),
+            Err(e) => {
+                eprintln!("Error: {:#}", e);
+                return (None, true);
+            }
+        },
+    };
+
+    let options = zuke.options().clone();
+    let result = block_on(zuke.run());
+    let outcome = block_on(outcome).ok();
+    let failed = match (&result, outcome) {
+        (Ok(()), Some(outcome)) => outcome.failed(),
+        _ => true,
+    };
+
+    if let Err(e) = result {
+        eprintln!("Error: {:#}", e);
+    }
+    (Some(options), failed)
+}
+
+/// What a [`Watcher`] found had changed since it last looked
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Changes {
+    /// Feature files that are new or were modified, in order
+    pub features: Vec<PathBuf>,
+    /// Whether any Rust source was added, removed, or modified
+    pub rust: bool,
+    /// Whether the test binary was rebuilt
+    pub binary: bool,
+}
+
+/// When a file was last modified, and its size. The size catches changes made within the
+/// resolution of the modification time.
+type Stamp = (SystemTime, u64);
+
+/// Polls feature files, Rust sources, and the test binary for changes. Used by `--watch`.
+pub struct Watcher {
+    paths: Vec<PathBuf>,
+    sources: Option<PathBuf>,
+    features: HashMap<PathBuf, Stamp>,
+    rust: HashMap<PathBuf, Stamp>,
+    exe: Option<(PathBuf, Stamp)>,
+}
+
+impl Watcher {
+    /// Watch the feature files in `paths`, which may be files or directories, and the Rust
+    /// sources in the `src` and `tests` directories of `sources`, if given. The running test
+    /// binary is watched too.
+    pub fn new(paths: Vec<PathBuf>, sources: Option<PathBuf>) -> Self {
+        let exe = std::env::current_exe().ok().and_then(|exe| {
+            let stamp = stamp_of(&exe)?;
+            Some((exe, stamp))
+        });
+        let mut watcher = Self {
+            paths,
+            sources,
+            features: HashMap::new(),
+            rust: HashMap::new(),
+            exe,
+        };
+        watcher.features = watcher.scan_features();
+        watcher.rust = watcher.scan_rust();
+        watcher
+    }
+
+    fn scan_features(&self) -> HashMap<PathBuf, Stamp> {
+        scan(&self.paths, "feature")
+    }
+
+    fn scan_rust(&self) -> HashMap<PathBuf, Stamp> {
+        match &self.sources {
+            Some(dir) => scan(&[dir.join("src"), dir.join("tests")], "rs"),
+            None => HashMap::new(),
+        }
+    }
+
+    /// Look for changes since the last call, or since the watcher was made. Doesn't wait.
+    pub fn changes(&mut self) -> Changes {
+        let binary = match &self.exe {
+            Some((exe, stamp)) => stamp_of(exe).map(|s| s != *stamp).unwrap_or(false),
+            None => false,
+        };
+
+        let rust = self.scan_rust();
+        let rust_changed = rust != self.rust;
+        self.rust = rust;
+
+        let features = self.scan_features();
+        let mut changed: Vec<_> = features
+            .iter()
+            .filter(|(path, stamp)| self.features.get(*path) != Some(*stamp))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.sort();
+        self.features = features;
+
+        Changes {
+            features: changed,
+            rust: rust_changed,
+            binary,
+        }
+    }
+
+    /// Wait for feature files to change, and return the ones that did. Returns `None` if `stop`
+    /// is set. Restarts the process if the test binary changes.
+    fn wait(&mut self, stop: &Flag) -> Option<Vec<PathBuf>> {
+        while !stop.is_set() {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let changes = self.changes();
+            if changes.binary {
+                if let Some((exe, _)) = &self.exe {
+                    restart(exe);
+                }
+            }
+            if changes.rust {
+                eprintln!("Rust sources changed. Rebuild the tests to pick up new steps.");
+            }
+            if !changes.features.is_empty() {
+                return Some(changes.features);
+            }
+        }
+        None
+    }
+}
+
+/// Stamps of files with an extension in `paths`, recursively
+fn scan(paths: &[PathBuf], extension: &str) -> HashMap<PathBuf, Stamp> {
+    let mut found = HashMap::new();
+    for path in paths {
+        let files = if path.is_dir() {
+            find_files(path.clone(), extension)
+        } else {
+            vec![path.clone()]
+        };
+        for file in files {
+            if let Some(stamp) = stamp_of(&file) {
+                found.insert(file, stamp);
+            }
+        }
+    }
+    found
+}
+
+fn stamp_of(path: &Path) -> Option<Stamp> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Replace this process with a new copy of the test binary, with the same arguments
+#[cfg(unix)]
+fn restart(exe: &Path) -> ! {
+    use std::os::unix::process::CommandExt;
+    eprintln!("The test binary changed. Restarting.");
+    let e = std::process::Command::new(exe)
+        .args(std::env::args_os().skip(1))
+        .exec();
+    eprintln!("Error: Could not restart: {}", e);
+    std::process::exit(101)
+}
+
+#[cfg(not(unix))]
+fn restart(_exe: &Path) -> ! {
+    eprintln!("The test binary changed. Run the tests again to pick up the changes.");
+    std::process::exit(0)
+}
+
+/// Remove arguments meant for libtest. `--skip FILTER` becomes `--exclude FILTER`, and `-q` becomes
+/// `--quiet`, given once. Returns `None` if `--list` was given.
+fn libtest_args<I: IntoIterator<Item = OsString>>(args: I) -> Option<Vec<OsString>> {
+    let mut out = vec![];
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let name = match arg.to_str() {
+            Some(s) => s.split('=').next().unwrap_or(s).to_string(),
+            None => {
+                out.push(arg);
+                continue;
+            }
+        };
+        let has_value = arg.to_str().map(|s| s.contains('=')).unwrap_or(false);
+
+        if name == "--list" {
+            return None;
+        } else if LIBTEST_FLAGS.contains(&name.as_str()) {
+            continue;
+        } else if name == "-q" || name == "--quiet" {
+            // libtest's quiet output is the plain reporter's. `cargo test -q -- -q` gives it twice.
+            if !out.iter().any(|a| a == "--quiet") {
+                out.push("--quiet".into());
+            }
+        } else if name.starts_with("-Z") && name.len() > 2 {
+            // -Zunstable-options
+            continue;
+        } else if LIBTEST_OPTIONS.contains(&name.as_str()) {
+            if !has_value {
+                args.next();
+            }
+        } else if name == "--skip" {
+            out.push("--exclude".into());
+            if has_value {
+                out.push(arg.to_str().unwrap()["--skip=".len()..].into());
+            } else {
+                out.extend(args.next());
+            }
+        } else {
+            out.push(arg);
+        }
+    }
+
+    Some(out)
+}
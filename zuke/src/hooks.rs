@@ -1,8 +1,26 @@
 //! Implements before/after hook functions, and tag expressions.
+//!
+//! Hook macros such as `#[before_scenario]` take an optional tag expression and an optional
+//! `order`, e.g. `#[before_scenario("@db and not @readonly", order = 10)]`. Before hooks run in
+//! ascending order, and after hooks run in descending order, so that setup and cleanup nest
+//! properly. The default order is 0. Hooks with the same order nest too: after hooks run in the
+//! reverse of their registration order.
+//!
+//! Hooks can also be limited to components by name, with `feature`, `rule`, `scenario`, or `path`
+//! (the feature file) and a regex that must match the whole name. For example,
+//! `#[before_scenario(feature = "Checkout.*")]` only runs for scenarios in features whose names
+//! start with "Checkout". This lets a shared step crate limit hooks to its own features, without
+//! tagging every scenario. Components without that kind of name, such as the global component for
+//! a `feature` filter, never match.
 
-use crate::{ComponentKind, Context, Fixture, Scope};
+use crate::panic::PanicToError;
+use crate::{Component, ComponentKind, Context, Event, Fixture, MultiError, Scope};
+use anyhow::Context as _;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use futures::future::BoxFuture;
+use regex::Regex;
+use std::sync::Arc;
 
 /// Simple, stack based operations for tag expressions
 #[derive(Debug)]
@@ -57,6 +75,7 @@ fn eval_expr(ops: &[Operation], context: &Context, stack: &mut Vec<bool>) -> boo
 
 /// Should a `BeforeAfterHook` run before or after? Usually macro generated
 #[allow(missing_docs)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BeforeAfter {
     Before,
     After,
@@ -64,6 +83,8 @@ pub enum BeforeAfter {
 
 /// Used to register a hook. Usually macro generated
 pub struct BeforeAfterHook {
+    /// The name of the hook function, including its module path
+    pub name: &'static str,
     /// Is this a before or after hook?
     pub when: BeforeAfter,
     /// This triggers before/after this type of component
@@ -72,9 +93,109 @@ pub struct BeforeAfterHook {
     pub func: for<'a> fn(&'a mut Context) -> BoxFuture<'a, anyhow::Result<()>>,
     /// The tag expression. May be empty.
     pub expr: Vec<Operation>,
+    /// Where this hook runs relative to other hooks for the same component. Before hooks run in
+    /// ascending order, and after hooks run in descending order. Before hooks with the same order
+    /// run in registration order, and after hooks in the reverse.
+    pub order: i32,
+    /// Name filters. The hook only runs if all of them match.
+    pub names: Vec<NameFilter>,
 }
 inventory::collect!(BeforeAfterHook);
 
+/// Which name a [`NameFilter`] checks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameField {
+    /// The feature's name
+    Feature,
+    /// The rule's name
+    Rule,
+    /// The scenario's name
+    Scenario,
+    /// The path of the feature file
+    Path,
+}
+
+/// Limits a hook to components with matching names. Usually macro generated
+#[derive(Debug)]
+pub struct NameFilter {
+    /// The name to check
+    pub field: NameField,
+    /// Must match the whole name
+    pub regex: Regex,
+}
+
+impl NameFilter {
+    /// Does the component have a matching name?
+    pub fn matches(&self, component: &Component) -> bool {
+        let name = match self.field {
+            NameField::Feature => component.feature().map(|f| f.name.as_str()),
+            NameField::Rule => component.rule().map(|r| r.name.as_str()),
+            NameField::Scenario => component.scenario().map(|s| s.name.as_str()),
+            NameField::Path => component.path().and_then(|p| p.to_str()),
+        };
+        name.map_or(false, |name| self.regex.is_match(name))
+    }
+}
+
+/// The result of running a single hook function. Sent to reporters via
+/// [`crate::Event::HookFinished`].
+#[derive(Debug)]
+pub struct HookOutcome {
+    /// The component the hook ran for
+    pub component: Arc<Component>,
+    /// The name of the hook function, including its module path
+    pub name: &'static str,
+    /// Whether this was a before or after hook
+    pub when: BeforeAfter,
+    /// When the hook started
+    pub started: DateTime<Utc>,
+    /// When the hook finished
+    pub ended: DateTime<Utc>,
+    /// The error returned by the hook, if any
+    pub error: Option<String>,
+}
+
+/// Run hooks whose tag expressions match, attributing failures to the hook that caused them.
+/// Before hooks stop at the first failure. After hooks all run, and report every failure.
+async fn run_hooks(
+    hooks: &[&'static BeforeAfterHook],
+    context: &mut Context,
+    when: BeforeAfter,
+) -> anyhow::Result<()> {
+    let mut errors = MultiError::new();
+    let mut stack = vec![];
+    for hook in hooks.iter() {
+        if !eval_expr(&hook.expr, context, &mut stack)
+            || !hook.names.iter().all(|f| f.matches(context.component()))
+        {
+            continue;
+        }
+
+        let started = Utc::now();
+        let result = PanicToError::from((hook.func)(context)).await;
+        let outcome = HookOutcome {
+            component: context.component().clone(),
+            name: hook.name,
+            when: hook.when,
+            started,
+            ended: Utc::now(),
+            error: result.as_ref().err().map(|e| format!("{:#}", e)),
+        };
+        context
+            .broadcast(Event::HookFinished(Arc::new(outcome)))
+            .await;
+
+        if let Err(e) = result.with_context(|| format!("Hook {} failed", hook.name)) {
+            errors.push(e);
+            if when == BeforeAfter::Before {
+                break;
+            }
+        }
+    }
+
+    errors.into_result()
+}
+
 #[derive(Default)]
 struct HookSet {
     before: Vec<&'static BeforeAfterHook>,
@@ -114,6 +235,20 @@ impl Fixture for HookRunner {
             set.push(hook);
         }
 
+        // After hooks unwind in the opposite order from before hooks
+        for set in [
+            &mut hooks.global,
+            &mut hooks.feature,
+            &mut hooks.rule,
+            &mut hooks.scenario,
+            &mut hooks.step,
+        ] {
+            set.before.sort_by_key(|hook| hook.order);
+            // The sort is stable, so hooks with the same order stay reversed
+            set.after.reverse();
+            set.after.sort_by_key(|hook| std::cmp::Reverse(hook.order));
+        }
+
         Ok(hooks)
     }
 
@@ -126,14 +261,7 @@ impl Fixture for HookRunner {
             ComponentKind::Step => &self.step,
         };
 
-        let mut stack = vec![];
-        for hook in set.before.iter() {
-            if eval_expr(&hook.expr, context, &mut stack) {
-                (hook.func)(context).await?;
-            }
-        }
-
-        Ok(())
+        run_hooks(&set.before, context, BeforeAfter::Before).await
     }
 
     async fn after(&self, context: &mut Context) -> anyhow::Result<()> {
@@ -145,13 +273,6 @@ impl Fixture for HookRunner {
             ComponentKind::Step => &self.step,
         };
 
-        let mut stack = vec![];
-        for hook in set.after.iter() {
-            if eval_expr(&hook.expr, context, &mut stack) {
-                (hook.func)(context).await?;
-            }
-        }
-
-        Ok(())
+        run_hooks(&set.after, context, BeforeAfter::After).await
     }
 }
@@ -0,0 +1,200 @@
+//! Interactive features that need a terminal, such as `--debug` and `@pause`
+//!
+//! Prompts are written to stderr, so that they don't mix with reports written to stdout.
+
+use crate::context::{Context, OpenContext};
+use crate::extra_options;
+use crate::outcome::Outcome;
+use crate::vocab::Vocab;
+use async_std::io::{prelude::*, stderr, stdin};
+use async_std::sync::{Mutex, MutexGuard};
+use clap::{App, Arg};
+use lazy_static::lazy_static;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+lazy_static! {
+    static ref TERMINAL: Mutex<()> = Mutex::new(());
+}
+
+const DEBUG_HELP: &str = "\
+Commands:
+    retry, r        Run the failed step again
+    continue, c     Keep the failure and carry on
+    cancel          Cancel the test run
+    abort           Abort the test run, without tearing down fixtures
+    help, h         Show this message
+Anything else is run as a step, e.g. `Given a user named \"alice\"`. The keyword is optional.";
+
+#[extra_options]
+fn interactive_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("debug")
+            .long("debug")
+            .help("When a step fails, pause the scenario and prompt for what to do"),
+    )
+    .arg(
+        Arg::with_name("interactive")
+            .long("interactive")
+            .help("Stop at @pause tags and wait for Enter"),
+    )
+}
+
+/// Commands for `--debug` to use instead of reading stdin, one for each prompt. Give it to
+/// [`ZukeBuilder::setting`](crate::ZukeBuilder::setting) to script the prompts, as in a test.
+/// Once the commands run out, the input has ended.
+#[derive(Clone, Default)]
+pub struct DebugInput {
+    commands: Arc<std::sync::Mutex<VecDeque<String>>>,
+}
+
+impl DebugInput {
+    /// Answer the prompts with `commands`, in order
+    pub fn new<I, S>(commands: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let commands = commands.into_iter().map(Into::into).collect();
+        Self {
+            commands: Arc::new(std::sync::Mutex::new(commands)),
+        }
+    }
+
+    fn next(&self) -> Option<String> {
+        self.commands.lock().unwrap().pop_front()
+    }
+}
+
+/// Take turns using the terminal. Scenarios run in parallel, so anything that prompts the user
+/// should hold this until it has its answer.
+pub async fn lock_terminal() -> MutexGuard<'static, ()> {
+    TERMINAL.lock().await
+}
+
+/// Print a prompt to stderr and read a line from stdin, without the line ending. Returns `None` at
+/// the end of input. Use [`lock_terminal`] first.
+pub async fn read_line(prompt: &str) -> Option<String> {
+    let mut out = stderr();
+    out.write_all(prompt.as_bytes()).await.ok()?;
+    out.flush().await.ok()?;
+
+    let mut line = String::new();
+    match stdin().read_line(&mut line).await {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim_end_matches(&['\r', '\n'][..]).to_string()),
+    }
+}
+
+/// With `--interactive`, print `message` and wait for the user to press Enter. Otherwise, do
+/// nothing, so that pauses left in a test suite don't hang CI.
+pub async fn pause(context: &Context, message: &str) {
+    if !context.options().opts.is_present("interactive") {
+        return;
+    }
+
+    let _terminal = lock_terminal().await;
+    eprintln!("\n{}", message);
+    read_line("Press Enter to continue...").await;
+}
+
+/// The reason a step failed, for display
+fn describe(outcome: &Outcome) -> String {
+    match &outcome.reason {
+        Some(r) => format!("{:#}", r),
+        None => outcome.verdict.to_string(),
+    }
+}
+
+/// Read the next `--debug` command, from [`DebugInput`] if there is one
+async fn read_command(context: &Context) -> Option<String> {
+    match context.options().get_extension::<DebugInput>() {
+        Some(input) => input.next(),
+        None => read_line("zuke> ").await,
+    }
+}
+
+/// Run the failed step again. Its after hooks run for the failed attempt, and its before hooks
+/// for the new one, as if it were a new step.
+async fn retry(open: &mut OpenContext, vocab: &Vocab) {
+    open.after_hooks().await;
+    let component = open.context.component().clone();
+    *open.context.outcome_mut() = Outcome::undecided(component);
+    open.before_hooks().await;
+    if !open.context.outcome().passed_or_undecided() {
+        return;
+    }
+
+    let result = vocab.execute(&mut open.context).await;
+    let outcome = open.context.outcome_mut();
+    if result.is_ok() && !outcome.is_undecided() {
+        outcome.ended = chrono::Utc::now();
+    } else {
+        outcome.set_result(result);
+    }
+}
+
+/// If `--debug` was given and the current step failed, prompt the user for what to do. The step's
+/// outcome may change if it is retried.
+pub(crate) async fn debug_failed_step(open: &mut OpenContext, vocab: &Vocab) {
+    let context = &open.context;
+    if !context.outcome().failed() || !context.options().opts.is_present("debug") {
+        return;
+    }
+
+    let (ty, keyword, value) = match context.step() {
+        Some(s) => (s.ty, s.keyword.clone(), s.value.clone()),
+        None => return,
+    };
+    let scenario = context
+        .component()
+        .scenario()
+        .map(|s| s.name.clone())
+        .unwrap_or_default();
+
+    let _terminal = lock_terminal().await;
+    eprintln!(
+        "\nStep failed in scenario {:?}:\n    {} {}\n{}\n\n{}",
+        scenario,
+        keyword,
+        value,
+        describe(context.outcome()),
+        DEBUG_HELP
+    );
+
+    while let Some(line) = read_command(&open.context).await {
+        match line.trim() {
+            "" => (),
+            "help" | "h" => eprintln!("{}", DEBUG_HELP),
+            "continue" | "c" => break,
+            "cancel" => {
+                open.context.options().canceled.set();
+                break;
+            }
+            "abort" => {
+                open.context.options().aborted.set();
+                break;
+            }
+            "retry" | "r" => {
+                retry(open, vocab).await;
+                let outcome = open.context.outcome();
+                if outcome.failed() {
+                    eprintln!("Failed again: {}", describe(outcome));
+                } else {
+                    eprintln!("{}", outcome.verdict);
+                    break;
+                }
+            }
+            text => {
+                let (ty, keyword, text) = vocab.split_keyword(text, ty);
+                match vocab
+                    .execute_text(&mut open.context, ty, keyword, text)
+                    .await
+                {
+                    Ok(()) => eprintln!("Ok"),
+                    Err(e) => eprintln!("Error: {:#}", e),
+                }
+            }
+        }
+    }
+}
@@ -19,39 +19,66 @@
 //! [3]: https://en.wikipedia.org/wiki/Test_fixture
 
 extern crate self as zuke;
+pub mod artifact;
+pub mod assert;
+pub mod clock;
 pub mod component;
+pub mod config;
 pub mod context;
 pub mod event;
 pub mod fixture;
 pub mod flag;
+pub mod harness;
 pub mod hooks;
+pub mod interactive;
+pub mod metadata;
 pub mod options;
 pub mod outcome;
 #[doc(hidden)]
 pub mod panic;
 pub mod parser;
+#[cfg(feature = "wasm")]
+pub mod plugin;
+pub mod query;
 #[doc(hidden)]
 pub mod reexport;
+pub mod remote;
+pub mod replay;
 pub mod reporter;
+pub mod run_info;
 pub mod runner;
+pub mod state;
 pub mod step;
+pub mod suite;
+pub mod testkit;
 pub mod top;
 pub mod vocab;
 
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
 #[cfg(feature = "tags")]
 pub mod tags;
 
+pub use artifact::*;
+pub use clock::*;
 pub use component::*;
+pub use config::*;
 pub use context::*;
 pub use event::*;
 pub use fixture::*;
+pub use metadata::*;
 pub use options::*;
 pub use outcome::*;
 pub use panic::*;
 pub use parser::*;
+pub use query::*;
+pub use replay::*;
 pub use reporter::*;
+pub use run_info::*;
 pub use runner::*;
+pub use state::*;
 pub use step::*;
+pub use suite::*;
 pub use top::*;
 pub use vocab::*;
 pub use zuke_macros::*;
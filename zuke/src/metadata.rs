@@ -0,0 +1,138 @@
+//! Per-file settings at the top of feature files
+//!
+//! A feature file can start with settings for the whole file, as `# zuke:` comments:
+//!
+//! ```gherkin
+//! # zuke: owner: team-payments
+//! # zuke: tags: @slow @database
+//! # zuke: timeout: 30s
+//! Feature: Refunds
+//! ```
+//!
+//! or as a front-matter block, which must be the very first thing in the file. Only simple
+//! `key: value` lines are understood.
+//!
+//! ```gherkin
+//! ---
+//! owner: team-payments
+//! tags: [slow, database]
+//! language: fr
+//! ---
+//! Fonctionnalité: Remboursements
+//! ```
+//!
+//! The settings are:
+//!
+//! - `tags`: tags added to the feature, separated by spaces or commas. The `@` is optional.
+//! - `timeout`: a timeout for each scenario, such as `30s`, as with the `@timeout` tag
+//! - `owner`: who owns the feature
+//! - `language`: the language of the file, unless it has a `# language:` comment
+//!
+//! Other keys are kept as well. Hooks and reporters can find the settings with
+//! [`crate::Component::metadata`].
+
+use crate::options::parse_duration;
+use anyhow::Context as _;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Settings from the top of a feature file. See the [module docs](self).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FeatureMetadata {
+    /// Tags added to the feature, without the `@`
+    pub tags: Vec<String>,
+    /// The timeout for each scenario in the feature
+    pub timeout: Option<Duration>,
+    /// Who owns the feature
+    pub owner: Option<String>,
+    /// The language of the feature file
+    pub language: Option<String>,
+    /// Other settings, by key
+    pub other: BTreeMap<String, String>,
+}
+
+impl FeatureMetadata {
+    /// A setting that zuke doesn't use itself
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.other.get(key).map(String::as_str)
+    }
+
+    /// Were there no settings?
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> anyhow::Result<()> {
+        match key {
+            "tags" => {
+                let tags = value
+                    .split(|c: char| c.is_whitespace() || matches!(c, ',' | '[' | ']'))
+                    .map(|t| t.trim_start_matches('@'))
+                    .filter(|t| !t.is_empty());
+                self.tags.extend(tags.map(String::from));
+            }
+            "timeout" => self.timeout = Some(parse_duration(value)?),
+            "owner" => self.owner = Some(value.into()),
+            "language" => self.language = Some(value.into()),
+            _ => {
+                self.other.insert(key.into(), value.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the settings at the top of a feature file. Returns them, along with the source with
+    /// any front-matter block blanked out, so that line numbers still match the file.
+    pub(crate) fn split(source: &str) -> anyhow::Result<(Self, String)> {
+        let mut metadata = Self::default();
+        let mut lines: Vec<&str> = source.lines().collect();
+        let mut start = 0;
+
+        if lines.first().map(|l| l.trim() == "---").unwrap_or(false) {
+            let end = lines[1..]
+                .iter()
+                .position(|l| l.trim() == "---")
+                .map(|i| i + 1)
+                .context("Front matter starts with \"---\", but doesn't end with one")?;
+            for (i, line) in lines[1..end].iter().enumerate() {
+                metadata
+                    .set_line(line)
+                    .with_context(|| format!("Bad front matter at line {}", i + 2))?;
+            }
+            for line in lines[..=end].iter_mut() {
+                *line = "";
+            }
+            start = end + 1;
+        }
+
+        // Comments, up until the first line that isn't one
+        for (i, line) in lines.iter().enumerate().skip(start) {
+            let line = line.trim();
+            if !line.is_empty() && !line.starts_with('#') {
+                break;
+            }
+            if let Some(setting) = line.strip_prefix("# zuke:") {
+                metadata
+                    .set_line(setting)
+                    .with_context(|| format!("Bad zuke comment at line {}", i + 1))?;
+            }
+        }
+
+        let mut source = lines.join("\n");
+        source.push('\n');
+        Ok((metadata, source))
+    }
+
+    fn set_line(&mut self, line: &str) -> anyhow::Result<()> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(());
+        }
+        let (key, value) = line
+            .split_once(':')
+            .with_context(|| format!("Expected \"key: value\", not {:?}", line))?;
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'');
+        self.set(key.trim(), value)
+    }
+}
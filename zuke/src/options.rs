@@ -1,12 +1,28 @@
 //! Top level test configuration
+use crate::component::ComponentId;
+use crate::config::Config;
 use crate::context::Context;
+use crate::fixture::{Fixture, FixtureReplacements};
 use crate::flag::Flag;
+use crate::remote::RemoteSteps;
+use crate::run_info::RunInfo;
+use crate::top::ConfigError;
 use crate::vocab::Vocab;
 use anyhow::Context as _;
-use clap::{App, Arg, ArgMatches};
+use clap::{App, Arg, ArgMatches, ErrorKind};
 use futures::future::BoxFuture;
 use regex::{RegexSet, RegexSetBuilder};
+use serde::de::DeserializeOwned;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::ffi::OsString;
 use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+
+/// The default prefix for environment variables that set command line options. See
+/// [`TestOptionsBuilder::env_prefix`].
+pub const ENV_PREFIX: &str = "ZUKE_";
 
 /// A callback that executes just prior to test execution.
 pub trait HookFn:
@@ -18,6 +34,16 @@ impl<F> HookFn for F where
 {
 }
 
+/// A named set of tag handlers. These are usually globally scoped fixtures that look at tags in
+/// their `before` and `after` methods, like those in [`crate::tags`]. See
+/// [`TestOptionsBuilder::tag_plugin`].
+pub struct TagPlugin {
+    /// The name of the plugin
+    pub name: String,
+    /// Installs the plugin just before the test run begins
+    pub hook: Box<dyn HookFn>,
+}
+
 /// Global test information
 ///
 /// This has also gained some global state...
@@ -28,16 +54,63 @@ pub struct TestOptions {
     pub vocab: Arc<Vocab>,
     /// Title of the test run. An arbitrary value that may be used by reporters.
     pub title: String,
+    /// The name of the suite this test run is part of, when a [`crate::SuiteRunner`] runs it.
+    /// Component keys start with it, so that suites that share a feature path don't collide.
+    pub suite: Option<String>,
     /// Hooks that run prior to test execution.
     pub pre_test_hooks: Arc<Vec<Box<dyn HookFn>>>,
+    /// Use the tag handlers in [`crate::tags`]
+    pub default_tags: bool,
+    /// Additional tag handlers. These are installed after the pre-test hooks.
+    pub tag_plugins: Arc<Vec<TagPlugin>>,
     /// Names of components to include. Not that an empty set means include everything
     pub included: RegexSet,
     /// Names of components to exclude. Not that an empty set means exclude nothing
     pub excluded: RegexSet,
+    /// Only run scenarios in this shard. Others are excluded.
+    pub shard: Option<Shard>,
+    /// Only run scenarios owned by one of these, from `--owner`. Others are excluded. Empty means
+    /// any scenario. See [`crate::Component::owners`].
+    pub owners: Vec<String>,
+    /// Run each scenario more than once
+    pub repeat: Option<Repeat>,
+    /// Benchmark each scenario, with `--bench`. Scenarios are repeated as well; see
+    /// [`Self::repeat`].
+    pub bench: Option<Bench>,
+    /// Steps that pass, but take longer than this, pass with warnings instead
+    pub warn_slow_step: Option<Duration>,
+    /// Settings from the config file
+    pub config: Config,
+    /// Typed settings from [`TestOptionsBuilder::setting`]
+    pub extensions: Extensions,
+    /// Fixtures set up as other types, from [`TestOptionsBuilder::replace_fixture`] and the
+    /// profile
+    pub fixture_replacements: FixtureReplacements,
+    /// The profile chosen with `--profile`, such as `smoke` or `full`
+    pub profile: Option<String>,
+    /// The chosen profile's settings, from [`TestOptionsBuilder::profile`]. Empty if there's no
+    /// profile.
+    pub profile_settings: Profile,
+    /// The random seed, from `--seed` or else chosen at random. Anything that generates test data
+    /// should be seeded from it, so that a failing run can be repeated.
+    pub seed: u64,
+    /// Where and how the test run happened, for reports
+    pub run_info: RunInfo,
     /// Notification that the user would like to cancel the test run
     pub canceled: Flag,
+    /// Notification that the test run should stop immediately, without waiting for steps to
+    /// notice cancellation or for fixtures to tear down.
+    pub aborted: Flag,
+    /// Problems with the options that didn't stop the test run, such as environment variables
+    /// that don't match any option. They become warnings on the test run's outcome.
+    pub warnings: Vec<String>,
 }
 
+/// An option from outside the command line doesn't match any command line option
+#[derive(Error, Debug)]
+#[error("{0} does not match any command line option")]
+struct UnknownOption(String);
+
 impl TestOptions {
     /// Creats a [`TestOptionsBuilder`]
     pub fn builder() -> TestOptionsBuilder {
@@ -58,6 +131,202 @@ impl TestOptions {
     pub fn excludes(&self, name: &str) -> bool {
         self.excluded.is_match(name)
     }
+
+    /// A custom setting from the `[settings]` table of the config file
+    pub fn setting(&self, key: &str) -> Option<&toml::Value> {
+        self.config.settings.get(key)
+    }
+
+    /// A custom setting from the `[settings]` table of the config file, deserialized as `T`.
+    /// `Ok(None)` if the setting isn't present.
+    ///
+    /// ```ignore
+    /// #[derive(Deserialize)]
+    /// struct Server {
+    ///     url: String,
+    ///     timeout: u32,
+    /// }
+    ///
+    /// // [settings.server]
+    /// // url = "http://localhost:8080"
+    /// // timeout = 30
+    /// let server: Option<Server> = context.options().setting_as("server")?;
+    /// ```
+    pub fn setting_as<T: DeserializeOwned>(&self, key: &str) -> anyhow::Result<Option<T>> {
+        match self.setting(key) {
+            None => Ok(None),
+            Some(value) => {
+                let value = value
+                    .clone()
+                    .try_into()
+                    .with_context(|| format!("Bad setting {:?}", key))?;
+                Ok(Some(value))
+            }
+        }
+    }
+
+    /// A typed setting given to [`TestOptionsBuilder::setting`] or [`crate::ZukeBuilder::setting`]
+    pub fn get_extension<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.extensions.get()
+    }
+
+    /// Was this profile chosen with `--profile`?
+    pub fn is_profile(&self, name: &str) -> bool {
+        self.profile.as_deref() == Some(name)
+    }
+
+    /// Is the component in the shard we're running? Always true if we're not sharding.
+    pub fn in_shard(&self, id: ComponentId) -> bool {
+        self.shard.map(|s| s.contains(id)).unwrap_or(true)
+    }
+}
+
+/// Typed settings, at most one of each type. Fixtures can use these to get configuration that's
+/// set up in code, rather than parsing command line arguments.
+#[derive(Default)]
+pub struct Extensions {
+    map: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Extensions {
+    /// Add a setting, replacing any other setting of the same type
+    pub fn insert<T: Any + Send + Sync>(&mut self, value: T) {
+        self.map.insert(TypeId::of::<T>(), Box::new(value));
+    }
+
+    /// Get a setting by type
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+}
+
+/// Settings that apply only when a profile is chosen with `--profile`, so that one suite can run
+/// at different depths: a quick smoke test against fakes, or everything against real backends.
+/// See [`TestOptionsBuilder::profile`].
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    /// Only scenarios with one of these tags run. Empty means any scenario.
+    pub tags: Vec<String>,
+    /// Scenarios with any of these tags are excluded
+    pub excluded_tags: Vec<String>,
+    fixture_replacements: FixtureReplacements,
+}
+
+impl Profile {
+    /// Only run scenarios with this tag, or another given here. Tags of the feature and rule
+    /// count.
+    pub fn tagged<S: AsRef<str>>(&mut self, tag: S) -> &mut Self {
+        self.tags.push(strip_at(tag.as_ref()));
+        self
+    }
+
+    /// Exclude scenarios with this tag
+    pub fn exclude_tagged<S: AsRef<str>>(&mut self, tag: S) -> &mut Self {
+        self.excluded_tags.push(strip_at(tag.as_ref()));
+        self
+    }
+
+    /// Set up fixture `R` wherever `T` is used. See [`TestOptionsBuilder::replace_fixture`].
+    pub fn replace_fixture<T, R>(&mut self) -> &mut Self
+    where
+        T: Fixture + From<R>,
+        R: Fixture,
+    {
+        self.fixture_replacements.replace::<T, R>();
+        self
+    }
+
+    /// Does a scenario with these tags run under this profile?
+    pub fn allows<'a, I>(&self, tags: I) -> bool
+    where
+        I: IntoIterator<Item = &'a String>,
+    {
+        let tags: Vec<_> = tags.into_iter().collect();
+        (self.tags.is_empty() || tags.iter().any(|t| self.tags.contains(*t)))
+            && !tags.iter().any(|t| self.excluded_tags.contains(*t))
+    }
+}
+
+fn strip_at(tag: &str) -> String {
+    tag.strip_prefix('@').unwrap_or(tag).to_string()
+}
+
+/// One of several shards that split up the test suite, so that separate test runs (e.g., CI jobs)
+/// can each run part of it. Scenarios are assigned to shards by [`ComponentId`], so every run
+/// agrees on which shard a scenario belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    /// Which shard this is, starting from 1
+    pub index: u64,
+    /// The total number of shards
+    pub count: u64,
+}
+
+impl Shard {
+    /// Is the component in this shard?
+    pub fn contains(&self, id: ComponentId) -> bool {
+        id.0 % self.count == self.index - 1
+    }
+}
+
+/// How many times to run each scenario. Each run is recorded as a child of the scenario's outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repeat {
+    /// The number of times to run each scenario
+    pub count: usize,
+    /// Stop repeating a scenario as soon as it fails
+    pub until_failure: bool,
+}
+
+/// How to benchmark scenarios, with `--bench`. Each scenario runs `warmup + iterations` times,
+/// and the bench reporter ([`crate::BenchReporter`]) times all but the warmup runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bench {
+    /// The number of timed runs of each scenario
+    pub iterations: usize,
+    /// The number of untimed runs first
+    pub warmup: usize,
+}
+
+impl std::str::FromStr for Shard {
+    type Err = anyhow::Error;
+
+    /// Parse a shard given as `K/N`
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let (index, count) = s
+            .split_once('/')
+            .ok_or_else(|| anyhow::anyhow!("Expected K/N"))?;
+        let index: u64 = index.trim().parse()?;
+        let count: u64 = count.trim().parse()?;
+        if count == 0 || index == 0 || index > count {
+            anyhow::bail!("Shard must be between 1/{0} and {0}/{0}", count.max(1));
+        }
+        Ok(Self { index, count })
+    }
+}
+
+/// Parse a duration with a unit, such as `500ms`, `2s`, `1.5m`, or `1h`. A bare number is in
+/// seconds.
+pub fn parse_duration(text: &str) -> anyhow::Result<Duration> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .with_context(|| format!("Bad duration {:?}", text))?;
+    let scale = match unit.trim() {
+        "ms" => 0.001,
+        "" | "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        unit => anyhow::bail!("Bad duration {:?}: unknown unit {:?}", text, unit),
+    };
+    Duration::try_from_secs_f64(number * scale)
+        .with_context(|| format!("Bad duration {:?}: too long", text))
 }
 
 /// A hook that can add command line arguments. Useful for adding arguments for test fixtures.
@@ -96,8 +365,19 @@ pub struct TestOptionsBuilder {
     // Can't contain clap::App, because that's not Send. Make it harder to test this struct using Zuke
     // itself
     title: String,
+    suite: Option<String>,
     pre_test_hooks: Vec<Box<dyn HookFn>>,
+    default_tags: bool,
+    tag_plugins: Vec<TagPlugin>,
     canceled: Flag,
+    aborted: Flag,
+    env_prefix: Option<String>,
+    default_config: bool,
+    extensions: Extensions,
+    step_priority: Vec<String>,
+    vocab: Option<Vocab>,
+    fixture_replacements: FixtureReplacements,
+    profiles: HashMap<String, Profile>,
 }
 
 impl Default for TestOptionsBuilder {
@@ -111,8 +391,19 @@ impl TestOptionsBuilder {
     pub fn new() -> Self {
         Self {
             title: String::from("Zuke"),
+            suite: None,
             pre_test_hooks: vec![],
+            default_tags: true,
+            tag_plugins: vec![],
             canceled: Flag::new(),
+            aborted: Flag::new(),
+            env_prefix: Some(ENV_PREFIX.to_string()),
+            default_config: true,
+            extensions: Extensions::default(),
+            step_priority: vec![],
+            vocab: None,
+            fixture_replacements: FixtureReplacements::default(),
+            profiles: HashMap::new(),
         }
     }
 
@@ -128,6 +419,44 @@ impl TestOptionsBuilder {
         self
     }
 
+    /// Name the suite this test run is part of. See [`TestOptions::suite`].
+    pub(crate) fn suite<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.suite = Some(name.into());
+        self
+    }
+
+    /// Use the tag handlers in [`crate::tags`], such as `@skip`. The default is true.
+    pub fn default_tags(&mut self, enable: bool) -> &mut Self {
+        self.default_tags = enable;
+        self
+    }
+
+    /// Add a named tag plugin, which is installed just before the test run begins.
+    pub fn tag_plugin<N: Into<String>, F: HookFn>(&mut self, name: N, hook: F) -> &mut Self {
+        self.tag_plugins.push(TagPlugin {
+            name: name.into(),
+            hook: Box::new(hook),
+        });
+        self
+    }
+
+    /// Use these steps rather than every registered step. See [`Vocab::builder`].
+    pub fn vocab(&mut self, vocab: Vocab) -> &mut Self {
+        self.vocab = Some(vocab);
+        self
+    }
+
+    /// When several step implementations match a step, prefer the one from the first of these
+    /// crates or modules that provides exactly one of them. See [`Vocab::with_step_priority`].
+    pub fn step_priority<I, S>(&mut self, names: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.step_priority = names.into_iter().map(Into::into).collect();
+        self
+    }
+
     /// Set the canceled flag. You probably won't need this.
     ///
     /// Used to share cancelation between multiple Zuke instances
@@ -136,6 +465,72 @@ impl TestOptionsBuilder {
         self
     }
 
+    /// Set the aborted flag. You probably won't need this.
+    ///
+    /// Used to share a forceful abort between multiple Zuke instances
+    pub fn abort(&mut self, flag: Flag) -> &mut Self {
+        self.aborted = flag;
+        self
+    }
+
+    /// Set the prefix of environment variables that set command line options. The default is
+    /// [`ENV_PREFIX`].
+    ///
+    /// Any option can be set by an environment variable named after its long form: `ZUKE_REPEAT=3`
+    /// is the same as `--repeat 3`, and `ZUKE_STRICT=true` is the same as `--strict`. Options given
+    /// on the command line take precedence. Options that may be given more than once combine
+    /// values from both. Variables that don't match an option are ignored, with a warning.
+    pub fn env_prefix<S: Into<String>>(&mut self, prefix: S) -> &mut Self {
+        self.env_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Don't read command line options from environment variables
+    pub fn ignore_env(&mut self) -> &mut Self {
+        self.env_prefix = None;
+        self
+    }
+
+    /// Don't read [`DEFAULT_CONFIG`](crate::config::DEFAULT_CONFIG) when no config file is given
+    /// with `--config`
+    pub fn ignore_config(&mut self) -> &mut Self {
+        self.default_config = false;
+        self
+    }
+
+    /// Add a typed setting, which fixtures can read with [`TestOptions::get_extension`]. There can
+    /// be one setting of each type.
+    pub fn setting<T: Any + Send + Sync>(&mut self, value: T) -> &mut Self {
+        self.extensions.insert(value);
+        self
+    }
+
+    /// Set up `R` wherever fixture `T` is used, such as a fake database in place of a real one.
+    /// Steps still ask for, and get, a `T`: the replacement is converted with `From`.
+    pub fn replace_fixture<T, R>(&mut self) -> &mut Self
+    where
+        T: Fixture + From<R>,
+        R: Fixture,
+    {
+        self.fixture_replacements.replace::<T, R>();
+        self
+    }
+
+    /// Settings for when `--profile name` is given, to add to. Fixtures and tag handlers can also
+    /// check for a profile with [`TestOptions::is_profile`]. Every profile must be set up here,
+    /// even one with no settings, so that a misspelled `--profile` is a
+    /// [`crate::ConfigError::UnknownProfile`].
+    ///
+    /// ```ignore
+    /// builder
+    ///     .profile("smoke")
+    ///     .tagged("smoke")
+    ///     .replace_fixture::<Database, FakeDatabase>();
+    /// ```
+    pub fn profile<S: Into<String>>(&mut self, name: S) -> &mut Profile {
+        self.profiles.entry(name.into()).or_default()
+    }
+
     /// Create the test options with default command line arguments
     pub fn build(self) -> anyhow::Result<TestOptions> {
         self.build_with_app(App::new("Zuke"))
@@ -143,7 +538,7 @@ impl TestOptionsBuilder {
 
     /// Add the base options
     fn add_base_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
-        app.arg(
+        let app = app.arg(
             Arg::with_name("name")
                 .short("n")
                 .long("name")
@@ -163,14 +558,152 @@ impl TestOptionsBuilder {
                 .value_name("REGEX")
                 .help("Don't run components (features, scenarios) that match REGEX"),
         )
+        .arg(
+            Arg::with_name("filter")
+                .index(1)
+                .multiple(true)
+                .value_name("FILTER")
+                .help("Only run components whose names match FILTER, as with --name"),
+        )
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .takes_value(true)
+                .value_name("FILE")
+                .help("Read settings from FILE [default: zuke.toml, if it exists]"),
+        )
+        .arg(
+            Arg::with_name("shard")
+                .long("shard")
+                .takes_value(true)
+                .value_name("K/N")
+                .help("Split scenarios into N shards, and only run shard K (starting from 1)"),
+        )
+        .arg(
+            Arg::with_name("owner")
+                .long("owner")
+                .takes_value(true)
+                .multiple(true)
+                .max_values(1)
+                .value_name("OWNER")
+                .help("Only run scenarios owned by OWNER, as with @owner(OWNER). May be repeated."),
+        )
+        .arg(
+            Arg::with_name("profile")
+                .long("profile")
+                .takes_value(true)
+                .value_name("NAME")
+                .help("Run with the settings of profile NAME, such as smoke or full"),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .takes_value(true)
+                .value_name("N")
+                .help("Seed random test data with N, to repeat an earlier run [default: random]"),
+        )
+        .arg(
+            Arg::with_name("strict")
+                .long("strict")
+                .help("Fail the test run if any steps are pending"),
+        )
+        .arg(
+            Arg::with_name("prune_outcomes")
+                .long("prune-outcomes")
+                .help("Keep only failed outcomes until the end of the run, to save memory"),
+        )
+        .arg(
+            Arg::with_name("hide_excluded")
+                .long("hide-excluded")
+                .help("Don't report excluded scenarios. They are counted as deselected instead."),
+        )
+        .arg(
+            Arg::with_name("strict_keywords")
+                .long("strict-keywords")
+                .help("Report steps that are only implemented for a different keyword"),
+        )
+        .arg(
+            Arg::with_name("no_normalize_text")
+                .long("no-normalize-text")
+                .help("Match step text exactly, without straightening quotes or collapsing spaces"),
+        )
+        .arg(
+            Arg::with_name("check")
+                .long("check")
+                .help("Check step implementations for conflicting patterns before running"),
+        )
+        .arg(
+            Arg::with_name("repeat")
+                .long("repeat")
+                .takes_value(true)
+                .value_name("N")
+                .help("Run each scenario N times"),
+        )
+        .arg(
+            Arg::with_name("repeat_until_failure")
+                .long("repeat-until-failure")
+                .takes_value(true)
+                .value_name("N")
+                .conflicts_with("repeat")
+                .help("Run each scenario up to N times, stopping at its first failure"),
+        )
+        .arg(
+            Arg::with_name("bench")
+                .long("bench")
+                .takes_value(true)
+                .value_name("N")
+                .conflicts_with_all(&["repeat", "repeat_until_failure"])
+                .help("Benchmark mode: run each scenario N times, and report how long it took"),
+        )
+        .arg(
+            Arg::with_name("bench_warmup")
+                .long("bench-warmup")
+                .takes_value(true)
+                .value_name("N")
+                .requires("bench")
+                .help("With --bench, run each scenario N more times first, untimed [default: 1]"),
+        )
+        .arg(
+            Arg::with_name("warn_slow_step")
+                .long("warn-slow-step")
+                .takes_value(true)
+                .value_name("DURATION")
+                .help("Steps that take longer than DURATION (e.g., 500ms) pass with warnings"),
+        )
+        .arg(
+            Arg::with_name("remote_steps")
+                .long("remote-steps")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("SERVER")
+                .help("Use steps from a step server: tcp://HOST:PORT, or a command to run"),
+        );
+
+        #[cfg(feature = "wasm")]
+        let app = app.arg(
+            Arg::with_name("plugin")
+                .long("plugin")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("PATH")
+                .help("Load steps from a WebAssembly plugin"),
+        );
+
+        app
     }
 
     /// Parse the base options
-    fn parse_base_options(opts: &ArgMatches<'static>) -> anyhow::Result<(RegexSet, RegexSet)> {
-        let included: Vec<_> = match opts.values_of("name") {
-            None => vec![],
-            Some(values) => values.collect(),
-        };
+    fn parse_base_options(
+        opts: &ArgMatches<'static>,
+    ) -> anyhow::Result<(RegexSet, RegexSet, Option<Shard>)> {
+        let included: Vec<_> = opts
+            .values_of("name")
+            .into_iter()
+            .flatten()
+            .chain(opts.values_of("filter").into_iter().flatten())
+            .collect();
         let included = RegexSetBuilder::new(included)
             .case_insensitive(true)
             .build()
@@ -185,7 +718,168 @@ impl TestOptionsBuilder {
             .build()
             .with_context(|| "Bad --exclude pattern")?;
 
-        Ok((included, excluded))
+        let shard = match opts.value_of("shard") {
+            None => None,
+            Some(s) => Some(s.parse().with_context(|| format!("Bad --shard {:?}", s))?),
+        };
+
+        Ok((included, excluded, shard))
+    }
+
+    /// Parse `--bench` and `--bench-warmup`
+    fn parse_bench(opts: &ArgMatches<'static>) -> anyhow::Result<Option<Bench>> {
+        let iterations = match opts.value_of("bench") {
+            Some(n) => n
+                .parse()
+                .with_context(|| format!("Bad --bench count {:?}", n))?,
+            None => return Ok(None),
+        };
+        if iterations == 0 {
+            anyhow::bail!("--bench count must be at least 1");
+        }
+        let warmup = match opts.value_of("bench_warmup") {
+            Some(n) => n
+                .parse()
+                .with_context(|| format!("Bad --bench-warmup count {:?}", n))?,
+            None => 1,
+        };
+        Ok(Some(Bench { iterations, warmup }))
+    }
+
+    /// Parse `--repeat` and `--repeat-until-failure`. `--bench` repeats scenarios too.
+    fn parse_repeat(opts: &ArgMatches<'static>) -> anyhow::Result<Option<Repeat>> {
+        if let Some(bench) = Self::parse_bench(opts)? {
+            return Ok(Some(Repeat {
+                count: bench.iterations + bench.warmup,
+                until_failure: false,
+            }));
+        }
+
+        let (count, until_failure) = match (
+            opts.value_of("repeat"),
+            opts.value_of("repeat_until_failure"),
+        ) {
+            (Some(n), _) => (n, false),
+            (None, Some(n)) => (n, true),
+            (None, None) => return Ok(None),
+        };
+
+        let count = count
+            .parse()
+            .with_context(|| format!("Bad repeat count {:?}", count))?;
+        if count == 0 {
+            anyhow::bail!("Repeat count must be at least 1");
+        }
+
+        Ok(Some(Repeat {
+            count,
+            until_failure,
+        }))
+    }
+
+    /// Convert environment variables starting with `prefix` to command line arguments, skipping
+    /// any that are already in `given`. Variables that don't match an option are left out, with a
+    /// warning, since the environment may be shared with other tools or versions.
+    fn env_args(
+        app: &App<'static, '_>,
+        arg0: &OsString,
+        prefix: &str,
+        given: &[OsString],
+        warnings: &mut Vec<String>,
+    ) -> anyhow::Result<Vec<OsString>> {
+        let mut vars: Vec<(String, String)> = std::env::vars_os()
+            .filter_map(|(k, v)| Some((k.into_string().ok()?, v)))
+            .filter(|(k, _)| k.starts_with(prefix) && k.len() > prefix.len())
+            .map(|(k, v)| {
+                v.into_string()
+                    .map(|v| (k.clone(), v))
+                    .map_err(|_| anyhow::anyhow!("{} is not valid unicode", k))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        vars.sort();
+
+        let mut args = vec![];
+        for (var, value) in vars {
+            let name = var[prefix.len()..].to_lowercase().replace('_', "-");
+            match Self::option_arg(app, arg0, &var, &name, &value, given) {
+                Ok(arg) => args.extend(arg),
+                Err(e) if e.is::<UnknownOption>() => warnings.push(format!("{}; ignoring it", e)),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(args)
+    }
+
+    /// Convert options from the config file to command line arguments, skipping any that are
+    /// already in `given`.
+    fn config_args(
+        app: &App<'static, '_>,
+        arg0: &OsString,
+        config: &Config,
+        given: &[OsString],
+    ) -> anyhow::Result<Vec<OsString>> {
+        let file = match &config.path {
+            Some(p) => p.display().to_string(),
+            None => String::from("config"),
+        };
+
+        let mut args = vec![];
+        for (name, value) in config.options.iter() {
+            let source = format!("{:?} in {}", name, file);
+            let arg = Self::option_arg(app, arg0, &source, name, value, given)?;
+            args.extend(arg);
+        }
+
+        Ok(args)
+    }
+
+    /// Convert an option from somewhere other than the command line (environment variables,
+    /// config file) to a command line argument. `source` names where it came from for error
+    /// messages. Returns `None` if it's a false flag, or if `given` already includes it.
+    fn option_arg(
+        app: &App<'static, '_>,
+        arg0: &OsString,
+        source: &str,
+        name: &str,
+        value: &str,
+        given: &[OsString],
+    ) -> anyhow::Result<Option<OsString>> {
+        if value.is_empty() {
+            return Ok(None);
+        }
+
+        let parse = |args: Vec<OsString>| {
+            app.clone()
+                .get_matches_from_safe(std::iter::once(arg0.clone()).chain(args))
+        };
+
+        let long = format!("--{}", name);
+        let arg = match parse(vec![long.clone().into()]) {
+            // It's a flag
+            Ok(_) => match value.to_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" => long,
+                "0" | "false" | "no" | "off" => return Ok(None),
+                _ => anyhow::bail!("{} must be true or false, not {:?}", source, value),
+            },
+            Err(e) if e.kind == ErrorKind::UnknownArgument => {
+                return Err(UnknownOption(source.to_string()).into())
+            }
+            Err(_) => format!("{}={}", long, value),
+        };
+
+        // Whatever was given takes precedence
+        let mut with_given = given.to_vec();
+        with_given.push(arg.clone().into());
+        match parse(with_given) {
+            Err(e)
+                if e.kind == ErrorKind::UnexpectedMultipleUsage
+                    || e.kind == ErrorKind::ArgumentConflict =>
+            {
+                Ok(None)
+            }
+            _ => Ok(Some(arg.into())),
+        }
     }
 
     /// Create the test options with custom command line arguments. Any registered
@@ -206,28 +900,140 @@ impl TestOptionsBuilder {
     {
         let Self {
             title,
+            suite,
             pre_test_hooks,
+            default_tags,
+            tag_plugins,
             canceled,
+            aborted,
+            env_prefix,
+            default_config,
+            extensions,
+            step_priority,
+            vocab,
+            mut fixture_replacements,
+            mut profiles,
         } = self;
 
-        let vocab = Arc::new(Vocab::new()?);
-
         app = Self::add_base_options(app);
         for extra in inventory::iter::<ExtraOptionsFunc>() {
             app = (extra.make_options)(app);
         }
 
-        let opts = app.get_matches_from_safe(iter)?;
-        let (included, excluded) = Self::parse_base_options(&opts)?;
+        // Merge options with precedence: command line, environment, config file
+        let mut args = iter.into_iter().map(Into::into);
+        let arg0: OsString = args.next().unwrap_or_else(|| OsString::from("zuke"));
+        let mut given: Vec<OsString> = args.collect();
+
+        let mut warnings = vec![];
+        if let Some(prefix) = env_prefix {
+            let mut env = Self::env_args(&app, &arg0, &prefix, &given, &mut warnings)?;
+            env.append(&mut given);
+            given = env;
+        }
+
+        let config = {
+            let opts = app
+                .clone()
+                .get_matches_from_safe(std::iter::once(arg0.clone()).chain(given.clone()))?;
+            match opts.value_of("config") {
+                Some(path) => Config::load(path)?,
+                None if default_config => Config::load_default(None)?,
+                None => Config::default(),
+            }
+        };
+        let mut args = Self::config_args(&app, &arg0, &config, &given)?;
+        args.insert(0, arg0);
+        args.append(&mut given);
+
+        let opts = app.get_matches_from_safe(args.clone())?;
+        let (included, excluded, shard) = Self::parse_base_options(&opts)?;
+        let repeat = Self::parse_repeat(&opts)?;
+        let bench = Self::parse_bench(&opts)?;
+        let owners = match opts.values_of("owner") {
+            Some(owners) => owners.map(String::from).collect(),
+            None => vec![],
+        };
+        let profile = opts.value_of("profile").map(String::from);
+        let profile_settings = match &profile {
+            Some(name) => match profiles.remove(name) {
+                Some(settings) => settings,
+                None => {
+                    let mut available: Vec<String> = profiles.into_keys().collect();
+                    available.sort();
+                    return Err(ConfigError::UnknownProfile {
+                        name: name.clone(),
+                        available,
+                    }
+                    .into());
+                }
+            },
+            None => Profile::default(),
+        };
+        fixture_replacements.extend(profile_settings.fixture_replacements.clone());
+        let seed = match opts.value_of("seed") {
+            Some(s) => s.parse().context("Bad --seed")?,
+            None => rand::random(),
+        };
+        let mut run_info = RunInfo::collect(&args, profile.clone());
+        run_info.seed = Some(seed);
+        let warn_slow_step = match opts.value_of("warn_slow_step") {
+            Some(d) => Some(parse_duration(d).context("Bad --warn-slow-step")?),
+            None => None,
+        };
+        let mut vocab = match vocab {
+            Some(v) => v,
+            None => Vocab::new()?,
+        };
+        // Flags only turn things on or off; otherwise the vocab keeps its own settings
+        if opts.is_present("strict_keywords") {
+            vocab = vocab.with_strict_keywords(true);
+        }
+        if opts.is_present("no_normalize_text") {
+            vocab = vocab.with_text_normalization(false);
+        }
+        if !step_priority.is_empty() {
+            vocab = vocab.with_step_priority(step_priority);
+        }
+        if let Some(servers) = opts.values_of("remote_steps") {
+            for server in servers {
+                let remote = RemoteSteps::from_arg(server)?;
+                vocab = vocab.with_steps(remote.steps()?)?;
+            }
+        }
+        #[cfg(feature = "wasm")]
+        if let Some(paths) = opts.values_of("plugin") {
+            for path in paths {
+                vocab = vocab.with_steps(crate::plugin::load_steps(path)?)?;
+            }
+        }
+        let vocab = Arc::new(vocab);
 
         Ok(TestOptions {
             opts,
             vocab,
             title,
+            suite,
             pre_test_hooks: Arc::new(pre_test_hooks),
+            default_tags,
+            tag_plugins: Arc::new(tag_plugins),
             included,
             excluded,
+            shard,
+            owners,
+            repeat,
+            bench,
+            warn_slow_step,
+            config,
+            extensions,
+            fixture_replacements,
+            profile,
+            profile_settings,
+            seed,
+            run_info,
             canceled,
+            aborted,
+            warnings,
         })
     }
 }
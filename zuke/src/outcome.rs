@@ -1,12 +1,19 @@
 //! Test outcomes
 
-use crate::component::{Component, ComponentKind};
+use crate::artifact::Artifact;
+use crate::assert::Diff;
+use crate::component::{Component, ComponentId, ComponentKind};
+use crate::query::Query;
 use crate::step::StepError;
+use crate::vocab::StepMatch;
 use anyhow;
 use chrono::{DateTime, Utc};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
 use std::collections::HashMap;
 use std::fmt;
+use std::str::FromStr;
 use std::sync::Arc;
+use thiserror::Error;
 
 /// A test result, but holds much more information about what happened
 #[derive(Debug)]
@@ -27,10 +34,131 @@ pub struct Outcome {
     /// for scenarios and rules. The top-level outcome can be traversed to get hierarchical
     /// information about the entire test run.
     pub children: Vec<Arc<Outcome>>,
+    /// Counts of children that were dropped, rather than kept in [`Self::children`], by kind.
+    /// See [`Self::add_child_pruned`].
+    pub pruned: HashMap<ComponentKind, Stat>,
+    /// If the component was skipped because something above it didn't pass, such as a feature
+    /// whose before hook failed, this names it. Unlike [`Self::reason`], this is passed down to
+    /// every component skipped as a result.
+    pub skip_cause: Option<SkipCause>,
+    /// Problems that didn't stop the component, from [`crate::Context::warn`]. A component
+    /// that passes with warnings is [`Verdict::PassedWithWarnings`].
+    pub warnings: Vec<anyhow::Error>,
+    /// Screenshots and the like, collected from fixtures when the component failed. See
+    /// [`crate::artifact`].
+    pub artifacts: Vec<Artifact>,
+    /// For a step, which implementation ran it, and the arguments it was given. `None` if the
+    /// step didn't run, or didn't match any implementation.
+    pub step_match: Option<StepMatch>,
+}
+
+/// Why a component was skipped without running. See [`Outcome::skip_cause`].
+#[derive(Debug, Clone)]
+pub struct SkipCause {
+    /// The component that didn't pass
+    pub component: Arc<Component>,
+    /// Its verdict when its children were skipped
+    pub verdict: Verdict,
+}
+
+impl SkipCause {
+    /// The cause of skipping `parent`'s children: `parent` itself, unless it was skipped for a
+    /// cause of its own
+    pub fn from_parent(parent: &Outcome) -> Self {
+        match &parent.skip_cause {
+            Some(cause) => cause.clone(),
+            None => Self {
+                component: parent.component.clone(),
+                verdict: parent.verdict,
+            },
+        }
+    }
+}
+
+impl fmt::Display for SkipCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.verdict {
+            Verdict::Skipped | Verdict::Excluded => String::from("was skipped"),
+            v => v.to_string(),
+        };
+        match self.component.kind() {
+            ComponentKind::Global => write!(f, "Skipped because the test run {}", what),
+            kind => write!(
+                f,
+                "Skipped because {} \"{}\" {}",
+                kind,
+                self.component.name(),
+                what
+            ),
+        }
+    }
+}
+
+/// Several errors from the same component, such as two fixtures that failed to tear down. Each
+/// error is shown with its full chain of causes.
+#[derive(Debug, Default)]
+pub struct MultiError {
+    errors: Vec<anyhow::Error>,
+}
+
+impl MultiError {
+    /// No errors yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an error. Another `MultiError` is flattened into this one, unless it has context
+    /// of its own.
+    pub fn push(&mut self, err: anyhow::Error) {
+        if err.chain().count() > 1 {
+            return self.errors.push(err);
+        }
+        match err.downcast::<MultiError>() {
+            Ok(multi) => self.errors.extend(multi.errors),
+            Err(e) => self.errors.push(e),
+        }
+    }
+
+    /// The errors, in the order they happened
+    pub fn errors(&self) -> &[anyhow::Error] {
+        &self.errors
+    }
+
+    /// The number of errors
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Are there no errors?
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// `Ok` if there were no errors, the error itself if there was one, or all of them
+    pub fn into_result(mut self) -> anyhow::Result<()> {
+        match self.errors.len() {
+            0 => Ok(()),
+            1 => Err(self.errors.pop().unwrap()),
+            _ => Err(self.into()),
+        }
+    }
+}
+
+impl std::error::Error for MultiError {}
+
+impl fmt::Display for MultiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} errors:", self.errors.len())?;
+        for (i, e) in self.errors.iter().enumerate() {
+            let text = format!("{:#}", e).replace('\n', "\n   ");
+            write!(f, "\n{:>2}. {}", i + 1, text)?;
+        }
+        Ok(())
+    }
 }
 
 /// A summary of how many things passed/failed/skipped.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct Stat {
     /// number of passing components
     pub passed: usize,
@@ -38,8 +166,24 @@ pub struct Stat {
     pub failed: usize,
     /// number of skipped components
     pub skipped: usize,
+    /// number of pending components
+    pub pending: usize,
+    /// number of passing components that had warnings
+    pub warnings: usize,
+    /// number of passing components that were expected to fail, and did
+    pub expected_failures: usize,
+    /// number of failed components that were expected to fail, but passed
+    pub unexpected_passes: usize,
+    /// number of failed components that were canceled
+    pub canceled: usize,
+    /// number of components that never reached a verdict. This should be zero once the test run
+    /// is done.
+    pub undecided: usize,
     /// total number of components
     pub total: usize,
+    /// number of components excluded from the test run and hidden with `--hide-excluded`. These
+    /// aren't part of the total.
+    pub deselected: usize,
 }
 
 /// The ultimate verdict for a test component. These are ordered from lowest priority (Skipped) to
@@ -58,6 +202,9 @@ pub enum Verdict {
     PassedWithWarnings,
     /// The component failed, but it was supposed to fail
     ExpectedFailure,
+    /// The component isn't finished being implemented. Neither passed nor failed, unless
+    /// `--strict` is given.
+    Pending,
     /// The component was supposed to fail, but it passed
     UnexpectedPass,
     /// The component failed
@@ -66,6 +213,47 @@ pub enum Verdict {
     Canceled,
 }
 
+impl Stat {
+    /// Count a single component with the given verdict
+    pub fn count(&mut self, verdict: Verdict) -> &mut Self {
+        self.total += 1;
+        if verdict.passed() {
+            self.passed += 1;
+        } else if verdict.is_pending() {
+            self.pending += 1;
+        } else if verdict.skipped() {
+            self.skipped += 1;
+        } else {
+            self.failed += 1;
+        }
+        match verdict {
+            Verdict::PassedWithWarnings => self.warnings += 1,
+            Verdict::ExpectedFailure => self.expected_failures += 1,
+            Verdict::UnexpectedPass => self.unexpected_passes += 1,
+            Verdict::Canceled => self.canceled += 1,
+            Verdict::Undecided => self.undecided += 1,
+            _ => (),
+        }
+        self
+    }
+
+    /// Add counts from another stat
+    pub fn merge(&mut self, other: &Stat) -> &mut Self {
+        self.passed += other.passed;
+        self.failed += other.failed;
+        self.skipped += other.skipped;
+        self.pending += other.pending;
+        self.warnings += other.warnings;
+        self.expected_failures += other.expected_failures;
+        self.unexpected_passes += other.unexpected_passes;
+        self.canceled += other.canceled;
+        self.undecided += other.undecided;
+        self.total += other.total;
+        self.deselected += other.deselected;
+        self
+    }
+}
+
 impl Default for Verdict {
     fn default() -> Self {
         Self::Undecided
@@ -91,6 +279,11 @@ impl Verdict {
         *self == Self::Undecided
     }
 
+    /// The verdict is pending
+    pub fn is_pending(&self) -> bool {
+        *self == Self::Pending
+    }
+
     /// The verdict is failed
     pub fn failed(&self) -> bool {
         matches!(
@@ -100,6 +293,64 @@ impl Verdict {
     }
 }
 
+/// All verdicts, in order
+const VERDICTS: [Verdict; 10] = [
+    Verdict::Undecided,
+    Verdict::Excluded,
+    Verdict::Skipped,
+    Verdict::Passed,
+    Verdict::PassedWithWarnings,
+    Verdict::ExpectedFailure,
+    Verdict::Pending,
+    Verdict::UnexpectedPass,
+    Verdict::Failed,
+    Verdict::Canceled,
+];
+
+impl Verdict {
+    /// A short, stable name for the verdict, for machine readable output. `Display` is meant for
+    /// humans, and may change.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verdict::Undecided => "undecided",
+            Verdict::Excluded => "excluded",
+            Verdict::Skipped => "skipped",
+            Verdict::Passed => "passed",
+            Verdict::PassedWithWarnings => "passed_with_warnings",
+            Verdict::ExpectedFailure => "expected_failure",
+            Verdict::Pending => "pending",
+            Verdict::UnexpectedPass => "unexpected_pass",
+            Verdict::Failed => "failed",
+            Verdict::Canceled => "canceled",
+        }
+    }
+}
+
+impl Serialize for Verdict {
+    /// As [`Verdict::name`]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// A verdict name was not recognized
+#[derive(Error, Debug)]
+#[error("Unknown verdict {0:?}")]
+pub struct UnknownVerdict(pub String);
+
+impl FromStr for Verdict {
+    type Err = UnknownVerdict;
+
+    /// Parse a verdict from its [`Verdict::name`]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        VERDICTS
+            .iter()
+            .find(|v| v.name() == s)
+            .copied()
+            .ok_or_else(|| UnknownVerdict(s.to_string()))
+    }
+}
+
 impl fmt::Display for Verdict {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let msg = match self {
@@ -109,6 +360,7 @@ impl fmt::Display for Verdict {
             Verdict::Passed => "passed",
             Verdict::PassedWithWarnings => "passed (with warnings)",
             Verdict::ExpectedFailure => "passed (expected failure)",
+            Verdict::Pending => "pending",
             Verdict::Failed => "failed",
             Verdict::UnexpectedPass => "failed (unexpected success)",
             Verdict::Canceled => "failed (canceled)",
@@ -136,6 +388,11 @@ impl Outcome {
             started: Utc::now(),
             ended: Utc::now(), // will be updated
             children: vec![],
+            pruned: HashMap::new(),
+            skip_cause: None,
+            warnings: vec![],
+            artifacts: vec![],
+            step_match: None,
         }
     }
 
@@ -157,7 +414,11 @@ impl Outcome {
             }
         };
 
-        Self::new(component, verdict)
+        let mut outcome = Self::new(component, verdict);
+        if verdict == Verdict::Skipped {
+            outcome.skip_cause = Some(SkipCause::from_parent(parent));
+        }
+        outcome
     }
 
     /// Set the component as [`Verdict::Skipped`], with no error message.
@@ -173,9 +434,22 @@ impl Outcome {
         self
     }
 
-    /// Set the component to passed
+    /// Set the component to passed, or passed with warnings if it has any
     pub fn set_passed(&mut self) -> &mut Self {
-        self.verdict = Verdict::Passed;
+        self.verdict = if self.warnings.is_empty() {
+            Verdict::Passed
+        } else {
+            Verdict::PassedWithWarnings
+        };
+        self
+    }
+
+    /// Add a warning. A component that has already passed now passes with warnings.
+    pub fn add_warning(&mut self, warning: anyhow::Error) -> &mut Self {
+        if self.verdict == Verdict::Passed {
+            self.verdict = Verdict::PassedWithWarnings;
+        }
+        self.warnings.push(warning);
         self
     }
 
@@ -190,7 +464,9 @@ impl Outcome {
     /// will set the verdict to [`Verdict::Failed`].
     pub fn set_result<T>(&mut self, result: anyhow::Result<T>) -> &mut Self {
         match result {
-            Ok(_) => self.verdict = Verdict::Passed,
+            Ok(_) => {
+                self.set_passed();
+            }
             Err(e) => {
                 self.set_err(e);
             }
@@ -204,21 +480,34 @@ impl Outcome {
     /// will honor [`StepError::Skip`], [`StepError::Warning`], etc. Otherwise this function will
     /// set the verdict to [`Verdict::Failed`].
     pub fn set_err(&mut self, err: anyhow::Error) -> &mut Self {
-        match err.downcast::<StepError>() {
-            Ok(e) => {
-                self.verdict = e.verdict;
-                self.reason = e.reason;
-            }
-            Err(e) => {
-                self.verdict = Verdict::Failed;
-                self.reason = Some(e);
-            }
-        };
+        let e = StepError::from(err);
+        self.verdict = e.verdict;
+        self.reason = e.reason;
 
         self.ended = Utc::now();
         self
     }
 
+    /// As [`Self::set_err`], but keep any reason the component already has. If it has one, the
+    /// reason becomes a [`MultiError`] of both, and the verdict is whichever is worse.
+    pub fn add_err(&mut self, err: anyhow::Error) -> &mut Self {
+        let previous = match self.reason.take() {
+            Some(previous) => previous,
+            None => return self.set_err(err),
+        };
+        let verdict = self.verdict;
+        self.set_err(err);
+        self.verdict = self.verdict.max(verdict);
+
+        let mut errors = MultiError::new();
+        errors.push(previous);
+        if let Some(e) = self.reason.take() {
+            errors.push(e);
+        }
+        self.reason = Some(errors.into_result().unwrap_err());
+        self
+    }
+
     /// Add a child to the outcome. This does not set the reason, which generally isn't for
     /// describing sub-components.
     pub fn add_child(&mut self, child: Arc<Outcome>) -> &mut Self {
@@ -230,6 +519,65 @@ impl Outcome {
         self
     }
 
+    /// As [`Self::add_child`], but unless the child failed, only its stats are kept. Its outcome
+    /// is dropped, along with everything under it. This keeps memory bounded on very large test
+    /// runs, at the cost of detail in the final outcome.
+    pub fn add_child_pruned(&mut self, child: Arc<Outcome>) -> &mut Self {
+        if child.failed() {
+            return self.add_child(child);
+        }
+
+        if child.verdict > self.verdict {
+            self.verdict = child.verdict;
+        }
+        for (kind, stat) in child.stats() {
+            self.pruned
+                .entry(kind)
+                .or_insert_with(Stat::default)
+                .merge(&stat);
+        }
+        self.ended = Utc::now();
+        self
+    }
+
+    /// A copy of this outcome with the same verdict, times, and stats, but without its children,
+    /// reason, warnings, or artifacts. What's under it is counted in [`Self::pruned`] instead. See
+    /// [`Self::add_child_pruned`].
+    pub fn pruned(&self) -> Outcome {
+        let mut outcome = Outcome::new(self.component.clone(), self.verdict);
+        outcome.started = self.started;
+        outcome.ended = self.ended;
+        outcome.pruned = self.pruned.clone();
+        for child in self.children.iter() {
+            let mut stats = child.stats();
+            // An iteration of a repeated scenario isn't counted as a scenario of its own
+            if Arc::ptr_eq(&child.component, &self.component) {
+                stats.remove(&child.kind());
+            }
+            for (kind, stat) in stats {
+                outcome
+                    .pruned
+                    .entry(kind)
+                    .or_insert_with(Stat::default)
+                    .merge(&stat);
+            }
+        }
+        outcome
+    }
+
+    /// Count the child, and everything under it, as deselected, without keeping its outcome or
+    /// changing the verdict. This is for excluded components hidden with `--hide-excluded`.
+    pub fn add_child_deselected(&mut self, child: Arc<Outcome>) -> &mut Self {
+        for (kind, stat) in child.stats() {
+            self.pruned
+                .entry(kind)
+                .or_insert_with(Stat::default)
+                .deselected += stat.total + stat.deselected;
+        }
+        self.ended = Utc::now();
+        self
+    }
+
     /// Return true if the component is still undecided
     pub fn is_undecided(&self) -> bool {
         self.verdict == Verdict::Undecided
@@ -245,6 +593,11 @@ impl Outcome {
         self.verdict.passed() || self.verdict == Verdict::Undecided
     }
 
+    /// Return true if the component is pending
+    pub fn is_pending(&self) -> bool {
+        self.verdict.is_pending()
+    }
+
     /// Return true if the component was skipped
     pub fn skipped(&self) -> bool {
         self.verdict.skipped()
@@ -255,30 +608,56 @@ impl Outcome {
         self.verdict.failed()
     }
 
-    /// Return basic stats about this outcome and all child outcomes.
+    /// The diff from a failed comparison, such as [`crate::assert_eq_diff!`], if that's why the
+    /// component failed
+    pub fn diff(&self) -> Option<&Diff> {
+        self.reason
+            .as_ref()?
+            .chain()
+            .find_map(|e| e.downcast_ref::<Diff>())
+    }
+
+    /// This outcome and everything under it as JSON. See the `Serialize` impl for the format.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Return basic stats about this outcome and all child outcomes, including pruned ones.
     pub fn stats(&self) -> HashMap<ComponentKind, Stat> {
         let mut stats = HashMap::new();
-        let mut outcomes = vec![self];
-
-        while let Some(outcome) = outcomes.pop() {
-            let entry = stats
-                .entry(outcome.component.kind())
-                .or_insert_with(Stat::default);
-            entry.total += 1;
-            if outcome.passed() {
-                entry.passed += 1;
-            } else if outcome.skipped() {
-                entry.skipped += 1;
-            } else {
-                entry.failed += 1;
+        let mut outcomes = vec![(self, true)];
+
+        while let Some((outcome, counted)) = outcomes.pop() {
+            if counted {
+                stats
+                    .entry(outcome.component.kind())
+                    .or_insert_with(Stat::default)
+                    .count(outcome.verdict);
+            }
+            for (kind, stat) in outcome.pruned.iter() {
+                stats.entry(*kind).or_insert_with(Stat::default).merge(stat);
             }
 
-            outcomes.extend(outcome.children.iter().map(Arc::as_ref));
+            // Repeated scenarios have a child per iteration, for the same component. Count the
+            // scenario once, but count every step that ran.
+            outcomes.extend(
+                outcome
+                    .children
+                    .iter()
+                    .map(|c| (c.as_ref(), !Arc::ptr_eq(&c.component, &outcome.component))),
+            );
         }
 
         stats
     }
 
+    /// Outcomes of each time a scenario ran, if it was repeated. Empty otherwise.
+    pub fn iterations(&self) -> impl Iterator<Item = &Arc<Outcome>> {
+        self.children
+            .iter()
+            .filter(move |c| Arc::ptr_eq(&c.component, &self.component))
+    }
+
     /// Return the component associated with this outcome
     pub fn component(&self) -> &Arc<Component> {
         &self.component
@@ -289,6 +668,11 @@ impl Outcome {
         self.component.kind()
     }
 
+    /// The stable ID of the component. See [`Component::id`].
+    pub fn id(&self) -> ComponentId {
+        self.component.id()
+    }
+
     /// Shortcut for self.component().tags_uninherited()
     pub fn tags_uninherited(&self) -> &[String] {
         self.component.tags_uninherited()
@@ -321,6 +705,27 @@ impl Outcome {
         found
     }
 
+    /// Find outcomes under this one by kind, verdict, tag, or name. See [`Query`].
+    pub fn query(self: &Arc<Self>) -> Query {
+        Query::new(self.clone())
+    }
+
+    /// The outcome of one of this scenario's steps, by its text. The keyword is optional, so
+    /// `Given a lever` and `a lever` both match; case doesn't matter. For a repeated scenario,
+    /// ask each of its [`Self::iterations`].
+    pub fn step<S: AsRef<str>>(&self, text: S) -> Option<&Arc<Outcome>> {
+        let text = text.as_ref().trim().to_lowercase();
+        self.children
+            .iter()
+            .filter(|c| c.kind() == ComponentKind::Step)
+            .find(|c| {
+                let step = c.component.step().unwrap();
+                let value = step.value.to_lowercase();
+                let full = format!("{} {}", step.keyword.trim(), step.value).to_lowercase();
+                text == value || text == full
+            })
+    }
+
     /// Recursively iterate through this outcome and its children for outcomes of type `kind`.
     pub fn iter_components(self: Arc<Self>, kind: ComponentKind) -> IterComponents {
         IterComponents {
@@ -330,13 +735,65 @@ impl Outcome {
     }
 }
 
+/// The component as [`Component::info`], times as RFC 3339, and the reason as text. Counts of
+/// pruned children are only included if there are any.
+impl Serialize for Outcome {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("Outcome", 11)?;
+        s.serialize_field("component", &self.component.info())?;
+        s.serialize_field("verdict", &self.verdict)?;
+        s.serialize_field("reason", &self.reason.as_ref().map(|e| format!("{:#}", e)))?;
+        s.serialize_field("started", &self.started.to_rfc3339())?;
+        s.serialize_field("ended", &self.ended.to_rfc3339())?;
+        s.serialize_field("children", &self.children)?;
+        if self.pruned.is_empty() {
+            s.skip_field("pruned")?;
+        } else {
+            s.serialize_field("pruned", &self.pruned)?;
+        }
+        match &self.skip_cause {
+            Some(cause) => s.serialize_field("skip_cause", &cause.to_string())?,
+            None => s.skip_field("skip_cause")?,
+        }
+        if self.warnings.is_empty() {
+            s.skip_field("warnings")?;
+        } else {
+            let warnings: Vec<_> = self.warnings.iter().map(|w| format!("{:#}", w)).collect();
+            s.serialize_field("warnings", &warnings)?;
+        }
+        if self.artifacts.is_empty() {
+            s.skip_field("artifacts")?;
+        } else {
+            // Just what they are; the data may be large, and isn't always text
+            let artifacts: Vec<_> = self
+                .artifacts
+                .iter()
+                .map(|a| {
+                    serde_json::json!({
+                        "name": a.name,
+                        "media_type": a.media_type,
+                        "size": a.data.len(),
+                    })
+                })
+                .collect();
+            s.serialize_field("artifacts", &artifacts)?;
+        }
+        match &self.step_match {
+            Some(m) => s.serialize_field("step_match", m)?,
+            None => s.skip_field("step_match")?,
+        }
+        s.end()
+    }
+}
+
 impl fmt::Display for Outcome {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.verdict)?;
 
-        match &self.reason {
-            Some(r) => write!(f, " ({})", r)?,
-            None => (),
+        match (&self.reason, &self.skip_cause) {
+            (Some(r), _) => write!(f, " ({})", r)?,
+            (None, Some(c)) => write!(f, " ({})", c)?,
+            (None, None) => (),
         }
 
         Ok(())
@@ -1,13 +1,15 @@
 //! Feature generation
 
 use crate::component::Component;
+use crate::metadata::FeatureMetadata;
 use crate::outcome::Outcome;
-use anyhow;
+use anyhow::{self, Context as _};
 use async_trait::async_trait;
 use futures::channel::mpsc;
 use futures::stream::{FuturesUnordered, StreamExt};
-use futures::{stream, SinkExt};
-use gherkin_rust::{Feature, GherkinEnv, Rule, Scenario};
+use futures::{stream, AsyncReadExt, SinkExt};
+use gherkin_rust::{Feature, GherkinEnv, Rule, Scenario, Step};
+use glob::Pattern;
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::fs;
@@ -26,12 +28,24 @@ enum FeatureSource {
     Dir(PathBuf),
     File(PathBuf),
     Source(String, String),
+    Glob(String),
+    Stdin,
 }
 
 /// Parses features from files, directories, or source strings
+///
+/// Directories are searched recursively for `*.feature` files. Anything matching a pattern in a
+/// `.zukeignore` file is skipped, along with everything under it. Each line of the file is a
+/// glob pattern, such as `assets` or `vendor/**/*.feature`. Patterns without a `/` match file and
+/// directory names anywhere below the `.zukeignore` file, and other patterns match paths relative
+/// to it. Blank lines and lines starting with `#` are ignored. See also [`Self::extension`],
+/// [`Self::ignore`], and [`Self::max_depth`].
 pub struct StandardParser {
     sources: Vec<FeatureSource>,
     language: String,
+    extensions: Vec<String>,
+    ignore: Vec<String>,
+    max_depth: Option<usize>,
 }
 
 impl Default for StandardParser {
@@ -46,6 +60,9 @@ impl StandardParser {
         Self {
             sources: vec![],
             language: "en".to_string(),
+            extensions: vec![],
+            ignore: vec![],
+            max_depth: None,
         }
     }
 
@@ -68,6 +85,35 @@ impl StandardParser {
         parser
     }
 
+    /// Set the default language of feature files, such as "fr". A `# language: xx` header in a
+    /// feature file overrides this. Step implementations are shared between all languages.
+    pub fn language<S: Into<String>>(&mut self, language: S) -> &mut Self {
+        self.language = language.into();
+        self
+    }
+
+    /// Search directories for files with this extension, such as "gherkin", rather than
+    /// "feature". Call more than once to search for several extensions. Files added by name are
+    /// parsed whatever their extension.
+    pub fn extension<S: Into<String>>(&mut self, extension: S) -> &mut Self {
+        self.extensions.push(extension.into());
+        self
+    }
+
+    /// Skip files and directories matching a glob pattern when searching directories, as if it
+    /// were in a `.zukeignore` file in each directory searched
+    pub fn ignore<S: Into<String>>(&mut self, pattern: S) -> &mut Self {
+        self.ignore.push(pattern.into());
+        self
+    }
+
+    /// Search at most this many directories below each directory added. At 0, only the
+    /// directory itself is searched. Unlimited by default.
+    pub fn max_depth(&mut self, depth: usize) -> &mut Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
     /// Add a feature from a source string.  The `filename` parameter is arbitrary and used for
     /// displaying information to the user.
     pub fn add_source(&mut self, filename: String, source: String) -> &mut Self {
@@ -76,7 +122,9 @@ impl StandardParser {
     }
 
     /// Add a file or directory as input. If `path` is a directory, it will be searched recursively
-    /// for `*.feature` files.
+    /// for `*.feature` files, or as configured with [`Self::extension`] and the like. A path of `-`
+    /// reads a feature from stdin. A path that doesn't exist but contains `*`, `?`, or `[` is
+    /// treated as a glob pattern; see [`Self::add_glob`].
     pub fn add_path<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
         let path = path.as_ref();
 
@@ -84,19 +132,63 @@ impl StandardParser {
         // sensible error at parse time.
         let source = match fs::metadata(path) {
             Ok(m) if m.is_dir() => FeatureSource::Dir(path.to_path_buf()),
-            _ => FeatureSource::File(path.to_path_buf()),
+            Ok(_) => FeatureSource::File(path.to_path_buf()),
+            Err(_) if path == Path::new("-") => FeatureSource::Stdin,
+            Err(_) => match path.to_str() {
+                Some(s) if s.contains(&['*', '?', '['][..]) => FeatureSource::Glob(s.to_string()),
+                _ => FeatureSource::File(path.to_path_buf()),
+            },
         };
 
         self.sources.push(source);
         self
     }
 
+    /// Were no inputs added?
+    pub(crate) fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Files and directories added with [`Self::add_path`]
+    pub(crate) fn paths(&self) -> Vec<PathBuf> {
+        self.sources
+            .iter()
+            .filter_map(|s| match s {
+                FeatureSource::Dir(p) | FeatureSource::File(p) => Some(p.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Replace all inputs with just these files
+    pub(crate) fn only_paths(&mut self, paths: Vec<PathBuf>) {
+        self.sources = paths.into_iter().map(FeatureSource::File).collect();
+    }
+
+    /// Add files and directories matching a glob pattern, such as `features/**/auth*.feature`.
+    /// Matching directories are searched recursively for `*.feature` files. It is an error if
+    /// nothing matches.
+    pub fn add_glob<S: Into<String>>(&mut self, pattern: S) -> &mut Self {
+        self.sources.push(FeatureSource::Glob(pattern.into()));
+        self
+    }
+
     async fn execute(
         self,
         global: Arc<Component>,
         output: mpsc::Sender<Outcome>,
     ) -> Result<(), mpsc::SendError> {
-        let StandardParser { sources, language } = self;
+        let StandardParser {
+            sources,
+            language,
+            extensions,
+            ignore,
+            max_depth,
+        } = self;
+        let discovery = match Discovery::new(extensions, &ignore, max_depth) {
+            Ok(d) => d,
+            Err(e) => return send_error("<ignore>".into(), e, &global, output).await,
+        };
         let mut sources = stream::iter(sources).fuse();
         let mut pending = FuturesUnordered::new();
 
@@ -110,11 +202,17 @@ impl StandardParser {
                                 parse_feature_file(path, &language, &global, &mut out).await
                             },
                             FeatureSource::Dir(path) => {
-                                parse_feature_dir(path, &language, &global, out).await
+                                parse_feature_dir(path, &discovery, &language, &global, out).await
                             },
                             FeatureSource::Source(filename, source) => {
                                 parse_feature_source(filename, source, &language, &global, out).await
                             },
+                            FeatureSource::Glob(pattern) => {
+                                parse_feature_glob(pattern, &discovery, &language, &global, out).await
+                            },
+                            FeatureSource::Stdin => {
+                                parse_feature_stdin(&language, &global, out).await
+                            },
                         }
                     };
                     pending.push(fut);
@@ -139,6 +237,205 @@ impl Parser for StandardParser {
     }
 }
 
+/// Parses features from ` ```gherkin ` code blocks in Markdown files, so that documentation can
+/// hold executable scenarios. Each code block is a separate feature. Line numbers refer to the
+/// Markdown file.
+///
+/// ````markdown
+/// # Logging in
+///
+/// Users log in with their email address.
+///
+/// ```gherkin
+/// Feature: Logging in
+///     Scenario: A user logs in
+///         Given a user "alice@example.com"
+///         When they log in
+///         Then they see their dashboard
+/// ```
+/// ````
+pub struct MarkdownParser {
+    paths: Vec<PathBuf>,
+    language: String,
+}
+
+impl Default for MarkdownParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarkdownParser {
+    /// Create a new `MarkdownParser` with no inputs.
+    pub fn new() -> Self {
+        Self {
+            paths: vec![],
+            language: "en".to_string(),
+        }
+    }
+
+    /// Create a new `MarkdownParser` with a file or directory as input.
+    ///
+    /// See also [`Self::add_path`]
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        let mut parser = Self::new();
+        parser.add_path(path);
+        parser
+    }
+
+    /// Set the default language of features. See [`StandardParser::language`].
+    pub fn language<S: Into<String>>(&mut self, language: S) -> &mut Self {
+        self.language = language.into();
+        self
+    }
+
+    /// Add a file or directory as input. If `path` is a directory, it will be searched recursively
+    /// for `*.md` files.
+    pub fn add_path<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
+        self.paths.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    async fn execute(
+        self,
+        global: Arc<Component>,
+        mut output: mpsc::Sender<Outcome>,
+    ) -> Result<(), mpsc::SendError> {
+        for path in self.paths {
+            let files = match fs::metadata(&path) {
+                Ok(m) if m.is_dir() => find_files(path, "md"),
+                _ => vec![path],
+            };
+            for file in files {
+                parse_markdown_file(file, &self.language, &global, &mut output).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Parser for MarkdownParser {
+    async fn parse(self: Box<Self>, global: Arc<Component>, output: mpsc::Sender<Outcome>) {
+        let _ = self.execute(global, output).await;
+    }
+}
+
+/// Recursively find files with the given extension, in no particular order. Errors are skipped.
+pub(crate) fn find_files(path: PathBuf, extension: &str) -> Vec<PathBuf> {
+    let mut found = vec![];
+    let mut dirs = vec![path];
+
+    while let Some(path) = dirs.pop() {
+        if let Ok(items) = fs::read_dir(path) {
+            for entry in items.flatten() {
+                let path = entry.path();
+                if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
+                    dirs.push(path);
+                } else if path.extension().map(|e| e == extension).unwrap_or(false) {
+                    found.push(path);
+                }
+            }
+        }
+    }
+
+    found
+}
+
+/// Find ` ```gherkin ` blocks in Markdown. Returns the line number of each block's opening fence
+/// and its contents, padded with blank lines so that line numbers match the Markdown file.
+fn gherkin_blocks(markdown: &str) -> Vec<(usize, String)> {
+    let mut blocks = vec![];
+    // (fence character, fence length, line of the opening fence, contents)
+    let mut current: Option<(char, usize, usize, String)> = None;
+
+    for (i, line) in markdown.lines().enumerate() {
+        let trimmed = line.trim();
+        let fence_char = match trimmed.chars().next() {
+            Some(c @ '`') | Some(c @ '~') => Some(c),
+            _ => None,
+        };
+        let fence_len = fence_char
+            .map(|f| trimmed.chars().take_while(|&c| c == f).count())
+            .unwrap_or(0);
+
+        match &mut current {
+            Some((c, len, start, contents)) => {
+                if fence_char == Some(*c) && fence_len >= *len && fence_len == trimmed.len() {
+                    blocks.push((*start + 1, std::mem::take(contents)));
+                    current = None;
+                } else {
+                    contents.push_str(line);
+                    contents.push('\n');
+                }
+            }
+            None => {
+                let info = &trimmed[fence_len..];
+                let lang = info.split_whitespace().next().unwrap_or("");
+                if fence_len >= 3 && lang.eq_ignore_ascii_case("gherkin") {
+                    let c = fence_char.unwrap();
+                    current = Some((c, fence_len, i, "\n".repeat(i + 1)));
+                }
+            }
+        }
+    }
+
+    blocks
+}
+
+async fn parse_markdown_file(
+    path: PathBuf,
+    lang: &str,
+    global: &Arc<Component>,
+    output: &mut mpsc::Sender<Outcome>,
+) -> Result<(), mpsc::SendError> {
+    let markdown = match fs::read_to_string(&path) {
+        Ok(m) => m,
+        Err(e) => {
+            let feature = Feature::builder()
+                .keyword("Feature".into())
+                .name(path.display().to_string())
+                .path(Some(path))
+                .build();
+            let mut outcome = Outcome::undecided(global.with_feature(feature));
+            outcome.set_err(e.into());
+            return output.send(outcome).await;
+        }
+    };
+
+    // Several features can come from one file, so each gets the line its block starts on
+    for (line, source) in gherkin_blocks(&markdown) {
+        let block_path = PathBuf::from(format!("{}:{}", path.display(), line));
+        let result = GherkinEnv::new(lang)
+            .map_err(anyhow::Error::from)
+            .and_then(|env| Ok(Feature::parse(&source, env)?));
+        let outcome = match result {
+            Ok(mut feature) => {
+                feature.path = Some(block_path);
+                let result = cook_feature(&mut feature);
+                let mut outcome = Outcome::undecided(global.with_feature(feature));
+                if let Err(e) = result {
+                    outcome.set_err(e);
+                }
+                outcome
+            }
+            Err(e) => {
+                let feature = Feature::builder()
+                    .keyword("Feature".into())
+                    .name(block_path.display().to_string())
+                    .path(Some(block_path))
+                    .build();
+                let mut outcome = Outcome::undecided(global.with_feature(feature));
+                outcome.set_err(e);
+                outcome
+            }
+        };
+        output.send(outcome).await?;
+    }
+
+    Ok(())
+}
+
 // this one is written to be either top level or called from parse_feature_dir
 async fn parse_feature_file(
     path: PathBuf,
@@ -147,9 +444,9 @@ async fn parse_feature_file(
     output: &mut mpsc::Sender<Outcome>,
 ) -> Result<(), mpsc::SendError> {
     let outcome = match do_parse_feature_file(&path, lang) {
-        Ok(mut feature) => {
+        Ok((mut feature, metadata)) => {
             let result = cook_feature(&mut feature);
-            let mut outcome = Outcome::undecided(global.with_feature(feature));
+            let mut outcome = Outcome::undecided(global.with_feature_metadata(feature, metadata));
             if let Err(e) = result {
                 outcome.set_err(e);
             }
@@ -171,15 +468,97 @@ async fn parse_feature_file(
 }
 
 /// maybe should go on a blocking task, but it's probably not the bottleneck.
-fn do_parse_feature_file(path: &Path, lang: &str) -> anyhow::Result<Feature> {
-    let env = GherkinEnv::new(lang)?;
-    let feature = Feature::parse_path(&path, env)?;
-    Ok(feature)
+fn do_parse_feature_file(path: &Path, lang: &str) -> anyhow::Result<(Feature, FeatureMetadata)> {
+    let source = fs::read_to_string(path)
+        .with_context(|| format!("Could not read path: {}", path.display()))?;
+    let (mut feature, metadata) =
+        do_parse_feature_source(&path.display().to_string(), &source, lang)
+            .with_context(|| format!("Could not parse feature file: {}", path.display()))?;
+    feature.path = Some(path.to_path_buf());
+    Ok((feature, metadata))
+}
+
+/// How directories are searched for features
+struct Discovery {
+    extensions: Vec<String>,
+    ignore: Vec<Pattern>,
+    max_depth: Option<usize>,
+}
+
+impl Discovery {
+    fn new(
+        mut extensions: Vec<String>,
+        ignore: &[String],
+        max_depth: Option<usize>,
+    ) -> anyhow::Result<Self> {
+        if extensions.is_empty() {
+            extensions.push("feature".into());
+        }
+        let ignore = ignore
+            .iter()
+            .map(|p| Pattern::new(p).with_context(|| format!("Bad ignore pattern {:?}", p)))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            extensions,
+            ignore,
+            max_depth,
+        })
+    }
+
+    fn is_feature(&self, path: &Path) -> bool {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(e) => self.extensions.iter().any(|x| x == e),
+            None => false,
+        }
+    }
+}
+
+/// Ignore patterns, and the directory they're relative to
+type IgnoreRules = Vec<(PathBuf, Arc<Vec<Pattern>>)>;
+
+/// Read the patterns in `dir/.zukeignore`, if it exists
+fn read_ignore_file(dir: &Path) -> anyhow::Result<Option<Vec<Pattern>>> {
+    let path = dir.join(".zukeignore");
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(anyhow::Error::new(e).context(format!("Couldn't read {}", path.display())))
+        }
+    };
+
+    let mut patterns = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let pattern = Pattern::new(line.trim_end_matches('/'))
+            .with_context(|| format!("Bad pattern at {}:{}", path.display(), i + 1))?;
+        patterns.push(pattern);
+    }
+    Ok(Some(patterns))
+}
+
+fn is_ignored(rules: &IgnoreRules, path: &Path) -> bool {
+    rules.iter().any(|(base, patterns)| {
+        let relative = path.strip_prefix(base).unwrap_or(path);
+        patterns.iter().any(|p| {
+            if p.as_str().contains('/') {
+                p.matches_path(relative)
+            } else {
+                path.file_name()
+                    .map(|n| p.matches(&n.to_string_lossy()))
+                    .unwrap_or(false)
+            }
+        })
+    })
 }
 
 /// maybe should go on a blocking task, but it's probably not the bottleneck.
 async fn parse_feature_dir(
     path: PathBuf,
+    discovery: &Discovery,
     lang: &str,
     global: &Arc<Component>,
     mut output: mpsc::Sender<Outcome>,
@@ -187,26 +566,36 @@ async fn parse_feature_dir(
     // skip errors. If the top level doesn't exist, we've already handled that when checking the
     // source type. Otherwise we don't want to crash because we recursed farther than the user
     // intended.
-    let mut dirs = vec![path];
+    let rules = vec![(path.clone(), Arc::new(discovery.ignore.clone()))];
+    let mut dirs = vec![(path, 0, rules)];
 
     let is_dir = |e: &fs::DirEntry| match e.file_type() {
         Ok(t) => t.is_dir(),
         Err(_) => false,
     };
 
-    let is_feature = |p: &Path| match p.extension() {
-        Some(s) => s == "feature",
-        None => false,
-    };
+    while let Some((path, depth, mut rules)) = dirs.pop() {
+        match read_ignore_file(&path) {
+            Ok(Some(patterns)) => rules.push((path.clone(), Arc::new(patterns))),
+            Ok(None) => (),
+            Err(e) => {
+                let name = path.join(".zukeignore").display().to_string();
+                send_error(name, e, global, output.clone()).await?;
+            }
+        }
 
-    while let Some(path) = dirs.pop() {
-        if let Ok(items) = fs::read_dir(path) {
+        if let Ok(items) = fs::read_dir(&path) {
             for entry in items.flatten() {
                 let path = entry.path();
+                if is_ignored(&rules, &path) {
+                    continue;
+                }
 
                 if is_dir(&entry) {
-                    dirs.push(path);
-                } else if is_feature(&path) {
+                    if discovery.max_depth.map(|max| depth < max).unwrap_or(true) {
+                        dirs.push((path, depth + 1, rules.clone()));
+                    }
+                } else if discovery.is_feature(&path) {
                     parse_feature_file(path, lang, global, &mut output).await?;
                 }
             }
@@ -224,7 +613,9 @@ async fn parse_feature_source(
     mut output: mpsc::Sender<Outcome>,
 ) -> Result<(), mpsc::SendError> {
     let outcome = match do_parse_feature_source(&filename, &source, lang) {
-        Ok(feature) => Outcome::undecided(global.with_feature(feature)),
+        Ok((feature, metadata)) => {
+            Outcome::undecided(global.with_feature_metadata(feature, metadata))
+        }
         Err(e) => {
             let feature = Feature::builder()
                 .keyword("Feature".into())
@@ -240,15 +631,107 @@ async fn parse_feature_source(
     output.send(outcome).await
 }
 
-fn do_parse_feature_source(filename: &str, source: &str, lang: &str) -> anyhow::Result<Feature> {
-    let env = GherkinEnv::new(lang)?;
-    let mut feature = Feature::parse(source, env)?;
+/// Parse the files and directories matching a glob pattern
+async fn parse_feature_glob(
+    pattern: String,
+    discovery: &Discovery,
+    lang: &str,
+    global: &Arc<Component>,
+    mut output: mpsc::Sender<Outcome>,
+) -> Result<(), mpsc::SendError> {
+    let paths = match glob::glob(&pattern) {
+        Ok(paths) => paths.flatten().collect::<Vec<_>>(),
+        Err(e) => {
+            let e = anyhow::Error::new(e).context(format!("Bad glob pattern {:?}", pattern));
+            return send_error(pattern, e, global, output).await;
+        }
+    };
+
+    if paths.is_empty() {
+        let e = anyhow::anyhow!("No files match {:?}", pattern);
+        return send_error(pattern, e, global, output).await;
+    }
+
+    for path in paths {
+        if path.is_dir() {
+            parse_feature_dir(path, discovery, lang, global, output.clone()).await?;
+        } else {
+            parse_feature_file(path, lang, global, &mut output).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse a feature from stdin
+async fn parse_feature_stdin(
+    lang: &str,
+    global: &Arc<Component>,
+    output: mpsc::Sender<Outcome>,
+) -> Result<(), mpsc::SendError> {
+    let mut source = String::new();
+    match async_std::io::stdin().read_to_string(&mut source).await {
+        Ok(_) => parse_feature_source("<stdin>".into(), source, lang, global, output).await,
+        Err(e) => {
+            let e = anyhow::Error::new(e).context("Could not read stdin");
+            send_error("<stdin>".into(), e, global, output).await
+        }
+    }
+}
+
+/// Report a source that couldn't be parsed as a failed feature
+async fn send_error(
+    name: String,
+    error: anyhow::Error,
+    global: &Arc<Component>,
+    mut output: mpsc::Sender<Outcome>,
+) -> Result<(), mpsc::SendError> {
+    let feature = Feature::builder()
+        .keyword("Feature".into())
+        .name(name.clone())
+        .path(Some(name.into()))
+        .build();
+    let mut outcome = Outcome::undecided(global.with_feature(feature));
+    outcome.set_err(error);
+    output.send(outcome).await
+}
+
+/// Parse a feature, along with the settings at the top of it. See [`crate::metadata`].
+fn do_parse_feature_source(
+    filename: &str,
+    source: &str,
+    lang: &str,
+) -> anyhow::Result<(Feature, FeatureMetadata)> {
+    let (metadata, source) = FeatureMetadata::split(source)?;
+    let env = GherkinEnv::new(metadata.language.as_deref().unwrap_or(lang))?;
+    let mut feature = Feature::parse(&source, env)?;
     feature.path = Some(PathBuf::from(filename));
-    Ok(feature)
+    for tag in metadata.tags.iter() {
+        if !feature.tags.contains(tag) {
+            feature.tags.push(tag.clone());
+        }
+    }
+    Ok((feature, metadata))
+}
+
+/// Parse and cook a feature given as text, in English unless its settings say otherwise
+pub(crate) fn parse_feature_text(
+    filename: &str,
+    source: &str,
+) -> anyhow::Result<(Feature, FeatureMetadata)> {
+    let (mut feature, metadata) = do_parse_feature_source(filename, source, "en")?;
+    cook_feature(&mut feature)?;
+    Ok((feature, metadata))
 }
 
 /// Function to expand scenario outlines into individual scenarios, etc.
 fn cook_feature(feature: &mut Feature) -> anyhow::Result<()> {
+    if let Some(background) = feature.background.as_mut() {
+        inherit_star_types(&mut background.steps);
+    }
+    for scenario in feature.scenarios.iter_mut() {
+        inherit_star_types(&mut scenario.steps);
+    }
     for rule in feature.rules.iter_mut() {
         cook_rule(rule)?;
     }
@@ -257,9 +740,24 @@ fn cook_feature(feature: &mut Feature) -> anyhow::Result<()> {
 }
 
 fn cook_rule(rule: &mut Rule) -> anyhow::Result<()> {
+    if let Some(background) = rule.background.as_mut() {
+        inherit_star_types(&mut background.steps);
+    }
+    for scenario in rule.scenarios.iter_mut() {
+        inherit_star_types(&mut scenario.steps);
+    }
     cook_scenarios(&mut rule.scenarios)
 }
 
+/// The parser reads `*` as `Given`, but like `And`, it should continue the step before it
+fn inherit_star_types(steps: &mut [Step]) {
+    for i in 1..steps.len() {
+        if steps[i].keyword.trim() == "*" {
+            steps[i].ty = steps[i - 1].ty;
+        }
+    }
+}
+
 fn cook_scenarios(scenarios: &mut Vec<Scenario>) -> anyhow::Result<()> {
     // we will continue past errors in order to make the cooked scenarios as complete as possible.
     // This might be helpful to the user. Only return the first error.
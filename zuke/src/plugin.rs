@@ -0,0 +1,397 @@
+//! Step implementations loaded from WebAssembly modules at startup
+//!
+//! Plugins let a team ship a step library without recompiling the test binary. Load them with
+//! `--plugin path/to/steps.wasm` (the text format, `.wat`, works too), or with
+//! [`WasmPlugin::load`] and [`crate::VocabBuilder::steps`]. Plugin steps work like any other step:
+//! they are matched, reported, and resolved in the same way. Each step runs in a fresh instance of
+//! the module, so steps share state only through the host API. Steps run on a blocking thread, off
+//! the executor. When a step is interrupted, because its scenario timed out or the run was
+//! canceled, the guest traps at its next loop or call.
+//!
+//! # Guest interface
+//!
+//! Strings cross the boundary as UTF-8 in the module's memory. Where a function returns a string,
+//! it returns a pointer and a length packed into an `i64`: `(ptr << 32) | len`.
+//!
+//! The module must export:
+//!
+//! * `memory`: its linear memory
+//! * `zuke_alloc(len: i32) -> i32`: allocate `len` bytes for the host to write into
+//! * `zuke_steps() -> i64`: a JSON array describing the steps, such as
+//!   `[{"pattern": "I have (?P<count>\\d+) widgets", "keyword": "given", "function": "widgets"}]`.
+//!   The pattern is a regular expression, matched case-insensitively against the whole step text.
+//!   The keyword is `given`, `when`, `then`, or `null` to match any of them.
+//! * One function per step, as named by `function`, taking `(ptr: i32, len: i32) -> i64`. The
+//!   argument is a JSON object of the named captures that matched. Return 0 if the step passed,
+//!   or a JSON object such as `{"verdict": "failed", "message": "No widgets"}`. The verdict may
+//!   be `failed`, `skipped`, or `pending`.
+//!
+//! The host provides these imports, in the `zuke` module:
+//!
+//! * `remember(name_ptr: i32, name_len: i32, value_ptr: i32, value_len: i32)`: as
+//!   [`crate::Context::remember`]
+//! * `recall(name_ptr: i32, name_len: i32) -> i64`: as [`crate::Context::recall`]. The value is
+//!   written to memory from `zuke_alloc`. Returns -1 if nothing was saved under the name.
+
+use crate::context::Context;
+use crate::step::{step_regex, StepError};
+use crate::vocab::{Location, StepImplementation};
+use anyhow::Context as _;
+use async_trait::async_trait;
+use futures::future::FutureExt;
+use gherkin_rust::StepType;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+use wasmtime::{Caller, Config, Engine, Linker, Memory, Module, Store, Trap};
+
+lazy_static! {
+    /// Steps from plugins loaded with `--plugin`, by path. The options can be built more than
+    /// once, and each load leaks its steps.
+    static ref LOADED: Mutex<HashMap<PathBuf, Vec<&'static dyn StepImplementation>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// A plugin module doesn't follow the [guest interface](self)
+#[derive(Error, Debug)]
+pub enum PluginError {
+    /// A required export is missing
+    #[error("Plugin {} does not export {name}", .path.display())]
+    MissingExport {
+        /// The module
+        path: PathBuf,
+        /// The missing export
+        name: String,
+    },
+    /// A string from the plugin wasn't in its memory, or wasn't UTF-8
+    #[error("Plugin returned a bad string")]
+    BadString,
+}
+
+/// A step as described by `zuke_steps`
+#[derive(Deserialize)]
+struct StepSpec {
+    pattern: String,
+    keyword: Option<String>,
+    function: String,
+}
+
+/// What a step function returned, when it didn't pass
+#[derive(Deserialize)]
+struct StepResult {
+    verdict: String,
+    #[serde(default)]
+    message: String,
+}
+
+/// Data available to host functions during a step
+#[derive(Default)]
+struct HostState {
+    saved: HashMap<String, String>,
+    remembered: Vec<(String, String)>,
+    interrupted: Arc<AtomicBool>,
+}
+
+/// A compiled WebAssembly step library. See the [module docs](self).
+pub struct WasmPlugin {
+    steps: Vec<&'static dyn StepImplementation>,
+}
+
+impl WasmPlugin {
+    /// Compile a plugin and ask it for its steps. The steps live for the rest of the program,
+    /// like steps registered with the step macros, so load each plugin once.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut config = Config::new();
+        config.epoch_interruption(true);
+        let engine = Engine::new(&config)?;
+        let module = Module::from_file(&engine, &path)
+            .with_context(|| format!("Couldn't load plugin {}", path.display()))?;
+        let linker = Arc::new(host_linker(&engine)?);
+
+        let mut store = new_store(&engine, HostState::default());
+        let instance = linker.instantiate(&mut store, &module)?;
+        let steps = instance
+            .get_typed_func::<(), i64, _>(&mut store, "zuke_steps")
+            .map_err(|_| missing(&path, "zuke_steps"))?
+            .call(&mut store, ())?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| missing(&path, "memory"))?;
+        let specs: Vec<StepSpec> = serde_json::from_str(read_packed(&memory, &store, steps)?)
+            .with_context(|| format!("Bad step list from plugin {}", path.display()))?;
+
+        // Steps from a plugin are "provided by" its file name, for `@use-steps` and friends
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().replace('-', "_"))
+            .unwrap_or_default();
+        let provider: &'static str = Box::leak(stem.into_boxed_str());
+
+        let mut steps = vec![];
+        for spec in specs {
+            let (regex, keyword) = step_regex(&spec.pattern, spec.keyword.as_deref())
+                .with_context(|| format!("Bad step in plugin {}", path.display()))?;
+
+            steps.push(WasmStep {
+                regex,
+                keyword,
+                location: Location {
+                    path: path.clone(),
+                    line: 0,
+                    module: provider,
+                },
+                function: Arc::new(StepFunction {
+                    path: path.clone(),
+                    name: spec.function,
+                    module: module.clone(),
+                    linker: linker.clone(),
+                }),
+            });
+        }
+
+        let steps = steps
+            .into_iter()
+            .map(|step| Box::leak(Box::new(step)) as &'static dyn StepImplementation)
+            .collect();
+        Ok(Self { steps })
+    }
+
+    /// The plugin's steps
+    pub fn steps(&self) -> Vec<&'static dyn StepImplementation> {
+        self.steps.clone()
+    }
+}
+
+/// The steps of the plugin at `path`, loading it the first time it's asked for
+pub(crate) fn load_steps(path: &str) -> anyhow::Result<Vec<&'static dyn StepImplementation>> {
+    let path = PathBuf::from(path);
+    let mut loaded = LOADED.lock().unwrap();
+    if let Some(steps) = loaded.get(&path) {
+        return Ok(steps.clone());
+    }
+    let steps = WasmPlugin::load(&path)?.steps();
+    loaded.insert(path, steps.clone());
+    Ok(steps)
+}
+
+/// A store for running the plugin. Nothing advances the engine's epoch until a step is
+/// interrupted; then every running guest checks whether it was the one, and traps if so.
+fn new_store(engine: &Engine, state: HostState) -> Store<HostState> {
+    let mut store = Store::new(engine, state);
+    store.set_epoch_deadline(1);
+    store.epoch_deadline_callback(|state: &mut HostState| {
+        if state.interrupted.load(Ordering::SeqCst) {
+            anyhow::bail!("Step interrupted");
+        }
+        Ok(1)
+    });
+    store
+}
+
+fn missing(path: &Path, name: &str) -> PluginError {
+    PluginError::MissingExport {
+        path: path.to_path_buf(),
+        name: name.to_string(),
+    }
+}
+
+/// One step from a plugin
+struct WasmStep {
+    regex: Regex,
+    keyword: Option<StepType>,
+    location: Location,
+    function: Arc<StepFunction>,
+}
+
+/// The function a step calls
+struct StepFunction {
+    path: PathBuf,
+    name: String,
+    module: Module,
+    linker: Arc<Linker<HostState>>,
+}
+
+impl StepFunction {
+    /// Run the step function in a fresh instance. Returns what the step saved, and how it
+    /// finished.
+    fn call(
+        &self,
+        state: HostState,
+        args: &str,
+    ) -> anyhow::Result<(HostState, Option<StepResult>)> {
+        let mut store = new_store(self.module.engine(), state);
+        // Interrupted before the store existed, so advancing the epoch missed it
+        if store.data().interrupted.load(Ordering::SeqCst) {
+            anyhow::bail!("Step interrupted");
+        }
+        let instance = self.linker.instantiate(&mut store, &self.module)?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| missing(&self.path, "memory"))?;
+        let func = instance
+            .get_typed_func::<(i32, i32), i64, _>(&mut store, &self.name)
+            .map_err(|_| missing(&self.path, &self.name))?;
+
+        let alloc = instance
+            .get_typed_func::<i32, i32, _>(&mut store, "zuke_alloc")
+            .map_err(|_| missing(&self.path, "zuke_alloc"))?;
+        let ptr = alloc.call(&mut store, args.len() as i32)?;
+        memory.write(&mut store, ptr as usize, args.as_bytes())?;
+
+        let result = func.call(&mut store, (ptr, args.len() as i32))?;
+        let result = match result {
+            0 => None,
+            packed => Some(serde_json::from_str(read_packed(&memory, &store, packed)?)?),
+        };
+        Ok((store.into_data(), result))
+    }
+}
+
+#[async_trait]
+impl StepImplementation for WasmStep {
+    fn regex(&self) -> &Regex {
+        &self.regex
+    }
+
+    fn location(&self) -> &Location {
+        &self.location
+    }
+
+    fn keyword(&self) -> Option<StepType> {
+        self.keyword
+    }
+
+    async fn execute(&self, context: &mut Context, captures: &Captures) -> anyhow::Result<()> {
+        let args: HashMap<&str, &str> = self
+            .regex
+            .capture_names()
+            .flatten()
+            .filter_map(|name| Some((name, captures.name(name)?.as_str())))
+            .collect();
+        let args = serde_json::to_string(&args)?;
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let state = HostState {
+            saved: context
+                .remembered()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            remembered: vec![],
+            interrupted: interrupted.clone(),
+        };
+
+        let function = self.function.clone();
+        let mut call = async_std::task::spawn_blocking(move || function.call(state, &args)).fuse();
+        let (state, result) = futures::select! {
+            r = call => r?,
+            e = context.interrupted().fuse() => {
+                // Stop the guest, and let it give the thread back before moving on
+                interrupted.store(true, Ordering::SeqCst);
+                self.function.module.engine().increment_epoch();
+                let _ = call.await;
+                return Err(e.into());
+            }
+        };
+        for (name, value) in state.remembered {
+            context.remember(name, value);
+        }
+
+        match result {
+            None => Ok(()),
+            Some(r) => Err(match r.verdict.as_str() {
+                "skipped" => StepError::skip_with_message(r.message),
+                "pending" => StepError::pending_with_message(r.message),
+                _ => StepError::fail_with_message(r.message),
+            }
+            .into()),
+        }
+    }
+}
+
+/// The imports every plugin gets
+fn host_linker(engine: &Engine) -> anyhow::Result<Linker<HostState>> {
+    let mut linker = Linker::new(engine);
+    linker.func_wrap(
+        "zuke",
+        "remember",
+        |mut caller: Caller<'_, HostState>,
+         name_ptr: i32,
+         name_len: i32,
+         value_ptr: i32,
+         value_len: i32|
+         -> Result<(), Trap> {
+            let memory = caller_memory(&mut caller)?;
+            let name = read_string(&memory, &caller, name_ptr, name_len)?.to_string();
+            let value = read_string(&memory, &caller, value_ptr, value_len)?.to_string();
+            let state = caller.data_mut();
+            state.saved.insert(name.clone(), value.clone());
+            state.remembered.push((name, value));
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        "zuke",
+        "recall",
+        |mut caller: Caller<'_, HostState>, name_ptr: i32, name_len: i32| -> Result<i64, Trap> {
+            let memory = caller_memory(&mut caller)?;
+            let name = read_string(&memory, &caller, name_ptr, name_len)?;
+            let value = match caller.data().saved.get(name) {
+                Some(v) => v.clone(),
+                None => return Ok(-1),
+            };
+
+            let alloc = caller
+                .get_export("zuke_alloc")
+                .and_then(|e| e.into_func())
+                .ok_or_else(|| Trap::new("Plugin does not export zuke_alloc"))?
+                .typed::<i32, i32, _>(&caller)
+                .map_err(|e| Trap::new(e.to_string()))?;
+            let ptr = alloc.call(&mut caller, value.len() as i32)?;
+            memory
+                .write(&mut caller, ptr as usize, value.as_bytes())
+                .map_err(|e| Trap::new(e.to_string()))?;
+            Ok(pack(ptr, value.len() as i32))
+        },
+    )?;
+    Ok(linker)
+}
+
+fn caller_memory(caller: &mut Caller<'_, HostState>) -> Result<Memory, Trap> {
+    caller
+        .get_export("memory")
+        .and_then(|e| e.into_memory())
+        .ok_or_else(|| Trap::new("Plugin does not export memory"))
+}
+
+fn pack(ptr: i32, len: i32) -> i64 {
+    (((ptr as u32 as u64) << 32) | (len as u32 as u64)) as i64
+}
+
+fn read_string<'a, T: 'a>(
+    memory: &Memory,
+    store: impl Into<wasmtime::StoreContext<'a, T>>,
+    ptr: i32,
+    len: i32,
+) -> Result<&'a str, PluginError> {
+    let data = memory.data(store);
+    let start = ptr as u32 as usize;
+    let end = start
+        .checked_add(len as u32 as usize)
+        .ok_or(PluginError::BadString)?;
+    let bytes = data.get(start..end).ok_or(PluginError::BadString)?;
+    std::str::from_utf8(bytes).map_err(|_| PluginError::BadString)
+}
+
+fn read_packed<'a, T: 'a>(
+    memory: &Memory,
+    store: impl Into<wasmtime::StoreContext<'a, T>>,
+    packed: i64,
+) -> Result<&'a str, PluginError> {
+    let packed = packed as u64;
+    read_string(memory, store, (packed >> 32) as i32, packed as u32 as i32)
+}
@@ -0,0 +1,175 @@
+//! Finding outcomes in a finished test run, for testing steps, fixtures, and reporters
+//!
+//! A test for a custom step usually runs a small feature in a sub-run, then checks what happened.
+//! [`Outcome::query`] finds the outcomes to check, [`Outcome::step`] finds a step within a
+//! scenario, and [`assert_counts!`](crate::assert_counts) checks the totals.
+//!
+//! ```ignore
+//! let failed = outcome
+//!     .query()
+//!     .kind(ComponentKind::Scenario)
+//!     .tagged("slow")
+//!     .verdict(Verdict::Failed)
+//!     .all();
+//! assert!(failed.is_empty());
+//!
+//! let scenario = outcome.query().kind(ComponentKind::Scenario).named("Checkout").one();
+//! assert_eq!(scenario.step("Given an empty cart").unwrap().verdict, Verdict::Passed);
+//!
+//! assert_counts!(outcome, Scenario { passed: 3, failed: 0 }, Step { total: 12 });
+//! ```
+
+use crate::component::ComponentKind;
+use crate::outcome::{Outcome, Verdict};
+use std::fmt;
+use std::sync::Arc;
+
+/// Outcomes under an outcome that match every condition given. See the [module docs](self).
+#[derive(Clone)]
+pub struct Query {
+    root: Arc<Outcome>,
+    kind: Option<ComponentKind>,
+    verdicts: Vec<Verdict>,
+    tags: Vec<String>,
+    name: Option<String>,
+}
+
+impl Query {
+    /// Match everything under `root`, including `root` itself
+    pub fn new(root: Arc<Outcome>) -> Self {
+        Self {
+            root,
+            kind: None,
+            verdicts: vec![],
+            tags: vec![],
+            name: None,
+        }
+    }
+
+    /// Only components of this kind
+    pub fn kind(mut self, kind: ComponentKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    /// Only components with this verdict. Given more than once, any of the verdicts match.
+    pub fn verdict(mut self, verdict: Verdict) -> Self {
+        self.verdicts.push(verdict);
+        self
+    }
+
+    /// Only components with this tag, with or without the `@`. Inherited tags count.
+    pub fn tagged<S: AsRef<str>>(mut self, tag: S) -> Self {
+        let tag = tag.as_ref();
+        self.tags
+            .push(tag.strip_prefix('@').unwrap_or(tag).to_string());
+        self
+    }
+
+    /// Only components with this name, ignoring case. A step's name is its text, without the
+    /// keyword.
+    pub fn named<S: AsRef<str>>(mut self, name: S) -> Self {
+        self.name = Some(name.as_ref().to_lowercase());
+        self
+    }
+
+    fn matches(&self, outcome: &Outcome) -> bool {
+        self.kind.map(|k| k == outcome.kind()).unwrap_or(true)
+            && (self.verdicts.is_empty() || self.verdicts.contains(&outcome.verdict))
+            && self.tags.iter().all(|t| outcome.tags().any(|tag| tag == t))
+            && self
+                .name
+                .as_ref()
+                .map(|n| *n == outcome.component().name().to_lowercase())
+                .unwrap_or(true)
+    }
+
+    /// Every matching outcome, in the order they appear in the features. Each iteration of a
+    /// repeated scenario is under the scenario, but isn't returned on its own.
+    pub fn all(&self) -> Vec<Arc<Outcome>> {
+        let mut found = vec![];
+        let mut stack = vec![(self.root.clone(), false)];
+
+        while let Some((outcome, iteration)) = stack.pop() {
+            if !iteration && self.matches(&outcome) {
+                found.push(outcome.clone());
+            }
+            stack.extend(
+                outcome
+                    .children
+                    .iter()
+                    .rev()
+                    .map(|c| (c.clone(), Arc::ptr_eq(c.component(), outcome.component()))),
+            );
+        }
+
+        found
+    }
+
+    /// The number of matching outcomes
+    pub fn count(&self) -> usize {
+        self.all().len()
+    }
+
+    /// The first matching outcome, if any
+    pub fn first(&self) -> Option<Arc<Outcome>> {
+        self.all().into_iter().next()
+    }
+
+    /// The only matching outcome. Panics, saying what was asked for, if there isn't exactly one.
+    pub fn one(&self) -> Arc<Outcome> {
+        let mut found = self.all();
+        match found.len() {
+            1 => found.pop().unwrap(),
+            0 => panic!("Found no {}", self),
+            n => panic!("Expected one {}, but found {}", self, n),
+        }
+    }
+}
+
+/// Describes what's asked for, such as `scenario named "checkout" tagged @slow`
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            Some(kind) => write!(f, "{}", kind)?,
+            None => write!(f, "component")?,
+        }
+        if let Some(name) = &self.name {
+            write!(f, " named {:?}", name)?;
+        }
+        for tag in self.tags.iter() {
+            write!(f, " tagged @{}", tag)?;
+        }
+        if !self.verdicts.is_empty() {
+            let verdicts: Vec<_> = self.verdicts.iter().map(|v| v.to_string()).collect();
+            write!(f, " with verdict {}", verdicts.join(" or "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Assert how many components of each kind had each outcome, as counted by [`Outcome::stats`].
+/// The fields are those of [`crate::Stat`]. Counts that aren't given aren't checked.
+///
+/// ```ignore
+/// assert_counts!(outcome, Scenario { passed: 2, failed: 1 }, Step { total: 9 });
+/// ```
+#[macro_export]
+macro_rules! assert_counts {
+    ($outcome:expr $(, $kind:ident { $($field:ident : $count:expr),* $(,)? })* $(,)?) => {{
+        let stats = $outcome.stats();
+        $(
+            let kind = $crate::ComponentKind::$kind;
+            let stat = stats.get(&kind).cloned().unwrap_or_default();
+            $(
+                assert_eq!(
+                    stat.$field,
+                    $count,
+                    "Wrong number of {} {}s",
+                    stringify!($field),
+                    kind
+                );
+            )*
+        )*
+    }};
+}
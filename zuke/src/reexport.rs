@@ -3,5 +3,7 @@
 pub use anyhow;
 pub use async_trait::async_trait;
 pub use futures;
+pub use gherkin_rust;
 pub use inventory;
 pub use regex;
+pub use toml;
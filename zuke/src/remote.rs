@@ -0,0 +1,324 @@
+//! Steps implemented by another process, such as a Python or Node step server
+//!
+//! Remote steps let a mixed-language team share one feature suite, with Zuke coordinating. The
+//! step server registers its patterns when Zuke connects, and Zuke forwards any step that matches
+//! one of them. Matching happens in Zuke, so remote steps are matched, reported, and resolved
+//! like any other step. Connect with `--remote-steps`, or with [`RemoteSteps`] and
+//! [`crate::VocabBuilder::steps`].
+//!
+//! # Protocol
+//!
+//! The protocol is [JSON-RPC 2.0][1], one message per line, over a child process's stdin and
+//! stdout or over TCP. Zuke sends requests and the server answers them, one at a time. Zuke
+//! matches answers to requests by `id`, and ignores answers to requests it has given up on.
+//!
+//! `step_definitions`, with no parameters, is sent once at startup. The result names the server
+//! and lists its steps:
+//!
+//! ```json
+//! {"name": "python_steps", "steps": [{"id": "widgets", "pattern": "I have (?P<count>\\d+) widgets", "keyword": "given"}]}
+//! ```
+//!
+//! Patterns are regular expressions, matched case-insensitively against the whole step text. The
+//! keyword is `given`, `when`, `then`, or `null` to match any of them. The name is used for
+//! `@use-steps`.
+//!
+//! `invoke` runs a step. Its parameters are the step's `id`, the named captures that matched as
+//! `args`, and the values saved by [`crate::Context::remember`] as `saved`:
+//!
+//! ```json
+//! {"id": "widgets", "args": {"count": "3"}, "saved": {"user": "alice"}}
+//! ```
+//!
+//! The result has a `verdict` of `passed`, `failed`, `skipped`, or `pending`, with an optional
+//! `message`, and optional `remember`: values to save for later steps. A JSON-RPC error fails the
+//! step.
+//!
+//! A server started as a child process should exit when its stdin closes.
+//!
+//! [1]: https://www.jsonrpc.org/specification
+
+use crate::context::Context;
+use crate::step::{step_regex, StepError};
+use crate::vocab::{Location, StepImplementation};
+use anyhow::Context as _;
+use async_trait::async_trait;
+use futures::future::FutureExt;
+use gherkin_rust::StepType;
+use parking_lot::Mutex;
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// The step server didn't follow the [protocol](self)
+#[derive(Error, Debug)]
+pub enum RemoteError {
+    /// The server closed the connection
+    #[error("Remote step server {0} disconnected")]
+    Disconnected(String),
+    /// The server answered with a JSON-RPC error
+    #[error("Remote step server {server} returned error {code}: {message}")]
+    Rpc {
+        /// The server
+        server: String,
+        /// The JSON-RPC error code
+        code: i64,
+        /// The JSON-RPC error message
+        message: String,
+    },
+    /// The server's answer wasn't a response to the request
+    #[error("Remote step server {0} sent an unexpected response")]
+    BadResponse(String),
+}
+
+#[derive(Deserialize)]
+struct Definitions {
+    name: String,
+    steps: Vec<Definition>,
+}
+
+#[derive(Deserialize)]
+struct Definition {
+    id: String,
+    pattern: String,
+    keyword: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Invocation {
+    verdict: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    remember: HashMap<String, String>,
+}
+
+/// A connection to a step server
+struct Connection {
+    /// Describes the server, for messages
+    server: String,
+    reader: BufReader<Box<dyn Read + Send>>,
+    writer: Box<dyn Write + Send>,
+    next_id: u64,
+    child: Option<Child>,
+}
+
+impl Connection {
+    /// Send a request and wait for its response
+    fn call(&mut self, method: &str, params: Value) -> anyhow::Result<Value> {
+        self.next_id += 1;
+        let id = self.next_id;
+        let request = json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params});
+        writeln!(self.writer, "{}", request)?;
+        self.writer.flush()?;
+
+        let mut response = loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(RemoteError::Disconnected(self.server.clone()).into());
+            }
+            let response: Value = serde_json::from_str(&line)
+                .with_context(|| format!("Bad response from remote step server {}", self.server))?;
+            match response["id"].as_u64() {
+                Some(answered) if answered == id => break response,
+                // An answer to an earlier request, whose caller gave up on it
+                Some(answered) if answered < id => continue,
+                _ => return Err(RemoteError::BadResponse(self.server.clone()).into()),
+            }
+        };
+        if let Some(error) = response.get("error") {
+            return Err(RemoteError::Rpc {
+                server: self.server.clone(),
+                code: error["code"].as_i64().unwrap_or(0),
+                message: error["message"].as_str().unwrap_or("").to_string(),
+            }
+            .into());
+        }
+        match response.get_mut("result") {
+            Some(result) => Ok(result.take()),
+            None => Err(RemoteError::BadResponse(self.server.clone()).into()),
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        if let Some(child) = self.child.as_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// A step server, and the steps it registered. See the [module docs](self).
+pub struct RemoteSteps {
+    name: String,
+    location: PathBuf,
+    connection: Arc<Mutex<Connection>>,
+    definitions: Vec<Definition>,
+}
+
+impl RemoteSteps {
+    /// Start a step server that talks over its stdin and stdout
+    pub fn spawn<I, S>(program: &str, args: I) -> anyhow::Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<std::ffi::OsStr>,
+    {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Couldn't start remote step server {}", program))?;
+        let writer = Box::new(child.stdin.take().unwrap());
+        let reader = Box::new(child.stdout.take().unwrap());
+        Self::start(program, reader, writer, Some(child))
+    }
+
+    /// Connect to a step server listening on TCP, such as `localhost:7878`
+    pub fn connect(address: &str) -> anyhow::Result<Self> {
+        let stream = TcpStream::connect(address)
+            .with_context(|| format!("Couldn't connect to remote step server {}", address))?;
+        let reader = Box::new(stream.try_clone()?);
+        Self::start(address, reader, Box::new(stream), None)
+    }
+
+    /// Connect as given on the command line: `tcp://HOST:PORT`, or a command to run. The command
+    /// is split into words as a shell would, so arguments can be quoted.
+    pub fn from_arg(arg: &str) -> anyhow::Result<Self> {
+        match arg.strip_prefix("tcp://") {
+            Some(address) => Self::connect(address),
+            None => {
+                let words = shell_words::split(arg)
+                    .with_context(|| format!("Bad --remote-steps command {:?}", arg))?;
+                let (program, args) = words
+                    .split_first()
+                    .context("Empty --remote-steps command")?;
+                Self::spawn(program, args)
+            }
+        }
+    }
+
+    fn start(
+        server: &str,
+        reader: Box<dyn Read + Send>,
+        writer: Box<dyn Write + Send>,
+        child: Option<Child>,
+    ) -> anyhow::Result<Self> {
+        let mut connection = Connection {
+            server: server.to_string(),
+            reader: BufReader::new(reader),
+            writer,
+            next_id: 0,
+            child,
+        };
+        let result = connection.call("step_definitions", Value::Null)?;
+        let definitions: Definitions = serde_json::from_value(result)
+            .with_context(|| format!("Bad step definitions from {}", server))?;
+
+        Ok(Self {
+            name: definitions.name,
+            location: PathBuf::from(server),
+            connection: Arc::new(Mutex::new(connection)),
+            definitions: definitions.steps,
+        })
+    }
+
+    /// The server's name, from `step_definitions`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The server's steps. They live for the rest of the program, like steps registered with the
+    /// step macros.
+    pub fn steps(&self) -> anyhow::Result<Vec<&'static dyn StepImplementation>> {
+        let module: &'static str = Box::leak(self.name.replace('-', "_").into_boxed_str());
+
+        let mut steps = vec![];
+        for definition in self.definitions.iter() {
+            let (regex, keyword) =
+                step_regex(&definition.pattern, definition.keyword.as_deref())
+                    .with_context(|| format!("Bad step from remote step server {}", self.name))?;
+
+            let step = Box::new(RemoteStep {
+                regex,
+                keyword,
+                location: Location {
+                    path: self.location.clone(),
+                    line: 0,
+                    module,
+                },
+                id: definition.id.clone(),
+                connection: self.connection.clone(),
+            });
+            steps.push(Box::leak(step) as &'static dyn StepImplementation);
+        }
+        Ok(steps)
+    }
+}
+
+/// One step from a step server
+struct RemoteStep {
+    regex: Regex,
+    keyword: Option<StepType>,
+    location: Location,
+    id: String,
+    connection: Arc<Mutex<Connection>>,
+}
+
+#[async_trait]
+impl StepImplementation for RemoteStep {
+    fn regex(&self) -> &Regex {
+        &self.regex
+    }
+
+    fn location(&self) -> &Location {
+        &self.location
+    }
+
+    fn keyword(&self) -> Option<StepType> {
+        self.keyword
+    }
+
+    async fn execute(&self, context: &mut Context, captures: &Captures) -> anyhow::Result<()> {
+        let args: HashMap<&str, &str> = self
+            .regex
+            .capture_names()
+            .flatten()
+            .filter_map(|name| Some((name, captures.name(name)?.as_str())))
+            .collect();
+        let saved: HashMap<&str, &str> = context.remembered().collect();
+        let params = json!({"id": self.id, "args": args, "saved": saved});
+
+        // The server handles one request at a time, and talking to it blocks. If the step is
+        // interrupted, the call finishes in the background, and the next step waits its turn.
+        let connection = self.connection.clone();
+        let call =
+            async_std::task::spawn_blocking(move || connection.lock().call("invoke", params));
+        let result = futures::select! {
+            r = call.fuse() => r?,
+            e = context.interrupted().fuse() => return Err(e.into()),
+        };
+        let invocation: Invocation = serde_json::from_value(result)?;
+
+        for (name, value) in invocation.remember {
+            context.remember(name, value);
+        }
+
+        let message = invocation.message;
+        match invocation.verdict.as_str() {
+            "passed" => Ok(()),
+            "skipped" => Err(StepError::skip_with_message(message).into()),
+            "pending" => Err(StepError::pending_with_message(message).into()),
+            _ => Err(StepError::fail_with_message(message).into()),
+        }
+    }
+}
@@ -0,0 +1,502 @@
+//! Record a test run, and replay it to reporters later
+//!
+//! With `--record FILE`, the events of a test run are saved to `FILE`, along with the features
+//! they came from. A [`Recording`] reads it back, and [`replay`] sends the events to reporters as
+//! if the tests were running again. That's handy for working on a reporter: try it against a real,
+//! large run, without running the tests each time.
+//!
+//! ```no_run
+//! # async fn f() -> anyhow::Result<()> {
+//! use zuke::reporter::{PlainReporter, Reporter};
+//!
+//! let reporter: Box<dyn Reporter> = Box::new(PlainReporter::default());
+//! zuke::replay("run.zuke", vec![reporter]).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A recording keeps each outcome's verdict, reason, warnings, times, and children. It doesn't
+//! keep hook events, artifacts, or step matches. Failures are text, rather than the original
+//! errors.
+//!
+//! The file is JSON, one object per line: a header, then each feature the first time it's
+//! needed, then each event. Components are found again by their feature, and the indexes of
+//! their rule, scenario, and step.
+
+use crate::component::{Component, ComponentKind};
+use crate::event::{Event, EventFilter};
+use crate::extra_options;
+use crate::metadata::FeatureMetadata;
+use crate::options::TestOptions;
+use crate::outcome::{Outcome, Stat};
+use crate::reporter::{Reporter, Supervised};
+use crate::runner::parse_time;
+use anyhow::{self, Context as _};
+use async_broadcast as broadcast;
+use async_std::fs::File;
+use async_trait::async_trait;
+use clap::{App, Arg};
+use futures::future::join_all;
+use futures::io::{AsyncWriteExt, BufWriter};
+use futures::join;
+use futures::stream::StreamExt;
+use gherkin_rust::Feature;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::{Arc, Weak};
+
+/// The format of recordings written by this version of Zuke
+const VERSION: u64 = 1;
+
+#[extra_options]
+fn record_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("record")
+            .long("record")
+            .value_name("FILE")
+            .takes_value(true)
+            .help("Record this run's events to FILE, to replay to reporters later"),
+    )
+}
+
+/// Reporter that records a test run to a file, for [`replay`]. Added automatically by
+/// [`crate::CommandLineReporter`] when `--record` is given.
+pub struct RecordReporter {
+    path: PathBuf,
+}
+
+impl RecordReporter {
+    /// Record to `path`
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+/// Turns events into lines of a recording. Features and outcomes are known by their addresses.
+#[derive(Default)]
+struct Recorder {
+    /// Recorded features are kept alive, so that their addresses aren't reused
+    features: HashMap<usize, (usize, Arc<Component>)>,
+    /// Outcomes that were recorded, but not yet given as a child. A weak reference is enough to
+    /// keep the address from being reused, without keeping outcomes alive after they're pruned.
+    outcomes: HashMap<usize, (usize, Weak<Outcome>)>,
+    finished: usize,
+}
+
+impl Recorder {
+    /// The lines for an event. Features are written the first time they're needed.
+    fn lines(&mut self, event: &Event) -> anyhow::Result<Vec<Value>> {
+        let mut lines = vec![];
+        match event {
+            Event::Started(component) => {
+                let component = self.component_json(component, &mut lines)?;
+                lines.push(json!({ "started": component }));
+            }
+            Event::Finished(outcome) => {
+                let json = self.outcome_json(outcome, &mut lines)?;
+                lines.push(json!({ "finished": json }));
+                self.outcomes.insert(
+                    Arc::as_ptr(outcome) as usize,
+                    (self.finished, Arc::downgrade(outcome)),
+                );
+                self.finished += 1;
+            }
+            Event::HookFinished(_) | Event::IterationFinished(_) => (),
+        }
+        Ok(lines)
+    }
+
+    fn component_json(
+        &mut self,
+        component: &Arc<Component>,
+        lines: &mut Vec<Value>,
+    ) -> anyhow::Result<Value> {
+        let feature = match component.feature() {
+            None => None,
+            Some(feature) => match self.features.get(&(feature as *const Feature as usize)) {
+                Some((index, _)) => Some(*index),
+                None => {
+                    let index = self.features.len();
+                    lines.push(json!({
+                        "feature": index,
+                        "source": serde_json::to_value(feature)?,
+                        "metadata": serde_json::to_value(component.metadata())?,
+                    }));
+                    self.features.insert(
+                        feature as *const Feature as usize,
+                        (index, component.clone()),
+                    );
+                    Some(index)
+                }
+            },
+        };
+        let rule = match (component.feature(), component.rule()) {
+            (Some(f), Some(rule)) => f.rules.iter().position(|r| ptr::eq(r, rule)),
+            _ => None,
+        };
+        let scenario = component.scenario().and_then(|scenario| {
+            let siblings = match component.rule() {
+                Some(r) => &r.scenarios,
+                None => &component.feature()?.scenarios,
+            };
+            siblings.iter().position(|s| ptr::eq(s, scenario))
+        });
+        Ok(json!({
+            "kind": component.kind().to_string(),
+            "feature": feature,
+            "key": component.key(),
+            "rule": rule,
+            "scenario": scenario,
+            "step_index": component.step_index(),
+            "step": component.step().map(|s| format!("{} {}", s.keyword, s.value)),
+        }))
+    }
+
+    /// Children that were recorded already are given by number. Each outcome is the child of
+    /// only one other, so it's forgotten once it's been given.
+    fn outcome_json(&mut self, outcome: &Outcome, lines: &mut Vec<Value>) -> anyhow::Result<Value> {
+        let mut children = vec![];
+        for child in outcome.children.iter() {
+            children.push(match self.outcomes.remove(&(Arc::as_ptr(child) as usize)) {
+                Some((n, _)) => json!({ "ref": n }),
+                None => self.outcome_json(child, lines)?,
+            });
+        }
+        let pruned: HashMap<String, &Stat> = outcome
+            .pruned
+            .iter()
+            .map(|(kind, stat)| (kind.to_string(), stat))
+            .collect();
+
+        Ok(json!({
+            "component": self.component_json(outcome.component(), lines)?,
+            "verdict": outcome.verdict.name(),
+            "reason": outcome.reason.as_ref().map(|e| format!("{:#}", e)),
+            "warnings": outcome.warnings.iter().map(|w| format!("{:#}", w)).collect::<Vec<_>>(),
+            "started": outcome.started.to_rfc3339(),
+            "ended": outcome.ended.to_rfc3339(),
+            "pruned": pruned,
+            "children": children,
+        }))
+    }
+}
+
+#[async_trait]
+impl Reporter for RecordReporter {
+    async fn report(
+        self: Box<Self>,
+        _global: Arc<Component>,
+        mut events: broadcast::Receiver<Event>,
+    ) -> anyhow::Result<()> {
+        let file = File::create(&self.path)
+            .await
+            .with_context(|| format!("Can't create recording {}", self.path.display()))?;
+        let mut out = BufWriter::new(file);
+        let header = json!({ "zuke_recording": VERSION });
+        out.write_all(format!("{}\n", header).as_bytes()).await?;
+
+        let mut recorder = Recorder::default();
+        while let Some(event) = events.next().await {
+            for line in recorder.lines(&event)? {
+                out.write_all(format!("{}\n", line).as_bytes()).await?;
+            }
+        }
+        out.flush().await?;
+        Ok(())
+    }
+
+    fn events(&self) -> EventFilter {
+        EventFilter::all().hooks(false).iterations(false)
+    }
+}
+
+/// A test run, read back from a file written with `--record`. See the [module docs](self).
+pub struct Recording {
+    global: Arc<Component>,
+    events: Vec<Event>,
+}
+
+/// A recorded feature or rule, with its scenarios and their steps, in order
+struct RecordedGroup {
+    component: Arc<Component>,
+    scenarios: Vec<(Arc<Component>, Vec<Arc<Component>>)>,
+}
+
+impl RecordedGroup {
+    fn new(component: Arc<Component>) -> anyhow::Result<Self> {
+        let mut scenarios = vec![];
+        for scenario in component.with_scenarios()? {
+            let mut steps = scenario.with_background()?;
+            steps.extend(scenario.with_steps()?);
+            scenarios.push((scenario, steps));
+        }
+        Ok(Self {
+            component,
+            scenarios,
+        })
+    }
+}
+
+/// A recorded feature, and its rules
+struct RecordedFeature {
+    feature: RecordedGroup,
+    rules: Vec<RecordedGroup>,
+}
+
+impl RecordedFeature {
+    fn new(
+        global: &Component,
+        feature: Feature,
+        metadata: FeatureMetadata,
+    ) -> anyhow::Result<Self> {
+        let feature = global.with_feature_metadata(feature, metadata);
+        let rules = feature
+            .with_rules()?
+            .into_iter()
+            .map(RecordedGroup::new)
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            feature: RecordedGroup::new(feature)?,
+            rules,
+        })
+    }
+
+    /// The component at the indexes in `value`, if it's in the feature
+    fn find(&self, kind: ComponentKind, value: &Value) -> Option<Arc<Component>> {
+        let index = |name: &str| value[name].as_u64().map(|i| i as usize);
+        let group = match index("rule") {
+            Some(r) => self.rules.get(r)?,
+            None => &self.feature,
+        };
+        match kind {
+            ComponentKind::Global => None,
+            ComponentKind::Feature => Some(self.feature.component.clone()),
+            ComponentKind::Rule => Some(group.component.clone()),
+            ComponentKind::Scenario => Some(group.scenarios.get(index("scenario")?)?.0.clone()),
+            ComponentKind::Step => {
+                let (_, steps) = group.scenarios.get(index("scenario")?)?;
+                steps.get(index("step_index")?).cloned()
+            }
+        }
+    }
+}
+
+fn parse_kind(kind: &str) -> anyhow::Result<ComponentKind> {
+    Ok(match kind {
+        "test" => ComponentKind::Global,
+        "feature" => ComponentKind::Feature,
+        "rule" => ComponentKind::Rule,
+        "scenario" => ComponentKind::Scenario,
+        "step" => ComponentKind::Step,
+        _ => anyhow::bail!("Unknown component kind {:?}", kind),
+    })
+}
+
+/// Reads a recording, line by line
+struct Loader {
+    global: Arc<Component>,
+    features: Vec<RecordedFeature>,
+    outcomes: Vec<Arc<Outcome>>,
+}
+
+impl Loader {
+    /// Find a recorded component. Steps run from inside other steps aren't in the feature, so
+    /// they're made again from `parent`.
+    fn component(
+        &self,
+        value: &Value,
+        parent: Option<&Arc<Component>>,
+    ) -> anyhow::Result<Arc<Component>> {
+        let kind = parse_kind(value["kind"].as_str().unwrap_or_default())?;
+        let feature = match value["feature"].as_u64() {
+            None => return Ok(self.global.clone()),
+            Some(i) => self
+                .features
+                .get(i as usize)
+                .ok_or_else(|| anyhow::anyhow!("Feature {} wasn't recorded", i))?,
+        };
+        if let Some(component) = feature.find(kind, value) {
+            return Ok(component);
+        }
+
+        let parent = parent.filter(|p| p.step().is_some());
+        match (parent, value["step"].as_str()) {
+            (Some(parent), Some(text)) => {
+                let (keyword, text) = text.split_once(' ').unwrap_or((text, ""));
+                let mut step = parent.step().unwrap().clone();
+                step.keyword = keyword.to_string();
+                step.value = text.to_string();
+                Ok(parent.with_synthetic_step(step)?)
+            }
+            _ => anyhow::bail!(
+                "No {} {:?} in the recorded features",
+                kind,
+                value["key"].as_str().unwrap_or_default()
+            ),
+        }
+    }
+
+    fn outcome(&self, value: &Value, parent: Option<&Arc<Component>>) -> anyhow::Result<Outcome> {
+        let component = self.component(&value["component"], parent)?;
+        let verdict = value["verdict"].as_str().unwrap_or_default().parse()?;
+        let mut outcome = Outcome::new(component.clone(), verdict);
+        outcome.reason = value["reason"].as_str().map(|r| anyhow::anyhow!("{}", r));
+        outcome.started = parse_time(&value["started"])?;
+        outcome.ended = parse_time(&value["ended"])?;
+        for warning in value["warnings"].as_array().into_iter().flatten() {
+            let warning = warning.as_str().unwrap_or_default();
+            outcome.warnings.push(anyhow::anyhow!("{}", warning));
+        }
+        for (kind, stat) in value["pruned"].as_object().into_iter().flatten() {
+            let stat: Stat = serde_json::from_value(stat.clone())?;
+            outcome.pruned.insert(parse_kind(kind)?, stat);
+        }
+
+        for child in value["children"].as_array().into_iter().flatten() {
+            let child = match child["ref"].as_u64() {
+                Some(n) => self
+                    .outcomes
+                    .get(n as usize)
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("Outcome {} wasn't recorded", n))?,
+                None => Arc::new(self.outcome(child, Some(&component))?),
+            };
+            outcome.children.push(child);
+        }
+        Ok(outcome)
+    }
+}
+
+impl Recording {
+    /// Read a recording. Its components are given `options`.
+    pub fn load<P: AsRef<Path>>(path: P, options: Arc<TestOptions>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Can't read recording {}", path.display()))?;
+        Self::parse(&text, options).with_context(|| format!("Bad recording {}", path.display()))
+    }
+
+    /// Read a recording from a string
+    pub fn parse(text: &str, options: Arc<TestOptions>) -> anyhow::Result<Self> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty());
+        let header: Value = match lines.next() {
+            Some((_, line)) => serde_json::from_str(line)?,
+            None => anyhow::bail!("The recording is empty"),
+        };
+        match header["zuke_recording"].as_u64() {
+            Some(VERSION) => (),
+            Some(v) => anyhow::bail!("Can't read version {} recordings", v),
+            None => anyhow::bail!("Not a recording"),
+        }
+
+        let mut loader = Loader {
+            global: Component::global(options),
+            features: vec![],
+            outcomes: vec![],
+        };
+        let mut events = vec![];
+
+        for (i, line) in lines {
+            let value: Value = serde_json::from_str(line)?;
+            let event = || -> anyhow::Result<Option<Event>> {
+                if let Some(started) = value.get("started") {
+                    return Ok(Some(Event::Started(loader.component(started, None)?)));
+                }
+                if let Some(finished) = value.get("finished") {
+                    return Ok(Some(Event::Finished(Arc::new(
+                        loader.outcome(finished, None)?,
+                    ))));
+                }
+                if value.get("feature").is_some() {
+                    return Ok(None);
+                }
+                anyhow::bail!("Unknown line");
+            };
+            let event = event().with_context(|| format!("At line {}", i + 1))?;
+
+            match event {
+                Some(event) => {
+                    if let Event::Finished(outcome) = &event {
+                        loader.outcomes.push(outcome.clone());
+                    }
+                    events.push(event);
+                }
+                None => {
+                    let feature: Feature = serde_json::from_value(value["source"].clone())?;
+                    let metadata: FeatureMetadata =
+                        serde_json::from_value(value["metadata"].clone())?;
+                    let feature = RecordedFeature::new(&loader.global, feature, metadata)
+                        .with_context(|| format!("At line {}", i + 1))?;
+                    loader.features.push(feature);
+                }
+            }
+        }
+
+        Ok(Self {
+            global: loader.global,
+            events,
+        })
+    }
+
+    /// The global component of the recorded run
+    pub fn global(&self) -> &Arc<Component> {
+        &self.global
+    }
+
+    /// The recorded events, in order
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// The final outcome of the recorded run, if it finished
+    pub fn outcome(&self) -> Option<&Arc<Outcome>> {
+        self.events.iter().rev().find_map(|e| match e {
+            Event::Finished(o) if o.kind() == ComponentKind::Global => Some(o),
+            _ => None,
+        })
+    }
+
+    /// Send the recorded events to `reporters`, as [`crate::Zuke::run`] would. Returns the first
+    /// error from a reporter, if any.
+    pub async fn report(&self, reporters: Vec<Box<dyn Reporter>>) -> anyhow::Result<()> {
+        let (events_tx, events_rx) = broadcast::broadcast(self.events.len().max(1));
+        let reporters =
+            join_all(reporters.into_iter().map(|r| {
+                Box::new(Supervised::new(r)).report(self.global.clone(), events_rx.clone())
+            }));
+        drop(events_rx);
+
+        let send = async {
+            for event in self.events.iter() {
+                if events_tx.broadcast(event.clone()).await.is_err() {
+                    break;
+                }
+            }
+            events_tx.close();
+        };
+
+        let (_, results) = join!(send, reporters);
+        results.into_iter().find(Result::is_err).unwrap_or(Ok(()))
+    }
+}
+
+/// Replay the run recorded in `path` to `reporters`, with default options. See the
+/// [module docs](self).
+pub async fn replay<P: AsRef<Path>>(
+    path: P,
+    reporters: Vec<Box<dyn Reporter>>,
+) -> anyhow::Result<()> {
+    // Not `TestOptions::new`, which would parse the program's own command line. The recording
+    // says what happened, so don't let the environment or a config file say otherwise.
+    let mut builder = TestOptions::builder();
+    builder.ignore_env().ignore_config();
+    let options = builder.build_with_app_from(App::new("replay"), ["replay"])?;
+    let recording = Recording::load(path, Arc::new(options))?;
+    recording.report(reporters).await
+}
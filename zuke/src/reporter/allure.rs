@@ -0,0 +1,430 @@
+//! Results for the Allure report viewer
+use super::Reporter;
+use crate::artifact::Artifact;
+use crate::component::{Component, ComponentId, ComponentKind};
+use crate::event::Event;
+use crate::hooks::{BeforeAfter, HookOutcome};
+use crate::options::TestOptions;
+use crate::outcome::{Outcome, Verdict};
+use crate::{extra_options, reporter};
+use anyhow::{self, Context as _};
+use async_broadcast as broadcast;
+use async_std::fs;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use clap::{App, Arg};
+use futures::stream::StreamExt;
+use gherkin_rust::Feature;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Where results go if `--allure-dir` isn't given
+pub const DEFAULT_ALLURE_DIR: &str = "allure-results";
+
+/// Tags of the form `@allure.label.NAME:VALUE` become Allure labels, such as
+/// `@allure.label.severity:critical`, rather than plain tags.
+const LABEL_TAG_PREFIX: &str = "allure.label.";
+
+/// Reporter that writes a directory of results for the [Allure](https://allurereport.org/)
+/// command line tool, as the scenarios finish: `allure generate allure-results` turns them into a
+/// report.
+///
+/// Each scenario becomes a `*-result.json` test result, with its steps. Docstrings, data tables,
+/// and [failure artifacts](crate::artifact) are attached to their steps. Each feature becomes a
+/// `*-container.json` holding its scenarios, along with the feature's hooks. Tags become labels,
+/// and history IDs come from [`Component::key`], so Allure can track a scenario from one run to
+/// the next. Information about the run goes in `environment.properties`.
+///
+/// Excluded scenarios aren't written. Existing results in the directory are kept, as Allure
+/// expects; clean the directory between runs if they aren't wanted.
+pub struct AllureReporter {
+    dir: PathBuf,
+}
+
+#[reporter("allure")]
+fn make_allure(_name: &str, options: &TestOptions) -> anyhow::Result<Box<dyn Reporter>> {
+    let dir = options
+        .opts
+        .value_of_os("allure_dir")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_ALLURE_DIR));
+    Ok(Box::new(AllureReporter::new(dir)))
+}
+
+#[extra_options]
+fn allure_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("allure_dir")
+            .long("allure-dir")
+            .value_name("DIR")
+            .takes_value(true)
+            .help("Write results from the allure reporter to DIR. Default is allure-results."),
+    )
+}
+
+impl AllureReporter {
+    /// Write results to `dir`, creating it if needed
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+/// A feature's container, filled in as its scenarios and hooks finish
+#[derive(Default)]
+struct Container {
+    children: Vec<String>,
+    befores: Vec<Value>,
+    afters: Vec<Value>,
+}
+
+#[async_trait]
+impl Reporter for AllureReporter {
+    async fn report(
+        self: Box<Self>,
+        global: Arc<Component>,
+        mut events: broadcast::Receiver<Event>,
+    ) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.dir)
+            .await
+            .with_context(|| format!("Couldn't create {}", self.dir.display()))?;
+        let mut writer = Writer {
+            dir: self.dir,
+            count: 0,
+            pending: vec![],
+        };
+        // By feature, rather than by key, which features from different places can share
+        let mut containers: HashMap<usize, Container> = HashMap::new();
+        let mut failed = None;
+
+        while let Some(event) = events.next().await {
+            match &event {
+                Event::Started(_) | Event::IterationFinished(_) => (),
+                Event::HookFinished(hook) => {
+                    if hook.component.kind() == ComponentKind::Feature {
+                        let container = containers.entry(feature_ptr(&hook.component)).or_default();
+                        match hook.when {
+                            BeforeAfter::Before => container.befores.push(hook_json(hook)),
+                            BeforeAfter::After => container.afters.push(hook_json(hook)),
+                        }
+                    }
+                }
+                Event::Finished(outcome) => match outcome.kind() {
+                    ComponentKind::Scenario if outcome.verdict != Verdict::Excluded => {
+                        let uuid = writer.result(outcome)?;
+                        containers
+                            .entry(feature_ptr(outcome.component()))
+                            .or_default()
+                            .children
+                            .push(uuid);
+                    }
+                    ComponentKind::Feature => {
+                        let container = containers.remove(&feature_ptr(outcome.component()));
+                        writer.container(outcome, container.unwrap_or_default())?;
+                    }
+                    ComponentKind::Global => {
+                        writer.environment(&global)?;
+                        failed = Some(outcome.failed());
+                    }
+                    _ => (),
+                },
+            }
+            writer.flush().await?;
+        }
+
+        match failed {
+            None => anyhow::bail!("Did not receive final test result"),
+            Some(true) => anyhow::bail!("Test run failed"),
+            Some(false) => Ok(()),
+        }
+    }
+}
+
+/// Writes files into the results directory
+struct Writer {
+    dir: PathBuf,
+    /// Files written so far, so that names are unique even for the same component
+    count: u64,
+    /// Files to write on the next [`Self::flush`]
+    pending: Vec<(String, Vec<u8>)>,
+}
+
+impl Writer {
+    /// A new UUID-shaped name for a file about `id`. Allure only needs them to be unique.
+    fn uuid(&mut self, id: ComponentId, time: DateTime<Utc>) -> String {
+        self.count += 1;
+        let n = (time.timestamp() as u64)
+            .wrapping_mul(1_000_000_000)
+            .wrapping_add(time.timestamp_subsec_nanos() as u64)
+            .wrapping_add(self.count);
+        format!(
+            "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            id.0 >> 32,
+            (id.0 >> 16) & 0xffff,
+            id.0 & 0xffff,
+            n >> 48,
+            n & 0xffff_ffff_ffff
+        )
+    }
+
+    fn write(&mut self, name: &str, contents: &[u8]) {
+        self.pending.push((name.to_string(), contents.to_vec()));
+    }
+
+    fn write_json(&mut self, name: &str, value: &Value) -> anyhow::Result<()> {
+        self.write(name, &serde_json::to_vec_pretty(value)?);
+        Ok(())
+    }
+
+    /// Write the files from the last event
+    async fn flush(&mut self) -> anyhow::Result<()> {
+        for (name, contents) in self.pending.drain(..) {
+            let path = self.dir.join(name);
+            fs::write(&path, contents)
+                .await
+                .with_context(|| format!("Couldn't write {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Write a scenario's result, and return its UUID
+    fn result(&mut self, outcome: &Outcome) -> anyhow::Result<String> {
+        let component = outcome.component();
+        let uuid = self.uuid(component.id(), outcome.started);
+
+        // Each iteration of a repeated scenario is shown as a step holding its own steps
+        let iterations: Vec<_> = outcome.iterations().collect();
+        let steps = if iterations.is_empty() {
+            self.steps(outcome)?
+        } else {
+            let mut steps = vec![];
+            for (i, iteration) in iterations.into_iter().enumerate() {
+                let mut step = result_json(&format!("Iteration {}", i + 1), iteration);
+                step["steps"] = Value::Array(self.steps(iteration)?);
+                steps.push(step);
+            }
+            steps
+        };
+
+        let parameters: Vec<_> = component
+            .example_index()
+            .map(|i| json!({"name": "example", "value": (i + 1).to_string()}))
+            .into_iter()
+            .collect();
+
+        let mut result = result_json(component.name(), outcome);
+        result["uuid"] = json!(uuid);
+        result["historyId"] = json!(component.key());
+        result["testCaseId"] = json!(component.key());
+        result["fullName"] = json!(component.key());
+        result["labels"] = Value::Array(labels(component));
+        result["parameters"] = Value::Array(parameters);
+        result["steps"] = Value::Array(steps);
+        result["attachments"] = Value::Array(self.attachments(outcome)?);
+
+        self.write_json(&format!("{}-result.json", uuid), &result)?;
+        Ok(uuid)
+    }
+
+    /// The steps run by an outcome, with their attachments
+    fn steps(&mut self, outcome: &Outcome) -> anyhow::Result<Vec<Value>> {
+        let mut steps = vec![];
+        for child in outcome.children.iter() {
+            let step = match child.component().step() {
+                Some(s) => s,
+                None => continue,
+            };
+
+            let mut attachments = vec![];
+            if let Some(docstring) = &step.docstring {
+                let artifact = Artifact::text("Docstring", docstring.as_str());
+                attachments.push(self.attachment(child, &artifact)?);
+            }
+            if let Some(table) = &step.table {
+                let csv: Vec<_> = table.rows.iter().map(|row| csv_row(row)).collect();
+                let artifact = Artifact::new("Table", "text/csv", csv.join(""));
+                attachments.push(self.attachment(child, &artifact)?);
+            }
+            attachments.extend(self.attachments(child)?);
+
+            let mut json = result_json(&format!("{} {}", step.keyword.trim(), step.value), child);
+            json["attachments"] = Value::Array(attachments);
+            // Steps run by other steps
+            json["steps"] = Value::Array(self.steps(child)?);
+            steps.push(json);
+        }
+        Ok(steps)
+    }
+
+    fn attachment(&mut self, outcome: &Outcome, artifact: &Artifact) -> anyhow::Result<Value> {
+        let source = format!(
+            "{}-attachment.{}",
+            self.uuid(outcome.id(), outcome.started),
+            artifact.extension()
+        );
+        self.write(&source, &artifact.data);
+        Ok(json!({"name": artifact.name, "source": source, "type": artifact.media_type}))
+    }
+
+    /// Attach the artifacts collected when the outcome failed
+    fn attachments(&mut self, outcome: &Outcome) -> anyhow::Result<Vec<Value>> {
+        outcome
+            .artifacts
+            .iter()
+            .map(|a| self.attachment(outcome, a))
+            .collect()
+    }
+
+    fn container(&mut self, outcome: &Outcome, container: Container) -> anyhow::Result<()> {
+        let uuid = self.uuid(outcome.id(), outcome.started);
+        let json = json!({
+            "uuid": uuid,
+            "name": outcome.component().name(),
+            "children": container.children,
+            "befores": container.befores,
+            "afters": container.afters,
+            "start": outcome.started.timestamp_millis(),
+            "stop": outcome.ended.timestamp_millis(),
+        });
+        self.write_json(&format!("{}-container.json", uuid), &json)
+    }
+
+    /// Describe the run, from [`TestOptions::run_info`]
+    fn environment(&mut self, global: &Component) -> anyhow::Result<()> {
+        let options = global.options();
+        let info = &options.run_info;
+        let mut properties = vec![("Title", options.title.clone())];
+        properties.extend(info.host().map(|h| ("Host", h.to_string())));
+        properties.extend(info.git_sha().map(|s| ("Commit", s.to_string())));
+        properties.extend(info.profile.clone().map(|p| ("Profile", p)));
+        properties.extend(info.seed.map(|s| ("Seed", s.to_string())));
+        properties.push(("Command line", info.command_line()));
+
+        let text: String = properties
+            .into_iter()
+            .map(|(key, value)| format!("{}={}\n", key.replace(' ', "\\ "), property(&value)))
+            .collect();
+        self.write("environment.properties", text.as_bytes());
+        Ok(())
+    }
+}
+
+/// Allure's status for a verdict. Failures in the steps themselves are `failed`; failures
+/// elsewhere, such as in hooks or fixtures, are `broken`.
+fn status(outcome: &Outcome) -> &'static str {
+    match outcome.verdict {
+        Verdict::Canceled => "broken",
+        v if v.failed() => {
+            let step_failed = outcome
+                .children
+                .iter()
+                .any(|c| c.kind() == ComponentKind::Step && c.failed());
+            if outcome.kind() == ComponentKind::Step || step_failed {
+                "failed"
+            } else {
+                "broken"
+            }
+        }
+        v if v.passed() => "passed",
+        Verdict::Undecided => "unknown",
+        _ => "skipped",
+    }
+}
+
+/// Why an outcome didn't pass, if it didn't
+fn status_details(outcome: &Outcome) -> Value {
+    let reason = outcome
+        .reason
+        .as_ref()
+        .map(|r| (r.to_string(), format!("{:#}", r)));
+    let (message, trace) = match (reason, &outcome.skip_cause) {
+        (Some(reason), _) => reason,
+        (None, Some(cause)) if outcome.skipped() => (cause.to_string(), String::new()),
+        _ => return json!({}),
+    };
+    json!({"message": message, "trace": trace})
+}
+
+/// The fields shared by results and steps
+fn result_json(name: &str, outcome: &Outcome) -> Value {
+    json!({
+        "name": name,
+        "status": status(outcome),
+        "statusDetails": status_details(outcome),
+        "stage": "finished",
+        "start": outcome.started.timestamp_millis(),
+        "stop": outcome.ended.timestamp_millis(),
+    })
+}
+
+fn hook_json(hook: &HookOutcome) -> Value {
+    let details = match &hook.error {
+        Some(e) => json!({"message": e}),
+        None => json!({}),
+    };
+    json!({
+        "name": hook.name,
+        "status": if hook.error.is_some() { "broken" } else { "passed" },
+        "statusDetails": details,
+        "stage": "finished",
+        "start": hook.started.timestamp_millis(),
+        "stop": hook.ended.timestamp_millis(),
+    })
+}
+
+fn labels(component: &Component) -> Vec<Value> {
+    let mut labels = vec![
+        json!({"name": "framework", "value": "zuke"}),
+        json!({"name": "language", "value": "rust"}),
+    ];
+    if let Some(feature) = component.feature() {
+        labels.push(json!({"name": "feature", "value": feature.name}));
+        labels.push(json!({"name": "suite", "value": feature.name}));
+    }
+    if let Some(rule) = component.rule() {
+        labels.push(json!({"name": "subSuite", "value": rule.name}));
+    }
+    if let Some(host) = component.options().run_info.host() {
+        labels.push(json!({"name": "host", "value": host}));
+    }
+    for tag in component.tags() {
+        match tag
+            .strip_prefix(LABEL_TAG_PREFIX)
+            .and_then(|l| l.split_once(':'))
+        {
+            Some((name, value)) => labels.push(json!({"name": name, "value": value})),
+            None => labels.push(json!({"name": "tag", "value": tag})),
+        }
+    }
+    labels
+}
+
+/// The address of the feature a component belongs to
+fn feature_ptr(component: &Component) -> usize {
+    component
+        .feature()
+        .map_or(0, |f| f as *const Feature as usize)
+}
+
+fn csv_row(row: &[String]) -> String {
+    let cells: Vec<_> = row
+        .iter()
+        .map(|cell| {
+            if cell.contains(|c| matches!(c, ',' | '"' | '\n')) {
+                format!("\"{}\"", cell.replace('"', "\"\""))
+            } else {
+                cell.clone()
+            }
+        })
+        .collect();
+    format!("{}\n", cells.join(","))
+}
+
+/// Escape a value for a `.properties` file
+fn property(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
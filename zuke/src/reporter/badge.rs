@@ -0,0 +1,162 @@
+//! A status badge, as a shields.io endpoint
+use super::{reporter_output, Reporter};
+use crate::component::{Component, ComponentKind};
+use crate::event::{Event, EventFilter};
+use crate::options::TestOptions;
+use crate::outcome::Outcome;
+use crate::{extra_options, reporter};
+use anyhow;
+use async_broadcast as broadcast;
+use async_std::io::{stdout, Stdout};
+use async_trait::async_trait;
+use clap::{App, Arg};
+use futures::io::{AllowStdIo, AsyncWrite, AsyncWriteExt};
+use futures::stream::StreamExt;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::sync::Arc;
+
+/// Reporter that writes a [shields.io endpoint](https://shields.io/badges/endpoint-badge) once
+/// the test run completes, such as:
+///
+/// ```json
+/// {"schemaVersion": 1, "label": "bdd", "message": "12/14 passed", "color": "yellow"}
+/// ```
+///
+/// Publish the file somewhere shields.io can fetch it, and a badge made from
+/// `https://img.shields.io/endpoint?url=...` shows how the last run went. The message counts
+/// scenarios that passed, out of those that passed or failed. The color goes from `brightgreen`,
+/// when every scenario passed, through `green`, `yellow`, and `orange`, to `red`. If nothing ran,
+/// the badge is `lightgrey`.
+///
+/// The label is "bdd", unless changed with `--badge-label`.
+pub struct BadgeReporter<T: AsyncWrite> {
+    out: T,
+    label: String,
+}
+
+#[reporter("badge")]
+fn make_badge(name: &str, options: &TestOptions) -> anyhow::Result<Box<dyn Reporter>> {
+    let label = options.opts.value_of("badge_label");
+    match reporter_output(name, options)? {
+        Some(file) => Ok(Box::new(BadgeReporter::from(file).with_label(label))),
+        None => Ok(Box::new(BadgeReporter::default().with_label(label))),
+    }
+}
+
+#[extra_options]
+fn badge_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("badge_label")
+            .long("badge-label")
+            .value_name("LABEL")
+            .takes_value(true)
+            .help("Label for the badge reporter's badge. Default is \"bdd\"."),
+    )
+}
+
+impl<T: AsyncWrite + Send + Sync + 'static> From<T> for BadgeReporter<T> {
+    fn from(out: T) -> Self {
+        Self {
+            out,
+            label: String::from("bdd"),
+        }
+    }
+}
+
+impl<T: Write + Send + Sync + 'static> From<T> for BadgeReporter<AllowStdIo<T>> {
+    fn from(out: T) -> Self {
+        Self {
+            out: AllowStdIo::new(out),
+            label: String::from("bdd"),
+        }
+    }
+}
+
+impl Default for BadgeReporter<Stdout> {
+    fn default() -> Self {
+        Self::from(stdout())
+    }
+}
+
+impl<T: AsyncWrite> BadgeReporter<T> {
+    /// Change the badge's label, if `label` is given
+    pub fn with_label<S: Into<String>>(mut self, label: Option<S>) -> Self {
+        if let Some(label) = label {
+            self.label = label.into();
+        }
+        self
+    }
+}
+
+#[async_trait]
+impl<T: AsyncWrite + Send + Sync + 'static> Reporter for BadgeReporter<T> {
+    async fn report(
+        self: Box<Self>,
+        _global: Arc<Component>,
+        mut events: broadcast::Receiver<Event>,
+    ) -> anyhow::Result<()> {
+        let mut outcome = None;
+        while let Some(event) = events.next().await {
+            match event {
+                Event::Finished(o) if o.kind() == ComponentKind::Global => outcome = Some(o),
+                _ => (),
+            }
+        }
+
+        let outcome = match outcome {
+            Some(o) => o,
+            None => anyhow::bail!("Did not receive final test result"),
+        };
+
+        let badge = serde_json::to_vec(&badge(&self.label, &outcome))?;
+        let out = self.out;
+        futures::pin_mut!(out);
+        out.write_all(&badge).await?;
+        out.write_all(b"\n").await?;
+        out.flush().await?;
+
+        if outcome.failed() {
+            anyhow::bail!("Test run failed");
+        }
+        Ok(())
+    }
+
+    fn events(&self) -> EventFilter {
+        EventFilter::none().finished(&[ComponentKind::Global])
+    }
+}
+
+/// The endpoint JSON for a finished test run
+fn badge(label: &str, outcome: &Outcome) -> Value {
+    let stat = outcome
+        .stats()
+        .remove(&ComponentKind::Scenario)
+        .unwrap_or_default();
+    let ran = stat.passed + stat.failed;
+
+    let (message, color) = if ran == 0 {
+        (String::from("no scenarios"), "lightgrey")
+    } else {
+        let rate = stat.passed as f64 / ran as f64;
+        let color = if stat.passed == ran && !outcome.failed() {
+            "brightgreen"
+        } else if rate >= 0.9 {
+            "green"
+        } else if rate >= 0.75 {
+            "yellow"
+        } else if rate >= 0.5 {
+            "orange"
+        } else {
+            "red"
+        };
+        (format!("{}/{} passed", stat.passed, ran), color)
+    };
+
+    json!({
+        "schemaVersion": 1,
+        "label": label,
+        "message": message,
+        "color": color,
+    })
+}
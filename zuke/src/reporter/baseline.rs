@@ -0,0 +1,276 @@
+//! Compare a test run against a previous one
+use super::plain::format_elapsed;
+use super::Reporter;
+use crate::component::{Component, ComponentKind};
+use crate::event::{Event, EventFilter};
+use crate::extra_options;
+use crate::options::TestOptions;
+use crate::outcome::{Outcome, Verdict};
+use anyhow::{self, Context as _};
+use async_broadcast as broadcast;
+use async_trait::async_trait;
+use chrono::Duration;
+use clap::{App, Arg};
+use futures::stream::StreamExt;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Scenarios that got slower by less than this aren't worth mentioning, no matter the ratio.
+const MIN_SLOWDOWN_MS: i64 = 100;
+
+#[extra_options]
+fn baseline_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("baseline")
+            .long("baseline")
+            .value_name("FILE")
+            .takes_value(true)
+            .help("Compare scenarios against a summary saved by --save-baseline"),
+    )
+    .arg(
+        Arg::with_name("save_baseline")
+            .long("save-baseline")
+            .value_name("FILE")
+            .takes_value(true)
+            .help("Save a summary of this run, for use with --baseline"),
+    )
+    .arg(
+        Arg::with_name("baseline_slowdown")
+            .long("baseline-slowdown")
+            .value_name("FACTOR")
+            .takes_value(true)
+            .help(
+                "How many times slower a scenario must be to be reported as slower. Default is 2.",
+            ),
+    )
+}
+
+/// The result of one scenario in a previous test run
+#[derive(Debug, Clone)]
+pub struct BaselineEntry {
+    /// The scenario's verdict
+    pub verdict: Verdict,
+    /// How long the scenario took
+    pub duration: Duration,
+}
+
+/// How a scenario has changed since the baseline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// The scenario failed, but didn't before
+    NewlyFailing,
+    /// The scenario passed, but failed before
+    NewlyPassing,
+    /// The scenario passed both times, but is significantly slower now
+    Slower {
+        /// How long the scenario took in the baseline
+        before: Duration,
+        /// How long the scenario took now
+        after: Duration,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::NewlyFailing => f.write_str("newly failing"),
+            Change::NewlyPassing => f.write_str("newly passing"),
+            Change::Slower { before, after } => write!(
+                f,
+                "slower: {} (was {})",
+                format_elapsed(*after),
+                format_elapsed(*before)
+            ),
+        }
+    }
+}
+
+/// A compact summary of a previous test run: the verdict and duration of each scenario, by
+/// [`Component::key`]. Reporters use it to point out what changed.
+#[derive(Debug, Clone)]
+pub struct Baseline {
+    scenarios: HashMap<String, BaselineEntry>,
+    slowdown: f64,
+}
+
+impl Baseline {
+    /// Summarize a finished test run
+    pub fn from_outcome(outcome: Arc<Outcome>) -> Self {
+        let scenarios = outcome
+            .iter_components(ComponentKind::Scenario)
+            .filter(|o| !o.skipped())
+            .map(|o| {
+                let entry = BaselineEntry {
+                    verdict: o.verdict,
+                    duration: o.ended - o.started,
+                };
+                (o.component().key(), entry)
+            })
+            .collect();
+
+        Self {
+            scenarios,
+            slowdown: 2.0,
+        }
+    }
+
+    /// Load a summary written by [`Self::save`]
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Can't read baseline {}", path.display()))?;
+        let value: Value = serde_json::from_str(&text)
+            .with_context(|| format!("Bad baseline {}", path.display()))?;
+
+        let mut scenarios = HashMap::new();
+        let entries = value
+            .get("scenarios")
+            .and_then(Value::as_object)
+            .ok_or_else(|| anyhow::anyhow!("Bad baseline {}", path.display()))?;
+
+        for (key, entry) in entries {
+            let verdict = entry
+                .get("verdict")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow::anyhow!("Missing verdict for {:?}", key))?
+                .parse()?;
+            let duration = entry
+                .get("duration_ms")
+                .and_then(Value::as_i64)
+                .ok_or_else(|| anyhow::anyhow!("Missing duration for {:?}", key))?;
+            scenarios.insert(
+                key.clone(),
+                BaselineEntry {
+                    verdict,
+                    duration: Duration::milliseconds(duration),
+                },
+            );
+        }
+
+        Ok(Self {
+            scenarios,
+            slowdown: 2.0,
+        })
+    }
+
+    /// Load the baseline given by `--baseline`, if any
+    pub fn from_options(options: &TestOptions) -> anyhow::Result<Option<Arc<Self>>> {
+        let path = match options.opts.value_of_os("baseline") {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+
+        let mut baseline = Self::load(path)?;
+        if let Some(factor) = options.opts.value_of("baseline_slowdown") {
+            let factor = factor
+                .parse()
+                .with_context(|| format!("Bad --baseline-slowdown {:?}", factor))?;
+            baseline = baseline.with_slowdown(factor);
+        }
+        Ok(Some(Arc::new(baseline)))
+    }
+
+    /// Write the summary to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        let scenarios: Map<String, Value> = self
+            .scenarios
+            .iter()
+            .map(|(key, entry)| {
+                let value = json!({
+                    "verdict": entry.verdict.name(),
+                    "duration_ms": entry.duration.num_milliseconds(),
+                });
+                (key.clone(), value)
+            })
+            .collect();
+
+        let text = json!({ "version": 1, "scenarios": scenarios }).to_string();
+        let path = path.as_ref();
+        fs::write(path, text).with_context(|| format!("Can't write baseline {}", path.display()))
+    }
+
+    /// Set how many times slower a scenario must be to count as [`Change::Slower`]
+    pub fn with_slowdown(mut self, factor: f64) -> Self {
+        self.slowdown = factor;
+        self
+    }
+
+    /// Look up a scenario by its [`Component::key`]
+    pub fn get(&self, key: &str) -> Option<&BaselineEntry> {
+        self.scenarios.get(key)
+    }
+
+    /// How a scenario has changed since the baseline, if it changed at all. Scenarios that didn't
+    /// run, this time or last time, haven't changed.
+    pub fn compare(&self, outcome: &Outcome) -> Option<Change> {
+        if outcome.kind() != ComponentKind::Scenario || outcome.skipped() {
+            return None;
+        }
+
+        let before = self.get(&outcome.component().key())?;
+        let after = outcome.ended - outcome.started;
+
+        if outcome.failed() && !before.verdict.failed() {
+            Some(Change::NewlyFailing)
+        } else if outcome.passed() && before.verdict.failed() {
+            Some(Change::NewlyPassing)
+        } else if outcome.passed()
+            && before.verdict.passed()
+            && (after - before.duration).num_milliseconds() >= MIN_SLOWDOWN_MS
+            && after.num_milliseconds() as f64
+                > before.duration.num_milliseconds() as f64 * self.slowdown
+        {
+            Some(Change::Slower {
+                before: before.duration,
+                after,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Reporter that saves a [`Baseline`] once the test run completes. Added automatically by
+/// [`super::CommandLineReporter`] when `--save-baseline` is given.
+pub struct SaveBaseline {
+    path: PathBuf,
+}
+
+impl SaveBaseline {
+    /// Save the baseline to `path`
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl Reporter for SaveBaseline {
+    async fn report(
+        self: Box<Self>,
+        _global: Arc<Component>,
+        mut events: broadcast::Receiver<Event>,
+    ) -> anyhow::Result<()> {
+        let mut final_result = None;
+        while let Some(event) = events.next().await {
+            if let Event::Finished(outcome) = event {
+                if outcome.kind() == ComponentKind::Global {
+                    final_result = Some(outcome);
+                }
+            }
+        }
+
+        match final_result {
+            // Other reporters decide whether the run passed
+            Some(outcome) => Baseline::from_outcome(outcome).save(&self.path),
+            None => anyhow::bail!("Did not receive final test result"),
+        }
+    }
+
+    fn events(&self) -> EventFilter {
+        EventFilter::none().finished(&[ComponentKind::Global])
+    }
+}
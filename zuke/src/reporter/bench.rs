@@ -0,0 +1,229 @@
+//! Benchmark results, for `--bench`
+use super::plain::format_elapsed;
+use super::{reporter_output, Reporter};
+use crate::component::{Component, ComponentKind};
+use crate::event::{Event, EventFilter};
+use crate::options::TestOptions;
+use crate::outcome::Outcome;
+use crate::{extra_options, reporter};
+use anyhow;
+use async_broadcast as broadcast;
+use async_std::io::{stdout, Stdout};
+use async_trait::async_trait;
+use chrono::Duration;
+use clap::{App, Arg};
+use futures::io::{AllowStdIo, AsyncWrite, AsyncWriteExt};
+use futures::stream::StreamExt;
+use serde::Serialize;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Reporter that prints how long each scenario took, once the test run completes. It's added on
+/// its own with `--bench N`, which runs each scenario N times, after some warmup runs that aren't
+/// timed (see [`crate::Bench`]). For each scenario, it shows the mean, median, 95th percentile,
+/// fastest, and slowest of the timed runs that passed.
+///
+/// With `--bench-json`, it writes the same as a JSON array instead, one object per scenario, with
+/// times in milliseconds:
+///
+/// ```json
+/// [{"id": "...", "key": "checkout.feature::Pay by card", "runs": 10, "failed": 0,
+///   "mean_ms": 412.5, "median_ms": 405.0, "p95_ms": 498.1, "min_ms": 390.2, "max_ms": 498.1}]
+/// ```
+pub struct BenchReporter<T: AsyncWrite> {
+    out: T,
+    json: bool,
+}
+
+#[reporter("bench")]
+pub(super) fn make_bench(name: &str, options: &TestOptions) -> anyhow::Result<Box<dyn Reporter>> {
+    let json = options.opts.is_present("bench_json");
+    match reporter_output(name, options)? {
+        Some(file) => Ok(Box::new(BenchReporter::from(file).with_json(json))),
+        None => Ok(Box::new(BenchReporter::default().with_json(json))),
+    }
+}
+
+#[extra_options]
+fn bench_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("bench_json")
+            .long("bench-json")
+            .help("Write benchmark results as JSON"),
+    )
+}
+
+impl<T: AsyncWrite + Send + Sync + 'static> From<T> for BenchReporter<T> {
+    fn from(out: T) -> Self {
+        Self { out, json: false }
+    }
+}
+
+impl<T: Write + Send + Sync + 'static> From<T> for BenchReporter<AllowStdIo<T>> {
+    fn from(out: T) -> Self {
+        Self {
+            out: AllowStdIo::new(out),
+            json: false,
+        }
+    }
+}
+
+impl Default for BenchReporter<Stdout> {
+    fn default() -> Self {
+        Self::from(stdout())
+    }
+}
+
+impl<T: AsyncWrite> BenchReporter<T> {
+    /// Write JSON instead of text
+    pub fn with_json(mut self, json: bool) -> Self {
+        self.json = json;
+        self
+    }
+}
+
+/// Timings for one scenario
+#[derive(Debug, Serialize)]
+struct BenchResult {
+    id: String,
+    key: String,
+    /// Timed runs that passed
+    runs: usize,
+    /// Timed runs that didn't pass, which aren't in the times
+    failed: usize,
+    mean_ms: f64,
+    median_ms: f64,
+    p95_ms: f64,
+    min_ms: f64,
+    max_ms: f64,
+}
+
+impl BenchResult {
+    /// Time the runs of `scenario`, skipping the first `warmup`. Returns `None` if none of them
+    /// passed.
+    fn new(scenario: &Outcome, warmup: usize) -> Option<Self> {
+        let runs: Vec<&Outcome> = match scenario.iterations().next() {
+            Some(_) => scenario
+                .iterations()
+                .skip(warmup)
+                .map(|o| o.as_ref())
+                .collect(),
+            None => vec![scenario],
+        };
+        let mut times: Vec<f64> = runs
+            .iter()
+            .filter(|o| o.passed())
+            .map(|o| millis(o.ended - o.started))
+            .collect();
+        if times.is_empty() {
+            return None;
+        }
+        times.sort_by(|a, b| a.total_cmp(b));
+
+        let n = times.len();
+        let median = if n % 2 == 0 {
+            (times[n / 2 - 1] + times[n / 2]) / 2.0
+        } else {
+            times[n / 2]
+        };
+        // Nearest rank
+        let p95 = times[((n as f64 * 0.95).ceil() as usize).max(1) - 1];
+
+        Some(Self {
+            id: scenario.id().to_string(),
+            key: scenario.component().key(),
+            runs: n,
+            failed: runs.len() - n,
+            mean_ms: times.iter().sum::<f64>() / n as f64,
+            median_ms: median,
+            p95_ms: p95,
+            min_ms: times[0],
+            max_ms: times[n - 1],
+        })
+    }
+}
+
+fn millis(d: Duration) -> f64 {
+    d.to_std().unwrap_or_default().as_secs_f64() * 1000.0
+}
+
+fn format_millis(ms: f64) -> String {
+    format_elapsed(Duration::microseconds((ms * 1000.0) as i64))
+}
+
+#[async_trait]
+impl<T: AsyncWrite + Send + Sync + 'static> Reporter for BenchReporter<T> {
+    async fn report(
+        self: Box<Self>,
+        global: Arc<Component>,
+        mut events: broadcast::Receiver<Event>,
+    ) -> anyhow::Result<()> {
+        let warmup = global.options().bench.map(|b| b.warmup).unwrap_or(0);
+        let mut final_result = None;
+        let mut results = vec![];
+
+        while let Some(event) = events.next().await {
+            if let Event::Finished(outcome) = event {
+                match outcome.kind() {
+                    ComponentKind::Global => final_result = Some(outcome),
+                    ComponentKind::Scenario if !outcome.skipped() => {
+                        results.extend(BenchResult::new(&outcome, warmup))
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        let outcome = match final_result {
+            Some(o) => o,
+            None => anyhow::bail!("Did not receive final test result"),
+        };
+
+        let text = if self.json {
+            let mut text = serde_json::to_string(&results)?;
+            text.push('\n');
+            text
+        } else {
+            bench_table(&results)
+        };
+
+        let out = self.out;
+        futures::pin_mut!(out);
+        out.write_all(text.as_bytes()).await?;
+        out.flush().await?;
+
+        if outcome.failed() {
+            anyhow::bail!("Test run failed");
+        }
+        Ok(())
+    }
+
+    fn events(&self) -> EventFilter {
+        EventFilter::none().finished(&[ComponentKind::Global, ComponentKind::Scenario])
+    }
+}
+
+fn bench_table(results: &[BenchResult]) -> String {
+    let mut text = String::from("Benchmarks:\n");
+    if results.is_empty() {
+        text.push_str("  (no scenarios passed)\n");
+    }
+    for r in results {
+        text.push_str(&format!("  {}\n", r.key));
+        text.push_str(&format!(
+            "    {} runs: mean {}, median {}, p95 {}, min {}, max {}",
+            r.runs,
+            format_millis(r.mean_ms),
+            format_millis(r.median_ms),
+            format_millis(r.p95_ms),
+            format_millis(r.min_ms),
+            format_millis(r.max_ms),
+        ));
+        if r.failed > 0 {
+            text.push_str(&format!(" ({} failed)", r.failed));
+        }
+        text.push('\n');
+    }
+    text.push('\n');
+    text
+}
@@ -1,7 +1,7 @@
 //! A trivial reporter that grabs the top-level result
 use super::Reporter;
 use crate::component::{Component, ComponentKind};
-use crate::event::Event;
+use crate::event::{Event, EventFilter};
 use crate::outcome::Outcome;
 use anyhow;
 use async_broadcast as broadcast;
@@ -46,4 +46,8 @@ impl Reporter for Collect {
         let _ = self.dest.send(outcome);
         Ok(())
     }
+
+    fn events(&self) -> EventFilter {
+        EventFilter::none().finished(&[ComponentKind::Global])
+    }
 }
@@ -1,15 +1,23 @@
 //! A reporter that creates other reporters based on the command line. Reporters that wish to
 //! participate need to register via `inventory::submit!`
+//!
+//! Every registered reporter gets its own `--<name>-output FILE` option, so that several reporters
+//! can write to different files in the same run. Use [`reporter_output`] to open it.
 
-use super::{DefaultReporter, Reporter};
+use super::bench::make_bench;
+use super::plain::{make_plain, PlainReporter, Verbosity};
+use super::{Reporter, SaveBaseline, Supervised};
 use crate::component::Component;
 use crate::event::Event;
 use crate::extra_options;
 use crate::options::TestOptions;
+use crate::replay::RecordReporter;
+use crate::top::ConfigError;
 use async_broadcast as broadcast;
 use async_trait::async_trait;
 use clap::{App, Arg};
 use futures::future::join_all;
+use std::fs;
 use std::sync::Arc;
 
 /// A reporter that creates other reporters based on the command line. Reporters that wish to
@@ -19,6 +27,16 @@ pub struct CommandLineReporter;
 
 #[extra_options]
 fn choose_reporter<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    let app = inventory::iter::<ReporterEntry>().fold(app, |app, entry| {
+        app.arg(
+            Arg::with_name(entry.output)
+                .long(entry.output)
+                .value_name("FILE")
+                .takes_value(true)
+                .help(entry.output_help),
+        )
+    });
+
     app.arg(
         Arg::with_name("reporters")
             .multiple(true)
@@ -29,15 +47,35 @@ fn choose_reporter<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
             .value_name("NAME")
             .help("Add a reporter. If no reporter is given, a default reporter will be used"),
     )
+    .arg(
+        Arg::with_name("reporter_fallback")
+            .long("reporter-fallback")
+            .help(
+                "If a reporter can't write its --<name>-output file, print the plain report to \
+                stdout instead",
+            ),
+    )
 }
 
 #[doc(hidden)]
 /// A reporter entry. You may prefer using the `#[reporter]` macro.
 pub struct ReporterEntry {
-    pub name: String,
+    pub name: &'static str,
+    /// Name of the `--<name>-output` option
+    pub output: &'static str,
+    pub output_help: &'static str,
     pub func: fn(name: &str, options: &TestOptions) -> anyhow::Result<Box<dyn Reporter>>,
 }
 
+/// Open the file given by the reporter's `--<name>-output` option, if any. `name` is the name the
+/// reporter was registered with, as passed to its `#[reporter]` function.
+pub fn reporter_output(name: &str, options: &TestOptions) -> anyhow::Result<Option<fs::File>> {
+    match options.opts.value_of_os(format!("{}-output", name)) {
+        Some(path) => Ok(Some(fs::File::create(path)?)),
+        None => Ok(None),
+    }
+}
+
 #[async_trait]
 impl Reporter for CommandLineReporter {
     async fn report(
@@ -70,22 +108,99 @@ impl Reporter for CommandLineReporter {
 }
 
 fn make_reporters(global: &Component) -> anyhow::Result<Vec<Box<dyn Reporter>>> {
-    let requested = match global.options().opts.values_of("reporters") {
-        Some(r) => r,
-        None => return Ok(vec![Box::new(DefaultReporter::default())]),
-    };
-
-    let entries: Vec<_> = inventory::iter::<ReporterEntry>().collect();
-    let mut reporters = vec![];
-    for req in requested {
-        let reporter = match entries.iter().find(|e| e.name == req) {
-            Some(e) => (e.func)(req, global.options())?,
-            None => anyhow::bail!("No such reporter {}", req),
-        };
-        reporters.push(reporter);
+    let opts = &global.options().opts;
+    let mut reporters: Vec<Box<dyn Reporter>> = vec![];
+
+    // Workers leave reporting to the coordinator
+    if opts.is_present("worker") {
+        return Ok(reporters);
+    }
+
+    match opts.values_of("reporters") {
+        Some(requested) => {
+            let entries: Vec<_> = inventory::iter::<ReporterEntry>().collect();
+            let plain_on_stdout =
+                requested.clone().any(|r| r == "plain") && !opts.is_present("plain-output");
+            for req in requested {
+                let entry = match entries.iter().find(|e| e.name == req) {
+                    Some(e) => e,
+                    None => return Err(unknown_reporter(req).into()),
+                };
+                let reporter = (entry.func)(req, global.options())?;
+                // Falling back to stdout only makes sense for a file, and only if the plain
+                // report isn't there already
+                let fallback = if opts.is_present("reporter_fallback")
+                    && opts.is_present(entry.output)
+                    && !plain_on_stdout
+                {
+                    Some(plain_fallback(global))
+                } else {
+                    None
+                };
+                reporters.push(Box::new(
+                    Supervised::new(reporter)
+                        .with_name(req)
+                        .with_fallback(fallback),
+                ));
+            }
+        }
+        None => reporters.push(Box::new(
+            Supervised::new(make_plain("plain", global.options())?).with_name("plain"),
+        )),
+    }
+
+    // --bench always shows its results, alongside whatever else was asked for
+    let bench_requested = opts
+        .values_of("reporters")
+        .into_iter()
+        .flatten()
+        .any(|r| r == "bench");
+    if global.options().bench.is_some() && !bench_requested {
+        reporters.push(Box::new(
+            Supervised::new(make_bench("bench", global.options())?).with_name("bench"),
+        ));
+    }
+
+    if let Some(path) = opts.value_of_os("save_baseline") {
+        reporters.push(Box::new(
+            Supervised::new(Box::new(SaveBaseline::new(path))).with_name("baseline"),
+        ));
+    }
+
+    if let Some(path) = opts.value_of_os("record") {
+        reporters.push(Box::new(
+            Supervised::new(Box::new(RecordReporter::new(path))).with_name("record"),
+        ));
     }
 
     Ok(reporters)
 }
 
+/// The plain reporter, on stdout, for `--reporter-fallback`
+fn plain_fallback(global: &Component) -> Box<dyn Reporter> {
+    let verbosity = Verbosity::from_options(global.options());
+    Box::new(PlainReporter::default().with_verbosity(verbosity))
+}
+
+fn unknown_reporter(name: &str) -> ConfigError {
+    let mut available: Vec<String> = inventory::iter::<ReporterEntry>()
+        .map(|e| e.name.to_string())
+        .collect();
+    available.sort();
+    ConfigError::UnknownReporter {
+        name: name.to_string(),
+        available,
+    }
+}
+
+/// Check that every `--reporter` is registered, before the test run starts
+pub(crate) fn check_reporters(options: &TestOptions) -> Result<(), ConfigError> {
+    for name in options.opts.values_of("reporters").into_iter().flatten() {
+        if !inventory::iter::<ReporterEntry>().any(|e| e.name == name) {
+            return Err(unknown_reporter(name));
+        }
+    }
+    Ok(())
+}
+
 inventory::collect!(ReporterEntry);
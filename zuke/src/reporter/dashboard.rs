@@ -0,0 +1,374 @@
+//! A live view of the test run in a web browser
+use super::{ReportModel, Reporter};
+use crate::component::{Component, ComponentKind};
+use crate::event::Event;
+use crate::options::TestOptions;
+use crate::outcome::{Outcome, Stat};
+use crate::{extra_options, reporter};
+use anyhow;
+use async_broadcast as broadcast;
+use async_std::task;
+use async_trait::async_trait;
+use clap::{App, Arg};
+use futures::future::{self, Either};
+use futures::stream::StreamExt;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tide::listener::Listener;
+use tide::{Body, Request, Response};
+use tide_websockets::{WebSocket, WebSocketConnection};
+
+/// Where the dashboard listens if `--dashboard-address` isn't given
+pub const DEFAULT_DASHBOARD_ADDRESS: &str = "127.0.0.1:8000";
+
+/// How many of the slowest steps to show
+const SLOWEST_COUNT: usize = 10;
+
+/// How often the latest state is built, and sent to each browser, if it changed
+const UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Reporter that serves a web page showing the test run as it happens: what's running, the
+/// counts so far, the slowest steps, and what failed. The page is fed over a websocket, so it
+/// updates live. Useful for long test runs, where the terminal output scrolls by too quickly.
+///
+/// The page keeps showing the final state once the test run is over, but the server stops when
+/// the program exits. The state is also served as JSON at `/state`.
+///
+/// The state is rebuilt at most every 250 ms, however quickly events arrive, and once more when the
+/// run is over.
+pub struct DashboardReporter {
+    address: String,
+}
+
+#[reporter("dashboard")]
+fn make_dashboard(_name: &str, options: &TestOptions) -> anyhow::Result<Box<dyn Reporter>> {
+    let address = options
+        .opts
+        .value_of("dashboard_address")
+        .unwrap_or(DEFAULT_DASHBOARD_ADDRESS);
+    Ok(Box::new(DashboardReporter::new(address)))
+}
+
+#[extra_options]
+fn dashboard_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("dashboard_address")
+            .long("dashboard-address")
+            .value_name("HOST:PORT")
+            .takes_value(true)
+            .help("Serve the dashboard reporter at HOST:PORT [default: 127.0.0.1:8000]"),
+    )
+}
+
+impl DashboardReporter {
+    /// Serve the dashboard at `address`, such as `127.0.0.1:8000`
+    pub fn new<S: Into<String>>(address: S) -> Self {
+        Self {
+            address: address.into(),
+        }
+    }
+}
+
+/// The latest state of the run, as sent to browsers
+#[derive(Default)]
+struct Snapshot {
+    version: u64,
+    json: String,
+    finished: bool,
+}
+
+type Shared = Arc<Mutex<Snapshot>>;
+
+#[async_trait]
+impl Reporter for DashboardReporter {
+    async fn report(
+        self: Box<Self>,
+        global: Arc<Component>,
+        mut events: broadcast::Receiver<Event>,
+    ) -> anyhow::Result<()> {
+        let shared = Shared::default();
+
+        let mut app = tide::with_state(shared.clone());
+        app.at("/").get(|_request: Request<Shared>| async move {
+            let mut response = Response::new(200);
+            response.set_body(Body::from_string(PAGE.to_string()));
+            response.set_content_type(tide::http::mime::HTML);
+            Ok(response)
+        });
+        app.at("/state").get(|request: Request<Shared>| async move {
+            let mut response = Response::new(200);
+            response.set_body(Body::from_string(request.state().lock().json.clone()));
+            response.set_content_type(tide::http::mime::JSON);
+            Ok(response)
+        });
+        app.at("/ws").get(WebSocket::new(
+            |request: Request<Shared>, connection: WebSocketConnection| async move {
+                send_updates(request.state().clone(), connection).await
+            },
+        ));
+
+        // Bind now, so that a bad address fails the reporter rather than a background task
+        let mut listener = app.bind(self.address.clone()).await?;
+        eprintln!("Dashboard at http://{}", self.address);
+        task::spawn(async move {
+            if let Err(e) = listener.accept().await {
+                eprintln!("Dashboard stopped: {}", e);
+            }
+        });
+
+        let mut model = ReportModel::new();
+        let mut slowest: Vec<Arc<Outcome>> = vec![];
+        let mut failures: Vec<Arc<Outcome>> = vec![];
+        let mut published = Instant::now();
+        let mut dirty = true;
+
+        loop {
+            // Publish changes once the interval is up, even if no more events come
+            let next = if dirty {
+                let wait = (published + UPDATE_INTERVAL).saturating_duration_since(Instant::now());
+                match future::select(events.next(), Box::pin(task::sleep(wait))).await {
+                    Either::Left((event, _)) => event,
+                    Either::Right(_) => {
+                        publish(&shared, &global, &model, &slowest, &failures);
+                        published = Instant::now();
+                        dirty = false;
+                        continue;
+                    }
+                }
+            } else {
+                events.next().await
+            };
+            let event = match next {
+                Some(event) => event,
+                None => break,
+            };
+
+            model.update(&event);
+            if let Event::Finished(outcome) = &event {
+                match outcome.kind() {
+                    ComponentKind::Step => {
+                        slowest.push(outcome.clone());
+                        slowest.sort_by_key(|o| std::cmp::Reverse(o.ended - o.started));
+                        slowest.truncate(SLOWEST_COUNT);
+                    }
+                    ComponentKind::Scenario if outcome.failed() => failures.push(outcome.clone()),
+                    _ => (),
+                }
+            }
+
+            dirty = true;
+            if model.is_finished() || published.elapsed() >= UPDATE_INTERVAL {
+                publish(&shared, &global, &model, &slowest, &failures);
+                published = Instant::now();
+                dirty = false;
+            }
+        }
+        if dirty {
+            publish(&shared, &global, &model, &slowest, &failures);
+        }
+
+        match model.outcome() {
+            None => anyhow::bail!("Did not receive final test result"),
+            Some(o) if o.failed() => anyhow::bail!("Test run failed"),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+/// Make the latest state available to browsers
+fn publish(
+    shared: &Shared,
+    global: &Component,
+    model: &ReportModel,
+    slowest: &[Arc<Outcome>],
+    failures: &[Arc<Outcome>],
+) {
+    let json = snapshot_json(global, model, slowest, failures).to_string();
+    let mut snapshot = shared.lock();
+    snapshot.version += 1;
+    snapshot.json = json;
+    snapshot.finished = model.is_finished();
+}
+
+/// Send the latest state to one browser whenever it changes, until the run is over
+async fn send_updates(shared: Shared, connection: WebSocketConnection) -> tide::Result<()> {
+    let mut sent = 0;
+    loop {
+        let (version, json, finished) = {
+            let snapshot = shared.lock();
+            (snapshot.version, snapshot.json.clone(), snapshot.finished)
+        };
+        if version != sent {
+            connection.send_string(json).await?;
+            sent = version;
+        }
+        if finished {
+            return Ok(());
+        }
+        task::sleep(UPDATE_INTERVAL).await;
+    }
+}
+
+fn seconds(outcome: &Outcome) -> f64 {
+    (outcome.ended - outcome.started)
+        .to_std()
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+fn stat_json(stat: Stat) -> Value {
+    json!({
+        "passed": stat.passed,
+        "failed": stat.failed,
+        "skipped": stat.skipped,
+        "pending": stat.pending,
+        "total": stat.total,
+    })
+}
+
+fn feature_name(component: &Component) -> Option<&str> {
+    component.feature().map(|f| f.name.as_str())
+}
+
+/// Why a scenario failed: its own reason, or else that of its first failed step
+fn failure_reason(outcome: &Outcome) -> Option<String> {
+    let reason = outcome.reason.as_ref().or_else(|| {
+        outcome
+            .children
+            .iter()
+            .find(|c| c.failed())
+            .and_then(|c| c.reason.as_ref())
+    })?;
+    Some(format!("{:#}", reason))
+}
+
+fn snapshot_json(
+    global: &Component,
+    model: &ReportModel,
+    slowest: &[Arc<Outcome>],
+    failures: &[Arc<Outcome>],
+) -> Value {
+    let running: Vec<_> = model
+        .running_kind(ComponentKind::Scenario)
+        .map(|c| {
+            json!({
+                "name": c.name(),
+                "feature": feature_name(c),
+                "seconds": model.elapsed(c).and_then(|d| d.to_std().ok()).unwrap_or_default().as_secs_f64(),
+            })
+        })
+        .collect();
+    let slowest: Vec<_> = slowest
+        .iter()
+        .map(|o| {
+            json!({
+                "name": o.component().name(),
+                "scenario": o.component().scenario().map(|s| s.name.as_str()),
+                "seconds": seconds(o),
+            })
+        })
+        .collect();
+    let failures: Vec<_> = failures
+        .iter()
+        .map(|o| {
+            json!({
+                "name": o.component().name(),
+                "feature": feature_name(o.component()),
+                "reason": failure_reason(o),
+            })
+        })
+        .collect();
+
+    json!({
+        "title": global.options().title,
+        "finished": model.is_finished(),
+        "verdict": model.outcome().map(|o| o.verdict.name()),
+        "seconds": model.run_time().and_then(|d| d.to_std().ok()).unwrap_or_default().as_secs_f64(),
+        "scenarios": stat_json(model.count(ComponentKind::Scenario)),
+        "steps": stat_json(model.count(ComponentKind::Step)),
+        "running": running,
+        "slowest": slowest,
+        "failures": failures,
+    })
+}
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Zuke</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 1.5em; }
+td, th { padding: 0.2em 0.8em; text-align: left; }
+.passed { color: #080; }
+.failed { color: #c00; }
+pre { background: #f4f4f4; padding: 0.5em; white-space: pre-wrap; }
+</style>
+</head>
+<body>
+<h1 id="title">Zuke</h1>
+<p id="status">Connecting...</p>
+<table>
+<tr><th></th><th class="passed">Passed</th><th class="failed">Failed</th><th>Skipped</th><th>Pending</th><th>Total</th></tr>
+<tr id="scenarios"></tr>
+<tr id="steps"></tr>
+</table>
+<h2>Running</h2>
+<table id="running"></table>
+<h2>Slowest steps</h2>
+<table id="slowest"></table>
+<h2>Failures</h2>
+<div id="failures"></div>
+<script>
+function cell(text) {
+    const td = document.createElement("td");
+    td.textContent = text;
+    return td;
+}
+function row(cells) {
+    const tr = document.createElement("tr");
+    cells.forEach(c => tr.appendChild(cell(c)));
+    return tr;
+}
+function counts(id, label, stat) {
+    const tr = document.getElementById(id);
+    tr.replaceChildren(...row([label, stat.passed, stat.failed, stat.skipped, stat.pending, stat.total]).children);
+}
+function rows(id, items) {
+    document.getElementById(id).replaceChildren(...items.map(row));
+}
+const socket = new WebSocket(`ws://${location.host}/ws`);
+socket.onmessage = message => {
+    const run = JSON.parse(message.data);
+    document.getElementById("title").textContent = run.title;
+    document.getElementById("status").textContent = run.finished
+        ? `Finished (${run.verdict}) in ${run.seconds.toFixed(1)} s`
+        : `Running for ${run.seconds.toFixed(1)} s`;
+    counts("scenarios", "Scenarios", run.scenarios);
+    counts("steps", "Steps", run.steps);
+    rows("running", run.running.map(s => [s.feature, s.name, `${s.seconds.toFixed(1)} s`]));
+    rows("slowest", run.slowest.map(s => [s.scenario, s.name, `${s.seconds.toFixed(3)} s`]));
+    document.getElementById("failures").replaceChildren(...run.failures.map(f => {
+        const div = document.createElement("div");
+        const h = document.createElement("h3");
+        h.className = "failed";
+        h.textContent = `${f.feature}: ${f.name}`;
+        const pre = document.createElement("pre");
+        pre.textContent = f.reason || "";
+        div.append(h, pre);
+        return div;
+    }));
+};
+socket.onclose = () => {
+    const status = document.getElementById("status");
+    if (!status.textContent.startsWith("Finished")) {
+        status.textContent += " (disconnected)";
+    }
+};
+</script>
+</body>
+</html>
+"#;
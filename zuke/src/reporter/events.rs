@@ -0,0 +1,165 @@
+//! A machine readable stream of events, one JSON object per line
+use super::{reporter_output, Reporter};
+use crate::component::{Component, ComponentKind};
+use crate::event::Event;
+use crate::hooks::BeforeAfter;
+use crate::options::TestOptions;
+use crate::run_info::RunInfo;
+use crate::{extra_options, reporter};
+use anyhow;
+use async_broadcast as broadcast;
+use async_std::io::{stdout, Stdout};
+use async_trait::async_trait;
+use chrono::Utc;
+use clap::{App, Arg};
+use futures::io::{AllowStdIo, AsyncWrite, AsyncWriteExt};
+use futures::stream::StreamExt;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::net::TcpStream;
+use std::sync::Arc;
+
+/// Reporter that writes each event as a line of JSON, as soon as it happens. Useful for driving
+/// external tools, such as dashboards, without embedding Zuke.
+pub struct EventsReporter<T: AsyncWrite> {
+    out: T,
+}
+
+#[reporter("events")]
+fn make_events(name: &str, options: &TestOptions) -> anyhow::Result<Box<dyn Reporter>> {
+    if let Some(addr) = options.opts.value_of("events_address") {
+        Ok(Box::new(EventsReporter::from(TcpStream::connect(addr)?)))
+    } else if let Some(file) = reporter_output(name, options)? {
+        Ok(Box::new(EventsReporter::from(file)))
+    } else {
+        Ok(Box::new(EventsReporter::default()))
+    }
+}
+
+#[extra_options]
+fn events_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("events_address")
+            .long("events-address")
+            .value_name("HOST:PORT")
+            .takes_value(true)
+            .conflicts_with("events-output")
+            .help("Send output from the events reporter to a TCP socket."),
+    )
+}
+
+impl<T: AsyncWrite + Send + Sync + 'static> From<T> for EventsReporter<T> {
+    fn from(out: T) -> Self {
+        Self { out }
+    }
+}
+
+impl<T: Write + Send + Sync + 'static> From<T> for EventsReporter<AllowStdIo<T>> {
+    fn from(out: T) -> Self {
+        Self {
+            out: AllowStdIo::new(out),
+        }
+    }
+}
+
+impl Default for EventsReporter<Stdout> {
+    fn default() -> Self {
+        Self::from(stdout())
+    }
+}
+
+#[async_trait]
+impl<T: AsyncWrite + Send + Sync + 'static> Reporter for EventsReporter<T> {
+    async fn report(
+        self: Box<Self>,
+        _global: Arc<Component>,
+        mut events: broadcast::Receiver<Event>,
+    ) -> anyhow::Result<()> {
+        let mut failed = None;
+
+        let out = self.out;
+        futures::pin_mut!(out);
+
+        while let Some(event) = events.next().await {
+            if let Event::Finished(outcome) = &event {
+                if outcome.kind() == ComponentKind::Global {
+                    failed = Some(outcome.failed());
+                }
+            }
+
+            let mut line = event_json(&event).to_string();
+            line.push('\n');
+            out.write_all(line.as_bytes()).await?;
+            out.flush().await?;
+        }
+
+        match failed {
+            None => anyhow::bail!("Did not receive final test result"),
+            Some(true) => anyhow::bail!("Test run failed"),
+            Some(false) => Ok(()),
+        }
+    }
+}
+
+fn event_json(event: &Event) -> Value {
+    match event {
+        Event::Started(component) => {
+            let mut started = json!({
+                "event": "started",
+                "component": component_json(component),
+                "time": Utc::now().to_rfc3339(),
+            });
+            // The test run starts with how to reproduce it
+            if component.kind() == ComponentKind::Global {
+                started["run_info"] = run_info_json(&component.options().run_info);
+            }
+            started
+        }
+        Event::Finished(outcome) | Event::IterationFinished(outcome) => {
+            let name = match event {
+                Event::IterationFinished(_) => "iteration_finished",
+                _ => "finished",
+            };
+            let mut finished = json!({
+                "event": name,
+                "component": component_json(outcome.component()),
+                "verdict": outcome.verdict.name(),
+                "reason": outcome.reason.as_ref().map(|e| format!("{:#}", e)),
+                "warnings": outcome.warnings.iter().map(|w| format!("{:#}", w)).collect::<Vec<_>>(),
+                "started": outcome.started.to_rfc3339(),
+                "ended": outcome.ended.to_rfc3339(),
+            });
+            if let Some(step_match) = &outcome.step_match {
+                finished["step_match"] = json!(step_match);
+            }
+            finished
+        }
+        Event::HookFinished(hook) => json!({
+            "event": "hook_finished",
+            "component": component_json(&hook.component),
+            "hook": hook.name,
+            "when": match hook.when {
+                BeforeAfter::Before => "before",
+                BeforeAfter::After => "after",
+            },
+            "error": hook.error,
+            "started": hook.started.to_rfc3339(),
+            "ended": hook.ended.to_rfc3339(),
+        }),
+    }
+}
+
+fn run_info_json(info: &RunInfo) -> Value {
+    json!({
+        "started": info.started.to_rfc3339(),
+        "host": info.host(),
+        "git_sha": info.git_sha(),
+        "profile": info.profile,
+        "seed": info.seed,
+        "args": info.args,
+    })
+}
+
+fn component_json(component: &Component) -> Value {
+    json!(component.info())
+}
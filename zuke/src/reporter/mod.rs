@@ -1,19 +1,43 @@
 //! Writes output given test outcomes
 
 use crate::component::Component;
-use crate::event::Event;
+use crate::event::{Event, EventFilter};
 use anyhow;
 use async_broadcast as broadcast;
 use async_std::io::Stdout;
 use async_trait::async_trait;
 use std::sync::Arc;
 
+pub mod allure;
+pub mod badge;
+pub mod baseline;
+pub mod bench;
 pub mod collect;
 pub mod command_line;
+#[cfg(feature = "dashboard")]
+pub mod dashboard;
+pub mod events;
+pub mod model;
+pub mod openmetrics;
 pub mod plain;
+pub mod supervisor;
+pub mod teamcity;
+pub mod timing;
+pub use allure::*;
+pub use badge::*;
+pub use baseline::*;
+pub use bench::*;
 pub use collect::*;
 pub use command_line::*;
+#[cfg(feature = "dashboard")]
+pub use dashboard::*;
+pub use events::*;
+pub use model::*;
+pub use openmetrics::*;
 pub use plain::*;
+pub use supervisor::*;
+pub use teamcity::*;
+pub use timing::*;
 
 /// A Reporter takes [`crate::Event`]s from a [`crate::runner::Runner`] and creates an output
 /// report from them.
@@ -26,6 +50,12 @@ pub trait Reporter: Send + Sync {
         global: Arc<Component>,
         events: broadcast::Receiver<Event>,
     ) -> anyhow::Result<()>;
+
+    /// The events this reporter needs. Others are left out of the `events` it's given. By
+    /// default, it gets every event.
+    fn events(&self) -> EventFilter {
+        EventFilter::all()
+    }
 }
 
 /// The default type of reporter to create if none are specified
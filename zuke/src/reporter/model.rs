@@ -0,0 +1,150 @@
+//! A live model of a test run, built from events
+use crate::component::{Component, ComponentKind};
+use crate::event::Event;
+use crate::outcome::{Outcome, Stat};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A component that has started, but not finished
+#[derive(Debug)]
+struct Running {
+    component: Arc<Component>,
+    started: DateTime<Utc>,
+    children: Vec<Arc<Outcome>>,
+}
+
+/// Tracks the state of a test run as events arrive. Pairs up [`Event::Started`] and
+/// [`Event::Finished`] so that reporters don't have to.
+///
+/// Feed it every event with [`Self::update`], and query it at any time. Finished components are
+/// attached to the innermost running component that contains them, so the tree can be
+/// inspected while the test is still in progress.
+///
+/// ```ignore
+/// let mut model = ReportModel::new();
+/// while let Some(event) = events.next().await {
+///     model.update(&event);
+///     for component in model.running_kind(ComponentKind::Scenario) {
+///         println!("{} ({})", component.name(), model.elapsed(component).unwrap());
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct ReportModel {
+    running: Vec<Running>,
+    counts: HashMap<ComponentKind, Stat>,
+    started: Option<DateTime<Utc>>,
+    outcome: Option<Arc<Outcome>>,
+}
+
+impl ReportModel {
+    /// Create a new, empty model
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the model with the next event
+    pub fn update(&mut self, event: &Event) {
+        match event {
+            Event::Started(component) => {
+                let now = Utc::now();
+                if component.kind() == ComponentKind::Global {
+                    self.started = Some(now);
+                }
+
+                self.running.push(Running {
+                    component: component.clone(),
+                    started: now,
+                    children: vec![],
+                });
+            }
+            Event::Finished(outcome) => {
+                if let Some(i) = self.find(outcome.component()) {
+                    self.running.remove(i);
+                }
+
+                self.counts
+                    .entry(outcome.kind())
+                    .or_insert_with(Stat::default)
+                    .count(outcome.verdict);
+
+                if outcome.kind() == ComponentKind::Global {
+                    self.outcome = Some(outcome.clone());
+                } else if let Some(parent) = self
+                    .running
+                    .iter_mut()
+                    .filter(|r| r.component.contains(outcome.component()))
+                    .max_by_key(|r| r.component.kind())
+                {
+                    parent.children.push(outcome.clone());
+                }
+            }
+            Event::HookFinished(_) | Event::IterationFinished(_) => (),
+        }
+    }
+
+    fn find(&self, component: &Arc<Component>) -> Option<usize> {
+        self.running
+            .iter()
+            .position(|r| Arc::ptr_eq(&r.component, component))
+    }
+
+    /// Components that have started but not finished, in the order they were started.
+    pub fn running(&self) -> impl Iterator<Item = &Arc<Component>> {
+        self.running.iter().map(|r| &r.component)
+    }
+
+    /// As [`Self::running`], but only components of one kind
+    pub fn running_kind(&self, kind: ComponentKind) -> impl Iterator<Item = &Arc<Component>> {
+        self.running().filter(move |c| c.kind() == kind)
+    }
+
+    /// Is the component currently running?
+    pub fn is_running(&self, component: &Arc<Component>) -> bool {
+        self.find(component).is_some()
+    }
+
+    /// How long a running component has been running. `None` if it isn't running.
+    pub fn elapsed(&self, component: &Arc<Component>) -> Option<Duration> {
+        let i = self.find(component)?;
+        Some(Utc::now() - self.running[i].started)
+    }
+
+    /// Outcomes of components that have finished inside of a running component. `None` if the
+    /// component isn't running. (Once it has finished, use [`Outcome::children`] instead.)
+    pub fn finished_children(&self, component: &Arc<Component>) -> Option<&[Arc<Outcome>]> {
+        let i = self.find(component)?;
+        Some(&self.running[i].children)
+    }
+
+    /// Counts of components that have finished so far, by kind
+    pub fn counts(&self) -> &HashMap<ComponentKind, Stat> {
+        &self.counts
+    }
+
+    /// Count of components of one kind that have finished so far
+    pub fn count(&self, kind: ComponentKind) -> Stat {
+        self.counts.get(&kind).cloned().unwrap_or_default()
+    }
+
+    /// How long the test run has been going. `None` if it hasn't started.
+    pub fn run_time(&self) -> Option<Duration> {
+        let started = self.started?;
+        let ended = match &self.outcome {
+            Some(o) => o.ended,
+            None => Utc::now(),
+        };
+        Some(ended - started)
+    }
+
+    /// The final outcome of the test run, once it has finished.
+    pub fn outcome(&self) -> Option<&Arc<Outcome>> {
+        self.outcome.as_ref()
+    }
+
+    /// Has the test run finished?
+    pub fn is_finished(&self) -> bool {
+        self.outcome.is_some()
+    }
+}
@@ -0,0 +1,290 @@
+//! Metrics about the test run, in OpenMetrics (Prometheus) text format
+use super::{reporter_output, Reporter};
+use crate::component::{Component, ComponentKind};
+use crate::event::{Event, EventFilter};
+use crate::options::TestOptions;
+use crate::outcome::{Outcome, Stat};
+use crate::{extra_options, reporter};
+use anyhow::{self, Context as _};
+use async_broadcast as broadcast;
+use async_std::io::{stdout, Stdout};
+use async_std::task;
+use async_trait::async_trait;
+use clap::{App, Arg};
+use futures::io::{AllowStdIo, AsyncWrite, AsyncWriteExt};
+use futures::stream::StreamExt;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+
+/// Upper bounds of the scenario duration histogram, in seconds
+pub const DURATION_BUCKETS: &[f64] = &[0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 300.0];
+
+const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// Reporter that writes metrics once the test run completes, so that test runs can be tracked
+/// over time with Prometheus and Grafana. The metrics are:
+///
+/// * `zuke_scenarios`: scenarios by verdict (`passed`, `failed`, `skipped`, or `pending`)
+/// * `zuke_feature_scenarios`: the same, for each feature
+/// * `zuke_scenario_duration_seconds`: a histogram of how long scenarios took
+/// * `zuke_run_duration_seconds`: how long the whole test run took
+/// * `zuke_run_passed`: 1 if the test run passed, or else 0
+///
+/// With `--openmetrics-push URL`, the metrics are sent to a Prometheus Pushgateway with an HTTP
+/// `PUT`, such as to `http://pushgateway:9091/metrics/job/nightly`.
+pub struct OpenMetricsReporter<T: AsyncWrite> {
+    out: T,
+    push: Option<String>,
+}
+
+#[reporter("openmetrics")]
+fn make_openmetrics(name: &str, options: &TestOptions) -> anyhow::Result<Box<dyn Reporter>> {
+    let push = options.opts.value_of("openmetrics_push");
+    if let Some(url) = push {
+        parse_url(url)?;
+    }
+
+    match (reporter_output(name, options)?, push) {
+        (Some(file), push) => Ok(Box::new(OpenMetricsReporter::from(file).with_push(push))),
+        // Pushing is enough; don't clutter stdout
+        (None, Some(url)) => Ok(Box::new(
+            OpenMetricsReporter::from(std::io::sink()).with_push(Some(url)),
+        )),
+        (None, None) => Ok(Box::new(OpenMetricsReporter::default())),
+    }
+}
+
+#[extra_options]
+fn openmetrics_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("openmetrics_push")
+            .long("openmetrics-push")
+            .value_name("URL")
+            .takes_value(true)
+            .help("Push metrics from the openmetrics reporter to a Pushgateway at URL (http only)"),
+    )
+}
+
+impl<T: AsyncWrite + Send + Sync + 'static> From<T> for OpenMetricsReporter<T> {
+    fn from(out: T) -> Self {
+        Self { out, push: None }
+    }
+}
+
+impl<T: Write + Send + Sync + 'static> From<T> for OpenMetricsReporter<AllowStdIo<T>> {
+    fn from(out: T) -> Self {
+        Self {
+            out: AllowStdIo::new(out),
+            push: None,
+        }
+    }
+}
+
+impl Default for OpenMetricsReporter<Stdout> {
+    fn default() -> Self {
+        Self::from(stdout())
+    }
+}
+
+impl<T: AsyncWrite> OpenMetricsReporter<T> {
+    /// Also push the metrics to a Pushgateway at `url`
+    pub fn with_push<S: Into<String>>(mut self, url: Option<S>) -> Self {
+        self.push = url.map(Into::into);
+        self
+    }
+}
+
+#[async_trait]
+impl<T: AsyncWrite + Send + Sync + 'static> Reporter for OpenMetricsReporter<T> {
+    async fn report(
+        self: Box<Self>,
+        _global: Arc<Component>,
+        mut events: broadcast::Receiver<Event>,
+    ) -> anyhow::Result<()> {
+        let mut scenarios = vec![];
+        let mut outcome = None;
+
+        while let Some(event) = events.next().await {
+            if let Event::Finished(o) = event {
+                match o.kind() {
+                    ComponentKind::Scenario => scenarios.push(o),
+                    ComponentKind::Global => outcome = Some(o),
+                    _ => (),
+                }
+            }
+        }
+
+        let outcome = match outcome {
+            Some(o) => o,
+            None => anyhow::bail!("Did not receive final test result"),
+        };
+
+        let text = metrics(&outcome, &scenarios);
+        let out = self.out;
+        futures::pin_mut!(out);
+        out.write_all(text.as_bytes()).await?;
+        out.flush().await?;
+
+        if let Some(url) = self.push {
+            task::spawn_blocking(move || push(&url, &text)).await?;
+        }
+
+        if outcome.failed() {
+            anyhow::bail!("Test run failed");
+        }
+        Ok(())
+    }
+
+    fn events(&self) -> EventFilter {
+        EventFilter::none().finished(&[ComponentKind::Global, ComponentKind::Scenario])
+    }
+}
+
+fn seconds(outcome: &Outcome) -> f64 {
+    (outcome.ended - outcome.started)
+        .to_std()
+        .unwrap_or_default()
+        .as_secs_f64()
+}
+
+/// Escape a label value
+fn label(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n")
+}
+
+fn stat_rows(stat: &Stat) -> [(&'static str, usize); 4] {
+    [
+        ("passed", stat.passed),
+        ("failed", stat.failed),
+        ("skipped", stat.skipped),
+        ("pending", stat.pending),
+    ]
+}
+
+/// The metrics, as OpenMetrics text
+fn metrics(outcome: &Outcome, scenarios: &[Arc<Outcome>]) -> String {
+    let mut total = Stat::default();
+    let mut by_feature: BTreeMap<&str, Stat> = BTreeMap::new();
+    for scenario in scenarios {
+        total.count(scenario.verdict);
+        let feature = scenario
+            .component()
+            .feature()
+            .map(|f| f.name.as_str())
+            .unwrap_or_default();
+        by_feature
+            .entry(feature)
+            .or_default()
+            .count(scenario.verdict);
+    }
+
+    // Writing to a String can't fail
+    let mut text = String::new();
+    text.push_str("# TYPE zuke_scenarios gauge\n");
+    text.push_str("# HELP zuke_scenarios Scenarios by verdict\n");
+    for (verdict, count) in stat_rows(&total) {
+        let _ = writeln!(text, "zuke_scenarios{{verdict=\"{}\"}} {}", verdict, count);
+    }
+
+    text.push_str("# TYPE zuke_feature_scenarios gauge\n");
+    text.push_str("# HELP zuke_feature_scenarios Scenarios by feature and verdict\n");
+    for (feature, stat) in by_feature.iter() {
+        for (verdict, count) in stat_rows(stat) {
+            let _ = writeln!(
+                text,
+                "zuke_feature_scenarios{{feature=\"{}\",verdict=\"{}\"}} {}",
+                label(feature),
+                verdict,
+                count
+            );
+        }
+    }
+
+    let durations: Vec<f64> = scenarios.iter().map(|s| seconds(s)).collect();
+    text.push_str("# TYPE zuke_scenario_duration_seconds histogram\n");
+    text.push_str("# HELP zuke_scenario_duration_seconds How long scenarios took\n");
+    for bound in DURATION_BUCKETS {
+        let count = durations.iter().filter(|d| *d <= bound).count();
+        let _ = writeln!(
+            text,
+            "zuke_scenario_duration_seconds_bucket{{le=\"{}\"}} {}",
+            bound, count
+        );
+    }
+    let _ = writeln!(
+        text,
+        "zuke_scenario_duration_seconds_bucket{{le=\"+Inf\"}} {}",
+        durations.len()
+    );
+    let _ = writeln!(
+        text,
+        "zuke_scenario_duration_seconds_count {}",
+        durations.len()
+    );
+    let _ = writeln!(
+        text,
+        "zuke_scenario_duration_seconds_sum {}",
+        durations.iter().sum::<f64>()
+    );
+
+    text.push_str("# TYPE zuke_run_duration_seconds gauge\n");
+    text.push_str("# HELP zuke_run_duration_seconds How long the test run took\n");
+    let _ = writeln!(text, "zuke_run_duration_seconds {}", seconds(outcome));
+
+    text.push_str("# TYPE zuke_run_passed gauge\n");
+    text.push_str("# HELP zuke_run_passed Whether the test run passed\n");
+    let _ = writeln!(text, "zuke_run_passed {}", u8::from(!outcome.failed()));
+
+    text.push_str("# EOF\n");
+    text
+}
+
+/// Split an `http://` URL into a `host:port` to connect to, and a path
+fn parse_url(url: &str) -> anyhow::Result<(String, &str)> {
+    let rest = url
+        .strip_prefix("http://")
+        .with_context(|| format!("--openmetrics-push must be an http:// URL, not {:?}", url))?;
+    let (host, path) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None => (rest, "/"),
+    };
+    anyhow::ensure!(!host.is_empty(), "No host in --openmetrics-push {:?}", url);
+
+    let address = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:80", host)
+    };
+    Ok((address, path))
+}
+
+/// Send metrics to a Pushgateway
+fn push(url: &str, text: &str) -> anyhow::Result<()> {
+    let (address, path) = parse_url(url)?;
+    let mut stream = TcpStream::connect(&address)
+        .with_context(|| format!("Couldn't connect to Pushgateway at {}", address))?;
+    write!(
+        stream,
+        "PUT {} HTTP/1.1\r\nHost: {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        address,
+        CONTENT_TYPE,
+        text.len(),
+        text
+    )?;
+    stream.flush()?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let status = response.lines().next().unwrap_or_default();
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => anyhow::bail!("Pushgateway at {} answered {:?}", url, status),
+    }
+}
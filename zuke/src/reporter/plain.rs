@@ -1,34 +1,106 @@
 //! A simple text based output
-use super::Reporter;
+use super::{reporter_output, Baseline, Reporter};
 use crate::component::{Component, ComponentKind};
-use crate::event::Event;
+use crate::event::{Event, EventFilter};
 use crate::options::TestOptions;
+use crate::tags::quarantine::Quarantined;
 use crate::{extra_options, reporter};
 use crate::{Outcome, Verdict};
-use anyhow;
+use anyhow::{self, Context as _};
 use async_broadcast as broadcast;
 use async_std::io::{stdout, Stdout};
 use async_trait::async_trait;
 use clap::{App, Arg};
-use futures::io::{AllowStdIo, AsyncWrite, AsyncWriteExt};
+use futures::io::{AllowStdIo, AsyncWrite, AsyncWriteExt, Cursor};
 use futures::stream::StreamExt;
+use std::collections::BTreeMap;
 use std::fs;
 use std::io;
-use std::io::Write;
-use std::path::PathBuf;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// How much [`PlainReporter`] prints. Every level ends with a summary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    /// Only failed scenarios, with their steps (`-q`)
+    Quiet,
+    /// Every feature and scenario. Steps are shown for scenarios that didn't simply pass.
+    #[default]
+    Normal,
+    /// Every step, with its duration (`-v`)
+    Verbose,
+    /// Every step, with its docstring or table (`-vv`)
+    VeryVerbose,
+}
+
+impl Verbosity {
+    /// Read `-q` and `-v` from the command line
+    pub fn from_options(options: &TestOptions) -> Self {
+        if options.opts.is_present("quiet") {
+            return Verbosity::Quiet;
+        }
+        match options.opts.occurrences_of("verbose") {
+            0 => Verbosity::Normal,
+            1 => Verbosity::Verbose,
+            _ => Verbosity::VeryVerbose,
+        }
+    }
+
+    /// Should a component with this outcome be printed at all?
+    fn shows(self, outcome: &Outcome) -> bool {
+        outcome.verdict != Verdict::Excluded && (self > Verbosity::Quiet || outcome.failed())
+    }
+
+    /// Should the steps of a scenario (or iteration) with this outcome be printed?
+    fn shows_steps(self, outcome: &Outcome) -> bool {
+        self >= Verbosity::Verbose || outcome.verdict != Verdict::Passed
+    }
+}
+
 /// Reporter that prints simple text output to a stream
 pub struct PlainReporter<T: AsyncWrite> {
     out: T,
+    baseline: Option<Arc<Baseline>>,
+    color: bool,
+    verbosity: Verbosity,
+    quiet_on_success: bool,
+    artifacts_dir: Option<PathBuf>,
 }
 
 #[reporter("plain")]
-fn make_plain(_name: &str, options: &TestOptions) -> anyhow::Result<Box<dyn Reporter>> {
-    // TODO: Make sure only one reporter can use "--output" at a time.
-    match options.opts.value_of_os("output") {
-        Some(path) => Ok(Box::new(PlainReporter::from(fs::File::create(path)?))),
-        None => Ok(Box::new(PlainReporter::default())),
+pub(super) fn make_plain(name: &str, options: &TestOptions) -> anyhow::Result<Box<dyn Reporter>> {
+    let baseline = Baseline::from_options(options)?;
+    let verbosity = Verbosity::from_options(options);
+    let quiet_on_success = options.opts.is_present("quiet_on_success");
+    let artifacts_dir = options.opts.value_of_os("artifacts_dir").map(PathBuf::from);
+    if let Some(file) = reporter_output(name, options)? {
+        Ok(Box::new(
+            PlainReporter::from(file)
+                .with_baseline(baseline)
+                .with_verbosity(verbosity)
+                .with_quiet_on_success(quiet_on_success)
+                .with_artifacts_dir(artifacts_dir),
+        ))
+    } else if let Some(path) = options.opts.value_of_os("output") {
+        let file = fs::File::create(path)?;
+        Ok(Box::new(
+            PlainReporter::from(file)
+                .with_baseline(baseline)
+                .with_verbosity(verbosity)
+                .with_quiet_on_success(quiet_on_success)
+                .with_artifacts_dir(artifacts_dir),
+        ))
+    } else {
+        let color = io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+        Ok(Box::new(
+            PlainReporter::default()
+                .with_baseline(baseline)
+                .with_color(color)
+                .with_verbosity(verbosity)
+                .with_quiet_on_success(quiet_on_success)
+                .with_artifacts_dir(artifacts_dir),
+        ))
     }
 }
 
@@ -40,13 +112,55 @@ fn plain_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
             .long("output")
             .value_name("FILE")
             .takes_value(true)
-            .help("Output file for text output. Default is stdout."),
+            .conflicts_with("plain-output")
+            .help("Output file for the plain reporter. Same as --plain-output."),
+    )
+    .arg(
+        Arg::with_name("verbose")
+            .short("v")
+            .long("verbose")
+            .multiple(true)
+            .help("Plain reporter prints every step. Twice to include docstrings and tables."),
+    )
+    .arg(
+        Arg::with_name("show_step_types")
+            .long("show-step-types")
+            .help(
+                "Plain reporter shows the type of And, But, and * steps, such as \"And (Given)\"",
+            ),
+    )
+    .arg(
+        Arg::with_name("quiet")
+            .short("q")
+            .long("quiet")
+            .conflicts_with("verbose")
+            .help("Plain reporter prints only failures and the summary"),
+    )
+    .arg(
+        Arg::with_name("quiet_on_success")
+            .long("quiet-on-success")
+            .conflicts_with_all(&["quiet", "verbose"])
+            .help("Plain reporter prints only the summary if the run passes, or everything if not"),
+    )
+    .arg(
+        Arg::with_name("artifacts_dir")
+            .long("artifacts-dir")
+            .value_name("DIR")
+            .takes_value(true)
+            .help("If the run fails, save a verbose report and failure artifacts to DIR"),
     )
 }
 
 impl<T: AsyncWrite + Send + Sync + 'static> From<T> for PlainReporter<T> {
     fn from(out: T) -> Self {
-        Self { out }
+        Self {
+            out,
+            baseline: None,
+            color: false,
+            verbosity: Verbosity::Normal,
+            quiet_on_success: false,
+            artifacts_dir: None,
+        }
     }
 }
 
@@ -54,6 +168,11 @@ impl<T: Write + Send + Sync + 'static> From<T> for PlainReporter<AllowStdIo<T>>
     fn from(out: T) -> Self {
         Self {
             out: AllowStdIo::new(out),
+            baseline: None,
+            color: false,
+            verbosity: Verbosity::Normal,
+            quiet_on_success: false,
+            artifacts_dir: None,
         }
     }
 }
@@ -68,20 +187,71 @@ impl Default for PlainReporter<Stdout> {
 impl<T: AsyncWrite + Send + Sync + 'static> Reporter for PlainReporter<T> {
     async fn report(
         self: Box<Self>,
-        _global: Arc<Component>,
+        global: Arc<Component>,
         events: broadcast::Receiver<Event>,
     ) -> anyhow::Result<()> {
-        self.execute(events).await
+        self.execute(global, events).await
+    }
+
+    fn events(&self) -> EventFilter {
+        EventFilter::none().finished(&[ComponentKind::Global, ComponentKind::Feature])
+    }
+}
+
+impl<T: AsyncWrite> PlainReporter<T> {
+    /// Point out scenarios that changed since a previous test run
+    pub fn with_baseline(mut self, baseline: Option<Arc<Baseline>>) -> Self {
+        self.baseline = baseline;
+        self
+    }
+
+    /// Color diffs from failed comparisons. Off by default.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Choose how much to print. Default is [`Verbosity::Normal`].
+    pub fn with_verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    /// Print nothing but the summary if the test run passes. If it fails, print every feature at
+    /// [`Verbosity::Verbose`] or above once the run is done. Off by default.
+    pub fn with_quiet_on_success(mut self, quiet_on_success: bool) -> Self {
+        self.quiet_on_success = quiet_on_success;
+        self
+    }
+
+    /// If the test run fails, save a very verbose report to `report.txt` in this directory, along
+    /// with the artifacts of every failed scenario and step. Off by default.
+    pub fn with_artifacts_dir(mut self, artifacts_dir: Option<PathBuf>) -> Self {
+        self.artifacts_dir = artifacts_dir;
+        self
     }
 }
 
 impl<T: AsyncWrite + Send + Sync + 'static> PlainReporter<T> {
-    async fn execute(self, mut events: broadcast::Receiver<Event>) -> anyhow::Result<()> {
+    async fn execute(
+        self,
+        global: Arc<Component>,
+        mut events: broadcast::Receiver<Event>,
+    ) -> anyhow::Result<()> {
         let mut final_result = None;
 
+        let baseline = self.baseline.as_deref();
+        let color = self.color;
+        let verbosity = self.verbosity;
+        let quiet_on_success = self.quiet_on_success;
+        let artifacts_dir = self.artifacts_dir;
         let out = self.out;
         futures::pin_mut!(out);
 
+        // Features held back until we know whether the run failed
+        let keep_features = quiet_on_success || artifacts_dir.is_some();
+        let mut features = vec![];
+
         // for now just print features as they complete
         while let Some(event) = events.next().await {
             if let Event::Finished(outcome) = event {
@@ -90,7 +260,12 @@ impl<T: AsyncWrite + Send + Sync + 'static> PlainReporter<T> {
                         final_result = Some(outcome);
                     }
                     ComponentKind::Feature => {
-                        print_feature(&mut out, outcome).await?;
+                        if keep_features {
+                            features.push(outcome.clone());
+                        }
+                        if !quiet_on_success {
+                            print_feature(&mut out, outcome, baseline, color, verbosity).await?;
+                        }
                     }
                     _ => (),
                 }
@@ -102,31 +277,25 @@ impl<T: AsyncWrite + Send + Sync + 'static> PlainReporter<T> {
             None => anyhow::bail!("Did not receive final test result"),
         };
 
-        let stats = outcome.stats();
-        let rows = [
-            (ComponentKind::Feature, "features"),
-            (ComponentKind::Rule, "rules"),
-            (ComponentKind::Scenario, "scenarios"),
-            (ComponentKind::Step, "steps"),
-        ];
-
-        for (kind, noun) in rows {
-            let stat = stats
-                .get(&kind)
-                .map(Clone::clone)
-                .unwrap_or_else(Default::default);
-            out.write_all(
-                format!(
-                    "{} {} passed, {} failed, {} skipped\n",
-                    stat.passed, noun, stat.failed, stat.skipped,
-                )
-                .as_ref(),
-            )
-            .await?;
+        if outcome.failed() {
+            if quiet_on_success {
+                let verbosity = verbosity.max(Verbosity::Verbose);
+                for feature in features.iter() {
+                    print_feature(&mut out, feature.clone(), baseline, color, verbosity).await?;
+                }
+            }
+            if let Some(dir) = artifacts_dir {
+                write_artifacts(&dir, &global, &outcome, &features).await?;
+            }
         }
 
-        out.write_all(format!("Took {}\n\n", format_duration(&outcome)).as_ref())
-            .await?;
+        print_summary(&mut out, &global, &outcome, verbosity).await?;
+        print_owners(&mut out, &outcome).await?;
+        print_quarantined(&mut out, &outcome).await?;
+
+        if let Some(baseline) = baseline {
+            print_changes(&mut out, &outcome, baseline).await?;
+        }
 
         // overall return code
         if outcome.failed() {
@@ -137,6 +306,176 @@ impl<T: AsyncWrite + Send + Sync + 'static> PlainReporter<T> {
     }
 }
 
+async fn print_summary<T: AsyncWrite + std::marker::Unpin>(
+    out: &mut T,
+    global: &Component,
+    outcome: &Arc<Outcome>,
+    verbosity: Verbosity,
+) -> io::Result<()> {
+    let stats = outcome.stats();
+    let rows = [
+        (ComponentKind::Feature, "features"),
+        (ComponentKind::Rule, "rules"),
+        (ComponentKind::Scenario, "scenarios"),
+        (ComponentKind::Step, "steps"),
+    ];
+
+    for (kind, noun) in rows {
+        let stat = stats
+            .get(&kind)
+            .map(Clone::clone)
+            .unwrap_or_else(Default::default);
+        // Only mention the rarer verdicts when they happened
+        let extras = [
+            (stat.pending, String::from("pending")),
+            (stat.warnings, format!("{} with warnings", noun)),
+            (stat.expected_failures, String::from("expected failures")),
+            (stat.unexpected_passes, String::from("unexpected passes")),
+            (stat.canceled, String::from("canceled")),
+            (stat.undecided, String::from("undecided")),
+            (stat.deselected, String::from("deselected")),
+        ];
+        let mut line = format!(
+            "{} {} passed, {} failed, {} skipped",
+            stat.passed, noun, stat.failed, stat.skipped
+        );
+        for (n, what) in extras.iter().filter(|(n, _)| *n > 0) {
+            line.push_str(&format!(", {} {}", n, what));
+        }
+        line.push('\n');
+        out.write_all(line.as_ref()).await?;
+    }
+
+    out.write_all(format!("Took {}\n", format_duration(outcome)).as_ref())
+        .await?;
+    if verbosity >= Verbosity::Verbose {
+        let info = &global.options().run_info;
+        out.write_all(format!("{}\nCommand line: {}\n", info, info.command_line()).as_ref())
+            .await?;
+    }
+    out.write_all(b"\n").await
+}
+
+/// List quarantined scenarios that failed (see [`crate::tags::quarantine`]), so that they aren't
+/// forgotten just because the run passed
+async fn print_quarantined<T: AsyncWrite + std::marker::Unpin>(
+    out: &mut T,
+    outcome: &Arc<Outcome>,
+) -> io::Result<()> {
+    let mut header = false;
+    for scenario in outcome.clone().iter_components(ComponentKind::Scenario) {
+        let warning = scenario
+            .warnings
+            .iter()
+            .find(|w| w.downcast_ref::<Quarantined>().is_some());
+        let warning = match warning {
+            Some(w) => w,
+            None => continue,
+        };
+        if !header {
+            out.write_all(b"Quarantined failures:\n").await?;
+            header = true;
+        }
+        let reason = warning
+            .chain()
+            .nth(1)
+            .map(|e| e.to_string())
+            .unwrap_or_default();
+        out.write_all(format!("  {}: {}\n", scenario.component().key(), reason).as_bytes())
+            .await?;
+    }
+    if header {
+        out.write_all(b"\n").await?;
+    }
+    Ok(())
+}
+
+/// Group failed scenarios by owner (see [`Component::owners`]), so each team can find its own.
+/// Nothing is printed unless one of them has an owner.
+async fn print_owners<T: AsyncWrite + std::marker::Unpin>(
+    out: &mut T,
+    outcome: &Arc<Outcome>,
+) -> io::Result<()> {
+    let mut by_owner: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut unowned = vec![];
+    for scenario in outcome.clone().iter_components(ComponentKind::Scenario) {
+        if !scenario.failed() {
+            continue;
+        }
+        let component = scenario.component();
+        let owners = component.owners();
+        if owners.is_empty() {
+            unowned.push(component.key());
+        }
+        for owner in owners {
+            by_owner.entry(owner).or_default().push(component.key());
+        }
+    }
+
+    if by_owner.is_empty() {
+        return Ok(());
+    }
+    if !unowned.is_empty() {
+        by_owner.insert(String::from("(no owner)"), unowned);
+    }
+
+    out.write_all(b"Failures by owner:\n").await?;
+    for (owner, keys) in by_owner {
+        out.write_all(format!("  {} ({})\n", owner, keys.len()).as_bytes())
+            .await?;
+        for key in keys {
+            out.write_all(format!("    {}\n", key).as_bytes()).await?;
+        }
+    }
+    out.write_all(b"\n").await
+}
+
+/// For `--artifacts-dir`: save a very verbose report of the failed run, and the artifacts of every
+/// failed scenario and step, each to its own file.
+async fn write_artifacts(
+    dir: &Path,
+    global: &Component,
+    outcome: &Arc<Outcome>,
+    features: &[Arc<Outcome>],
+) -> anyhow::Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("Couldn't create {}", dir.display()))?;
+
+    let mut report = Cursor::new(Vec::new());
+    for feature in features.iter() {
+        print_feature(
+            &mut report,
+            feature.clone(),
+            None,
+            false,
+            Verbosity::VeryVerbose,
+        )
+        .await?;
+    }
+    print_summary(&mut report, global, outcome, Verbosity::Verbose).await?;
+    let path = dir.join("report.txt");
+    fs::write(&path, report.into_inner())
+        .with_context(|| format!("Couldn't write {}", path.display()))?;
+
+    let mut count = 0;
+    for kind in [ComponentKind::Scenario, ComponentKind::Step] {
+        for o in outcome.clone().iter_components(kind) {
+            for artifact in o.artifacts.iter() {
+                count += 1;
+                let stem: String = artifact
+                    .name
+                    .chars()
+                    .map(|c| if c.is_alphanumeric() { c } else { '-' })
+                    .collect();
+                let name = format!("{:03}-{}.{}", count, stem, artifact.extension());
+                let path = dir.join(name);
+                fs::write(&path, &artifact.data)
+                    .with_context(|| format!("Couldn't write {}", path.display()))?;
+            }
+        }
+    }
+    Ok(())
+}
+
 fn is_scenario(outcome: &&Arc<Outcome>) -> bool {
     outcome.kind() == ComponentKind::Scenario
 }
@@ -144,8 +483,11 @@ fn is_scenario(outcome: &&Arc<Outcome>) -> bool {
 async fn print_feature<T: AsyncWrite + std::marker::Unpin>(
     out: &mut T,
     outcome: Arc<Outcome>,
+    baseline: Option<&Baseline>,
+    color: bool,
+    verbosity: Verbosity,
 ) -> io::Result<()> {
-    if outcome.verdict == Verdict::Excluded {
+    if !verbosity.shows(&outcome) {
         return Ok(());
     }
 
@@ -177,7 +519,7 @@ async fn print_feature<T: AsyncWrite + std::marker::Unpin>(
 
     // Scenarios first, then rules
     for child in outcome.children.iter().filter(is_scenario) {
-        print_scenario(out, child, "  ").await?;
+        print_scenario(out, child, "  ", baseline, color, verbosity).await?;
     }
 
     for child in outcome
@@ -185,7 +527,7 @@ async fn print_feature<T: AsyncWrite + std::marker::Unpin>(
         .iter()
         .filter(|o| o.kind() == ComponentKind::Rule)
     {
-        print_rule(out, child).await?;
+        print_rule(out, child, baseline, color, verbosity).await?;
     }
 
     out.write_all("\n".as_ref()).await?;
@@ -195,8 +537,11 @@ async fn print_feature<T: AsyncWrite + std::marker::Unpin>(
 async fn print_rule<T: AsyncWrite + std::marker::Unpin>(
     out: &mut T,
     outcome: &Arc<Outcome>,
+    baseline: Option<&Baseline>,
+    color: bool,
+    verbosity: Verbosity,
 ) -> io::Result<()> {
-    if outcome.verdict == Verdict::Excluded {
+    if !verbosity.shows(outcome) {
         return Ok(());
     }
 
@@ -219,7 +564,7 @@ async fn print_rule<T: AsyncWrite + std::marker::Unpin>(
     .await?;
 
     for child in outcome.children.iter().filter(is_scenario) {
-        print_scenario(out, child, "    ").await?;
+        print_scenario(out, child, "    ", baseline, color, verbosity).await?;
     }
 
     out.write_all("\n".as_ref()).await?;
@@ -230,16 +575,24 @@ async fn print_scenario<T: AsyncWrite + std::marker::Unpin>(
     out: &mut T,
     outcome: &Arc<Outcome>,
     indent: &str,
+    baseline: Option<&Baseline>,
+    color: bool,
+    verbosity: Verbosity,
 ) -> io::Result<()> {
-    if outcome.verdict == Verdict::Excluded {
+    if !verbosity.shows(outcome) {
         return Ok(());
     }
 
+    let change = match baseline.and_then(|b| b.compare(outcome)) {
+        Some(c) => format!(" [{}]", c),
+        None => String::new(),
+    };
+
     let feature = outcome.component().feature().unwrap();
     let scenario = outcome.component().scenario().unwrap();
     out.write_all(
         format!(
-            "{}{}: {}\t# {}:{} {}\n",
+            "{}{}: {}\t# {}:{} {}{}\n",
             indent,
             scenario.keyword,
             scenario.name,
@@ -250,25 +603,56 @@ async fn print_scenario<T: AsyncWrite + std::marker::Unpin>(
                 .display(),
             scenario.position.line,
             format_duration(outcome),
+            change,
         )
         .as_ref(),
     )
     .await?;
 
-    // If there is a scenario-level reason, print it out.
+    // If there is a scenario-level reason, print it out. Otherwise say why it was skipped.
     if let Some(err) = outcome.reason.as_ref() {
         out.write_all(textwrap::indent(&format!("{:?}", &err), "  ").as_bytes())
             .await?;
         out.write_all("\n\n".as_ref()).await?;
+    } else if let Some(cause) = outcome.skip_cause.as_ref() {
+        out.write_all(format!("  {}\n\n", cause).as_bytes()).await?;
     }
 
     let indent = format!("  {}", indent);
-    for child in outcome
+    let steps = outcome
         .children
         .iter()
-        .filter(|o| o.kind() == ComponentKind::Step)
-    {
-        print_step(out, child, &indent).await?;
+        .filter(|o| o.kind() == ComponentKind::Step);
+    for child in steps.filter(|_| verbosity.shows_steps(outcome)) {
+        print_step(out, child, &indent, color, verbosity).await?;
+    }
+
+    for (i, iteration) in outcome.iterations().enumerate() {
+        out.write_all(
+            format!(
+                "{}Iteration {}\t# {} {}\n",
+                indent,
+                i + 1,
+                iteration.verdict,
+                format_duration(iteration),
+            )
+            .as_ref(),
+        )
+        .await?;
+
+        let indent = format!("  {}", indent);
+        if let Some(err) = iteration.reason.as_ref() {
+            out.write_all(textwrap::indent(&format!("{:?}\n", &err), &indent).as_bytes())
+                .await?;
+        }
+
+        let steps = iteration
+            .children
+            .iter()
+            .filter(|o| o.kind() == ComponentKind::Step);
+        for child in steps.filter(|_| verbosity.shows_steps(iteration)) {
+            print_step(out, child, &indent, color, verbosity).await?;
+        }
     }
 
     out.write_all("\n".as_ref()).await?;
@@ -279,31 +663,109 @@ async fn print_step<T: AsyncWrite + std::marker::Unpin>(
     out: &mut T,
     outcome: &Arc<Outcome>,
     indent: &str,
+    color: bool,
+    verbosity: Verbosity,
 ) -> io::Result<()> {
-    // currently we don't have info on where the steps were implemented, except in nightly
+    // the outcome doesn't record which step implementation ran, so we can't say where it is
     let step = outcome.component().step().unwrap();
     let duration = format_duration(outcome);
+    let mut keyword = step.keyword.trim().to_string();
+    if outcome
+        .component()
+        .options()
+        .opts
+        .is_present("show_step_types")
+    {
+        match outcome.component().step_type_keyword() {
+            Some(type_keyword) if type_keyword != keyword => {
+                keyword = format!("{} ({})", keyword, type_keyword);
+            }
+            _ => (),
+        }
+    }
     out.write_all(
         format!(
             "{}{} {}\t# {} {}\n",
-            indent, step.keyword, step.value, outcome.verdict, duration
+            indent, keyword, step.value, outcome.verdict, duration
         )
         .as_ref(),
     )
     .await?;
 
+    if verbosity >= Verbosity::VeryVerbose {
+        let indent = format!("{}  ", indent);
+        if let Some(docstring) = &step.docstring {
+            let text = format!("\"\"\"\n{}\n\"\"\"\n", docstring);
+            out.write_all(textwrap::indent(&text, &indent).as_bytes())
+                .await?;
+        }
+        if let Some(table) = &step.table {
+            for row in table.rows.iter() {
+                let row = format!("{}| {} |\n", indent, row.join(" | "));
+                out.write_all(row.as_bytes()).await?;
+            }
+        }
+    }
+
     if let Some(e) = &outcome.reason {
         let indent = format!("{}  ", indent);
-        let errmsg = format!("{:?}\n", e);
+        let errmsg = match outcome.diff() {
+            Some(diff) => diff.render(color),
+            None => format!("{:?}\n", e),
+        };
         let errmsg = textwrap::indent(&errmsg, &indent);
         out.write_all(errmsg.as_ref()).await?;
     }
 
+    for warning in outcome.warnings.iter() {
+        let text = format!("Warning: {:#}\n", warning);
+        out.write_all(textwrap::indent(&text, &format!("{}  ", indent)).as_bytes())
+            .await?;
+    }
+
+    for artifact in outcome.artifacts.iter() {
+        let text = format!(
+            "{}  Artifact: {} ({}, {} bytes)\n",
+            indent,
+            artifact.name,
+            artifact.media_type,
+            artifact.data.len()
+        );
+        out.write_all(text.as_bytes()).await?;
+    }
+
     Ok(())
 }
 
+async fn print_changes<T: AsyncWrite + std::marker::Unpin>(
+    out: &mut T,
+    outcome: &Arc<Outcome>,
+    baseline: &Baseline,
+) -> io::Result<()> {
+    let changes: Vec<_> = outcome
+        .clone()
+        .iter_components(ComponentKind::Scenario)
+        .filter_map(|o| Some((baseline.compare(&o)?, o)))
+        .collect();
+
+    if changes.is_empty() {
+        return out.write_all(b"No changes since baseline\n\n").await;
+    }
+
+    out.write_all(b"Changes since baseline:\n").await?;
+    for (change, o) in changes {
+        out.write_all(format!("  {}: {}\n", o.component().key(), change).as_bytes())
+            .await?;
+    }
+    out.write_all(b"\n").await
+}
+
 fn format_duration(outcome: &Arc<Outcome>) -> String {
-    let duration = outcome.ended - outcome.started;
+    format_elapsed(outcome.ended - outcome.started)
+}
+
+/// Format a duration for humans, choosing a sensible unit
+pub(super) fn format_elapsed(duration: chrono::Duration) -> String {
     if let Some(ns) = duration.num_nanoseconds() {
         if ns < 500_000 {
             // 0 -> 500us, display as us
@@ -0,0 +1,172 @@
+//! Keeps one reporter's problems from holding up the test run
+use super::Reporter;
+use crate::component::{Component, ComponentKind};
+use crate::event::{Event, EventFilter};
+use crate::flag::Flag;
+use anyhow;
+use async_broadcast::{self as broadcast, TrySendError};
+use async_std::task;
+use async_trait::async_trait;
+use futures::join;
+use futures::stream::StreamExt;
+use std::io;
+use std::sync::Arc;
+
+/// Events a reporter can fall behind by before its channel grows
+const CAPACITY: usize = 256;
+
+/// A reporter that runs another reporter, so that it can't hold up the test run or the other
+/// reporters. Reporters added with [`crate::ZukeBuilder::reporter`] run this way, as do those that
+/// [`crate::CommandLineReporter`] makes.
+///
+/// - Only the events the reporter wants (see [`Reporter::events`]) are passed along.
+/// - Events are passed along without waiting, so a reporter that is stuck, such as on a write to
+///   a full pipe, doesn't stop the runner from sending more. Its events are kept for it instead.
+/// - If the reporter fails before the test run is over, or can't write its output, the error is
+///   printed to stderr right away, rather than only when the run ends. It is still returned at
+///   the end.
+/// - If there's a fallback reporter, it takes over from the failed one, starting with every
+///   event so far. See `--reporter-fallback`.
+pub struct Supervised {
+    name: Option<String>,
+    reporter: Box<dyn Reporter>,
+    fallback: Option<Box<dyn Reporter>>,
+}
+
+impl Supervised {
+    /// Supervise `reporter`
+    pub fn new(reporter: Box<dyn Reporter>) -> Self {
+        Self {
+            name: None,
+            reporter,
+            fallback: None,
+        }
+    }
+
+    /// Name the reporter in error messages
+    pub fn with_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Start `fallback` if the reporter fails before the test run is over
+    pub fn with_fallback(mut self, fallback: Option<Box<dyn Reporter>>) -> Self {
+        self.fallback = fallback;
+        self
+    }
+}
+
+/// Start the fallback reporter, catching it up on the events so far
+fn start_fallback(
+    fallback: Box<dyn Reporter>,
+    global: Arc<Component>,
+    history: Vec<Event>,
+) -> (
+    broadcast::Sender<Event>,
+    task::JoinHandle<anyhow::Result<()>>,
+) {
+    let filter = fallback.events();
+    let (mut tx, rx) = broadcast::broadcast(CAPACITY.max(history.len() + 1));
+    for event in history.into_iter().filter(|e| filter.matches(e)) {
+        forward(&mut tx, event);
+    }
+    (tx, task::spawn(fallback.report(global, rx)))
+}
+
+/// Send an event without waiting. If the receiver is behind, make room for it.
+fn forward(tx: &mut broadcast::Sender<Event>, event: Event) {
+    if let Err(TrySendError::Full(event)) = tx.try_broadcast(event) {
+        tx.set_capacity(tx.capacity() * 2);
+        let _ = tx.try_broadcast(event);
+    }
+}
+
+#[async_trait]
+impl Reporter for Supervised {
+    async fn report(
+        self: Box<Self>,
+        global: Arc<Component>,
+        mut events: broadcast::Receiver<Event>,
+    ) -> anyhow::Result<()> {
+        let Self {
+            name,
+            reporter,
+            mut fallback,
+        } = *self;
+        let filter = reporter.events();
+        let fallback_filter = fallback.as_ref().map(|f| f.events());
+        let (mut tx, rx) = broadcast::broadcast(CAPACITY);
+        let run_over = Flag::new();
+        let failed = Flag::new();
+        let done = Flag::new();
+
+        let primary = async {
+            let result = reporter.report(global.clone(), rx).await;
+            if let Err(e) = &result {
+                // Reporters fail at the end if tests failed. That's not a problem with the
+                // reporter.
+                if !run_over.is_set() || e.chain().any(|c| c.is::<io::Error>()) {
+                    match &name {
+                        Some(name) => eprintln!("Error: Reporter {} failed: {:#}", name, e),
+                        None => eprintln!("Error: A reporter failed: {:#}", e),
+                    }
+                    failed.set();
+                }
+            }
+            done.set();
+            result
+        };
+
+        let relay = async {
+            let mut history = vec![];
+            let mut backup: Option<(broadcast::Sender<Event>, task::JoinHandle<_>)> = None;
+
+            while let Some(event) = events.next().await {
+                if matches!(&event, Event::Finished(o) if o.kind() == ComponentKind::Global) {
+                    run_over.set();
+                }
+
+                if failed.is_set() && backup.is_none() {
+                    if let Some(fallback) = fallback.take() {
+                        let history = std::mem::take(&mut history);
+                        backup = Some(start_fallback(fallback, global.clone(), history));
+                    }
+                }
+
+                match &mut backup {
+                    Some((fallback_tx, _)) => {
+                        if fallback_filter.map_or(false, |f| f.matches(&event)) {
+                            forward(fallback_tx, event.clone());
+                        }
+                    }
+                    None if fallback.is_some() => history.push(event.clone()),
+                    None => (),
+                }
+                if filter.matches(&event) {
+                    forward(&mut tx, event);
+                }
+            }
+
+            tx.close();
+
+            // It may have failed on one of the last events
+            done.wait().await;
+            if failed.is_set() && backup.is_none() {
+                if let Some(fallback) = fallback.take() {
+                    backup = Some(start_fallback(fallback, global.clone(), history));
+                }
+            }
+
+            match backup {
+                Some((fallback_tx, handle)) => {
+                    fallback_tx.close();
+                    handle.await
+                }
+                None => Ok(()),
+            }
+        };
+
+        let (result, fallback_result) = join!(primary, relay);
+        result.and(fallback_result)
+    }
+}
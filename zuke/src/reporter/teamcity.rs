@@ -0,0 +1,240 @@
+//! TeamCity service messages
+use super::{reporter_output, Reporter};
+use crate::assert::Diff;
+use crate::component::{Component, ComponentKind};
+use crate::event::Event;
+use crate::options::TestOptions;
+use crate::outcome::Outcome;
+use crate::reporter;
+use anyhow;
+use async_broadcast as broadcast;
+use async_std::io::{stdout, Stdout};
+use async_trait::async_trait;
+use futures::io::{AllowStdIo, AsyncWrite, AsyncWriteExt};
+use futures::stream::StreamExt;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Reporter that writes [TeamCity service
+/// messages](https://www.jetbrains.com/help/teamcity/service-messages.html) as tests start and
+/// finish. TeamCity and IntelliJ use these to show test results while the tests are still
+/// running.
+///
+/// Features and rules are reported as suites, and scenarios as tests. Each feature gets its own
+/// `flowId` so that features running in parallel don't get mixed up.
+pub struct TeamCityReporter<T: AsyncWrite> {
+    out: T,
+}
+
+#[reporter("teamcity")]
+fn make_teamcity(name: &str, options: &TestOptions) -> anyhow::Result<Box<dyn Reporter>> {
+    match reporter_output(name, options)? {
+        Some(file) => Ok(Box::new(TeamCityReporter::from(file))),
+        None => Ok(Box::new(TeamCityReporter::default())),
+    }
+}
+
+impl<T: AsyncWrite + Send + Sync + 'static> From<T> for TeamCityReporter<T> {
+    fn from(out: T) -> Self {
+        Self { out }
+    }
+}
+
+impl<T: Write + Send + Sync + 'static> From<T> for TeamCityReporter<AllowStdIo<T>> {
+    fn from(out: T) -> Self {
+        Self {
+            out: AllowStdIo::new(out),
+        }
+    }
+}
+
+impl Default for TeamCityReporter<Stdout> {
+    fn default() -> Self {
+        Self::from(stdout())
+    }
+}
+
+#[async_trait]
+impl<T: AsyncWrite + Send + Sync + 'static> Reporter for TeamCityReporter<T> {
+    async fn report(
+        self: Box<Self>,
+        _global: Arc<Component>,
+        mut events: broadcast::Receiver<Event>,
+    ) -> anyhow::Result<()> {
+        let mut failed = None;
+
+        let out = self.out;
+        futures::pin_mut!(out);
+
+        while let Some(event) = events.next().await {
+            let messages = match &event {
+                Event::Started(component) => started(component),
+                Event::Finished(outcome) => {
+                    if outcome.kind() == ComponentKind::Global {
+                        failed = Some(outcome.failed());
+                    }
+                    finished(outcome)
+                }
+                Event::HookFinished(_) | Event::IterationFinished(_) => vec![],
+            };
+
+            for message in messages {
+                out.write_all(message.as_bytes()).await?;
+            }
+            out.flush().await?;
+        }
+
+        match failed {
+            None => anyhow::bail!("Did not receive final test result"),
+            Some(true) => anyhow::bail!("Test run failed"),
+            Some(false) => Ok(()),
+        }
+    }
+}
+
+fn started(component: &Component) -> Vec<String> {
+    match component.kind() {
+        ComponentKind::Feature | ComponentKind::Rule => {
+            vec![message("testSuiteStarted", component, &[])]
+        }
+        ComponentKind::Scenario => vec![message("testStarted", component, &[])],
+        ComponentKind::Global | ComponentKind::Step => vec![],
+    }
+}
+
+fn finished(outcome: &Outcome) -> Vec<String> {
+    let component = outcome.component();
+    match component.kind() {
+        ComponentKind::Feature | ComponentKind::Rule => {
+            let mut messages = vec![];
+            // Errors in the suite itself (parse errors, fixture setup, etc.) have no test to
+            // attach to, so make one up.
+            if outcome.failed() && outcome.reason.is_some() {
+                let details = failure_details(outcome);
+                messages.push(message("testStarted", component, &[]));
+                messages.push(message(
+                    "testFailed",
+                    component,
+                    &[
+                        ("message", &outcome.verdict.to_string()),
+                        ("details", &details),
+                    ],
+                ));
+                messages.push(message("testFinished", component, &[]));
+            }
+            messages.push(message("testSuiteFinished", component, &[]));
+            messages
+        }
+        ComponentKind::Scenario => {
+            let duration = (outcome.ended - outcome.started)
+                .num_milliseconds()
+                .max(0)
+                .to_string();
+            let mut messages = vec![];
+
+            if outcome.failed() {
+                let details = failure_details(outcome);
+                let verdict = outcome.verdict.to_string();
+                let mut attrs = vec![("message", verdict.as_str()), ("details", &details)];
+                // IDEs show a comparison failure side by side
+                if let Some(diff) = failed_diff(outcome) {
+                    attrs.push(("type", "comparisonFailure"));
+                    attrs.push(("expected", &diff.expected));
+                    attrs.push(("actual", &diff.actual));
+                }
+                messages.push(message("testFailed", component, &attrs));
+            } else if outcome.is_pending() {
+                let reason = match &outcome.reason {
+                    Some(r) => format!("Pending: {:#}", r),
+                    None => String::from("Pending"),
+                };
+                messages.push(message("testIgnored", component, &[("message", &reason)]));
+            } else if outcome.skipped() {
+                let reason = match (&outcome.reason, &outcome.skip_cause) {
+                    (Some(r), _) => format!("{:#}", r),
+                    (None, Some(c)) => c.to_string(),
+                    (None, None) => outcome.verdict.to_string(),
+                };
+                messages.push(message("testIgnored", component, &[("message", &reason)]));
+            }
+
+            messages.push(message(
+                "testFinished",
+                component,
+                &[("duration", &duration)],
+            ));
+            messages
+        }
+        ComponentKind::Global | ComponentKind::Step => vec![],
+    }
+}
+
+/// Describe why a component failed. If the failure came from a child, such as a step, name the
+/// child too.
+fn failure_details(outcome: &Outcome) -> String {
+    if let Some(reason) = &outcome.reason {
+        return format!("{:#}", reason);
+    }
+
+    outcome
+        .children
+        .iter()
+        .filter(|c| c.failed())
+        .map(|c| match &c.reason {
+            Some(r) => format!("{} {}: {:#}", c.kind(), c.component().name(), r),
+            None => format!("{} {}: {}", c.kind(), c.component().name(), c.verdict),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The diff from a failed comparison in the scenario, or in one of its steps
+fn failed_diff(outcome: &Outcome) -> Option<&Diff> {
+    outcome.diff().or_else(|| {
+        outcome
+            .children
+            .iter()
+            .filter(|c| c.failed())
+            .find_map(|c| c.diff())
+    })
+}
+
+fn message(name: &str, component: &Component, attrs: &[(&str, &str)]) -> String {
+    let mut msg = format!(
+        "##teamcity[{} name='{}' flowId='{}'",
+        name,
+        escape(component.name()),
+        escape(&flow_id(component)),
+    );
+    for (key, value) in attrs {
+        msg.push_str(&format!(" {}='{}'", key, escape(value)));
+    }
+    msg.push_str("]\n");
+    msg
+}
+
+/// Features run in parallel, but everything within a feature runs in order, so a feature makes a
+/// good flow.
+fn flow_id(component: &Component) -> String {
+    match (component.path(), component.feature()) {
+        (Some(path), _) => path.display().to_string(),
+        (None, Some(feature)) => feature.name.clone(),
+        (None, None) => String::from("global"),
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '|' => escaped.push_str("||"),
+            '\'' => escaped.push_str("|'"),
+            '\n' => escaped.push_str("|n"),
+            '\r' => escaped.push_str("|r"),
+            '[' => escaped.push_str("|["),
+            ']' => escaped.push_str("|]"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,223 @@
+//! A profile of where the test run spent its time
+use super::plain::format_elapsed;
+use super::{reporter_output, Reporter};
+use crate::component::{Component, ComponentKind};
+use crate::event::{Event, EventFilter};
+use crate::options::TestOptions;
+use crate::outcome::Outcome;
+use crate::{extra_options, reporter};
+use anyhow;
+use async_broadcast as broadcast;
+use async_std::io::{stdout, Stdout};
+use async_trait::async_trait;
+use chrono::Duration;
+use clap::{App, Arg};
+use futures::io::{AllowStdIo, AsyncWrite, AsyncWriteExt};
+use futures::stream::StreamExt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Upper bounds of the histogram buckets, in milliseconds. Anything slower goes in a final bucket.
+const BUCKETS: [(i64, &str); 5] = [
+    (10, "< 10 ms"),
+    (100, "< 100 ms"),
+    (1_000, "< 1 s"),
+    (10_000, "< 10 s"),
+    (60_000, "< 1 min"),
+];
+
+/// Width of the longest histogram bar
+const BAR_WIDTH: usize = 40;
+
+/// Reporter that prints a timing profile once the test run completes: the slowest scenarios and
+/// steps, total time per feature, and a histogram of scenario durations. Skipped and excluded
+/// components didn't run, and aren't counted.
+pub struct TimingReporter<T: AsyncWrite> {
+    out: T,
+    count: usize,
+}
+
+#[reporter("timing")]
+fn make_timing(name: &str, options: &TestOptions) -> anyhow::Result<Box<dyn Reporter>> {
+    let count = count(options)?;
+    match reporter_output(name, options)? {
+        Some(file) => Ok(Box::new(TimingReporter::from(file).with_count(count))),
+        None => Ok(Box::new(TimingReporter::default().with_count(count))),
+    }
+}
+
+fn count(options: &TestOptions) -> anyhow::Result<usize> {
+    match options.opts.value_of("timing_count") {
+        Some(n) => Ok(n.parse()?),
+        None => Ok(10),
+    }
+}
+
+#[extra_options]
+fn timing_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("timing_count")
+            .long("timing-count")
+            .value_name("N")
+            .takes_value(true)
+            .help(
+                "Number of slow scenarios and steps shown by the timing reporter. Default is 10.",
+            ),
+    )
+}
+
+impl<T: AsyncWrite + Send + Sync + 'static> From<T> for TimingReporter<T> {
+    fn from(out: T) -> Self {
+        Self { out, count: 10 }
+    }
+}
+
+impl<T: Write + Send + Sync + 'static> From<T> for TimingReporter<AllowStdIo<T>> {
+    fn from(out: T) -> Self {
+        Self {
+            out: AllowStdIo::new(out),
+            count: 10,
+        }
+    }
+}
+
+impl Default for TimingReporter<Stdout> {
+    fn default() -> Self {
+        Self::from(stdout())
+    }
+}
+
+impl<T: AsyncWrite> TimingReporter<T> {
+    /// Set the number of slowest scenarios and steps to show
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = count;
+        self
+    }
+}
+
+#[async_trait]
+impl<T: AsyncWrite + Send + Sync + 'static> Reporter for TimingReporter<T> {
+    async fn report(
+        self: Box<Self>,
+        _global: Arc<Component>,
+        mut events: broadcast::Receiver<Event>,
+    ) -> anyhow::Result<()> {
+        let mut final_result = None;
+        let mut features = vec![];
+        let mut scenarios = vec![];
+        let mut steps = vec![];
+
+        while let Some(event) = events.next().await {
+            if let Event::Finished(outcome) = event {
+                if outcome.skipped() {
+                    continue;
+                }
+
+                match outcome.kind() {
+                    ComponentKind::Global => final_result = Some(outcome),
+                    ComponentKind::Feature => features.push(outcome),
+                    ComponentKind::Rule => (),
+                    ComponentKind::Scenario => scenarios.push(outcome),
+                    ComponentKind::Step => steps.push(outcome),
+                }
+            }
+        }
+
+        let outcome = match final_result {
+            Some(o) => o,
+            None => anyhow::bail!("Did not receive final test result"),
+        };
+
+        let count = self.count;
+        let out = self.out;
+        futures::pin_mut!(out);
+
+        let mut text = String::new();
+        slowest(&mut text, "Slowest scenarios", &mut scenarios, count);
+        slowest(&mut text, "Slowest steps", &mut steps, count);
+        slowest(&mut text, "Time per feature", &mut features, usize::MAX);
+        histogram(&mut text, &scenarios);
+        text.push_str(&format!(
+            "Total: {}\n\n",
+            format_elapsed(duration(&outcome))
+        ));
+        out.write_all(text.as_bytes()).await?;
+        out.flush().await?;
+
+        if outcome.failed() {
+            anyhow::bail!("Test run failed");
+        } else {
+            Ok(())
+        }
+    }
+
+    fn events(&self) -> EventFilter {
+        EventFilter::none().finished(&[
+            ComponentKind::Global,
+            ComponentKind::Feature,
+            ComponentKind::Scenario,
+            ComponentKind::Step,
+        ])
+    }
+}
+
+fn duration(outcome: &Outcome) -> Duration {
+    outcome.ended - outcome.started
+}
+
+/// Where a component lives, for humans
+fn location(component: &Component) -> String {
+    let path = component
+        .path()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("<???>"));
+    match component.line() {
+        Some(line) => format!("{}:{}", path.display(), line),
+        None => path.display().to_string(),
+    }
+}
+
+fn slowest(text: &mut String, title: &str, outcomes: &mut [Arc<Outcome>], count: usize) {
+    if outcomes.is_empty() {
+        return;
+    }
+
+    outcomes.sort_by_key(|o| std::cmp::Reverse(duration(o)));
+    text.push_str(&format!("{}:\n", title));
+    for outcome in outcomes.iter().take(count) {
+        text.push_str(&format!(
+            "  {:>12}  {}\t# {}\n",
+            format_elapsed(duration(outcome)),
+            outcome.component().name(),
+            location(outcome.component()),
+        ));
+    }
+    text.push('\n');
+}
+
+fn histogram(text: &mut String, scenarios: &[Arc<Outcome>]) {
+    if scenarios.is_empty() {
+        return;
+    }
+
+    let mut counts = [0usize; BUCKETS.len() + 1];
+    for outcome in scenarios {
+        let ms = duration(outcome).num_milliseconds();
+        let bucket = BUCKETS
+            .iter()
+            .position(|(limit, _)| ms < *limit)
+            .unwrap_or(BUCKETS.len());
+        counts[bucket] += 1;
+    }
+
+    let max = counts.iter().copied().max().unwrap_or(0).max(1);
+    let labels = BUCKETS.iter().map(|(_, label)| *label).chain([">= 1 min"]);
+
+    text.push_str("Scenario durations:\n");
+    for (label, count) in labels.zip(counts) {
+        let bar = "#".repeat((count * BAR_WIDTH + max - 1) / max);
+        text.push_str(&format!("  {:>9} {:>5} {}\n", label, count, bar));
+    }
+    text.push('\n');
+}
@@ -0,0 +1,128 @@
+//! Where and how a test run happened, so that reports can say how to reproduce it
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use std::ffi::OsString;
+use std::fmt;
+use std::process::{Command, Stdio};
+
+lazy_static! {
+    // These can run commands, so they're looked up once, and only if a reporter asks
+    static ref HOST: Option<String> = hostname();
+    static ref GIT_SHA: Option<String> = git_sha();
+}
+
+/// Information about the test run as a whole, collected when the options are built. Reporters
+/// find it on [`crate::TestOptions::run_info`], through the global component.
+#[derive(Debug, Clone)]
+pub struct RunInfo {
+    /// When the test run started
+    pub started: DateTime<Utc>,
+    /// The profile chosen with `--profile`
+    pub profile: Option<String>,
+    /// The random seed, if the test run has one
+    pub seed: Option<u64>,
+    /// The full command line, including options from environment variables and the config file
+    pub args: Vec<String>,
+}
+
+impl RunInfo {
+    /// Collect information about this test run, as started with `args`. The host and commit are
+    /// looked up when first asked for.
+    pub fn collect(args: &[OsString], profile: Option<String>) -> Self {
+        Self {
+            started: Utc::now(),
+            profile,
+            seed: None,
+            args: args
+                .iter()
+                .map(|a| a.to_string_lossy().to_string())
+                .collect(),
+        }
+    }
+
+    /// The machine running the tests, if known
+    pub fn host(&self) -> Option<&'static str> {
+        HOST.as_deref()
+    }
+
+    /// The git commit being tested, if known: from `GIT_COMMIT`, `GITHUB_SHA`, or `CI_COMMIT_SHA`,
+    /// or else from `git rev-parse HEAD`
+    pub fn git_sha(&self) -> Option<&'static str> {
+        GIT_SHA.as_deref()
+    }
+
+    /// The command line, with arguments quoted where needed
+    pub fn command_line(&self) -> String {
+        let args: Vec<_> = self
+            .args
+            .iter()
+            .map(|a| {
+                if a.is_empty() || a.contains(char::is_whitespace) || a.contains('"') {
+                    format!("{:?}", a)
+                } else {
+                    a.clone()
+                }
+            })
+            .collect();
+        args.join(" ")
+    }
+}
+
+/// Summarizes the run in a line, such as `Started 2021-06-01T12:00:00Z on ci-7, commit 1a2b3c4,
+/// profile smoke`
+impl fmt::Display for RunInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Started {}", self.started.to_rfc3339())?;
+        if let Some(host) = self.host() {
+            write!(f, " on {}", host)?;
+        }
+        if let Some(sha) = self.git_sha() {
+            write!(f, ", commit {}", sha)?;
+        }
+        if let Some(profile) = &self.profile {
+            write!(f, ", profile {}", profile)?;
+        }
+        if let Some(seed) = self.seed {
+            write!(f, ", seed {}", seed)?;
+        }
+        Ok(())
+    }
+}
+
+fn non_empty(s: String) -> Option<String> {
+    let s = s.trim();
+    if s.is_empty() {
+        None
+    } else {
+        Some(s.to_string())
+    }
+}
+
+/// Ask a command for a line of output. `None` if it doesn't exist or fails.
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    non_empty(String::from_utf8(output.stdout).ok()?)
+}
+
+fn hostname() -> Option<String> {
+    ["HOSTNAME", "COMPUTERNAME"]
+        .iter()
+        .find_map(|var| non_empty(std::env::var(var).ok()?))
+        .or_else(|| non_empty(std::fs::read_to_string("/etc/hostname").ok()?))
+        .or_else(|| command_output("hostname", &[]))
+}
+
+fn git_sha() -> Option<String> {
+    ["GIT_COMMIT", "GITHUB_SHA", "CI_COMMIT_SHA"]
+        .iter()
+        .find_map(|var| non_empty(std::env::var(var).ok()?))
+        .or_else(|| command_output("git", &["rev-parse", "HEAD"]))
+}
@@ -0,0 +1,874 @@
+//! Running scenarios in other processes, possibly on other machines.
+//!
+//! A coordinator ([`DistributedRunner`]) parses features as usual, but instead of running
+//! scenarios itself, it hands them to workers ([`WorkerRunner`]) one at a time. A worker is the
+//! same test binary, started with `--worker`. It parses the same features, runs the scenarios it
+//! is given, and sends back their outcomes. The coordinator rebuilds the outcomes and broadcasts
+//! them as events, so reporters can't tell the difference.
+//!
+//! The protocol is one JSON object per line. The coordinator sends
+//! `{"scenario": KEY, "feature": FEATURE, "rule": RULE, "index": INDEX}`, where `KEY` is the
+//! scenario's [`Component::key`] and `FEATURE` is its feature's. `RULE` is the index of the rule
+//! the scenario is in, or `null`, and `INDEX` is the index of the scenario within its rule or
+//! feature. Scenarios are found by position rather than by key, since two scenarios can have the
+//! same name. The worker answers with a line starting with `zuke-worker: `, followed by
+//! `{"scenario": KEY, "outcome": OUTCOME}` or `{"scenario": KEY, "error": MESSAGE}`. Other lines
+//! from the worker are passed along to stderr. The worker exits at the end of its input.
+
+use super::parts::{self, RunResult};
+use super::Runner;
+use crate::component::{Component, ComponentKind};
+use crate::context::OpenContext;
+use crate::event::Event;
+use crate::extra_options;
+use crate::options::{parse_duration, TestOptions};
+use crate::outcome::{Outcome, Verdict};
+use anyhow::Context as _;
+use async_broadcast as broadcast;
+use async_std::channel;
+use async_std::io::{stdin, stdout, BufReader};
+use async_std::net::{TcpListener, TcpStream};
+use async_std::process;
+use async_std::task;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use clap::{App, Arg};
+use futures::channel::{mpsc, oneshot};
+use futures::future::{join_all, FutureExt};
+use futures::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Marks lines from a worker that belong to the protocol
+const PREFIX: &str = "zuke-worker: ";
+
+/// Options that coordinate workers, and shouldn't be passed along to them. These take a value.
+const COORDINATOR_OPTIONS: &[&str] = &["--workers", "--listen", "--listen-timeout"];
+
+/// How long to wait for a worker to connect to `--listen`, by default
+const LISTEN_TIMEOUT: &str = "60s";
+
+/// Flags that shouldn't be passed along to workers. Workers can't use the terminal.
+const COORDINATOR_FLAGS: &[&str] = &["--watch", "--debug", "--interactive", "--isolate-scenarios"];
+
+#[extra_options]
+fn distributed_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("workers")
+            .long("workers")
+            .value_name("COUNT")
+            .takes_value(true)
+            .validator(|v| match v.parse::<usize>() {
+                Ok(_) => Ok(()),
+                Err(_) => Err(String::from("Must be a number")),
+            })
+            .help("Run scenarios in this many worker processes"),
+    )
+    .arg(
+        Arg::with_name("listen")
+            .long("listen")
+            .value_name("HOST:PORT")
+            .takes_value(true)
+            .help("Accept workers over TCP, started elsewhere with --worker HOST:PORT"),
+    )
+    .arg(
+        Arg::with_name("listen_timeout")
+            .long("listen-timeout")
+            .value_name("DURATION")
+            .takes_value(true)
+            .requires("listen")
+            .validator(|v| parse_duration(&v).map(|_| ()).map_err(|e| e.to_string()))
+            .help("With --listen, give up if no worker is connected for this long [default: 60s]"),
+    )
+    .arg(
+        Arg::with_name("isolate_scenarios")
+            .long("isolate-scenarios")
+            .conflicts_with("listen")
+            .help("Run each scenario in a new process, so that a crash only fails that scenario"),
+    )
+    .arg(
+        Arg::with_name("worker")
+            .long("worker")
+            .value_name("HOST:PORT")
+            .takes_value(true)
+            .conflicts_with_all(&["workers", "listen", "isolate_scenarios"])
+            .help("Run as a worker for a coordinator at HOST:PORT, or over stdin/stdout if -"),
+    )
+}
+
+/// Run the tests as a worker, if `--worker` was given, or as a coordinator, if `--workers`,
+/// `--listen`, or `--isolate-scenarios` were given. Otherwise, use `runner`.
+pub(crate) fn choose_runner(runner: Box<dyn Runner>, options: &TestOptions) -> Box<dyn Runner> {
+    if let Some(address) = options.opts.value_of("worker") {
+        Box::new(WorkerRunner::new(address))
+    } else if ["workers", "listen", "isolate_scenarios"]
+        .iter()
+        .any(|name| options.opts.is_present(name))
+    {
+        Box::new(DistributedRunner::new())
+    } else {
+        runner
+    }
+}
+
+/// Where a scenario is: its feature's key, the index of its rule, if it's in one, and its index
+/// among the scenarios of its rule or feature. Unlike [`Component::key`], this is unique.
+struct Position {
+    feature: String,
+    rule: Option<usize>,
+    index: usize,
+}
+
+/// A unit of work for a worker: a scenario to run, and where to send the result
+struct WorkItem {
+    key: String,
+    position: Position,
+    reply: oneshot::Sender<anyhow::Result<Value>>,
+}
+
+/// A runner that hands scenarios to worker processes. Global, feature, and rule hooks run in the
+/// coordinator, around the whole feature. Workers run global hooks too, once each, and feature
+/// and rule hooks again around each scenario they're given, so feature and rule fixtures are set
+/// up for each scenario. Scenario and step hooks run only in the workers.
+///
+/// Workers are started locally with `--workers COUNT`, or connect over TCP to `--listen
+/// HOST:PORT` from wherever they're running. Remote workers must be started from the same test
+/// binary, with the same features and arguments. If neither option is given, one local worker is
+/// started per CPU. If no worker is connected for `--listen-timeout` (one minute, by default),
+/// the scenarios still waiting fail.
+///
+/// If a worker exits in the middle of a scenario, that scenario fails, and the rest go to other
+/// workers.
+///
+/// With `--isolate-scenarios`, each scenario gets a new worker process, so that steps that abort,
+/// leak, or corrupt global state can't affect other scenarios. `--workers` limits how many run at
+/// once.
+#[derive(Default)]
+pub struct DistributedRunner {
+    workers: Option<usize>,
+    listen: Option<String>,
+    listen_timeout: Option<Duration>,
+    isolate: bool,
+}
+
+impl DistributedRunner {
+    /// Create a new `DistributedRunner`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start this many local workers. `--workers` takes precedence.
+    pub fn workers(mut self, count: usize) -> Self {
+        self.workers = Some(count);
+        self
+    }
+
+    /// Accept workers over TCP at this address. `--listen` takes precedence.
+    pub fn listen<S: Into<String>>(mut self, address: S) -> Self {
+        self.listen = Some(address.into());
+        self
+    }
+
+    /// Give up on remote workers if none is connected for this long. `--listen-timeout` takes
+    /// precedence.
+    pub fn listen_timeout(mut self, timeout: Duration) -> Self {
+        self.listen_timeout = Some(timeout);
+        self
+    }
+
+    /// Run each scenario in a new worker process. Doesn't work with [`Self::listen`].
+    /// `--isolate-scenarios` also turns this on.
+    pub fn isolate_scenarios(mut self, isolate: bool) -> Self {
+        self.isolate = isolate;
+        self
+    }
+
+    async fn execute(
+        self,
+        global: Arc<Component>,
+        features: mpsc::Receiver<Outcome>,
+        events: broadcast::Sender<Event>,
+    ) -> anyhow::Result<()> {
+        let opts = &global.options().opts;
+        let listen = opts.value_of("listen").map(String::from).or(self.listen);
+        let listen_timeout = match opts.value_of("listen_timeout") {
+            Some(timeout) => parse_duration(timeout)?,
+            None => self
+                .listen_timeout
+                .map_or_else(|| parse_duration(LISTEN_TIMEOUT), Ok)?,
+        };
+        let isolate = self.isolate || opts.is_present("isolate_scenarios");
+        if isolate && listen.is_some() {
+            anyhow::bail!("Scenarios can't be isolated with remote workers");
+        }
+        let workers = match opts.value_of("workers") {
+            Some(count) => count.parse()?,
+            None => match (self.workers, &listen) {
+                (Some(count), _) => count,
+                (None, Some(_)) => 0,
+                (None, None) => num_cpus(),
+            },
+        };
+
+        let mut open = OpenContext::new_global(global.clone());
+        open.set_events(events.clone());
+        parts::begin_run(&mut open, &events).await?;
+
+        let (queue, pending) = channel::unbounded();
+        let pool = Arc::new(Pool {
+            pending,
+            live: AtomicUsize::new(0),
+            listening: listen.is_some(),
+            idle_since: parking_lot::Mutex::new(Some(Instant::now())),
+        });
+
+        let mut local = vec![];
+        for _ in 0..workers {
+            if isolate {
+                pool.join();
+                local.push(pool.clone().serve_isolated().boxed());
+                continue;
+            }
+            match spawn_worker() {
+                Ok(child) => {
+                    pool.join();
+                    local.push(pool.clone().serve_child(child).boxed());
+                }
+                Err(e) => eprintln!("Error: Could not start a worker: {:#}", e),
+            }
+        }
+        let local = task::spawn(join_all(local));
+
+        let listener = match listen {
+            Some(address) => {
+                let listener = TcpListener::bind(&address)
+                    .await
+                    .with_context(|| format!("Could not listen at {}", address))?;
+                task::spawn(pool.clone().watch(address, listen_timeout));
+                Some(task::spawn(pool.clone().accept(listener)))
+            }
+            None => None,
+        };
+        if workers == 0 && listener.is_none() {
+            pool.close();
+        }
+
+        let mut outcomes = vec![];
+        {
+            let mut features = features.fuse();
+            let mut pending_features = FuturesUnordered::new();
+            loop {
+                futures::select! {
+                    feat = features.select_next_some() => {
+                        let feature_open = open.with_feature(feat);
+                        pending_features.push(run_feature(feature_open, &queue, &events));
+                    },
+                    outcome = pending_features.select_next_some() => outcomes.push(outcome?),
+                    complete => break,
+                }
+            }
+        }
+
+        // Workers exit at the end of their input
+        drop(queue);
+        pool.close();
+        if let Some(listener) = listener {
+            listener.cancel().await;
+        }
+        local.await;
+
+        let aborted = global.options().aborted.is_set();
+        parts::finish_run(open, outcomes, aborted, &events).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Runner for DistributedRunner {
+    async fn run(
+        self: Box<Self>,
+        global: Arc<Component>,
+        features: mpsc::Receiver<Outcome>,
+        events: broadcast::Sender<Event>,
+    ) {
+        assert_eq!(global.kind(), ComponentKind::Global);
+        if let Err(e) = self.execute(global, features, events).await {
+            eprintln!("Error: {:#}", e);
+        }
+    }
+}
+
+fn num_cpus() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+async fn run_feature(
+    mut open: OpenContext,
+    queue: &channel::Sender<WorkItem>,
+    events: &broadcast::Sender<Event>,
+) -> RunResult {
+    parts::begin_group(&mut open, events).await?;
+
+    let feature = open.context.component().key();
+    let mut pending = FuturesUnordered::new();
+    for (r, rule) in open.with_rules().unwrap().into_iter().enumerate() {
+        pending.push(run_rule(rule, &feature, r, queue, events).boxed());
+    }
+    for (index, scenario) in open.with_scenarios().unwrap().into_iter().enumerate() {
+        let position = Position {
+            feature: feature.clone(),
+            rule: None,
+            index,
+        };
+        pending.push(dispatch(scenario, position, queue, events).boxed());
+    }
+
+    let mut outcomes = vec![];
+    while let Some(outcome) = pending.next().await {
+        outcomes.push(outcome?);
+    }
+
+    parts::finish_group(open, outcomes, events).await
+}
+
+async fn run_rule(
+    mut open: OpenContext,
+    feature: &str,
+    rule: usize,
+    queue: &channel::Sender<WorkItem>,
+    events: &broadcast::Sender<Event>,
+) -> RunResult {
+    parts::begin_group(&mut open, events).await?;
+
+    let pending = open
+        .with_scenarios()
+        .unwrap()
+        .into_iter()
+        .enumerate()
+        .map(|(index, s)| {
+            let position = Position {
+                feature: feature.to_string(),
+                rule: Some(rule),
+                index,
+            };
+            dispatch(s, position, queue, events)
+        });
+    let outcomes = join_all(pending)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    parts::finish_group(open, outcomes, events).await
+}
+
+/// Send a scenario to a worker and wait for its outcome. Scenarios that won't run anyway are
+/// handled here.
+async fn dispatch(
+    open: OpenContext,
+    position: Position,
+    queue: &channel::Sender<WorkItem>,
+    events: &broadcast::Sender<Event>,
+) -> RunResult {
+    let component = open.context.component().clone();
+    if !component.is_included()
+        || open.context.outcome().skipped()
+        || component.options().canceled.is_set()
+    {
+        return parts::run_scenario(open, events).await;
+    }
+    drop(open);
+
+    events.broadcast(Event::Started(component.clone())).await?;
+    let (reply, result) = oneshot::channel();
+    let item = WorkItem {
+        key: component.key(),
+        position,
+        reply,
+    };
+    let result = match queue.send(item).await {
+        Ok(()) => result
+            .await
+            .unwrap_or_else(|_| Err(anyhow::anyhow!("No workers are left"))),
+        Err(_) => Err(anyhow::anyhow!("No workers are left")),
+    };
+
+    let outcome = match result.and_then(|value| outcome_from_json(component.clone(), &value)) {
+        Ok(outcome) => {
+            broadcast_steps(&outcome, events).await?;
+            outcome
+        }
+        Err(e) => {
+            let mut outcome = Outcome::undecided(component);
+            outcome.set_err(e);
+            outcome
+        }
+    };
+
+    let outcome = Arc::new(outcome);
+    events.broadcast(Event::Finished(outcome.clone())).await?;
+    Ok(outcome)
+}
+
+/// Broadcast events for the steps of a scenario that ran elsewhere, including steps of each
+/// iteration, followed by the iteration itself.
+async fn broadcast_steps(
+    outcome: &Outcome,
+    events: &broadcast::Sender<Event>,
+) -> Result<(), broadcast::SendError<Event>> {
+    for child in outcome.children.iter() {
+        if child.kind() == ComponentKind::Step {
+            events
+                .broadcast(Event::Started(child.component().clone()))
+                .await?;
+            events.broadcast(Event::Finished(child.clone())).await?;
+        } else {
+            for step in child.children.iter() {
+                events
+                    .broadcast(Event::Started(step.component().clone()))
+                    .await?;
+                events.broadcast(Event::Finished(step.clone())).await?;
+            }
+            events
+                .broadcast(Event::IterationFinished(child.clone()))
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Workers waiting for scenarios
+struct Pool {
+    pending: channel::Receiver<WorkItem>,
+    live: AtomicUsize,
+    listening: bool,
+    /// When the last worker went away, if none are connected
+    idle_since: parking_lot::Mutex<Option<Instant>>,
+}
+
+impl Pool {
+    /// Stop handing out work. Scenarios still waiting fail.
+    fn close(&self) {
+        self.close_with("No workers are left");
+    }
+
+    fn close_with(&self, message: &str) {
+        self.pending.close();
+        while let Ok(item) = self.pending.try_recv() {
+            let _ = item.reply.send(Err(anyhow::anyhow!("{}", message)));
+        }
+    }
+
+    /// A worker has connected
+    fn join(&self) {
+        self.live.fetch_add(1, Ordering::SeqCst);
+        *self.idle_since.lock() = None;
+    }
+
+    /// A worker has gone away. Returns true if it was the last one.
+    fn leave(&self) -> bool {
+        let mut idle_since = self.idle_since.lock();
+        let last = self.live.fetch_sub(1, Ordering::SeqCst) == 1;
+        if last {
+            *idle_since = Some(Instant::now());
+        }
+        last
+    }
+
+    /// Close the pool if no remote worker is connected for `timeout`
+    async fn watch(self: Arc<Self>, address: String, timeout: Duration) {
+        while !self.pending.is_closed() {
+            let idle = *self.idle_since.lock();
+            match idle {
+                Some(since) if since.elapsed() >= timeout => {
+                    let message = format!(
+                        "No workers connected to {} for {:.1} s (--listen-timeout)",
+                        address,
+                        timeout.as_secs_f64()
+                    );
+                    return self.close_with(&message);
+                }
+                Some(since) => task::sleep(timeout.saturating_sub(since.elapsed())).await,
+                None => task::sleep(timeout).await,
+            }
+        }
+    }
+
+    async fn accept(self: Arc<Self>, listener: TcpListener) {
+        let mut incoming = listener.incoming();
+        while let Some(stream) = incoming.next().await {
+            match stream {
+                Ok(stream) => {
+                    self.join();
+                    let reader = BufReader::new(stream.clone());
+                    task::spawn(self.clone().serve(reader, stream));
+                }
+                Err(e) => eprintln!("Error: Could not accept a worker: {}", e),
+            }
+        }
+    }
+
+    async fn serve_child(self: Arc<Self>, mut child: process::Child) {
+        let input = child.stdin.take().unwrap();
+        let output = BufReader::new(child.stdout.take().unwrap());
+        self.serve(output, input).await;
+        let _ = child.status().await;
+    }
+
+    /// Start a new worker for each scenario, until there are no more. Counts as one worker in
+    /// `live`.
+    async fn serve_isolated(self: Arc<Self>) {
+        while let Ok(item) = self.pending.recv().await {
+            let result = match spawn_worker() {
+                Ok(child) => run_isolated(child, &item).await,
+                Err(e) => Err(e.context("Could not start a worker")),
+            };
+            let _ = item.reply.send(result);
+        }
+        self.live.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Hand scenarios to a worker until there are no more, or the worker goes away. The worker
+    /// should already be counted in `live`.
+    async fn serve<R, W>(self: Arc<Self>, reader: R, mut writer: W)
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut lines = reader.lines();
+
+        while let Ok(item) = self.pending.recv().await {
+            let result = exchange(&item, &mut lines, &mut writer).await;
+            let gone = result.is_err();
+            let _ = item.reply.send(result);
+            if gone {
+                break;
+            }
+        }
+
+        // Don't leave scenarios waiting for workers that will never come. Remote workers have
+        // until --listen-timeout to show up.
+        if self.leave() && !self.listening {
+            self.close();
+        }
+    }
+}
+
+/// Run one scenario in a new worker, and wait for it to exit
+async fn run_isolated(mut child: process::Child, item: &WorkItem) -> anyhow::Result<Value> {
+    let mut input = child.stdin.take().unwrap();
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    let result = exchange(item, &mut lines, &mut input).await;
+    drop(input);
+
+    let status = child.status().await?;
+    match result {
+        Err(e) if !status.success() => {
+            Err(e.context(format!("The worker stopped with {}", status)))
+        }
+        result => result,
+    }
+}
+
+/// Send a scenario to a worker and read back its outcome
+async fn exchange<L, W>(item: &WorkItem, lines: &mut L, writer: &mut W) -> anyhow::Result<Value>
+where
+    L: futures::Stream<Item = std::io::Result<String>> + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut request = json!({
+        "scenario": item.key,
+        "feature": item.position.feature,
+        "rule": item.position.rule,
+        "index": item.position.index,
+    })
+    .to_string();
+    request.push('\n');
+    writer
+        .write_all(request.as_bytes())
+        .await
+        .context("The worker went away")?;
+    writer.flush().await.context("The worker went away")?;
+
+    while let Some(line) = lines.next().await {
+        let line = line.context("The worker went away")?;
+        let message = match line.strip_prefix(PREFIX) {
+            Some(message) => message,
+            None => {
+                eprintln!("{}", line);
+                continue;
+            }
+        };
+
+        let mut response: Value = serde_json::from_str(message).context("Bad worker response")?;
+        if let Some(e) = response["error"].as_str() {
+            anyhow::bail!("{}", e);
+        }
+        return Ok(response["outcome"].take());
+    }
+
+    anyhow::bail!("The worker exited while running the scenario")
+}
+
+/// Start a worker from this test binary, talking over stdin and stdout
+fn spawn_worker() -> anyhow::Result<process::Child> {
+    let exe = std::env::current_exe()?;
+    let mut command = process::Command::new(exe);
+    command
+        .args(worker_args(std::env::args_os().skip(1)))
+        .arg("--worker")
+        .arg("-")
+        .stdin(process::Stdio::piped())
+        .stdout(process::Stdio::piped())
+        .kill_on_drop(true);
+    Ok(command.spawn()?)
+}
+
+/// This process's arguments, without the ones meant for the coordinator
+fn worker_args<I: IntoIterator<Item = OsString>>(args: I) -> Vec<OsString> {
+    let mut out = vec![];
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let (name, has_value) = match arg.to_str() {
+            Some(s) => (s.split('=').next().unwrap_or(s), s.contains('=')),
+            None => {
+                out.push(arg);
+                continue;
+            }
+        };
+
+        if COORDINATOR_FLAGS.contains(&name) {
+            continue;
+        } else if COORDINATOR_OPTIONS.contains(&name) {
+            if !has_value {
+                args.next();
+            }
+        } else {
+            out.push(arg);
+        }
+    }
+
+    out
+}
+
+/// A runner that takes scenarios from a coordinator. See [`DistributedRunner`]. Scenarios run one
+/// at a time; start more workers to run more at once.
+pub struct WorkerRunner {
+    address: String,
+}
+
+type Reader = Box<dyn AsyncBufRead + Unpin + Send>;
+type Writer = Box<dyn AsyncWrite + Unpin + Send>;
+
+impl WorkerRunner {
+    /// Create a new `WorkerRunner` for a coordinator at `address`, or for stdin and stdout if
+    /// `address` is `-`.
+    pub fn new<S: Into<String>>(address: S) -> Self {
+        Self {
+            address: address.into(),
+        }
+    }
+
+    async fn execute(
+        self,
+        global: Arc<Component>,
+        features: mpsc::Receiver<Outcome>,
+        events: broadcast::Sender<Event>,
+    ) -> anyhow::Result<()> {
+        let mut open = OpenContext::new_global(global);
+        open.set_events(events.clone());
+        parts::begin_run(&mut open, &events).await?;
+
+        // Features by key
+        let features: Vec<_> = features.collect().await;
+        let by_key: HashMap<_, _> = features
+            .iter()
+            .enumerate()
+            .map(|(f, feature)| (feature.component().key(), f))
+            .collect();
+
+        let (reader, mut writer): (Reader, Writer) = if self.address == "-" {
+            (Box::new(BufReader::new(stdin())), Box::new(stdout()))
+        } else {
+            let stream = TcpStream::connect(&self.address)
+                .await
+                .with_context(|| format!("Could not connect to {}", self.address))?;
+            (Box::new(BufReader::new(stream.clone())), Box::new(stream))
+        };
+
+        let mut outcomes = vec![];
+        let mut lines = reader.lines();
+        while let Some(line) = lines.next().await {
+            let response =
+                respond(&line?, &by_key, &features, &open, &mut outcomes, &events).await?;
+            writer.write_all(response.as_bytes()).await?;
+            writer.flush().await?;
+        }
+
+        parts::finish_run(open, outcomes, false, &events).await?;
+        Ok(())
+    }
+}
+
+/// Run the scenario a request asks for, and return the response line
+async fn respond(
+    request: &str,
+    by_key: &HashMap<String, usize>,
+    features: &[Outcome],
+    open: &OpenContext,
+    outcomes: &mut Vec<Arc<Outcome>>,
+    events: &broadcast::Sender<Event>,
+) -> anyhow::Result<String> {
+    let request: Value = serde_json::from_str(request).context("Bad request")?;
+    let key = request["scenario"].as_str().unwrap_or_default();
+    let rule = request["rule"].as_u64().map(|r| r as usize);
+    let index = request["index"].as_u64().map(|i| i as usize);
+    let feature = request["feature"]
+        .as_str()
+        .and_then(|f| by_key.get(f))
+        .map(|&f| features[f].component());
+
+    let response = match (feature, index) {
+        (Some(feature), Some(index)) if scenario_at(feature, rule, index).is_some() => {
+            let feature = Outcome::undecided(feature.clone());
+            let (feature, scenario) =
+                run_one(open.with_feature(feature), rule, index, events).await?;
+            outcomes.push(feature);
+            json!({ "scenario": key, "outcome": outcome_json(&scenario) })
+        }
+        _ => json!({ "scenario": key, "error": format!("No such scenario {:?}", key) }),
+    };
+
+    Ok(format!("{}{}\n", PREFIX, response))
+}
+
+#[async_trait]
+impl Runner for WorkerRunner {
+    async fn run(
+        self: Box<Self>,
+        global: Arc<Component>,
+        features: mpsc::Receiver<Outcome>,
+        events: broadcast::Sender<Event>,
+    ) {
+        assert_eq!(global.kind(), ComponentKind::Global);
+        if let Err(e) = self.execute(global, features, events).await {
+            eprintln!("Error: {:#}", e);
+        }
+    }
+}
+
+/// The scenario at `index` in `feature`, or in its rule at index `rule` if given
+fn scenario_at(
+    feature: &Arc<Component>,
+    rule: Option<usize>,
+    index: usize,
+) -> Option<Arc<Component>> {
+    let parent = match rule {
+        None => feature.clone(),
+        Some(r) => feature.with_rules().ok()?.into_iter().nth(r)?,
+    };
+    parent.with_scenarios().ok()?.into_iter().nth(index)
+}
+
+/// Run the scenario at `index` of a feature, in rule `rule` if given. Returns the outcomes of the
+/// feature and the scenario.
+async fn run_one(
+    mut feature: OpenContext,
+    rule: Option<usize>,
+    index: usize,
+    events: &broadcast::Sender<Event>,
+) -> Result<(Arc<Outcome>, Arc<Outcome>), broadcast::SendError<Event>> {
+    parts::begin_group(&mut feature, events).await?;
+
+    let (child, scenario) = match rule {
+        None => {
+            let open = feature.with_scenarios().unwrap().swap_remove(index);
+            let scenario = parts::run_scenario(open, events).await?;
+            (scenario.clone(), scenario)
+        }
+        Some(r) => {
+            let mut rule = feature.with_rules().unwrap().swap_remove(r);
+            parts::begin_group(&mut rule, events).await?;
+            let open = rule.with_scenarios().unwrap().swap_remove(index);
+            let scenario = parts::run_scenario(open, events).await?;
+            let rule = parts::finish_group(rule, vec![scenario.clone()], events).await?;
+            (rule, scenario)
+        }
+    };
+
+    let feature = parts::finish_group(feature, vec![child], events).await?;
+    Ok((feature, scenario))
+}
+
+/// Serialize an outcome and its children, for sending to a coordinator
+fn outcome_json(outcome: &Outcome) -> Value {
+    json!({
+        "kind": outcome.kind().to_string(),
+        "step": outcome.component().step().map(|s| format!("{} {}", s.keyword, s.value)),
+        "verdict": outcome.verdict.name(),
+        "reason": outcome.reason.as_ref().map(|e| format!("{:#}", e)),
+        "started": outcome.started.to_rfc3339(),
+        "ended": outcome.ended.to_rfc3339(),
+        "children": outcome.children.iter().map(|c| outcome_json(c)).collect::<Vec<_>>(),
+    })
+}
+
+/// Rebuild an outcome for `component` from [`outcome_json`]. Children are matched up with the
+/// component's steps in order. Steps run with [`crate::Context::run_step`] become synthetic steps.
+fn outcome_from_json(component: Arc<Component>, value: &Value) -> anyhow::Result<Outcome> {
+    let verdict = value["verdict"].as_str().unwrap_or_default().parse()?;
+    let mut outcome = Outcome::new(component.clone(), verdict);
+    outcome.reason = value["reason"].as_str().map(|r| anyhow::anyhow!("{}", r));
+    outcome.started = parse_time(&value["started"])?;
+    outcome.ended = parse_time(&value["ended"])?;
+
+    let mut steps = match component.kind() {
+        ComponentKind::Scenario => {
+            let mut steps = component.with_background()?;
+            steps.extend(component.with_steps()?);
+            steps.into_iter()
+        }
+        _ => vec![].into_iter(),
+    };
+
+    let children = value["children"].as_array().map(Vec::as_slice);
+    for child in children.unwrap_or_default() {
+        let child_component = match (component.kind(), child["kind"].as_str()) {
+            // An iteration of a repeated scenario
+            (ComponentKind::Scenario, Some("scenario")) => component.clone(),
+            (ComponentKind::Scenario, _) => steps
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("The worker ran more steps than expected"))?,
+            _ => {
+                let parent = component.step().unwrap();
+                let text = child["step"].as_str().unwrap_or_default();
+                let (keyword, value) = text.split_once(' ').unwrap_or((text, ""));
+                let mut step = parent.clone();
+                step.keyword = keyword.to_string();
+                step.value = value.to_string();
+                component.with_synthetic_step(step)?
+            }
+        };
+        let child = outcome_from_json(child_component, child)?;
+        outcome.children.push(Arc::new(child));
+    }
+
+    if outcome.verdict == Verdict::Undecided {
+        anyhow::bail!("The worker did not finish the scenario");
+    }
+    Ok(outcome)
+}
+
+pub(crate) fn parse_time(value: &Value) -> anyhow::Result<DateTime<Utc>> {
+    let time = value.as_str().unwrap_or_default();
+    Ok(DateTime::parse_from_rfc3339(time)
+        .with_context(|| format!("Bad time {:?}", time))?
+        .with_timezone(&Utc))
+}
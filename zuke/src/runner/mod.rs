@@ -1,14 +1,23 @@
 //! Test Runner
 
 use crate::component::Component;
+use crate::context::OpenContext;
 use crate::event::Event;
 use crate::outcome::Outcome;
 use async_broadcast as broadcast;
 use async_trait::async_trait;
 use futures::channel::mpsc;
+use std::any::type_name;
 use std::sync::Arc;
 
+mod distributed;
+mod partitioned;
+pub mod parts;
+mod sequential;
 mod standard;
+pub use distributed::*;
+pub use partitioned::*;
+pub use sequential::*;
 pub use standard::*;
 
 /// A runner consumes features from a [`crate::parser::Parser`], runs tests, and sends the outcomes
@@ -22,4 +31,20 @@ pub trait Runner: Send + Sync {
         features: mpsc::Receiver<Outcome>,
         events: broadcast::Sender<Event>,
     );
+
+    /// Run some of the features in a test run that something else began, and return their
+    /// outcomes. This is how a [`PartitionedRunner`] shares features between runners. `global` is
+    /// the test run's context, already through [`parts::begin_run`]. Stop early if the test run is
+    /// aborted, returning the outcomes so far.
+    ///
+    /// By default, a runner can't be used this way.
+    async fn run_features(
+        self: Box<Self>,
+        global: &OpenContext,
+        features: mpsc::Receiver<Outcome>,
+        events: broadcast::Sender<Event>,
+    ) -> anyhow::Result<Vec<Arc<Outcome>>> {
+        let _ = (global, features, events);
+        anyhow::bail!("{} can't run part of a test run", type_name::<Self>())
+    }
 }
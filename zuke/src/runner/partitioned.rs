@@ -0,0 +1,141 @@
+use super::parts;
+use super::Runner;
+use crate::component::{Component, ComponentKind};
+use crate::context::OpenContext;
+use crate::event::Event;
+use crate::outcome::Outcome;
+use async_broadcast as broadcast;
+use async_trait::async_trait;
+use futures::channel::mpsc;
+use futures::future::join_all;
+use futures::join;
+use futures::sink::SinkExt;
+use futures::stream::StreamExt;
+use std::sync::Arc;
+
+/// Decides whether a feature belongs to a partition. See [`PartitionedRunner`].
+pub type Partition = Box<dyn Fn(&Component) -> bool + Send + Sync>;
+
+/// A runner that shares features between other runners. Each feature goes to the runner of the
+/// first partition that accepts it, or else to the default runner. The runners run alongside each
+/// other, within one test run: global hooks and fixtures are shared, and their outcomes are
+/// merged into one.
+///
+/// Each runner must support [`Runner::run_features`], as [`super::StandardRunner`] and
+/// [`super::SequentialRunner`] do.
+///
+/// ```ignore
+/// // @serial features run one scenario at a time; everything else runs as usual
+/// let runner = PartitionedRunner::new(StandardRunner::new()).tagged("serial", SequentialRunner);
+/// ```
+///
+/// See also [`crate::ZukeBuilder::runner_for`].
+pub struct PartitionedRunner {
+    partitions: Vec<(Partition, Box<dyn Runner>)>,
+    default: Box<dyn Runner>,
+}
+
+impl PartitionedRunner {
+    /// Create a `PartitionedRunner` that gives features to `default` unless a partition accepts
+    /// them
+    pub fn new<T: Runner + 'static>(default: T) -> Self {
+        Self::with_partitions(Box::new(default), vec![])
+    }
+
+    pub(crate) fn with_partitions(
+        default: Box<dyn Runner>,
+        partitions: Vec<(Partition, Box<dyn Runner>)>,
+    ) -> Self {
+        Self {
+            partitions,
+            default,
+        }
+    }
+
+    /// Give features that `predicate` accepts to `runner`. Partitions are tried in the order they
+    /// were added.
+    pub fn partition<F, T>(mut self, predicate: F, runner: T) -> Self
+    where
+        F: Fn(&Component) -> bool + Send + Sync + 'static,
+        T: Runner + 'static,
+    {
+        self.partitions
+            .push((Box::new(predicate), Box::new(runner)));
+        self
+    }
+
+    /// Give features tagged `@tag` to `runner`
+    pub fn tagged<S: Into<String>, T: Runner + 'static>(self, tag: S, runner: T) -> Self {
+        let tag = tag.into();
+        self.partition(move |c| c.tags().any(|t| *t == tag), runner)
+    }
+}
+
+#[async_trait]
+impl Runner for PartitionedRunner {
+    async fn run(
+        self: Box<Self>,
+        global: Arc<Component>,
+        features: mpsc::Receiver<Outcome>,
+        events: broadcast::Sender<Event>,
+    ) {
+        assert_eq!(global.kind(), ComponentKind::Global);
+        let mut open = OpenContext::new_global(global);
+        open.set_events(events.clone());
+        if parts::begin_run(&mut open, &events).await.is_err() {
+            return;
+        }
+
+        // A runner that can't run a partition fails the test run
+        let outcomes = match self.run_features(&open, features, events.clone()).await {
+            Ok(outcomes) => outcomes,
+            Err(e) => {
+                open.context.outcome_mut().set_err(e);
+                vec![]
+            }
+        };
+        let aborted = open.context.options().aborted.is_set();
+        let _ = parts::finish_run(open, outcomes, aborted, &events).await;
+    }
+
+    async fn run_features(
+        self: Box<Self>,
+        global: &OpenContext,
+        mut features: mpsc::Receiver<Outcome>,
+        events: broadcast::Sender<Event>,
+    ) -> anyhow::Result<Vec<Arc<Outcome>>> {
+        let PartitionedRunner {
+            partitions,
+            default,
+        } = *self;
+
+        let mut predicates = vec![];
+        let mut senders = vec![];
+        let mut runs = vec![];
+        let everything: Partition = Box::new(|_| true);
+        for (predicate, runner) in partitions.into_iter().chain([(everything, default)]) {
+            let (tx, rx) = mpsc::channel(256);
+            predicates.push(predicate);
+            senders.push(tx);
+            runs.push(runner.run_features(global, rx, events.clone()));
+        }
+
+        // Closing the channels tells each runner there are no more features
+        let route = async move {
+            while let Some(feature) = features.next().await {
+                let i = predicates
+                    .iter()
+                    .position(|p| p(feature.component()))
+                    .unwrap_or(predicates.len() - 1);
+                let _ = senders[i].send(feature).await;
+            }
+        };
+
+        let (_, results) = join!(route, join_all(runs));
+        let mut outcomes = vec![];
+        for result in results {
+            outcomes.extend(result?);
+        }
+        Ok(outcomes)
+    }
+}
@@ -0,0 +1,388 @@
+//! Building blocks for runners. Each function drives one kind of component: it broadcasts events,
+//! runs fixture hooks, and produces an outcome. They leave scheduling up to the caller, so a custom
+//! [`super::Runner`] can decide what runs when, and in what order, without reimplementing the
+//! rest. See [`super::StandardRunner`] for an example that runs everything concurrently.
+//!
+//! A runner is expected to:
+//!
+//! 1. Call [`begin_run`] with a global context.
+//! 2. For each feature, call [`begin_group`]. Then, for each rule, call [`begin_group`], run its
+//!    scenarios with [`run_scenario`], and call [`finish_group`]. Run the feature's scenarios with
+//!    [`run_scenario`], and then call [`finish_group`] for the feature.
+//! 3. Call [`finish_run`] with the features' outcomes.
+
+use crate::component::{Component, ComponentKind};
+use crate::context::OpenContext;
+use crate::event::Event;
+use crate::interactive::debug_failed_step;
+use crate::outcome::{Outcome, Verdict};
+use crate::panic::PanicToError;
+use crate::step::StepError;
+use async_broadcast as broadcast;
+use async_std::task;
+use chrono::Utc;
+use futures::future::FutureExt;
+use std::sync::Arc;
+
+/// The result of running a component. The only error is a closed event channel.
+pub type RunResult = Result<Arc<Outcome>, broadcast::SendError<Event>>;
+
+/// Start the test run: broadcast the start event, then run pre-test hooks, tag plugins, and global
+/// before hooks. Global state is read-only afterward.
+pub async fn begin_run(
+    open: &mut OpenContext,
+    events: &broadcast::Sender<Event>,
+) -> Result<(), broadcast::SendError<Event>> {
+    assert_eq!(open.context.kind(), ComponentKind::Global);
+    let component = open.context.component().clone();
+    events.broadcast(Event::Started(component)).await?;
+    setup_run(open).await;
+    Ok(())
+}
+
+/// Run pre-test hooks, tag plugins, and global before hooks, then seal global state. Failures go
+/// on the test run's outcome.
+pub(crate) async fn setup_run(open: &mut OpenContext) {
+    for warning in open.context.options().warnings.clone() {
+        open.context.warn(warning);
+    }
+
+    // Pre-test hooks, then tag plugins.
+    let hooks = open.context.options().pre_test_hooks.clone();
+    let plugins = open.context.options().tag_plugins.clone();
+    for hook in hooks.iter() {
+        if let Err(e) = PanicToError::from(hook(&mut open.context)).await {
+            open.context
+                .outcome_mut()
+                .set_err(anyhow::anyhow!("Pre-test hook failed: {}", e));
+            break;
+        }
+    }
+    if open.context.outcome().passed_or_undecided() {
+        for plugin in plugins.iter() {
+            if let Err(e) = PanicToError::from((plugin.hook)(&mut open.context)).await {
+                open.context.outcome_mut().set_err(anyhow::anyhow!(
+                    "Tag plugin {} failed: {}",
+                    plugin.name,
+                    e
+                ));
+                break;
+            }
+        }
+    }
+
+    open.before_hooks().await;
+    open.context.seal_state();
+}
+
+/// Finish the test run with the outcomes of its features. If `aborted`, fixtures are abandoned
+/// rather than torn down.
+pub async fn finish_run(
+    mut open: OpenContext,
+    outcomes: Vec<Arc<Outcome>>,
+    aborted: bool,
+    events: &broadcast::Sender<Event>,
+) -> Result<(), broadcast::SendError<Event>> {
+    let mut outcome = if aborted {
+        open.abandon()
+    } else {
+        open.after_hooks().await;
+        open.finalize().await
+    };
+    for o in outcomes {
+        if hidden(&o) {
+            outcome.add_child_deselected(o);
+        } else if prune_outcomes(&outcome) {
+            outcome.add_child_pruned(o);
+        } else {
+            outcome.add_child(o);
+        }
+    }
+
+    // Pending steps only fail the test run in strict mode
+    if outcome.is_pending() && outcome.component().options().opts.is_present("strict") {
+        outcome.set_err(anyhow::anyhow!("Steps are pending (--strict)"));
+    }
+
+    let outcome = Arc::new(outcome);
+    events.broadcast(Event::Finished(outcome)).await?;
+    Ok(())
+}
+
+/// With `--prune-outcomes`, groups and the test run keep only the outcomes of children that
+/// failed. Reporters still see every outcome as it finishes.
+fn prune_outcomes(outcome: &Outcome) -> bool {
+    outcome
+        .component()
+        .options()
+        .opts
+        .is_present("prune_outcomes")
+}
+
+/// With `--hide-excluded`, excluded scenarios, and features and rules excluded by name, aren't
+/// broadcast, and their parents count them as deselected rather than keeping their outcomes.
+fn hidden(outcome: &Outcome) -> bool {
+    outcome.verdict == Verdict::Excluded
+        && (outcome.kind() == ComponentKind::Scenario || outcome.component().is_excluded())
+        && outcome
+            .component()
+            .options()
+            .opts
+            .is_present("hide_excluded")
+}
+
+/// Start a feature or rule: broadcast the start event and run before hooks.
+pub async fn begin_group(
+    open: &mut OpenContext,
+    events: &broadcast::Sender<Event>,
+) -> Result<(), broadcast::SendError<Event>> {
+    assert!(matches!(
+        open.context.kind(),
+        ComponentKind::Feature | ComponentKind::Rule
+    ));
+    if !hidden(open.context.outcome()) {
+        events
+            .broadcast(Event::Started(open.context.component().clone()))
+            .await?;
+    }
+    open.before_hooks().await;
+    Ok(())
+}
+
+/// Finish a feature or rule with the outcomes of its children: run after hooks, then broadcast
+/// the final outcome.
+pub async fn finish_group(
+    mut open: OpenContext,
+    outcomes: Vec<Arc<Outcome>>,
+    events: &broadcast::Sender<Event>,
+) -> RunResult {
+    open.after_hooks().await;
+    let outcome = open.context.outcome_mut();
+    for o in outcomes {
+        if hidden(&o) {
+            outcome.add_child_deselected(o);
+        } else if prune_outcomes(outcome) {
+            outcome.add_child_pruned(o);
+        } else {
+            outcome.add_child(o);
+        }
+    }
+
+    let outcome = Arc::new(open.finalize().await);
+    if !hidden(&outcome) {
+        events.broadcast(Event::Finished(outcome.clone())).await?;
+    }
+    Ok(outcome)
+}
+
+/// Run a scenario, including any repetitions, from start to finish.
+pub async fn run_scenario(mut open: OpenContext, events: &broadcast::Sender<Event>) -> RunResult {
+    assert_eq!(open.context.kind(), ComponentKind::Scenario);
+
+    // inclusion isn't late evaluated for scenarios
+    if !open.context.component().is_included() {
+        open.context.outcome_mut().set_excluded();
+    }
+    if hidden(open.context.outcome()) {
+        return Ok(Arc::new(open.finalize().await));
+    }
+
+    let component = open.context.component().clone();
+    events.broadcast(Event::Started(component.clone())).await?;
+
+    let repeat = match component.options().repeat {
+        Some(r) if !open.context.outcome().skipped() => r,
+        _ => {
+            let outcome = Arc::new(run_iteration(open, events).await?);
+            events.broadcast(Event::Finished(outcome.clone())).await?;
+            return Ok(prune_scenario(outcome));
+        }
+    };
+
+    // Each iteration gets its own scenario fixtures, hooks, and outcome, and becomes a child of
+    // the scenario's outcome. An iteration that fails doesn't stop the next from running.
+    for _ in 0..repeat.count {
+        let iteration = Arc::new(run_iteration(open.with_iteration(), events).await?);
+        events
+            .broadcast(Event::IterationFinished(iteration.clone()))
+            .await?;
+        let stop = (repeat.until_failure && iteration.failed())
+            || iteration.verdict == Verdict::Canceled
+            || component.options().canceled.is_set();
+        open.context.outcome_mut().add_child(iteration);
+        if stop {
+            break;
+        }
+    }
+
+    let outcome = Arc::new(open.finalize().await);
+    events.broadcast(Event::Finished(outcome.clone())).await?;
+    Ok(prune_scenario(outcome))
+}
+
+/// With `--prune-outcomes`, a scenario that didn't fail is pruned as soon as it's broadcast,
+/// rather than when its feature finishes, so that its steps can be dropped once reporters are done
+/// with them. Only its stats are kept.
+fn prune_scenario(outcome: Arc<Outcome>) -> Arc<Outcome> {
+    if prune_outcomes(&outcome) && !outcome.failed() {
+        Arc::new(outcome.pruned())
+    } else {
+        outcome
+    }
+}
+
+/// Run a scenario once, in its own task. The scenario's fixtures and hooks are set up and torn
+/// down. Repeated scenarios call this once for each iteration, using
+/// [`OpenContext::with_iteration`].
+pub async fn run_iteration(
+    open: OpenContext,
+    events: &broadcast::Sender<Event>,
+) -> Result<Outcome, broadcast::SendError<Event>> {
+    // spawn a task. This is the part that we want to be truly parallel, and we have less
+    // control over what the user ultimately runs. If they block a bit by accident, we don't
+    // want to grind to a halt everywhere.
+    let component = open.context.component().clone();
+    let abort_flag = component.options().aborted.clone();
+    let canceled = move || {
+        let mut outcome = Outcome::undecided(component);
+        outcome.set_err(StepError::cancel_with_message("Test run aborted").into());
+        Ok(outcome)
+    };
+    if abort_flag.is_set() {
+        return canceled();
+    }
+
+    let worker = task::spawn(scenario_worker(open, events.clone()));
+    let abort = abort_flag.wait().fuse();
+    futures::pin_mut!(abort);
+
+    // Check for an abort first, so that once the run is aborted, every scenario still running
+    // finishes as canceled.
+    futures::select_biased! {
+        () = abort => (),
+        outcome = worker.fuse() => return outcome,
+    };
+
+    // A blocking step may still be running on another thread, holding references into the
+    // scenario's context. The worker was dropped above, which detaches it rather than pulling the
+    // context out from under the step. Its fixtures are torn down if it ever finishes.
+    canceled()
+}
+
+async fn scenario_worker(
+    mut open: OpenContext,
+    events: broadcast::Sender<Event>,
+) -> Result<Outcome, broadcast::SendError<Event>> {
+    let component = open.context.component().clone();
+    assert_eq!(component.kind(), ComponentKind::Scenario);
+    open.before_hooks().await;
+
+    for step in component.with_background().unwrap() {
+        let outcome = run_step(&mut open, step, &events).await?;
+        open.context.outcome_mut().add_child(outcome);
+    }
+
+    for step in component.with_steps().unwrap() {
+        let outcome = run_step(&mut open, step, &events).await?;
+        open.context.outcome_mut().add_child(outcome);
+    }
+
+    // Reset to scenario level component before teardown
+    open.set_component(component);
+    open.after_hooks().await;
+    collect_scenario_artifacts(&mut open).await;
+    Ok(open.finalize().await)
+}
+
+/// If the scenario failed on its own account, such as in a hook, rather than because a step
+/// failed, collect artifacts for the scenario. Failed steps collect their own.
+pub(crate) async fn collect_scenario_artifacts(open: &mut OpenContext) {
+    let outcome = open.context.outcome();
+    if outcome.failed() && outcome.reason.is_some() {
+        open.collect_failure_artifacts().await;
+    }
+}
+
+/// Run one step of a scenario, including step hooks. Steps after a failure are skipped.
+pub async fn run_step(
+    open: &mut OpenContext,
+    component: Arc<Component>,
+    events: &broadcast::Sender<Event>,
+) -> RunResult {
+    events.broadcast(Event::Started(component.clone())).await?;
+    let outcome = Arc::new(execute_step(open, component).await);
+    events.broadcast(Event::Finished(outcome.clone())).await?;
+    Ok(outcome)
+}
+
+/// Run one step of a scenario, including step hooks, without broadcasting events
+pub(crate) async fn execute_step(open: &mut OpenContext, component: Arc<Component>) -> Outcome {
+    let vocab = open.context.options().vocab.clone();
+    let scenario = open.context.outcome();
+    let outcome = if scenario.skipped() {
+        // Skip with the same type (Excluded/Skipped), and the same cause
+        Outcome::with_parent(component.clone(), scenario)
+    } else if !scenario.passed_or_undecided() {
+        // Includes canceled and pending steps, so nothing else in the scenario runs.
+        Outcome::new(component.clone(), Verdict::Skipped)
+    } else if open.context.options().canceled.is_set() {
+        // Don't start new steps after cancellation. This is the only way to cancel a
+        // scenario made up of blocking steps.
+        Outcome::new(component.clone(), Verdict::Canceled)
+    } else {
+        Outcome::undecided(component)
+    };
+
+    // The step's outcome lives on the context while hooks run, so they can inspect or
+    // override it.
+    open.begin_step(outcome);
+    if open.context.outcome().is_undecided() {
+        open.before_hooks().await;
+
+        if open.context.outcome().passed_or_undecided() {
+            let result = match open.context.check_deadline() {
+                Ok(()) => vocab.execute(&mut open.context).await,
+                Err(e) => Err(e.into()),
+            };
+            let outcome = open.context.outcome_mut();
+            match result {
+                // Don't clobber a verdict set by a hook or the step itself
+                Ok(()) if !outcome.is_undecided() => {
+                    outcome.ended = Utc::now();
+                }
+                result => {
+                    outcome.set_result(result);
+                }
+            }
+            if open.context.outcome().failed() {
+                open.collect_failure_artifacts().await;
+            }
+            debug_failed_step(open, &vocab).await;
+            warn_if_slow(open.context.outcome_mut());
+        }
+
+        open.after_hooks().await;
+    }
+
+    open.end_step()
+}
+
+/// With `--warn-slow-step`, a step that passed, but took too long, passes with warnings instead
+fn warn_if_slow(outcome: &mut Outcome) {
+    let limit = match outcome.component().options().warn_slow_step {
+        Some(limit) => limit,
+        None => return,
+    };
+
+    let elapsed = (outcome.ended - outcome.started)
+        .to_std()
+        .unwrap_or_default();
+    if outcome.verdict == Verdict::Passed && elapsed > limit {
+        outcome.verdict = Verdict::PassedWithWarnings;
+        outcome.reason = Some(anyhow::anyhow!(
+            "Slow step: took {:.3} s, more than the {:.3} s limit (--warn-slow-step)",
+            elapsed.as_secs_f64(),
+            limit.as_secs_f64()
+        ));
+    }
+}
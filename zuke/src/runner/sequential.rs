@@ -0,0 +1,106 @@
+use super::parts::{self, RunResult};
+use super::Runner;
+use crate::component::{Component, ComponentKind};
+use crate::context::OpenContext;
+use crate::event::Event;
+use crate::outcome::Outcome;
+use async_broadcast as broadcast;
+use async_trait::async_trait;
+use futures::channel::mpsc;
+use futures::future::FutureExt;
+use futures::stream::StreamExt;
+use std::sync::Arc;
+
+/// A runner that runs one scenario at a time, in file order, finishing each feature before
+/// starting the next. Useful for features that can't share the machine with anything else, with a
+/// [`super::PartitionedRunner`].
+#[derive(Default)]
+pub struct SequentialRunner;
+
+impl SequentialRunner {
+    /// Create a new `SequentialRunner`
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Runner for SequentialRunner {
+    async fn run(
+        self: Box<Self>,
+        global: Arc<Component>,
+        features: mpsc::Receiver<Outcome>,
+        events: broadcast::Sender<Event>,
+    ) {
+        assert_eq!(global.kind(), ComponentKind::Global);
+        let mut open = OpenContext::new_global(global);
+        open.set_events(events.clone());
+        if parts::begin_run(&mut open, &events).await.is_err() {
+            return;
+        }
+
+        // Features that can't be run fail the test run, rather than leaving it unfinished
+        let outcomes = match self.run_features(&open, features, events.clone()).await {
+            Ok(outcomes) => outcomes,
+            Err(e) => {
+                open.context.outcome_mut().set_err(e);
+                vec![]
+            }
+        };
+        let aborted = open.context.options().aborted.is_set();
+        let _ = parts::finish_run(open, outcomes, aborted, &events).await;
+    }
+
+    async fn run_features(
+        self: Box<Self>,
+        global: &OpenContext,
+        mut features: mpsc::Receiver<Outcome>,
+        events: broadcast::Sender<Event>,
+    ) -> anyhow::Result<Vec<Arc<Outcome>>> {
+        let aborted = global.context.options().aborted.clone();
+        let mut outcomes = vec![];
+        while let Some(feature) = features.next().await {
+            // An abort drops the feature in progress
+            let run = run_feature(global.with_feature(feature), &events).fuse();
+            let abort = aborted.wait().fuse();
+            futures::pin_mut!(run, abort);
+            futures::select! {
+                outcome = run => outcomes.push(outcome?),
+                () = abort => break,
+            }
+        }
+        Ok(outcomes)
+    }
+}
+
+/// Run a feature, one rule or scenario at a time, in file order
+async fn run_feature(mut open: OpenContext, events: &broadcast::Sender<Event>) -> RunResult {
+    parts::begin_group(&mut open, events).await?;
+
+    let mut children = open.with_rules().unwrap();
+    children.extend(open.with_scenarios().unwrap());
+    children.sort_by_key(|c| c.context.component().line());
+
+    let mut outcomes = vec![];
+    for child in children {
+        let outcome = match child.context.kind() {
+            ComponentKind::Rule => run_rule(child, events).await?,
+            _ => parts::run_scenario(child, events).await?,
+        };
+        outcomes.push(outcome);
+    }
+
+    parts::finish_group(open, outcomes, events).await
+}
+
+/// Run a rule, one scenario at a time
+async fn run_rule(mut open: OpenContext, events: &broadcast::Sender<Event>) -> RunResult {
+    parts::begin_group(&mut open, events).await?;
+
+    let mut outcomes = vec![];
+    for scenario in open.with_scenarios().unwrap() {
+        outcomes.push(parts::run_scenario(scenario, events).await?);
+    }
+
+    parts::finish_group(open, outcomes, events).await
+}
@@ -1,263 +1,374 @@
+use super::parts::{self, RunResult};
 use super::Runner;
 use crate::component::{Component, ComponentKind};
 use crate::context::OpenContext;
 use crate::event::Event;
+use crate::extra_options;
 use crate::outcome::Outcome;
-use crate::panic::PanicToError;
+use crate::reporter::Baseline;
 use anyhow;
 use async_broadcast as broadcast;
-use async_std::task;
+use async_std::channel;
 use async_trait::async_trait;
+use clap::{App, Arg};
 use futures::channel::mpsc;
-use futures::future::join_all;
-use futures::stream::{FuturesUnordered, StreamExt};
+use futures::future::{join_all, Future, FutureExt};
+use futures::stream::{self, FuturesUnordered, StreamExt};
+use std::num::NonZeroUsize;
+use std::str::FromStr;
 use std::sync::Arc;
+use thiserror::Error;
+
+#[extra_options]
+fn schedule_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("schedule")
+            .long("schedule")
+            .value_name("POLICY")
+            .takes_value(true)
+            .possible_values(&["file-order", "failed-first", "slowest-first"])
+            .help("The order to start scenarios in, based on --baseline. Default is file-order."),
+    )
+    .arg(
+        Arg::with_name("lane_size")
+            .long("lane-size")
+            .value_name("COUNT")
+            .takes_value(true)
+            .validator(|v| match v.parse::<NonZeroUsize>() {
+                Ok(_) => Ok(()),
+                Err(_) => Err(String::from("Must be a positive number")),
+            })
+            .help("Run at most COUNT scenarios of each feature at a time"),
+    )
+}
 
-/// The standard test runner
-pub struct StandardRunner {}
-
-#[async_trait]
-impl Runner for StandardRunner {
-    async fn run(
-        self: Box<Self>,
-        global: Arc<Component>,
-        features: mpsc::Receiver<Outcome>,
-        events: broadcast::Sender<Event>,
-    ) {
-        assert_eq!(global.kind(), ComponentKind::Global);
-        let _ = self.execute(global, features, events).await;
-    }
+/// The order that [`StandardRunner`] starts scenarios in. Everything still runs concurrently, but
+/// scenarios that start first get a head start on threads for blocking steps. Scenarios that
+/// aren't in the baseline come after the rest, in file order.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Schedule {
+    /// The order they appear in the feature files
+    FileOrder,
+    /// Scenarios that failed in the baseline first, so that the test run fails fast
+    FailedFirst,
+    /// The slowest scenarios in the baseline first, so that they don't hold up the end of the
+    /// test run
+    SlowestFirst,
 }
 
-impl Default for StandardRunner {
+impl Default for Schedule {
     fn default() -> Self {
-        Self::new()
+        Self::FileOrder
     }
 }
 
-impl StandardRunner {
-    /// Create a new `StandardRunner`
-    pub fn new() -> Self {
-        Self {}
+/// A schedule name was not recognized
+#[derive(Error, Debug)]
+#[error("Unknown schedule {0:?}")]
+pub struct UnknownSchedule(pub String);
+
+impl FromStr for Schedule {
+    type Err = UnknownSchedule;
+
+    /// Parse a schedule from its `--schedule` name
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "file-order" => Ok(Self::FileOrder),
+            "failed-first" => Ok(Self::FailedFirst),
+            "slowest-first" => Ok(Self::SlowestFirst),
+            _ => Err(UnknownSchedule(s.to_string())),
+        }
     }
+}
 
-    async fn execute(
-        self,
-        global: Arc<Component>,
-        features: mpsc::Receiver<Outcome>,
-        events: broadcast::Sender<Event>,
-    ) -> anyhow::Result<()> {
-        let mut open = OpenContext::new_global(global);
-        let component = open.context.component().clone();
-        let mut outcomes = vec![];
-
-        events.broadcast(Event::Started(component)).await?;
-
-        // Pre-test hooks.
-        let hooks = open.context.options().pre_test_hooks.clone();
-        for hook in hooks.iter() {
-            if let Err(e) = PanicToError::from(hook(&mut open.context)).await {
-                open.context
-                    .outcome_mut()
-                    .set_err(anyhow::anyhow!("Pre-test hook failed: {}", e));
-                break;
-            }
-        }
+/// Puts components in the order given by a [`Schedule`], and limits how many run at once
+struct Scheduler {
+    schedule: Schedule,
+    baseline: Option<Arc<Baseline>>,
+    lane_size: Option<NonZeroUsize>,
+}
 
-        open.before_hooks().await;
+impl Scheduler {
+    fn is_file_order(&self) -> bool {
+        self.schedule == Schedule::FileOrder || self.baseline.is_none()
+    }
 
-        {
-            let mut features = features.fuse();
-            let mut pending_features = FuturesUnordered::new();
-            loop {
-                futures::select! {
-                    feat = features.select_next_some() => {
-                        let feature_open = open.with_feature(feat);
-                        let fut = self.run_feature(feature_open, &events);
-                        pending_features.push(fut);
-                    },
-                    outcome = pending_features.select_next_some() => {
-                        match outcome {
-                            Err(e) => return Err(e.into()),
-                            Ok(o) => outcomes.push(o),
-                        };
-                    },
-                    complete => break,
+    /// Lower ranks start first. A feature or rule ranks as its first scenario.
+    fn rank(&self, component: &Component) -> i64 {
+        if component.kind() != ComponentKind::Scenario {
+            let mut scenarios = component.with_scenarios().unwrap_or_default();
+            if component.kind() == ComponentKind::Feature {
+                for rule in component.with_rules().unwrap_or_default() {
+                    scenarios.extend(rule.with_scenarios().unwrap_or_default());
                 }
             }
+            return scenarios
+                .iter()
+                .map(|s| self.rank(s))
+                .min()
+                .unwrap_or(i64::MAX);
         }
 
-        open.after_hooks().await;
-        let mut outcome = open.finalize().await;
-        for o in outcomes {
-            outcome.add_child(o);
+        let entry = match self.baseline.as_ref().and_then(|b| b.get(&component.key())) {
+            Some(entry) => entry,
+            None => return i64::MAX,
+        };
+        match self.schedule {
+            Schedule::FileOrder => 0,
+            Schedule::FailedFirst if entry.verdict.failed() => 0,
+            Schedule::FailedFirst => 1,
+            Schedule::SlowestFirst => -entry.duration.num_milliseconds(),
         }
-
-        let outcome = Arc::new(outcome);
-        events.broadcast(Event::Finished(outcome)).await?;
-
-        Ok(())
     }
 
-    async fn run_feature(
-        &self,
-        mut open: OpenContext,
-        events: &broadcast::Sender<Event>,
-    ) -> Result<Arc<Outcome>, broadcast::SendError<Event>> {
-        assert_eq!(open.context.kind(), ComponentKind::Feature);
-        let component = open.context.component().clone();
-        let mut outcomes = vec![];
+    /// Sort contexts into the order they should start in. The sort is stable, so ties stay in file
+    /// order.
+    fn sort(&self, contexts: &mut Vec<OpenContext>) {
+        if !self.is_file_order() {
+            contexts.sort_by_cached_key(|o| self.rank(o.context.component()));
+        }
+    }
+}
 
-        events.broadcast(Event::Started(component.clone())).await?;
+/// A feature's lane: a bounded queue that its scenarios wait in for a turn to run
+struct Lane {
+    put: channel::Sender<()>,
+    take: channel::Receiver<()>,
+}
 
-        open.before_hooks().await;
+impl Lane {
+    fn new(size: NonZeroUsize) -> Self {
+        let (put, take) = channel::bounded(size.get());
+        // One turn for each place in the lane. The channel has room for all of them.
+        for _ in 0..size.get() {
+            let _ = put.try_send(());
+        }
+        Self { put, take }
+    }
 
-        {
-            let mut pending_rules = open
-                .with_rules()
-                .unwrap()
-                .into_iter()
-                .map(|r| self.run_rule(r, events))
-                .collect::<FuturesUnordered<_>>();
-            let mut pending_scenarios = open
-                .with_scenarios()
-                .unwrap()
-                .into_iter()
-                .map(|s| self.run_scenario(s, events))
-                .collect::<FuturesUnordered<_>>();
+    /// Wait for a turn, then run `fut`
+    async fn run<F: Future>(lane: Option<&Self>, fut: F) -> F::Output {
+        let lane = match lane {
+            Some(lane) => lane,
+            None => return fut.await,
+        };
+        let _ = lane.take.recv().await;
+        let output = fut.await;
+        let _ = lane.put.send(()).await;
+        output
+    }
+}
 
-            loop {
-                let outcome = futures::select! {
-                    r = pending_rules.select_next_some() => r,
-                    r = pending_scenarios.select_next_some() => r,
-                    complete => break,
-                }?;
+/// Features and rules tagged `@ordered` run their scenarios one at a time, in file order
+fn is_ordered(open: &OpenContext) -> bool {
+    open.context.tags().any(|t| t == "ordered")
+}
 
-                outcomes.push(outcome);
-            }
-        }
+/// The standard test runner. It runs everything concurrently, starting scenarios in the order
+/// given by its [`Schedule`].
+///
+/// Each feature gets its own lane. By default, a lane has no limit, but with `--lane-size`, only
+/// that many of the feature's scenarios run at a time, while features still run alongside each
+/// other. Features and rules tagged `@ordered` run one scenario at a time, in file order,
+/// regardless of the schedule. Their rules and scenarios inherit the tag.
+#[derive(Default)]
+pub struct StandardRunner {
+    schedule: Schedule,
+    lane_size: Option<NonZeroUsize>,
+}
 
-        open.after_hooks().await;
-        for o in outcomes {
-            open.context.outcome_mut().add_child(o);
+#[async_trait]
+impl Runner for StandardRunner {
+    async fn run(
+        self: Box<Self>,
+        global: Arc<Component>,
+        features: mpsc::Receiver<Outcome>,
+        events: broadcast::Sender<Event>,
+    ) {
+        assert_eq!(global.kind(), ComponentKind::Global);
+        let mut open = OpenContext::new_global(global);
+        open.set_events(events.clone());
+        if parts::begin_run(&mut open, &events).await.is_err() {
+            return;
         }
 
-        let outcome = Arc::new(open.finalize().await);
-        events.broadcast(Event::Finished(outcome.clone())).await?;
-        Ok(outcome)
+        // Features that can't be run fail the test run, rather than leaving it unfinished
+        let outcomes = match self.run_features(&open, features, events.clone()).await {
+            Ok(outcomes) => outcomes,
+            Err(e) => {
+                open.context.outcome_mut().set_err(e);
+                vec![]
+            }
+        };
+        let aborted = open.context.options().aborted.is_set();
+        let _ = parts::finish_run(open, outcomes, aborted, &events).await;
     }
 
-    async fn run_rule(
-        &self,
-        mut open: OpenContext,
-        events: &broadcast::Sender<Event>,
-    ) -> Result<Arc<Outcome>, broadcast::SendError<Event>> {
-        assert_eq!(open.context.kind(), ComponentKind::Rule);
-
-        events
-            .broadcast(Event::Started(open.context.component().clone()))
-            .await?;
-        open.before_hooks().await;
+    async fn run_features(
+        self: Box<Self>,
+        global: &OpenContext,
+        features: mpsc::Receiver<Outcome>,
+        events: broadcast::Sender<Event>,
+    ) -> anyhow::Result<Vec<Arc<Outcome>>> {
+        // A bad baseline is reported by the reporters that use it
+        let options = global.context.options();
+        let scheduler = Scheduler {
+            schedule: match options.opts.value_of("schedule") {
+                Some(name) => name.parse()?,
+                None => self.schedule,
+            },
+            baseline: Baseline::from_options(options).ok().flatten(),
+            lane_size: match options.opts.value_of("lane_size") {
+                Some(size) => Some(size.parse()?),
+                None => self.lane_size,
+            },
+        };
+        let mut outcomes = vec![];
 
-        let outcomes;
+        // An abort drops any features still in progress. Their scenarios will notice the abort
+        // on their own.
+        let abort_flag = options.aborted.clone();
         {
-            let pending = open
-                .with_scenarios()
-                .unwrap()
-                .into_iter()
-                .map(|s| self.run_scenario(s, events));
+            let run_features = async {
+                // Features can only be put in order once they've all been parsed
+                let mut features = if scheduler.is_file_order() {
+                    features.boxed()
+                } else {
+                    let mut all: Vec<_> = features.collect().await;
+                    all.sort_by_cached_key(|f| scheduler.rank(f.component()));
+                    stream::iter(all).boxed()
+                }
+                .fuse();
+                let mut pending_features = FuturesUnordered::new();
+                loop {
+                    futures::select! {
+                        feat = features.select_next_some() => {
+                            let feature_open = global.with_feature(feat);
+                            let fut = run_feature(feature_open, &scheduler, &events);
+                            pending_features.push(fut);
+                        },
+                        outcome = pending_features.select_next_some() => {
+                            match outcome {
+                                Err(e) => return Err(e),
+                                Ok(o) => outcomes.push(o),
+                            };
+                        },
+                        complete => break,
+                    }
+                }
+                Ok(())
+            }
+            .fuse();
+            let abort = abort_flag.wait().fuse();
+            futures::pin_mut!(run_features, abort);
 
-            outcomes = join_all(pending)
-                .await
-                .into_iter()
-                .filter_map(Result::ok)
-                .collect::<Vec<_>>();
+            futures::select! {
+                result = run_features => result?,
+                () = abort => (),
+            }
         }
 
-        open.after_hooks().await;
-        for o in outcomes {
-            open.context.outcome_mut().add_child(o);
-        }
+        Ok(outcomes)
+    }
+}
 
-        let outcome = Arc::new(open.finalize().await);
-        events.broadcast(Event::Finished(outcome.clone())).await?;
-        Ok(outcome)
+impl StandardRunner {
+    /// Create a new `StandardRunner`
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    async fn run_scenario(
-        &self,
-        mut open: OpenContext,
-        events: &broadcast::Sender<Event>,
-    ) -> Result<Arc<Outcome>, broadcast::SendError<Event>> {
-        assert_eq!(open.context.kind(), ComponentKind::Scenario);
+    /// Set the order to start scenarios in. `--schedule` takes precedence.
+    pub fn schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
 
-        // inclusion isn't late evaluated for scenarios
-        if !open.context.component().is_included() {
-            open.context.outcome_mut().set_excluded();
-        }
+    /// Run at most `size` scenarios of each feature at a time. `--lane-size` takes precedence.
+    pub fn lane_size(mut self, size: NonZeroUsize) -> Self {
+        self.lane_size = Some(size);
+        self
+    }
+}
 
-        let component = open.context.component();
-        events.broadcast(Event::Started(component.clone())).await?;
+async fn run_feature(
+    mut open: OpenContext,
+    scheduler: &Scheduler,
+    events: &broadcast::Sender<Event>,
+) -> RunResult {
+    assert_eq!(open.context.kind(), ComponentKind::Feature);
+    let mut outcomes = vec![];
+
+    parts::begin_group(&mut open, events).await?;
+
+    {
+        let ordered = is_ordered(&open);
+        let lane = scheduler.lane_size.map(Lane::new);
+        let lane = lane.as_ref();
+
+        // Rules and scenarios start in the order they're pushed
+        let mut children = open.with_rules().unwrap();
+        children.extend(open.with_scenarios().unwrap());
+        children.sort_by_key(|c| c.context.component().line());
+        if !ordered {
+            scheduler.sort(&mut children);
+        }
 
-        // spawn a task. This is the part that we want to be truly parallel, and we have less
-        // control over what the user ultimately runs. If they block a bit by accident, we don't
-        // want to grind to a halt everywhere.
-        let outcome = task::spawn(Self::scenario_worker(open, events.clone())).await?;
+        let pending = children
+            .into_iter()
+            .map(|child| match child.context.kind() {
+                ComponentKind::Rule => run_rule(child, scheduler, lane, events).boxed(),
+                _ => Lane::run(lane, parts::run_scenario(child, events)).boxed(),
+            });
 
-        let outcome = Arc::new(outcome);
-        events.broadcast(Event::Finished(outcome.clone())).await?;
-        Ok(outcome)
+        if ordered {
+            for child in pending {
+                outcomes.push(child.await?);
+            }
+        } else {
+            let mut pending = pending.collect::<FuturesUnordered<_>>();
+            while let Some(outcome) = pending.next().await {
+                outcomes.push(outcome?);
+            }
+        }
     }
 
-    async fn scenario_worker(
-        mut open: OpenContext,
-        events: broadcast::Sender<Event>,
-    ) -> Result<Outcome, broadcast::SendError<Event>> {
-        let component = open.context.component().clone();
-        assert_eq!(component.kind(), ComponentKind::Scenario);
-        open.before_hooks().await;
-
-        for step in component.with_background().unwrap() {
-            open.set_component(step);
-            let outcome = Self::run_step(&mut open, &events).await?;
-            open.context.outcome_mut().add_child(outcome);
-        }
+    parts::finish_group(open, outcomes, events).await
+}
 
-        for step in component.with_steps().unwrap() {
-            open.set_component(step);
-            let outcome = Self::run_step(&mut open, &events).await?;
-            open.context.outcome_mut().add_child(outcome);
+async fn run_rule(
+    mut open: OpenContext,
+    scheduler: &Scheduler,
+    lane: Option<&Lane>,
+    events: &broadcast::Sender<Event>,
+) -> RunResult {
+    assert_eq!(open.context.kind(), ComponentKind::Rule);
+
+    parts::begin_group(&mut open, events).await?;
+
+    let mut outcomes = vec![];
+    {
+        let ordered = is_ordered(&open);
+        let mut scenarios = open.with_scenarios().unwrap();
+        if !ordered {
+            scheduler.sort(&mut scenarios);
         }
+        let pending = scenarios
+            .into_iter()
+            .map(|s| Lane::run(lane, parts::run_scenario(s, events)));
 
-        // Reset to scenario level component before teardown
-        open.set_component(component);
-        open.after_hooks().await;
-        Ok(open.finalize().await)
-    }
-
-    async fn run_step(
-        open: &mut OpenContext,
-        events: &broadcast::Sender<Event>,
-    ) -> Result<Arc<Outcome>, broadcast::SendError<Event>> {
-        // TODO: This is the most important place to handle cancellation
-
-        let vocab = open.context.options().vocab.clone();
-        let component = open.context.component().clone();
-        let mut outcome = Outcome::with_parent(component.clone(), open.context.outcome());
-        events.broadcast(Event::Started(component)).await?;
-
-        if open.context.outcome().skipped() {
-            // Skip with the same type (Excluded/Skipped)
-            outcome.verdict = open.context.outcome().verdict;
-        } else if open.context.outcome().failed() {
-            outcome.set_skip();
+        if ordered {
+            for scenario in pending {
+                outcomes.extend(scenario.await.ok());
+            }
         } else {
-            let result = vocab.execute(&mut open.context).await;
-            outcome.set_result(result);
+            outcomes = join_all(pending)
+                .await
+                .into_iter()
+                .filter_map(Result::ok)
+                .collect::<Vec<_>>();
         }
-
-        let outcome = Arc::new(outcome);
-        events.broadcast(Event::Finished(outcome.clone())).await?;
-        Ok(outcome)
     }
+
+    parts::finish_group(open, outcomes, events).await
 }
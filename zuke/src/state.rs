@@ -0,0 +1,84 @@
+//! Values computed once at the start of the test run and shared with everything after it
+//!
+//! `#[before_all]` hooks can compute a value, such as a dataset loaded from disk, and store it
+//! with [`crate::Context::set_state`]. Any later context can read it with
+//! [`crate::Context::state`]. This is lighter than a fixture when there's nothing to set up per
+//! component and nothing to tear down.
+//!
+//! ```ignore
+//! struct Dataset(Vec<Record>);
+//!
+//! #[before_all]
+//! async fn load_dataset(context: &mut Context) -> anyhow::Result<()> {
+//!     let records = load_records("tests/data/records.json")?;
+//!     context.set_state(Dataset(records))?;
+//!     Ok(())
+//! }
+//!
+//! #[given("a record from the dataset")]
+//! fn a_record(context: &mut Context) -> anyhow::Result<()> {
+//!     let dataset = context.state::<Dataset>().expect("Dataset wasn't loaded");
+//!     // ...
+//! }
+//! ```
+//!
+//! State is read-only once the global before hooks have finished.
+
+use parking_lot::RwLock;
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// State can't be set
+#[derive(Error, Debug)]
+pub enum StateError {
+    /// State was set after the global before hooks finished
+    #[error("Can't set {0}: global state is read-only after the test run starts")]
+    ReadOnly(&'static str),
+    /// State was set from a context other than the global one
+    #[error("Can't set {0}: global state can only be set from before_all hooks")]
+    NotGlobal(&'static str),
+}
+
+/// Values shared by every context in a test run, one per type. See the [module docs](self).
+#[derive(Default)]
+pub struct GlobalState {
+    values: RwLock<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>,
+    sealed: AtomicBool,
+}
+
+impl GlobalState {
+    /// An empty, writable state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store a value, replacing any value of the same type
+    pub fn set<T: Any + Send + Sync>(&self, value: T) -> Result<(), StateError> {
+        if self.is_sealed() {
+            return Err(StateError::ReadOnly(type_name::<T>()));
+        }
+        self.values
+            .write()
+            .insert(TypeId::of::<T>(), Arc::new(value));
+        Ok(())
+    }
+
+    /// The stored value of type `T`, if any
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        let value = self.values.read().get(&TypeId::of::<T>())?.clone();
+        value.downcast::<T>().ok()
+    }
+
+    /// Make the state read-only. Runners call this once the global before hooks have run.
+    pub fn seal(&self) {
+        self.sealed.store(true, Ordering::SeqCst);
+    }
+
+    /// Is the state read-only?
+    pub fn is_sealed(&self) -> bool {
+        self.sealed.load(Ordering::SeqCst)
+    }
+}
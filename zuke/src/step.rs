@@ -1,6 +1,9 @@
 //! Misc things for implementing steps
 
 use crate::outcome::Verdict;
+use gherkin_rust::StepType;
+use regex::Regex;
+use std::cell::Cell;
 use std::error::Error;
 use std::fmt;
 
@@ -10,6 +13,22 @@ use std::fmt;
 /// Internally, all errors returned from a step, hook, or fixture are transparently converted to
 /// this type, so users only need to return it directly if they want to skip or cause some other
 /// action to happen.
+///
+/// Steps and hooks may return `Result<T, E>` for any `E: Into<StepError>`, so a custom error type
+/// can choose its own verdict:
+///
+/// ```ignore
+/// impl From<NotSupported> for StepError {
+///     fn from(e: NotSupported) -> Self {
+///         StepError::skip_with_reason(e)
+///     }
+/// }
+///
+/// #[given("a feature that may not be supported")]
+/// fn maybe_supported() -> Result<(), NotSupported> {
+///     // ...
+/// }
+/// ```
 pub struct StepError {
     /// The type of "error" (failed, skipped, etc.)
     /// Even Passed is allowed, though it wouldn't make much sense.
@@ -46,7 +65,68 @@ impl Error for StepError {
 
 impl From<anyhow::Error> for StepError {
     fn from(e: anyhow::Error) -> Self {
-        Self::fail_with_reason(e)
+        // Don't lose the verdict if it's already a StepError. If context was added to it, keep
+        // the whole error as the reason, rather than unwrapping it and dropping the context.
+        let root: &(dyn Error + Send + Sync + 'static) = e.as_ref();
+        if root.is::<StepError>() {
+            return e.downcast().unwrap();
+        }
+        match e.downcast_ref::<StepError>() {
+            Some(inner) => Self {
+                verdict: inner.verdict,
+                reason: Some(e),
+            },
+            None => Self::fail_with_reason(e),
+        }
+    }
+}
+
+impl From<Verdict> for StepError {
+    fn from(verdict: Verdict) -> Self {
+        Self {
+            verdict,
+            reason: None,
+        }
+    }
+}
+
+/// Converts errors returned from steps and hooks, preferring [`StepError`] over
+/// `anyhow::Error`. Used by our macros; not meant to be used directly.
+#[doc(hidden)]
+pub struct ConvertError<E>(Cell<Option<E>>);
+
+impl<E> ConvertError<E> {
+    pub fn new(e: E) -> Self {
+        Self(Cell::new(Some(e)))
+    }
+
+    fn take(&self) -> E {
+        self.0.take().expect("Error already converted")
+    }
+}
+
+/// Chosen first, if the error can be converted to [`StepError`]
+#[doc(hidden)]
+pub trait ViaStepError {
+    fn into_anyhow(&self) -> anyhow::Error;
+}
+
+impl<E: Into<StepError>> ViaStepError for ConvertError<E> {
+    fn into_anyhow(&self) -> anyhow::Error {
+        let e: StepError = self.take().into();
+        e.into()
+    }
+}
+
+/// Chosen otherwise, for any error anyhow can handle
+#[doc(hidden)]
+pub trait ViaAnyhow {
+    fn into_anyhow(&self) -> anyhow::Error;
+}
+
+impl<E: Into<anyhow::Error>> ViaAnyhow for &ConvertError<E> {
+    fn into_anyhow(&self) -> anyhow::Error {
+        self.take().into()
     }
 }
 
@@ -125,6 +205,30 @@ impl StepError {
         }
     }
 
+    /// Mark the component as pending (not yet implemented), with no message
+    pub fn pending() -> Self {
+        Self {
+            verdict: Verdict::Pending,
+            reason: None,
+        }
+    }
+
+    /// Mark the component as pending, with an error message
+    pub fn pending_with_reason<E: Into<anyhow::Error>>(reason: E) -> Self {
+        Self {
+            verdict: Verdict::Pending,
+            reason: Some(reason.into()),
+        }
+    }
+
+    /// Mark the component as pending, with a string message
+    pub fn pending_with_message<M: Into<String>>(message: M) -> Self {
+        Self {
+            verdict: Verdict::Pending,
+            reason: Some(anyhow::anyhow!(message.into())),
+        }
+    }
+
     /// Cancel with no message
     pub fn cancel() -> Self {
         Self {
@@ -150,6 +254,23 @@ impl StepError {
     }
 }
 
+/// Build the regex for a step defined at run time, such as by a plugin or remote step server, from
+/// its pattern and keyword: `given`, `when`, `then`, or `None` to match any of them.
+pub(crate) fn step_regex(
+    pattern: &str,
+    keyword: Option<&str>,
+) -> anyhow::Result<(Regex, Option<StepType>)> {
+    let (prefix, keyword) = match keyword {
+        Some("given") => ("Given ", Some(StepType::Given)),
+        Some("when") => ("When ", Some(StepType::When)),
+        Some("then") => ("Then ", Some(StepType::Then)),
+        None => ("(?:Given|When|Then) ", None),
+        Some(other) => anyhow::bail!("Unknown step keyword {:?}", other),
+    };
+    let regex = Regex::new(&format!("^(?i){}{}$", prefix, pattern))?;
+    Ok((regex, keyword))
+}
+
 /// Fail the component. Note that `anyhow::bail!` or simply returning an error will work equally
 /// well for failing.
 #[macro_export]
@@ -187,6 +308,20 @@ macro_rules! warn {
     }};
 }
 
+/// Mark the component as pending, for steps that aren't finished being implemented. Pending
+/// scenarios don't fail the test run unless `--strict` is given.
+#[macro_export]
+macro_rules! pending {
+    () => {{
+        return ::std::result::Result::Err($crate::step::StepError::pending().into());
+    }};
+    ($msg:tt) => {{
+        return ::std::result::Result::Err(
+            $crate::step::StepError::pending_with_reason(anyhow::anyhow!($msg)).into(),
+        );
+    }};
+}
+
 /// Cancel the component.
 #[macro_export]
 macro_rules! cancel {
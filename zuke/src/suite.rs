@@ -0,0 +1,234 @@
+//! Run several test suites as one
+//!
+//! A [`SuiteRunner`] runs several [`Zuke`] instances, each with its own features and command
+//! line, and reports on them together. Their features appear under one test run, so a monorepo
+//! with BDD tests in several crates can have one report for all of them.
+//!
+//! ```no_run
+//! # async fn f() -> anyhow::Result<()> {
+//! use zuke::{SuiteRunner, ZukeBuilder};
+//!
+//! let mut payments = ZukeBuilder::new();
+//! payments.feature_path("payments/tests/features");
+//! let mut accounts = ZukeBuilder::new();
+//! accounts.feature_path("accounts/tests/features");
+//!
+//! SuiteRunner::new()
+//!     .title("Everything")
+//!     .suite("payments", payments)
+//!     .suite_with_args("accounts", accounts, ["--strict"])
+//!     .run()
+//!     .await
+//! # }
+//! ```
+//!
+//! Each suite runs in turn, with its own global fixtures and hooks. Component keys start with the
+//! suite's name, as in `payments::tests/features/refund.feature::Refund`, so that suites with the
+//! same feature paths can be told apart. The reporters are the [`SuiteRunner`]'s own: reporters
+//! added to the suites aren't used. The command line of the [`SuiteRunner`] chooses them, as it
+//! would for [`Zuke`].
+
+use crate::component::{Component, ComponentKind};
+use crate::event::Event;
+use crate::options::TestOptionsBuilder;
+use crate::outcome::{MultiError, Outcome, Stat, Verdict};
+use crate::reporter::command_line::check_reporters;
+use crate::reporter::{CommandLineReporter, Reporter, Supervised};
+use crate::top::{handle_ctrlc, CancelMethod, ConfigError, Zuke, ZukeBuilder};
+use anyhow;
+use async_broadcast as broadcast;
+use clap::App;
+use futures::future::join_all;
+use futures::join;
+use futures::stream::StreamExt;
+use std::ffi::OsString;
+use std::sync::Arc;
+
+/// Runs several test suites, and reports on them as one test run. See the [module docs](self).
+pub struct SuiteRunner {
+    cancel_method: CancelMethod,
+    options_builder: TestOptionsBuilder,
+    suites: Vec<(String, ZukeBuilder, Vec<OsString>)>,
+    reporters: Vec<Box<dyn Reporter>>,
+}
+
+impl Default for SuiteRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SuiteRunner {
+    /// Create a new [`SuiteRunner`], with no suites
+    pub fn new() -> Self {
+        Self {
+            cancel_method: CancelMethod::CtrlC,
+            options_builder: TestOptionsBuilder::new(),
+            suites: vec![],
+            reporters: vec![],
+        }
+    }
+
+    /// Set the title of the combined test run
+    pub fn title<T: Into<String>>(&mut self, title: T) -> &mut Self {
+        self.options_builder.title(title);
+        self
+    }
+
+    /// Set the method of cancelling the test run. Every suite shares it.
+    pub fn cancel_method(&mut self, method: CancelMethod) -> &mut Self {
+        self.cancel_method = method;
+        self
+    }
+
+    /// Add a suite, with no command line options of its own
+    pub fn suite<N: Into<String>>(&mut self, name: N, builder: ZukeBuilder) -> &mut Self {
+        self.suite_with_args(name, builder, Vec::<OsString>::new())
+    }
+
+    /// Add a suite, with its own command line options. `args` doesn't include the program name.
+    pub fn suite_with_args<N, I, T>(&mut self, name: N, builder: ZukeBuilder, args: I) -> &mut Self
+    where
+        N: Into<String>,
+        I: IntoIterator<Item = T>,
+        T: Into<OsString>,
+    {
+        let args = args.into_iter().map(Into::into).collect();
+        self.suites.push((name.into(), builder, args));
+        self
+    }
+
+    /// Add a reporter. If none are added, a [`CommandLineReporter`] is used. The reporter runs
+    /// [`Supervised`].
+    pub fn reporter<T: Reporter + 'static>(&mut self, reporter: T) -> &mut Self {
+        self.reporters
+            .push(Box::new(Supervised::new(Box::new(reporter))));
+        self
+    }
+
+    /// Run every suite, with reporters chosen by the program's command line
+    pub async fn run(&mut self) -> anyhow::Result<()> {
+        self.run_from(std::env::args_os()).await
+    }
+
+    /// As [`Self::run`], but with the command line given by `iter`
+    pub async fn run_from<I, T>(&mut self, iter: I) -> anyhow::Result<()>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let mut obj = Self::new();
+        std::mem::swap(&mut obj, self);
+        let SuiteRunner {
+            cancel_method,
+            mut options_builder,
+            suites,
+            mut reporters,
+        } = obj;
+
+        if suites.is_empty() {
+            return Err(ConfigError::NoFeatures.into());
+        }
+
+        let handler = cancel_method.apply(&mut options_builder);
+        let options = Arc::new(options_builder.build_with_app_from(App::new("Zuke"), iter)?);
+        check_reporters(&options)?;
+        if let Some(forceful) = handler {
+            handle_ctrlc(forceful, &options)?;
+        }
+
+        let mut zukes = vec![];
+        for (name, mut builder, args) in suites {
+            builder
+                .cancel_method(CancelMethod::Shared(options.canceled.clone()))
+                .abort_flag(options.aborted.clone())
+                .suite(&name);
+            let args = std::iter::once(OsString::from(&name)).chain(args);
+            let zuke = builder
+                .build_with_app_from(App::new("Zuke"), args)
+                .map_err(|e| e.context(format!("Suite {}", name)))?;
+            zukes.push((name, zuke));
+        }
+
+        if reporters.is_empty() {
+            reporters.push(Box::new(CommandLineReporter::default()));
+        }
+
+        let global = Component::global(options);
+        let (events_tx, events_rx) = broadcast::broadcast(256);
+        let reporters = join_all(
+            reporters
+                .into_iter()
+                .map(|r| r.report(global.clone(), events_rx.clone())),
+        );
+        drop(events_rx);
+
+        let (_, results) = join!(run_suites(global, zukes, events_tx), reporters);
+        results.into_iter().find(Result::is_err).unwrap_or(Ok(()))
+    }
+}
+
+/// Run each suite in turn. Their features are reported as part of `global`, in place of their
+/// own test runs.
+async fn run_suites(
+    global: Arc<Component>,
+    zukes: Vec<(String, Zuke)>,
+    events: broadcast::Sender<Event>,
+) {
+    let mut outcome = Outcome::undecided(global.clone());
+    let mut errors = MultiError::new();
+    let _ = events.broadcast(Event::Started(global)).await;
+
+    for (name, zuke) in zukes {
+        let suite_global = Component::global(zuke.options().clone());
+        let (suite_tx, mut suite_rx) = broadcast::broadcast(256);
+
+        let forward = async {
+            let mut finished = None;
+            while let Some(event) = suite_rx.next().await {
+                match event {
+                    Event::Started(c) if c.kind() == ComponentKind::Global => (),
+                    Event::Finished(o) if o.kind() == ComponentKind::Global => finished = Some(o),
+                    event => {
+                        let _ = events.broadcast(event).await;
+                    }
+                }
+            }
+            finished
+        };
+        let (_, finished) = join!(zuke.run_tests(suite_global, suite_tx), forward);
+
+        let suite = match finished {
+            Some(suite) => suite,
+            None => {
+                outcome.verdict = outcome.verdict.max(Verdict::Canceled);
+                errors.push(anyhow::anyhow!("Suite {} did not finish", name));
+                continue;
+            }
+        };
+        outcome.verdict = outcome.verdict.max(suite.verdict);
+        outcome.children.extend(suite.children.iter().cloned());
+        for (kind, stat) in suite.pruned.iter() {
+            outcome
+                .pruned
+                .entry(*kind)
+                .or_insert_with(Stat::default)
+                .merge(stat);
+        }
+        if let Some(reason) = &suite.reason {
+            errors.push(anyhow::anyhow!("Suite {}: {:#}", name, reason));
+        }
+        for warning in suite.warnings.iter() {
+            outcome
+                .warnings
+                .push(anyhow::anyhow!("Suite {}: {:#}", name, warning));
+        }
+    }
+
+    if let Err(e) = errors.into_result() {
+        outcome.reason = Some(e);
+    }
+    outcome.ended = chrono::Utc::now();
+    let _ = events.broadcast(Event::Finished(Arc::new(outcome))).await;
+    events.close();
+}
@@ -0,0 +1,185 @@
+//! Parsing tags with arguments, such as `@retry(3)`, `@timeout(30s)`, or `@lock(db)`
+//!
+//! Arguments are separated by commas. Numbers become [`TagArg::Number`], and anything else
+//! becomes [`TagArg::Text`]. Text can be quoted to include commas or parentheses:
+//! `@owner("payments, EU")`. Inside quotes, `\"` and `\\` are escapes.
+//!
+//! ```ignore
+//! async fn before(&self, context: &mut Context) -> anyhow::Result<()> {
+//!     if let Some(args) = parse_tag("retry", context.tags()) {
+//!         let count = args?.number(0).unwrap_or(1.0);
+//!         // ...
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::options::parse_duration;
+use std::time::Duration;
+use thiserror::Error;
+
+/// A tag's arguments couldn't be parsed
+#[derive(Error, Debug)]
+#[error("Bad tag @{tag}: {problem}")]
+pub struct BadTag {
+    /// The whole tag, without the `@`
+    pub tag: String,
+    /// What was wrong with it
+    pub problem: String,
+}
+
+/// One argument to a tag
+#[derive(Debug, Clone, PartialEq)]
+pub enum TagArg {
+    /// An unquoted number
+    Number(f64),
+    /// Anything else
+    Text(String),
+}
+
+impl TagArg {
+    /// The argument, if it's text
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            TagArg::Text(s) => Some(s),
+            TagArg::Number(_) => None,
+        }
+    }
+
+    /// The argument as a number
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            TagArg::Number(n) => Some(*n),
+            TagArg::Text(_) => None,
+        }
+    }
+}
+
+/// The arguments of a tag, in order. A tag without parentheses has none.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Args(pub Vec<TagArg>);
+
+impl Args {
+    /// The number of arguments
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Were there no arguments?
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The argument at `index`, if there is one
+    pub fn get(&self, index: usize) -> Option<&TagArg> {
+        self.0.get(index)
+    }
+
+    /// The argument at `index`, if it's text
+    pub fn str(&self, index: usize) -> Option<&str> {
+        self.get(index)?.as_str()
+    }
+
+    /// The argument at `index`, if it's a number
+    pub fn number(&self, index: usize) -> Option<f64> {
+        self.get(index)?.as_number()
+    }
+
+    /// The argument at `index` as a duration: a number of seconds, or text with a unit such as
+    /// `500ms` or `2m`. `None` if it's missing or isn't a duration.
+    pub fn duration(&self, index: usize) -> Option<Duration> {
+        match self.get(index)? {
+            TagArg::Number(n) => Duration::try_from_secs_f64(*n).ok(),
+            TagArg::Text(s) => parse_duration(s).ok(),
+        }
+    }
+}
+
+/// Parse the first tag called `name`, with or without arguments, from `tags` (as given by
+/// [`crate::Context::tags`], without the `@`). `None` if there's no such tag.
+pub fn parse_tag<'a, I>(name: &str, tags: I) -> Option<Result<Args, BadTag>>
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    parse_tags(name, tags).into_iter().next()
+}
+
+/// As [`parse_tag`], but for every tag called `name`, such as a scenario with several `@lock`s
+pub fn parse_tags<'a, I>(name: &str, tags: I) -> Vec<Result<Args, BadTag>>
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    tags.into_iter()
+        .filter_map(|tag| {
+            let rest = tag.strip_prefix(name)?;
+            if rest.is_empty() {
+                return Some(Ok(Args::default()));
+            }
+            let inner = rest.strip_prefix('(')?;
+            let result = match inner.strip_suffix(')') {
+                Some(inner) => parse_args(inner),
+                None => Err(String::from("missing ')'")),
+            };
+            Some(result.map_err(|problem| BadTag {
+                tag: tag.clone(),
+                problem,
+            }))
+        })
+        .collect()
+}
+
+/// Parse the text between the parentheses
+fn parse_args(text: &str) -> Result<Args, String> {
+    let mut args = vec![];
+    let mut chars = text.chars().peekable();
+    if text.trim().is_empty() {
+        return Ok(Args(args));
+    }
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let arg = if chars.next_if_eq(&'"').is_some() {
+            let mut s = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some(c @ ('"' | '\\')) => s.push(c),
+                        Some(c) => return Err(format!("unknown escape '\\{}'", c)),
+                        None => return Err(String::from("unterminated quote")),
+                    },
+                    Some(c) => s.push(c),
+                    None => return Err(String::from("unterminated quote")),
+                }
+            }
+            TagArg::Text(s)
+        } else {
+            let mut s = String::new();
+            while let Some(c) = chars.next_if(|c| *c != ',') {
+                if c == '"' {
+                    return Err(String::from("quotes must surround the whole argument"));
+                }
+                s.push(c);
+            }
+            let s = s.trim();
+            if s.is_empty() {
+                return Err(String::from("empty argument"));
+            }
+            // Only things that look like numbers, so that e.g. `nan` stays text
+            let numeric = s.starts_with(|c: char| c.is_ascii_digit() || "+-.".contains(c));
+            match s.parse::<f64>() {
+                Ok(n) if numeric => TagArg::Number(n),
+                _ => TagArg::Text(s.to_string()),
+            }
+        };
+        args.push(arg);
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.next() {
+            None => return Ok(Args(args)),
+            Some(',') => (),
+            Some(c) => return Err(format!("expected ',' but found '{}'", c)),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Fixture to implement `@lock(...)` and `@lock-shared(...)` tags
+
+use super::{parse_tags, TagArg};
+use crate::{ComponentKind, Context, Fixture, Scope};
+use async_std::sync::{Condvar, Mutex};
+use async_trait::async_trait;
+use futures::future::FutureExt;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A fixture that implements `@lock(<name>)` and `@lock-shared(<name>)` tags, such as
+/// `@lock(database)`. Scenarios that lock the same name don't run at the same time, while
+/// everything else stays parallel. Scenarios with `@lock-shared` on a name may run alongside each
+/// other, but not alongside a scenario with `@lock` on it.
+///
+/// Locks are held from before the scenario's first step until after its last. A tag on a feature
+/// or rule applies to each of its scenarios separately. A scenario with several tags takes all of
+/// its locks at once, so scenarios can't deadlock by taking them in a different order.
+pub struct Lock {
+    holders: Mutex<HashMap<String, Holders>>,
+    released: Condvar,
+    /// By the address of the scenario's component, which no other running scenario shares. IDs
+    /// can be shared by inline features with the same name.
+    held: Mutex<HashMap<usize, Vec<(String, bool)>>>,
+}
+
+/// Who holds a lock
+#[derive(Default)]
+struct Holders {
+    shared: usize,
+    exclusive: bool,
+}
+
+impl Holders {
+    fn available(&self, shared: bool) -> bool {
+        !self.exclusive && (shared || self.shared == 0)
+    }
+}
+
+/// Locks named by a component's tags, as (name, shared). Exclusive wins if a name is tagged both
+/// ways.
+fn requested_locks(context: &Context) -> anyhow::Result<Vec<(String, bool)>> {
+    let mut locks: HashMap<String, bool> = HashMap::new();
+    for (tag, shared) in [("lock-shared", true), ("lock", false)] {
+        for args in parse_tags(tag, context.tags()) {
+            let args = args?;
+            let name = match (args.len(), args.get(0)) {
+                (1, Some(TagArg::Text(name))) => name.clone(),
+                (1, Some(TagArg::Number(n))) => n.to_string(),
+                _ => anyhow::bail!("@{} takes a lock name, e.g. @{}(database)", tag, tag),
+            };
+            let entry = locks.entry(name).or_insert(shared);
+            *entry &= shared;
+        }
+    }
+    Ok(locks.into_iter().collect())
+}
+
+impl Lock {
+    /// Wait until all of the locks are available, then take them
+    async fn acquire(&self, locks: &[(String, bool)]) {
+        let mut holders = self.holders.lock().await;
+        while !locks.iter().all(|(name, shared)| {
+            holders
+                .get(name)
+                .map(|h| h.available(*shared))
+                .unwrap_or(true)
+        }) {
+            holders = self.released.wait(holders).await;
+        }
+
+        for (name, shared) in locks {
+            let h = holders.entry(name.clone()).or_default();
+            if *shared {
+                h.shared += 1;
+            } else {
+                h.exclusive = true;
+            }
+        }
+    }
+
+    async fn release(&self, locks: &[(String, bool)]) {
+        let mut holders = self.holders.lock().await;
+        for (name, shared) in locks {
+            if let Some(h) = holders.get_mut(name) {
+                if *shared {
+                    h.shared -= 1;
+                } else {
+                    h.exclusive = false;
+                }
+                if h.shared == 0 && !h.exclusive {
+                    holders.remove(name);
+                }
+            }
+        }
+        self.released.notify_all();
+    }
+}
+
+#[async_trait]
+impl Fixture for Lock {
+    const SCOPE: Scope = Scope::Global;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self {
+            holders: Mutex::new(HashMap::new()),
+            released: Condvar::new(),
+            held: Mutex::new(HashMap::new()),
+        })
+    }
+
+    async fn before(&self, context: &mut Context) -> anyhow::Result<()> {
+        if context.kind() != ComponentKind::Scenario {
+            return Ok(());
+        }
+
+        let locks = requested_locks(context)?;
+        if locks.is_empty() {
+            return Ok(());
+        }
+
+        // Waiting for a lock can take a while; don't hold up cancellation.
+        futures::select! {
+            () = self.acquire(&locks).fuse() => (),
+            e = context.interrupted().fuse() => return Err(e.into()),
+        }
+        self.held
+            .lock()
+            .await
+            .insert(Arc::as_ptr(context.component()) as usize, locks);
+        Ok(())
+    }
+
+    async fn after(&self, context: &mut Context) -> anyhow::Result<()> {
+        if context.kind() != ComponentKind::Scenario {
+            return Ok(());
+        }
+
+        let held = Arc::as_ptr(context.component()) as usize;
+        let locks = self.held.lock().await.remove(&held);
+        if let Some(locks) = locks {
+            self.release(&locks).await;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+//! Fixture to implement `@max-duration` tags
+
+use super::parse_tags;
+use crate::{extra_options, ComponentKind, Context, Fixture, Scope};
+use async_trait::async_trait;
+use clap::{App, Arg};
+use std::time::Duration;
+
+/// A fixture that implements `@max-duration(<duration>)` tags, such as `@max-duration(2s)`. A
+/// scenario that passes, but takes longer than that, fails. With `@max-duration(2s, warn)`, or
+/// with `--max-duration-warns`, it passes with warnings instead. A tag on a feature or rule
+/// applies to each of its scenarios separately. If there are several, the shortest wins.
+///
+/// Unlike `@timeout`, the scenario isn't stopped: it runs to the end, and then its time is
+/// checked.
+pub struct MaxDuration;
+
+#[extra_options]
+fn max_duration_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("max_duration_warns")
+            .long("max-duration-warns")
+            .help("Scenarios that take longer than their @max-duration pass with warnings"),
+    )
+}
+
+#[async_trait]
+impl Fixture for MaxDuration {
+    const SCOPE: Scope = Scope::Global;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn after(&self, context: &mut Context) -> anyhow::Result<()> {
+        if context.kind() != ComponentKind::Scenario || context.outcome().failed() {
+            return Ok(());
+        }
+
+        let mut limit: Option<Duration> = None;
+        let mut warn = context.options().opts.is_present("max_duration_warns");
+        for args in parse_tags("max-duration", context.tags()) {
+            let args = args?;
+            let max = match (args.len(), args.duration(0), args.str(1)) {
+                (1, Some(d), _) => d,
+                (2, Some(d), Some("warn")) => {
+                    warn = true;
+                    d
+                }
+                _ => anyhow::bail!("@max-duration takes a duration, e.g. @max-duration(2s)"),
+            };
+            limit = Some(limit.map_or(max, |l| l.min(max)));
+        }
+        let limit = match limit {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+
+        // The scenario hasn't ended yet, but its last step has. Outcomes use the real time, so a
+        // scenario without steps is measured against it, not a mock clock.
+        let outcome = context.outcome();
+        let ended = match outcome.children.iter().map(|c| c.ended).max() {
+            Some(ended) => ended,
+            None => chrono::Utc::now(),
+        };
+        let elapsed = (ended - outcome.started).to_std().unwrap_or_default();
+        if elapsed <= limit {
+            return Ok(());
+        }
+
+        let message = format!(
+            "Took {:.3} s, more than the {:.3} s limit (@max-duration)",
+            elapsed.as_secs_f64(),
+            limit.as_secs_f64()
+        );
+        if warn {
+            context.warn(message);
+            Ok(())
+        } else {
+            anyhow::bail!(message)
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Fixture to implement `@max-parallel(...)` tags
+
+use super::{parse_tags, TagArg};
+use crate::{ComponentKind, Context, Fixture, Scope};
+use async_std::sync::{Condvar, Mutex};
+use async_trait::async_trait;
+use futures::future::FutureExt;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A fixture that implements `@max-parallel(...)` tags, which limit how many scenarios run at
+/// once, while everything else stays parallel.
+///
+/// - `@max-parallel(<name>, <n>)`, such as `@max-parallel(gpu, 2)`: At most `n` scenarios with
+///   this tag, with the same name, run at the same time across the whole run. It may be on a
+///   feature, rule, or scenario.
+/// - `@max-parallel(<n>)` on a feature or rule: At most `n` of its scenarios run at the same time.
+///
+/// Each scenario counts against every limit it is tagged with, from before its first step until
+/// after its last. If the same name is given different limits, each scenario waits until fewer
+/// than its own limit are running. A scenario takes all of its slots at once, as with
+/// [`super::lock::Lock`].
+pub struct MaxParallel {
+    running: Mutex<HashMap<String, usize>>,
+    released: Condvar,
+    /// By the address of the scenario's component, which no other running scenario shares. IDs
+    /// can be shared by inline features with the same name.
+    held: Mutex<HashMap<usize, Vec<(String, usize)>>>,
+}
+
+const USAGE: &str = "@max-parallel takes a limit and an optional name, e.g. @max-parallel(gpu, 2)";
+
+fn limit(arg: Option<&TagArg>) -> anyhow::Result<usize> {
+    match arg {
+        Some(TagArg::Number(n)) if *n >= 1.0 && n.fract() == 0.0 => Ok(*n as usize),
+        _ => anyhow::bail!(USAGE),
+    }
+}
+
+/// Limits named by a component's tags, as (name, limit). Unnamed limits are named after the
+/// feature or rule they are on. The lowest limit for a name wins.
+fn requested_slots(context: &Context) -> anyhow::Result<Vec<(String, usize)>> {
+    let feature = context.feature().map(|f| match &f.path {
+        Some(path) => path.display().to_string(),
+        None => f.name.clone(),
+    });
+    let rule = context.rule().map(|r| &r.name);
+    let levels = [
+        (
+            context.feature().map(|f| &f.tags),
+            feature.as_ref().map(|f| format!("feature {}", f)),
+        ),
+        (
+            context.rule().map(|r| &r.tags),
+            rule.map(|r| format!("rule {}::{}", feature.as_deref().unwrap_or_default(), r)),
+        ),
+        (context.scenario().map(|s| &s.tags), None),
+    ];
+
+    let mut slots: HashMap<String, usize> = HashMap::new();
+    for (tags, owner) in levels {
+        for args in parse_tags("max-parallel", tags.into_iter().flatten()) {
+            let args = args?;
+            let (name, limit) = match (args.len(), args.get(0)) {
+                (1, _) => match &owner {
+                    Some(owner) => (owner.clone(), limit(args.get(0))?),
+                    None => anyhow::bail!("@max-parallel needs a name on a scenario: {}", USAGE),
+                },
+                (2, Some(TagArg::Text(name))) => (name.clone(), limit(args.get(1))?),
+                (2, Some(TagArg::Number(n))) => (n.to_string(), limit(args.get(1))?),
+                _ => anyhow::bail!(USAGE),
+            };
+            let entry = slots.entry(name).or_insert(limit);
+            *entry = (*entry).min(limit);
+        }
+    }
+    Ok(slots.into_iter().collect())
+}
+
+impl MaxParallel {
+    /// Wait until there is room under every limit, then take a slot in each
+    async fn acquire(&self, slots: &[(String, usize)]) {
+        let mut running = self.running.lock().await;
+        while !slots
+            .iter()
+            .all(|(name, limit)| running.get(name).copied().unwrap_or(0) < *limit)
+        {
+            running = self.released.wait(running).await;
+        }
+
+        for (name, _) in slots {
+            *running.entry(name.clone()).or_default() += 1;
+        }
+    }
+
+    async fn release(&self, slots: &[(String, usize)]) {
+        let mut running = self.running.lock().await;
+        for (name, _) in slots {
+            if let Some(count) = running.get_mut(name) {
+                *count -= 1;
+                if *count == 0 {
+                    running.remove(name);
+                }
+            }
+        }
+        self.released.notify_all();
+    }
+}
+
+#[async_trait]
+impl Fixture for MaxParallel {
+    const SCOPE: Scope = Scope::Global;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self {
+            running: Mutex::new(HashMap::new()),
+            released: Condvar::new(),
+            held: Mutex::new(HashMap::new()),
+        })
+    }
+
+    async fn before(&self, context: &mut Context) -> anyhow::Result<()> {
+        if context.kind() != ComponentKind::Scenario {
+            return Ok(());
+        }
+
+        let slots = requested_slots(context)?;
+        if slots.is_empty() {
+            return Ok(());
+        }
+
+        // Waiting for a slot can take a while; don't hold up cancellation.
+        futures::select! {
+            () = self.acquire(&slots).fuse() => (),
+            e = context.interrupted().fuse() => return Err(e.into()),
+        }
+        self.held
+            .lock()
+            .await
+            .insert(Arc::as_ptr(context.component()) as usize, slots);
+        Ok(())
+    }
+
+    async fn after(&self, context: &mut Context) -> anyhow::Result<()> {
+        if context.kind() != ComponentKind::Scenario {
+            return Ok(());
+        }
+
+        let held = Arc::as_ptr(context.component()) as usize;
+        let slots = self.held.lock().await.remove(&held);
+        if let Some(slots) = slots {
+            self.release(&slots).await;
+        }
+        Ok(())
+    }
+}
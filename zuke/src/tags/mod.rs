@@ -4,13 +4,36 @@
 
 use crate::{before_all, Context};
 use futures::future::{BoxFuture, FutureExt};
+pub mod args;
 pub mod fail;
+pub mod lock;
+pub mod max_duration;
+pub mod max_parallel;
+pub mod pause;
+pub mod quarantine;
 pub mod skip;
+pub mod slow;
+pub mod timeout;
+pub mod wip;
+
+pub use args::{parse_tag, parse_tags, Args, BadTag, TagArg};
 
 #[before_all]
 async fn add_default_tags(context: &mut Context) -> anyhow::Result<()> {
+    if !context.options().default_tags {
+        return Ok(());
+    }
+
     context.use_fixture::<skip::Skip>().await?;
     context.use_fixture::<fail::Fail>().await?;
+    context.use_fixture::<slow::Slow>().await?;
+    context.use_fixture::<timeout::Timeout>().await?;
+    context.use_fixture::<wip::Wip>().await?;
+    context.use_fixture::<pause::Pause>().await?;
+    context.use_fixture::<lock::Lock>().await?;
+    context.use_fixture::<max_parallel::MaxParallel>().await?;
+    context.use_fixture::<max_duration::MaxDuration>().await?;
+    context.use_fixture::<quarantine::Quarantine>().await?;
     Ok(())
 }
 
@@ -0,0 +1,43 @@
+//! Fixture to implement `@pause` tags
+
+use crate::interactive::pause;
+use crate::{Context, Fixture, Scope};
+use async_trait::async_trait;
+
+/// A fixture that implements `@pause` tags. With `--interactive`, scenarios tagged `@pause` wait
+/// for the user to press Enter before each step, to allow inspecting the system under test.
+/// Without `--interactive`, the tag does nothing.
+///
+/// Scenarios running in parallel take turns prompting.
+pub struct Pause;
+
+#[async_trait]
+impl Fixture for Pause {
+    const SCOPE: Scope = Scope::Global;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn before(&self, context: &mut Context) -> anyhow::Result<()> {
+        let step = match context.step() {
+            Some(s) => s,
+            None => return Ok(()),
+        };
+
+        if context.tags().any(|t| t == "pause") {
+            let scenario = context
+                .component()
+                .scenario()
+                .map(|s| s.name.as_str())
+                .unwrap_or_default();
+            let message = format!(
+                "Paused in scenario {:?} before:\n    {} {}",
+                scenario, step.keyword, step.value
+            );
+            pause(context, &message).await;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+//! Fixture to implement `@quarantined` tags and the quarantine file
+
+use crate::{extra_options, ComponentKind, Context, Fixture, Scope, Verdict};
+use anyhow::Context as _;
+use async_trait::async_trait;
+use clap::{App, Arg};
+use std::collections::HashSet;
+use std::fmt;
+
+/// A fixture for flaky scenarios. A scenario is quarantined if it, or its rule or feature, is
+/// tagged `@quarantined`, or if it's listed in the file given with `--quarantine`.
+///
+/// Quarantined scenarios still run, but if one fails it passes with warnings instead, so that the
+/// test run as a whole can still pass. The failure is kept as a [`Quarantined`] warning, which the
+/// plain reporter lists in a section of its own.
+///
+/// The quarantine file lists one scenario per line, either by [`crate::Component::key`] or by
+/// [`crate::Component::id`]. Blank lines and lines starting with `#` are ignored. Like any other
+/// option, it can be set in the configuration file:
+///
+/// ```toml
+/// [options]
+/// quarantine = "tests/quarantine.txt"
+/// ```
+pub struct Quarantine {
+    listed: HashSet<String>,
+}
+
+/// The warning left on a quarantined scenario that failed. Find it with
+/// `warning.downcast_ref::<Quarantined>()`.
+#[derive(Debug)]
+pub struct Quarantined;
+
+impl fmt::Display for Quarantined {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Failed, but quarantined")
+    }
+}
+
+#[extra_options]
+fn quarantine_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("quarantine")
+            .long("quarantine")
+            .value_name("FILE")
+            .takes_value(true)
+            .help(
+                "File listing quarantined scenarios, by key or ID, one per line. Failures in \
+                quarantined scenarios are reported as warnings.",
+            ),
+    )
+}
+
+impl Quarantine {
+    fn is_quarantined(&self, context: &Context) -> bool {
+        if context.tags().any(|t| t == "quarantined") {
+            return true;
+        }
+        let component = context.component();
+        self.listed.contains(&component.key()) || self.listed.contains(&component.id().to_string())
+    }
+}
+
+#[async_trait]
+impl Fixture for Quarantine {
+    const SCOPE: Scope = Scope::Global;
+
+    async fn setup(context: &mut Context) -> anyhow::Result<Self> {
+        let path = match context.options().opts.value_of("quarantine") {
+            Some(path) => path,
+            None => {
+                return Ok(Self {
+                    listed: HashSet::new(),
+                })
+            }
+        };
+
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read quarantine file {}", path))?;
+        let listed = text
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(String::from)
+            .collect();
+        Ok(Self { listed })
+    }
+
+    async fn after(&self, context: &mut Context) -> anyhow::Result<()> {
+        if context.component().kind() != ComponentKind::Scenario
+            || context.outcome().verdict != Verdict::Failed
+            || !self.is_quarantined(context)
+        {
+            return Ok(());
+        }
+
+        // Keep the reason it failed: its own, or else that of the step that failed
+        let outcome = context.outcome_mut();
+        let reason = match outcome.reason.take() {
+            Some(reason) => reason,
+            None => {
+                let step = outcome
+                    .children
+                    .iter()
+                    .find(|c| c.verdict == Verdict::Failed);
+                match step.and_then(|s| s.reason.as_ref()) {
+                    Some(reason) => anyhow::anyhow!("{:#}", reason),
+                    None => anyhow::anyhow!("A step failed"),
+                }
+            }
+        };
+        outcome.warnings.push(reason.context(Quarantined));
+        outcome.verdict = Verdict::PassedWithWarnings;
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+//! Fixture to implement `@slow` tags
+
+use crate::{extra_options, Context, Fixture, Scope};
+use async_trait::async_trait;
+use clap::{App, Arg};
+
+/// A fixture that implements `@slow` tags. Slow components are skipped unless `--include-slow` is
+/// given.
+pub struct Slow;
+
+#[extra_options]
+fn slow_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("include_slow")
+            .long("include-slow")
+            .help("Run features and scenarios tagged @slow"),
+    )
+}
+
+#[async_trait]
+impl Fixture for Slow {
+    const SCOPE: Scope = Scope::Global;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn before(&self, context: &mut Context) -> anyhow::Result<()> {
+        if context.component().step().is_some() {
+            return Ok(());
+        }
+
+        if context.options().opts.is_present("include_slow") {
+            return Ok(());
+        }
+
+        if context.tags().any(|t| t == "slow") {
+            zuke::skip!("Slow; use --include-slow to run");
+        }
+
+        Ok(())
+    }
+}
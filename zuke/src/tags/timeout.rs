@@ -0,0 +1,62 @@
+//! Fixture to implement `@timeout` tags
+
+use super::parse_tags;
+use crate::{ComponentKind, Context, Fixture, Scope};
+use anyhow::Context as _;
+use async_trait::async_trait;
+use std::time::Duration;
+
+/// A fixture that implements `@timeout(<duration>)` tags, such as `@timeout(30s)`,
+/// `@timeout(500ms)`, or `@timeout(2)` (in seconds). The older `@timeout-<secs>` form, such as
+/// `@timeout-30` or `@timeout-0.5`, also works. Each scenario fails if it doesn't finish in time.
+/// A tag on a feature or rule applies to each of its scenarios separately, as does a `timeout`
+/// setting at the top of the feature file (see [`crate::metadata`]). If there are several, the
+/// shortest wins.
+///
+/// See [`Context::set_timeout`] for what happens when time runs out.
+pub struct Timeout;
+
+#[async_trait]
+impl Fixture for Timeout {
+    const SCOPE: Scope = Scope::Global;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn before(&self, context: &mut Context) -> anyhow::Result<()> {
+        if context.kind() != ComponentKind::Scenario {
+            return Ok(());
+        }
+
+        // A timeout at the top of the feature file works like a tag on the feature
+        let mut timeout: Option<Duration> = context.component().metadata().timeout;
+        for tag in context.tags() {
+            let secs = match tag.strip_prefix("timeout-") {
+                Some(s) => s,
+                None => continue,
+            };
+            let secs = secs
+                .parse()
+                .ok()
+                .and_then(|s| Duration::try_from_secs_f64(s).ok())
+                .with_context(|| format!("Bad timeout tag @{}", tag))?;
+            timeout = Some(timeout.map_or(secs, |t| t.min(secs)));
+        }
+
+        for args in parse_tags("timeout", context.tags()) {
+            let args = args?;
+            let secs = match (args.len(), args.duration(0)) {
+                (1, Some(d)) => d,
+                _ => anyhow::bail!("@timeout takes a duration, e.g. @timeout(30s)"),
+            };
+            timeout = Some(timeout.map_or(secs, |t| t.min(secs)));
+        }
+
+        if let Some(timeout) = timeout {
+            context.set_timeout(timeout);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+//! Fixture to implement `@wip` tags
+
+use crate::{extra_options, ComponentKind, Context, Fixture, Scope, Verdict};
+use async_trait::async_trait;
+use clap::{App, Arg};
+
+/// A fixture that implements `@wip` ("work in progress") tags.
+///
+/// Components tagged `@wip` are skipped, unless `--wip` is given. Then *only* `@wip` scenarios
+/// run, and any that pass are marked as an unexpected pass, as a reminder to remove the tag.
+pub struct Wip;
+
+#[extra_options]
+fn wip_options<'a>(app: App<'static, 'a>) -> App<'static, 'a> {
+    app.arg(
+        Arg::with_name("wip")
+            .long("wip")
+            .help("Only run scenarios tagged @wip, and fail those that pass"),
+    )
+}
+
+fn is_wip(context: &Context) -> bool {
+    context.tags().any(|t| t == "wip")
+}
+
+#[async_trait]
+impl Fixture for Wip {
+    const SCOPE: Scope = Scope::Global;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn before(&self, context: &mut Context) -> anyhow::Result<()> {
+        if context.component().step().is_some() {
+            return Ok(());
+        }
+
+        if !context.options().opts.is_present("wip") {
+            if is_wip(context) {
+                zuke::skip!("Work in progress; use --wip to run");
+            }
+        } else if context.kind() == ComponentKind::Scenario && !is_wip(context) {
+            zuke::skip!("Not a work in progress (--wip)");
+        }
+
+        Ok(())
+    }
+
+    async fn after(&self, context: &mut Context) -> anyhow::Result<()> {
+        if context.kind() != ComponentKind::Scenario
+            || !context.options().opts.is_present("wip")
+            || !is_wip(context)
+        {
+            return Ok(());
+        }
+
+        let outcome = context.outcome_mut();
+        if outcome.passed_or_undecided() {
+            outcome.verdict = Verdict::UnexpectedPass;
+            outcome.reason = Some(anyhow::anyhow!(
+                "Work in progress passed; remove the @wip tag"
+            ));
+        }
+
+        Ok(())
+    }
+}
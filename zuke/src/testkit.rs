@@ -0,0 +1,239 @@
+//! Testing step implementations on their own, without a test run
+//!
+//! A step library can be tested by running features in a sub-run, but that's a lot of machinery
+//! for checking a single step. [`StepTester`] runs a few steps in a scenario of their own, with
+//! fakes in place of the fixtures they use, and returns the scenario's outcome to inspect.
+//!
+//! ```ignore
+//! let outcome = StepTester::new()
+//!     .step("Given a user named alice")
+//!     .step("When I order 3 widgets")
+//!     .fixture(FakeWarehouse::with_stock(2))
+//!     .run()
+//!     .await?;
+//! let order = outcome.step("When I order 3 widgets").unwrap();
+//! assert_eq!(order.verdict, Verdict::Failed);
+//! ```
+//!
+//! Steps are matched against the steps registered with the step macros, unless another
+//! vocabulary is given with [`StepTester::vocab`]. Hooks run as usual: tag plugins, then global,
+//! feature, scenario, and step hooks, whether from fixtures or from the hook macros. The feature
+//! and scenario have no tags, so tagged hooks don't run. If a global or feature before hook fails,
+//! the steps run anyway, and the failure is in the scenario's outcome. There are no events, so
+//! nothing is reported, and fixtures that subscribe to events won't see any.
+
+use crate::component::Component;
+use crate::context::{Context, OpenContext};
+use crate::fixture::Fixture;
+use crate::hooks::HookRunner;
+use crate::options::TestOptionsBuilder;
+use crate::outcome::Outcome;
+use crate::parser::parse_feature_text;
+use crate::runner::parts::{collect_scenario_artifacts, execute_step, setup_run};
+use crate::vocab::Vocab;
+use anyhow::Context as _;
+use clap::App;
+use futures::future::{BoxFuture, FutureExt};
+use gherkin_rust::Table;
+use std::sync::Arc;
+
+/// A step, as given to the tester
+struct TestStep {
+    text: String,
+    docstring: Option<String>,
+    table: Option<Vec<Vec<String>>>,
+}
+
+/// A fixture to put in place before the steps run. Fixture isn't object safe, so this hides the
+/// type.
+trait Injection: Send {
+    fn inject<'a>(self: Box<Self>, context: &'a mut Context) -> BoxFuture<'a, anyhow::Result<()>>;
+}
+
+struct Injected<T>(T);
+
+impl<T: Fixture> Injection for Injected<T> {
+    fn inject<'a>(self: Box<Self>, context: &'a mut Context) -> BoxFuture<'a, anyhow::Result<()>> {
+        async move { context.insert_fixture(self.0).await }.boxed()
+    }
+}
+
+/// Runs steps in a scenario of their own. See the [module docs](self).
+pub struct StepTester {
+    steps: Vec<TestStep>,
+    options: TestOptionsBuilder,
+    args: Vec<String>,
+    fixtures: Vec<Box<dyn Injection>>,
+    saved: Vec<(String, String)>,
+}
+
+impl Default for StepTester {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StepTester {
+    /// A tester with no steps
+    pub fn new() -> Self {
+        Self {
+            steps: vec![],
+            options: TestOptionsBuilder::new(),
+            args: vec![],
+            fixtures: vec![],
+            saved: vec![],
+        }
+    }
+
+    /// Add a step, with its keyword, such as `Given I have 3 widgets`. `And`, `But`, and `*`
+    /// continue the step before.
+    pub fn step<S: Into<String>>(mut self, text: S) -> Self {
+        self.steps.push(TestStep {
+            text: text.into(),
+            docstring: None,
+            table: None,
+        });
+        self
+    }
+
+    fn last_step(&mut self) -> &mut TestStep {
+        self.steps
+            .last_mut()
+            .expect("Add a step before its docstring or table")
+    }
+
+    /// Give the last step a docstring
+    pub fn docstring<S: Into<String>>(mut self, text: S) -> Self {
+        self.last_step().docstring = Some(text.into());
+        self
+    }
+
+    /// Give the last step a data table, as rows of cells
+    pub fn table<R, C, S>(mut self, rows: R) -> Self
+    where
+        R: IntoIterator<Item = C>,
+        C: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let rows = rows
+            .into_iter()
+            .map(|row| row.into_iter().map(Into::into).collect())
+            .collect();
+        self.last_step().table = Some(rows);
+        self
+    }
+
+    /// Use `fixture` rather than setting one up. Steps that call [`Context::use_fixture`] for its
+    /// type get this one instead. It's torn down when the steps finish, like any other fixture.
+    pub fn fixture<T: Fixture>(mut self, fixture: T) -> Self {
+        self.fixtures.push(Box::new(Injected(fixture)));
+        self
+    }
+
+    /// Set up fixture `R` wherever `T` is used. See [`TestOptionsBuilder::replace_fixture`].
+    pub fn replace_fixture<T, R>(mut self) -> Self
+    where
+        T: Fixture + From<R>,
+        R: Fixture,
+    {
+        self.options.replace_fixture::<T, R>();
+        self
+    }
+
+    /// Save a value before the steps run, as if by an earlier step calling [`Context::remember`]
+    pub fn remember<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.saved.push((name.into(), value.into()));
+        self
+    }
+
+    /// Match steps against `vocab`, rather than every registered step
+    pub fn vocab(mut self, vocab: Vocab) -> Self {
+        self.options.vocab(vocab);
+        self
+    }
+
+    /// Add a command line argument, such as `--strict`. Environment variables are ignored.
+    pub fn arg<S: Into<String>>(mut self, arg: S) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Run the steps, and return the scenario's outcome. Errors if the steps or arguments can't
+    /// be parsed; failing steps are in the outcome.
+    pub async fn run(self) -> anyhow::Result<Arc<Outcome>> {
+        let Self {
+            steps,
+            mut options,
+            args,
+            fixtures,
+            saved,
+        } = self;
+
+        let mut source = String::from("Feature: Step test\n  Scenario: Step test\n");
+        for step in steps.iter() {
+            source.push_str("    ");
+            source.push_str(&step.text);
+            source.push('\n');
+        }
+        let (mut feature, metadata) = parse_feature_text("<testkit>", &source)?;
+        let scenario = feature.scenarios.first_mut().context("No steps to test")?;
+        anyhow::ensure!(
+            scenario.steps.len() == steps.len(),
+            "Each step must be one line, starting with a keyword"
+        );
+        for (parsed, step) in scenario.steps.iter_mut().zip(steps) {
+            parsed.docstring = step.docstring;
+            parsed.table = step.table.map(|rows| Table::builder().rows(rows).build());
+        }
+
+        options.ignore_env();
+        let args = std::iter::once(String::from("zuke-testkit")).chain(args);
+        let options = options.build_with_app_from(App::new("zuke-testkit"), args)?;
+        let global = Component::global(Arc::new(options));
+
+        // As a test run would: hooks from the hook macros, then the rest of the global setup
+        let mut open = OpenContext::new_global(global.clone());
+        open.context.use_fixture::<HookRunner>().await?;
+        setup_run(&mut open).await;
+        let mut feature = open.with_feature(Outcome::undecided(
+            global.with_feature_metadata(feature, metadata),
+        ));
+        feature.before_hooks().await;
+        let mut scenario = feature
+            .with_scenarios()?
+            .pop()
+            .context("No steps to test")?;
+
+        for fixture in fixtures {
+            fixture.inject(&mut scenario.context).await?;
+        }
+        for (name, value) in saved {
+            scenario.context.remember(name, value);
+        }
+
+        let component = scenario.context.component().clone();
+        scenario.before_hooks().await;
+        for step in component.with_steps()? {
+            let outcome = execute_step(&mut scenario, step).await;
+            scenario.context.outcome_mut().add_child(Arc::new(outcome));
+        }
+        scenario.set_component(component);
+        scenario.after_hooks().await;
+        collect_scenario_artifacts(&mut scenario).await;
+
+        // Feature and global hooks and fixtures are torn down too. Their errors belong to the
+        // scenario, since there's nothing else to report them on.
+        let mut outcome = scenario.finalize().await;
+        feature.after_hooks().await;
+        let feature = feature.finalize().await;
+        open.after_hooks().await;
+        for finished in [feature, open.finalize().await] {
+            if finished.failed() {
+                if let Some(reason) = finished.reason {
+                    outcome.add_err(reason);
+                }
+            }
+        }
+        Ok(Arc::new(outcome))
+    }
+}
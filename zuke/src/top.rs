@@ -7,12 +7,16 @@ pub use super::*;
 use crate::flag::Flag;
 use crate::hooks::HookRunner;
 use async_broadcast as broadcast;
+use async_std::task;
 use clap::App;
 use futures::channel::mpsc;
 use futures::future::{join_all, BoxFuture, FutureExt};
 use futures::join;
-use std::path::Path;
+use std::any::Any;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
 
 /// TODO: Put this somewhere sensible
 struct PanicSilencer {
@@ -37,9 +41,59 @@ impl PanicSilencer {
     }
 }
 
+/// A [`ZukeBuilder`] can't build a test runner as configured
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    /// No parser was added, and the default parser has no features
+    #[error(
+        "No features to test. Add them with ZukeBuilder::feature_path, feature_source, or \
+         feature_glob, or with `features` in the config file."
+    )]
+    NoFeatures,
+    /// `--reporter` named a reporter that isn't registered
+    #[error("No such reporter {name}. Available reporters: {}", .available.join(", "))]
+    UnknownReporter {
+        /// The reporter asked for
+        name: String,
+        /// The registered reporters
+        available: Vec<String>,
+    },
+    /// `--profile` named a profile that isn't set up with [`ZukeBuilder::profile`]
+    #[error("No such profile {name}. Available profiles: {}", available_or_none(.available))]
+    UnknownProfile {
+        /// The profile asked for
+        name: String,
+        /// The profiles set up
+        available: Vec<String>,
+    },
+    /// An option replaces the runner given to [`ZukeBuilder::runner`] or
+    /// [`ZukeBuilder::runner_for`]
+    #[error("{0} can't be used with a custom runner")]
+    RunnerConflict(String),
+    /// The builder already built a test runner
+    #[error("This ZukeBuilder has already been built. Use a new builder for each test run.")]
+    AlreadyBuilt,
+    /// Ctrl+C handling couldn't be set up, usually because another test runner in this process
+    /// already handles it
+    #[error(
+        "Could not set up Ctrl+C handling: {0}. Use CancelMethod::Shared or CancelMethod::Manual \
+         for all but one test runner."
+    )]
+    CtrlC(#[from] ctrlc::Error),
+}
+
+fn available_or_none(names: &[String]) -> String {
+    if names.is_empty() {
+        String::from("(none)")
+    } else {
+        names.join(", ")
+    }
+}
+
 /// Top level tester
 pub struct Zuke {
     silence_panics: bool,
+    cancel_deadline: Option<Duration>,
     parsers: Vec<Box<dyn Parser>>,
     runner: Box<dyn Runner>,
     reporters: Vec<Box<dyn Reporter>>,
@@ -47,6 +101,11 @@ pub struct Zuke {
 }
 
 impl Zuke {
+    /// Options for this test run
+    pub(crate) fn options(&self) -> &Arc<TestOptions> {
+        &self.options
+    }
+
     /// Create a [`ZukeBuilder`] to customize this instance.
     ///
     /// At a miniumum, you will need to call [`ZukeBuilder::feature_path`] or
@@ -58,6 +117,32 @@ impl Zuke {
     /// Run the test suite. Returns the final outcome, regardless of success or failure. Its return
     /// value is based on the reporters, if any.
     pub async fn run(mut self) -> anyhow::Result<()> {
+        let global = Component::global(self.options.clone());
+        let (events_tx, events_rx) = broadcast::broadcast(256);
+
+        // launch reporters. They were supervised as they were added.
+        let reporters: Vec<_> = self
+            .reporters
+            .drain(..)
+            .map(|r| r.report(global.clone(), events_rx.clone()))
+            .collect::<Vec<_>>();
+        let reporters = join_all(reporters);
+
+        // Let them all run to completion
+        drop(events_rx);
+        let (_, results) = join!(self.run_tests(global, events_tx), reporters);
+
+        // Return the result, from reporters
+        results.into_iter().find(Result::is_err).unwrap_or(Ok(()))
+    }
+
+    /// Run the tests without reporting on them. Events go to `events_tx`, which is closed once
+    /// the tests are done.
+    pub(crate) async fn run_tests(
+        mut self,
+        global: Arc<Component>,
+        events_tx: broadcast::Sender<Event>,
+    ) {
         // disable "thread ... panicked" message at every assertion failure
         let _silence = if self.silence_panics {
             Some(PanicSilencer::new())
@@ -65,9 +150,19 @@ impl Zuke {
             None
         };
 
-        let global = Component::global(self.options.clone());
+        // Escalate a graceful cancel to an abort if it takes too long
+        let watchdog = self.cancel_deadline.map(|deadline| {
+            let canceled = self.options.canceled.clone();
+            let aborted = self.options.aborted.clone();
+            task::spawn(async move {
+                canceled.wait().await;
+                task::sleep(deadline).await;
+                aborted.set();
+            })
+        });
+
         let (features_tx, features_rx) = mpsc::channel(256);
-        let (events_tx, events_rx) = broadcast::broadcast(256);
+        let events_closer = events_tx.clone();
 
         // launch parsers and runners
         let mut runners = vec![self.runner.run(global.clone(), features_rx, events_tx)];
@@ -76,23 +171,16 @@ impl Zuke {
                 .drain(..)
                 .map(|p| p.parse(global.clone(), features_tx.clone())),
         );
-        let runners = join_all(runners);
-
-        // launch reporters
-        let reporters: Vec<_> = self
-            .reporters
-            .drain(..)
-            .map(|r| r.report(global.clone(), events_rx.clone()))
-            .collect::<Vec<_>>();
-        let reporters = join_all(reporters);
-
-        // Let them all run to completion
         drop(features_tx);
-        drop(events_rx);
-        let (_, results) = join!(runners, reporters);
 
-        // Return the result, from reporters
-        results.into_iter().find(Result::is_err).unwrap_or(Ok(()))
+        // Close the event channel once the runners are done. Contexts of aborted scenarios may
+        // never be dropped, and can't be allowed to keep the reporters waiting.
+        join_all(runners).await;
+        events_closer.close();
+
+        if let Some(watchdog) = watchdog {
+            watchdog.cancel().await;
+        }
     }
 }
 
@@ -100,21 +188,62 @@ impl Zuke {
 pub enum CancelMethod {
     /// Installs a Ctrl+C handler. May also be canceled manually.
     CtrlC,
+    /// Installs a Ctrl+C handler. The first Ctrl+C cancels gracefully, as with
+    /// [`CancelMethod::CtrlC`]. A second Ctrl+C aborts the test run outright, without waiting for
+    /// fixtures to tear down.
+    CtrlCForceful,
     /// Share a cancellation flag with something else
     Shared(Flag),
-    /// Manually cancel via `TestOptions::canceled.set()`
+    /// Manually cancel via `TestOptions::canceled.set()`, or abort via
+    /// `TestOptions::aborted.set()`
     Manual,
 }
 
+impl CancelMethod {
+    /// Share a cancellation flag with `options`, if there is one. Returns whether to install a
+    /// Ctrl+C handler, and if so, whether it's forceful.
+    pub(crate) fn apply(self, options: &mut TestOptionsBuilder) -> Option<bool> {
+        match self {
+            CancelMethod::CtrlC => Some(false),
+            CancelMethod::CtrlCForceful => Some(true),
+            CancelMethod::Shared(flag) => {
+                options.cancel(flag);
+                None
+            }
+            CancelMethod::Manual => None,
+        }
+    }
+}
+
+/// Cancel the test run on Ctrl+C. If `forceful`, a second Ctrl+C aborts it.
+pub(crate) fn handle_ctrlc(forceful: bool, options: &TestOptions) -> Result<(), ConfigError> {
+    let canceled = options.canceled.clone();
+    let aborted = options.aborted.clone();
+    ctrlc::set_handler(move || {
+        if forceful && canceled.is_set() {
+            aborted.set();
+        } else {
+            canceled.set();
+        }
+    })?;
+    Ok(())
+}
+
 /// A builder for [`Zuke`]
 pub struct ZukeBuilder {
     silence_panics: bool,
     cancel_method: CancelMethod,
+    cancel_deadline: Option<Duration>,
+    check_vocab: bool,
     options_builder: TestOptionsBuilder,
     default_parser: Option<StandardParser>,
     parsers: Vec<Box<dyn Parser>>,
     runner: Box<dyn Runner>,
+    partitions: Vec<(Partition, Box<dyn Runner>)>,
     reporters: Vec<Box<dyn Reporter>>,
+    only_features: Option<Vec<PathBuf>>,
+    custom_runner: bool,
+    built: bool,
 }
 
 impl Default for ZukeBuilder {
@@ -132,31 +261,39 @@ impl ZukeBuilder {
         let mut zuke = Self {
             silence_panics: true,
             cancel_method: CancelMethod::CtrlC,
+            cancel_deadline: None,
+            check_vocab: false,
             options_builder: TestOptionsBuilder::new(),
             parsers: vec![],
             reporters: vec![],
             runner: Box::new(StandardRunner::new()),
+            partitions: vec![],
             default_parser: None,
+            only_features: None,
+            custom_runner: false,
+            built: false,
         };
 
         zuke.use_fixture::<HookRunner>();
         zuke
     }
 
-    /// Create a [`Zuke`] test runner using a default set of command line arguments.  This will
-    /// reset the builder to its default state.
+    /// Create a [`Zuke`] test runner using a default set of command line arguments. A builder can
+    /// only be built once; building it again is a [`ConfigError::AlreadyBuilt`].
     pub fn build(&mut self) -> anyhow::Result<Zuke> {
         self.build_with_app(App::new("Zuke"))
     }
 
     /// Create a [`Zuke`] test runner using a specified set of command line arguments. Extra
     /// command line arguments via [`TestOptions`] will be added to the set of command line
-    /// arguments. This resets the builder to its default state.
+    /// arguments.
     pub fn build_with_app(&mut self, app: App<'static, '_>) -> anyhow::Result<Zuke> {
         self.build_with_app_from(app, &mut std::env::args_os())
     }
 
     /// As `build_with_app`, but allows you to specify your own command line arguments.
+    ///
+    /// Configuration mistakes, such as having no features to test, are a [`ConfigError`].
     pub fn build_with_app_from<I, T>(
         &mut self,
         app: App<'static, '_>,
@@ -166,6 +303,10 @@ impl ZukeBuilder {
         I: IntoIterator<Item = T>,
         T: Into<std::ffi::OsString> + Clone,
     {
+        if self.built {
+            return Err(ConfigError::AlreadyBuilt.into());
+        }
+
         if self.reporters.is_empty() {
             self.command_line_reporter();
         }
@@ -174,41 +315,77 @@ impl ZukeBuilder {
             self.default_parser();
         }
 
-        if let Some(p) = self.default_parser.take() {
-            self.parsers.push(Box::new(p));
-        }
-
         let mut obj = Self::new();
         std::mem::swap(&mut obj, self);
+        self.built = true;
         let ZukeBuilder {
             silence_panics,
             cancel_method,
-            parsers,
+            cancel_deadline,
+            check_vocab,
+            mut default_parser,
+            mut parsers,
             runner,
+            partitions,
             reporters,
             mut options_builder,
+            only_features,
+            custom_runner,
             ..
         } = obj;
 
-        let mut handler = false;
-        match cancel_method {
-            CancelMethod::CtrlC => {
-                handler = true;
+        let handler = cancel_method.apply(&mut options_builder);
+
+        let options = Arc::new(options_builder.build_with_app_from(app, iter)?);
+        if check_vocab || options.opts.is_present("check") {
+            options.vocab.check()?;
+        }
+        crate::reporter::command_line::check_reporters(&options)?;
+
+        if !options.config.features.is_empty() {
+            let parser = default_parser.get_or_insert_with(StandardParser::default);
+            for path in options.config.features.iter() {
+                parser.add_path(path);
             }
-            CancelMethod::Shared(flag) => {
-                options_builder.cancel(flag);
+        }
+
+        if let Some(paths) = only_features {
+            parsers.clear();
+            default_parser
+                .get_or_insert_with(StandardParser::default)
+                .only_paths(paths);
+        }
+
+        if let Some(p) = default_parser.filter(|p| !p.is_empty()) {
+            parsers.push(Box::new(p));
+        }
+        if parsers.is_empty() {
+            return Err(ConfigError::NoFeatures.into());
+        }
+
+        if custom_runner || !partitions.is_empty() {
+            let replaced_by = ["workers", "listen", "isolate_scenarios"]
+                .iter()
+                .find(|name| options.opts.is_present(name));
+            if let Some(name) = replaced_by {
+                let option = format!("--{}", name.replace('_', "-"));
+                return Err(ConfigError::RunnerConflict(option).into());
             }
-            CancelMethod::Manual => (),
+        }
+        let runner: Box<dyn Runner> = if partitions.is_empty() {
+            runner
+        } else {
+            Box::new(PartitionedRunner::with_partitions(runner, partitions))
         };
+        let runner = crate::runner::choose_runner(runner, &options);
 
-        let options = Arc::new(options_builder.build_with_app_from(app, iter)?);
-        if handler {
-            let canceled = options.canceled.clone();
-            ctrlc::set_handler(move || canceled.set()).expect("Could not set up Ctrl+C handling");
+        if let Some(forceful) = handler {
+            handle_ctrlc(forceful, &options)?;
         }
 
         Ok(Zuke {
             silence_panics,
+            cancel_deadline,
             parsers,
             runner,
             reporters,
@@ -222,6 +399,28 @@ impl ZukeBuilder {
         self
     }
 
+    /// Abort the test run if it hasn't finished within `deadline` of being canceled. By default
+    /// a canceled test run waits as long as it takes for fixtures to tear down.
+    pub fn cancel_deadline(&mut self, deadline: Duration) -> &mut Self {
+        self.cancel_deadline = Some(deadline);
+        self
+    }
+
+    /// Share an abort flag with something else. Setting the flag stops the test run immediately,
+    /// without waiting for fixtures to tear down.
+    pub fn abort_flag(&mut self, flag: Flag) -> &mut Self {
+        self.options_builder.abort(flag);
+        self
+    }
+
+    /// Check step implementations for conflicting patterns when building, rather than waiting for
+    /// a feature to use a conflicting step. The same as passing `--check` on the command line.
+    /// Default is false.
+    pub fn check_vocab(&mut self, check: bool) -> &mut Self {
+        self.check_vocab = check;
+        self
+    }
+
     #[doc(hidden)]
     /// Leave the default hook that prints information about panics. Generally this isn't what you
     /// want, because it will spam the output every time an assert! fails. Used for debugging Zuke
@@ -232,12 +431,70 @@ impl ZukeBuilder {
         self
     }
 
+    /// Use these steps rather than every step linked into the binary. This lets several Zuke
+    /// instances in the same process have different vocabularies. See [`Vocab::builder`].
+    pub fn vocab(&mut self, vocab: Vocab) -> &mut Self {
+        self.options_builder.vocab(vocab);
+        self
+    }
+
+    /// Resolve steps implemented by several crates. See [`TestOptionsBuilder::step_priority`].
+    pub fn step_priority<I, S>(&mut self, names: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options_builder.step_priority(names);
+        self
+    }
+
+    /// Set the prefix of environment variables that set command line options. See
+    /// [`TestOptionsBuilder::env_prefix`].
+    pub fn env_prefix<S: Into<String>>(&mut self, prefix: S) -> &mut Self {
+        self.options_builder.env_prefix(prefix);
+        self
+    }
+
+    /// Don't read command line options from environment variables
+    pub fn ignore_env(&mut self) -> &mut Self {
+        self.options_builder.ignore_env();
+        self
+    }
+
+    /// Add a typed setting. See [`TestOptionsBuilder::setting`].
+    pub fn setting<T: Any + Send + Sync>(&mut self, value: T) -> &mut Self {
+        self.options_builder.setting(value);
+        self
+    }
+
+    /// Set up fixture `R` wherever `T` is used, such as a fake backend in place of a real one. See
+    /// [`TestOptionsBuilder::replace_fixture`].
+    pub fn replace_fixture<T, R>(&mut self) -> &mut Self
+    where
+        T: Fixture + From<R>,
+        R: Fixture,
+    {
+        self.options_builder.replace_fixture::<T, R>();
+        self
+    }
+
+    /// Settings for when `--profile name` is given. See [`TestOptionsBuilder::profile`].
+    pub fn profile<S: Into<String>>(&mut self, name: S) -> &mut Profile {
+        self.options_builder.profile(name)
+    }
+
     /// Set the overall title of the test. Used to customize reporter output.
     pub fn title<T: Into<String>>(&mut self, title: T) -> &mut Self {
         self.options_builder.title(title);
         self
     }
 
+    /// Name the suite this test run is part of. See [`TestOptions::suite`].
+    pub(crate) fn suite<S: Into<String>>(&mut self, name: S) -> &mut Self {
+        self.options_builder.suite(name);
+        self
+    }
+
     /// Cause a function to execute at global scope, just before the first feature runs.
     pub fn pre_test_hook<F: HookFn>(&mut self, hook: F) -> &mut Self {
         self.options_builder.pre_test_hook(hook);
@@ -253,6 +510,23 @@ impl ZukeBuilder {
         self.pre_test_hook(hook::<F>)
     }
 
+    /// Use the default tag handlers in [`crate::tags`], such as `@skip` and `@expect-fail`. The
+    /// default is true.
+    pub fn with_default_tags(&mut self, enable: bool) -> &mut Self {
+        self.options_builder.default_tags(enable);
+        self
+    }
+
+    /// Add a fixture that handles tags, as a named tag plugin. Like [`ZukeBuilder::use_fixture`],
+    /// the fixture must be globally scoped.
+    pub fn tag_plugin<F: Fixture, N: Into<String>>(&mut self, name: N) -> &mut Self {
+        fn hook<F: Fixture>(context: &mut Context) -> BoxFuture<'_, anyhow::Result<()>> {
+            context.use_fixture::<F>().boxed()
+        }
+        self.options_builder.tag_plugin(name, hook::<F>);
+        self
+    }
+
     /// Add a custom parser. Multiple parsers may be added. If no parser is added, a default parser
     /// will be used based on [`ZukeBuilder::feature_path`] and [`ZukeBuilder::feature_source`].
     pub fn parser<T: Parser + 'static>(&mut self, parser: T) -> &mut Self {
@@ -263,20 +537,61 @@ impl ZukeBuilder {
     /// Add a custom runner. If no custom runner is added, the default runner will be used.
     pub fn runner<T: Runner + 'static>(&mut self, runner: T) -> &mut Self {
         self.runner = Box::new(runner);
+        self.custom_runner = true;
         self
     }
 
+    /// Run features that `predicate` accepts with `runner`, alongside the main runner, which runs
+    /// the rest. If several predicates accept a feature, the first one added wins. Outcomes are
+    /// merged into one test run. See [`PartitionedRunner`].
+    pub fn runner_for<F, T>(&mut self, predicate: F, runner: T) -> &mut Self
+    where
+        F: Fn(&Component) -> bool + Send + Sync + 'static,
+        T: Runner + 'static,
+    {
+        self.partitions
+            .push((Box::new(predicate), Box::new(runner)));
+        self
+    }
+
+    /// Run features tagged `@tag` with `runner`, as with [`ZukeBuilder::runner_for`]
+    ///
+    /// ```ignore
+    /// Zuke::builder()
+    ///     .feature_path("features")
+    ///     .runner_for_tag("serial", SequentialRunner::new())
+    ///     .build()?
+    /// ```
+    pub fn runner_for_tag<S: Into<String>, T: Runner + 'static>(
+        &mut self,
+        tag: S,
+        runner: T,
+    ) -> &mut Self {
+        let tag = tag.into();
+        self.runner_for(move |c| c.tags().any(|t| *t == tag), runner)
+    }
+
     /// Add a custom reporter. Multiple reporters may be added. If no reporters are added, the
-    /// command line will be examined to find a reporter (choosing a default if needed).
+    /// command line will be examined to find a reporter (choosing a default if needed). The
+    /// reporter runs [`Supervised`].
     pub fn reporter<T: Reporter + 'static>(&mut self, reporter: T) -> &mut Self {
-        self.reporters.push(Box::new(reporter));
+        self.reporters
+            .push(Box::new(Supervised::new(Box::new(reporter))));
         self
     }
 
+    /// Have any reporters been added?
+    pub(crate) fn has_reporters(&self) -> bool {
+        !self.reporters.is_empty()
+    }
+
     /// Explicitly add reporters from the command line. Additional reporters may still be added in
     /// addition to the default.
     pub fn command_line_reporter(&mut self) -> &mut Self {
-        self.reporter(CommandLineReporter::default())
+        // It supervises the reporters it makes, so it isn't supervised itself
+        self.reporters
+            .push(Box::new(CommandLineReporter::default()));
+        self
     }
 
     /// Explicitly add the default parser. Additional reporters may still be added in addition to
@@ -288,6 +603,54 @@ impl ZukeBuilder {
         self
     }
 
+    /// Set the default language of features added by [`ZukeBuilder::feature_path`] and
+    /// [`ZukeBuilder::feature_source`], such as "fr". The default is English. A `# language: xx`
+    /// header in a feature file overrides this.
+    pub fn language<S: Into<String>>(&mut self, language: S) -> &mut Self {
+        self.default_parser();
+        self.default_parser.as_mut().unwrap().language(language);
+        self
+    }
+
+    /// Search directories added with [`ZukeBuilder::feature_path`] for files with this extension,
+    /// rather than "feature". See [`StandardParser::extension`].
+    pub fn feature_extension<S: Into<String>>(&mut self, extension: S) -> &mut Self {
+        self.default_parser();
+        self.default_parser.as_mut().unwrap().extension(extension);
+        self
+    }
+
+    /// Skip files and directories matching a glob pattern when searching directories added with
+    /// [`ZukeBuilder::feature_path`]. See [`StandardParser::ignore`].
+    pub fn ignore_features<S: Into<String>>(&mut self, pattern: S) -> &mut Self {
+        self.default_parser();
+        self.default_parser.as_mut().unwrap().ignore(pattern);
+        self
+    }
+
+    /// Search at most this many directories below those added with
+    /// [`ZukeBuilder::feature_path`]. See [`StandardParser::max_depth`].
+    pub fn max_feature_depth(&mut self, depth: usize) -> &mut Self {
+        self.default_parser();
+        self.default_parser.as_mut().unwrap().max_depth(depth);
+        self
+    }
+
+    /// Feature files and directories added with [`Self::feature_path`]
+    pub(crate) fn feature_paths(&self) -> Vec<PathBuf> {
+        match &self.default_parser {
+            Some(p) => p.paths(),
+            None => vec![],
+        }
+    }
+
+    /// Run only these feature files, in place of any other features or parsers. Used to re-run
+    /// changed features.
+    pub(crate) fn only_features(&mut self, paths: Vec<PathBuf>) -> &mut Self {
+        self.only_features = Some(paths);
+        self
+    }
+
     /// Add a feature file or directory of features to the test run
     pub fn feature_path<P: AsRef<Path>>(&mut self, path: P) -> &mut Self {
         self.default_parser();
@@ -295,6 +658,14 @@ impl ZukeBuilder {
         self
     }
 
+    /// Add feature files or directories matching a glob pattern, such as
+    /// `features/**/auth*.feature`
+    pub fn feature_glob<S: Into<String>>(&mut self, pattern: S) -> &mut Self {
+        self.default_parser();
+        self.default_parser.as_mut().unwrap().add_glob(pattern);
+        self
+    }
+
     /// Add a feature as a source string. The `filename` parameter is an arbitrary value used for
     /// output.
     pub fn feature_source<N: Into<String>, S: Into<String>>(
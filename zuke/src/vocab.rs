@@ -1,11 +1,16 @@
 //! Registry for step implementations
 
+use crate::component::ComponentKind;
 use crate::context::Context;
 use crate::panic::PanicToError;
 use async_trait::async_trait;
 use gherkin_rust::StepType;
 use inventory;
 use regex::{Captures, Regex, RegexSet, RegexSetBuilder};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -19,26 +24,156 @@ pub enum Error {
         what: String,
     },
     /// Multiple implementations found for the step
-    #[error("Multiple implementations found for {what:?}")]
+    #[error("Multiple implementations found for {what:?}: {}", list_locations(.locations))]
     MultipleMatches {
         /// The expanded step that matched
         what: String,
-        /// Where it matched. (Not meaningful currently.)
+        /// Where the matching implementations are
         locations: Vec<Location>,
     },
     /// Something went wrong dispatching the step implementation
     #[error("Wiring error: Bad parameters")]
     BadParameters,
+    /// Several step implementations have exactly the same pattern
+    #[error("Conflicting step implementations:{}", list_conflicts(.0))]
+    Conflicts(Vec<Conflict>),
+    /// The step is only implemented for a different keyword (`--strict-keywords`)
+    #[error("{what:?} is implemented as a {} step at {location}", keyword_name(.keyword))]
+    KeywordMismatch {
+        /// The expanded step that failed to match
+        what: String,
+        /// The keyword of the implementation
+        keyword: StepType,
+        /// Where the implementation is
+        location: Location,
+    },
 }
 
-/// A location where a step was implemented. Currently unused as this information is not exposed to
-/// our macros except on nightly.
+fn keyword_name(keyword: &StepType) -> &'static str {
+    match keyword {
+        StepType::Given => "Given",
+        StepType::When => "When",
+        StepType::Then => "Then",
+    }
+}
+
+/// Step implementations that share a pattern. See [`Vocab::check`].
 #[derive(Debug, Clone)]
+pub struct Conflict {
+    /// The pattern, as a regular expression
+    pub pattern: String,
+    /// Where the implementations are
+    pub locations: Vec<Location>,
+}
+
+fn list_conflicts(conflicts: &[Conflict]) -> String {
+    conflicts
+        .iter()
+        .map(|c| format!("\n  {:?} at {}", c.pattern, list_locations(&c.locations)))
+        .collect()
+}
+
+fn list_locations(locations: &[Location]) -> String {
+    locations
+        .iter()
+        .map(|l| format!("{} (in {})", l, l.module))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A location where a step was implemented. This is where the `#[given]`, `#[when]`, or `#[then]`
+/// attribute appears.
+#[derive(Debug, Clone, Serialize)]
 pub struct Location {
-    /// The source file of the step implementation
+    /// The source file of the step implementation, relative to the crate that defined it
     pub path: PathBuf,
     /// The line number of the step implementation
     pub line: i32,
+    /// The module that implemented the step, as given by `module_path!()`. The first component is
+    /// the crate.
+    pub module: &'static str,
+}
+
+impl Location {
+    /// The crate that implemented the step
+    pub fn crate_name(&self) -> &'static str {
+        self.module.split("::").next().unwrap_or(self.module)
+    }
+
+    /// Was the step implemented in `name`, which is a crate or module path? Dashes in crate names
+    /// are treated as underscores, as in `use` statements.
+    pub fn provided_by(&self, name: &str) -> bool {
+        let name = name.replace('-', "_");
+        match self.module.strip_prefix(name.as_str()) {
+            Some(rest) => rest.is_empty() || rest.starts_with("::"),
+            None => false,
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.line)
+    }
+}
+
+/// How a step was understood: which implementation matched it, and what it captured. See
+/// [`crate::Outcome::step_match`].
+#[derive(Debug, Clone, Serialize)]
+pub struct StepMatch {
+    /// The implementation's pattern, as a regular expression
+    pub pattern: String,
+    /// Where the implementation is
+    pub location: Location,
+    /// The text that was matched: the keyword in English, then the step with any
+    /// `${...}` expanded
+    pub text: String,
+    /// The captured arguments, in order
+    pub args: Vec<StepArg>,
+}
+
+/// An argument captured from a step. See [`StepMatch`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StepArg {
+    /// The name of the capture group, if it has one
+    pub name: Option<String>,
+    /// The captured text, or `None` for an optional group that didn't match
+    pub value: Option<String>,
+}
+
+impl StepMatch {
+    fn new(step: &dyn StepImplementation, text: &str, captures: &Captures) -> Self {
+        let regex = step.regex();
+        let args = regex
+            .capture_names()
+            .enumerate()
+            .skip(1)
+            .map(|(i, name)| StepArg {
+                name: name.map(String::from),
+                value: captures.get(i).map(|m| m.as_str().to_string()),
+            })
+            .collect();
+        Self {
+            pattern: regex.as_str().to_string(),
+            location: step.location().clone(),
+            text: text.to_string(),
+            args,
+        }
+    }
+}
+
+impl fmt::Display for StepMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} at {}", self.pattern, self.location)?;
+        for (i, arg) in self.args.iter().enumerate() {
+            let value = arg.value.as_deref().unwrap_or("<none>");
+            match &arg.name {
+                Some(name) => write!(f, "\n  {} = {:?}", name, value)?,
+                None => write!(f, "\n  {} = {:?}", i + 1, value)?,
+            }
+        }
+        Ok(())
+    }
 }
 
 /// A step implementation
@@ -50,72 +185,305 @@ pub struct Location {
 pub trait StepImplementation: Send + Sync {
     /// The regular expression for this step
     fn regex(&self) -> &Regex;
-    /// The location this step was defined at. Not currently meaningful.
+    /// The location this step was defined at.
     fn location(&self) -> &Location;
+    /// The keyword this step was implemented for, or `None` if it matches any keyword.
+    fn keyword(&self) -> Option<StepType> {
+        None
+    }
+    /// The keyword in other languages, such as `Étant donné`, from `keyword = "..."` in the step
+    /// macro. See [`Vocab::split_keyword`].
+    fn keyword_aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
     /// Execute this step implementation.
     async fn execute(&self, context: &mut Context, args: &Captures) -> anyhow::Result<()>;
 }
 
-/// Central registry of all step implementations
+/// Central registry of step implementations
 ///
-/// User's won't interact with this directly.
+/// By default this is every step linked into the binary. Use [`Vocab::builder`] to choose a
+/// subset, and give it to [`crate::ZukeBuilder::vocab`].
 pub struct Vocab {
     regexes: RegexSet,
     steps: Vec<&'static dyn StepImplementation>,
+    strict_keywords: bool,
+    normalize_text: bool,
+    step_priority: Vec<String>,
 }
 
 impl Vocab {
-    /// Create a new `Vocab` objecct.
+    /// Create a new `Vocab` objecct with every registered step.
     pub fn new() -> Result<Self, regex::Error> {
-        let steps: Vec<_> = inventory::iter::<&'static dyn StepImplementation>
-            .into_iter()
-            .copied()
-            .collect();
+        Self::builder().build()
+    }
+
+    /// Choose which registered steps to use
+    pub fn builder() -> VocabBuilder {
+        VocabBuilder::default()
+    }
+
+    /// Create a `Vocab` with specific steps
+    pub fn from_steps(steps: Vec<&'static dyn StepImplementation>) -> Result<Self, regex::Error> {
         let regexes = RegexSetBuilder::new(steps.iter().map(|s| s.regex().as_str()))
             .case_insensitive(true)
             .build()?;
 
-        Ok(Self { steps, regexes })
+        Ok(Self {
+            steps,
+            regexes,
+            strict_keywords: false,
+            normalize_text: true,
+            step_priority: vec![],
+        })
+    }
+
+    /// A copy of this vocabulary with more steps, keeping its settings
+    pub fn with_steps<I>(mut self, steps: I) -> Result<Self, regex::Error>
+    where
+        I: IntoIterator<Item = &'static dyn StepImplementation>,
+    {
+        let mut all = std::mem::take(&mut self.steps);
+        all.extend(steps);
+        let new = Self::from_steps(all)?;
+        Ok(Self {
+            regexes: new.regexes,
+            steps: new.steps,
+            ..self
+        })
+    }
+
+    /// The step implementations in use
+    pub fn steps(&self) -> &[&'static dyn StepImplementation] {
+        &self.steps
+    }
+
+    /// As [`split_keyword`], but also understands the keyword aliases of these steps, such as
+    /// `Étant donné`. The longest alias that starts `text` wins.
+    pub fn split_keyword<'a>(
+        &self,
+        text: &'a str,
+        current: StepType,
+    ) -> (StepType, &'a str, &'a str) {
+        let alias = self
+            .steps
+            .iter()
+            .filter_map(|s| Some((s.keyword()?, s.keyword_aliases())))
+            .flat_map(|(ty, aliases)| aliases.iter().map(move |a| (ty, *a)))
+            .filter_map(|(ty, alias)| {
+                let (keyword, rest) = (text.get(..alias.len())?, &text[alias.len()..]);
+                let boundary = alias.ends_with('\'') || rest.starts_with(char::is_whitespace);
+                if boundary && keyword.to_lowercase() == alias.to_lowercase() {
+                    Some((ty, keyword, rest.trim_start()))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|(_, keyword, _)| keyword.len());
+
+        match alias {
+            Some(split) => split,
+            None => split_keyword(text, current),
+        }
+    }
+
+    /// Validate that the implementation's keyword agrees with the step's keyword. Steps that only
+    /// have an implementation under a different keyword report where that implementation is,
+    /// rather than just failing to match.
+    pub fn with_strict_keywords(mut self, strict: bool) -> Self {
+        self.strict_keywords = strict;
+        self
+    }
+
+    /// Canonicalize step text before matching it, as with [`normalize_text`]. Arguments are still
+    /// captured as written. On by default.
+    pub fn with_text_normalization(mut self, normalize: bool) -> Self {
+        self.normalize_text = normalize;
+        self
+    }
+
+    /// When several implementations match a step, prefer the one from the first of these crates
+    /// or modules that provides exactly one of them. Names are as in [`Location::module`], such
+    /// as `shared_steps` or `shared_steps::http`. `@use-steps(...)` tags take precedence.
+    pub fn with_step_priority(mut self, priority: Vec<String>) -> Self {
+        self.step_priority = priority;
+        self
+    }
+
+    /// Look for step implementations with exactly the same pattern. Such steps can never be used,
+    /// but otherwise aren't noticed until a feature tries to use them.
+    ///
+    /// This does not catch different patterns that happen to match the same step.
+    pub fn check(&self) -> Result<(), Error> {
+        let mut by_pattern: HashMap<&str, Vec<Location>> = HashMap::new();
+        for step in self.steps.iter() {
+            by_pattern
+                .entry(step.regex().as_str())
+                .or_default()
+                .push(step.location().clone());
+        }
+
+        let mut conflicts: Vec<_> = by_pattern
+            .into_iter()
+            .filter(|(_, locations)| locations.len() > 1)
+            .map(|(pattern, locations)| Conflict {
+                pattern: pattern.to_string(),
+                locations,
+            })
+            .collect();
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            conflicts.sort_by(|a, b| a.pattern.cmp(&b.pattern));
+            Err(Error::Conflicts(conflicts))
+        }
     }
 
     /// Execute a step
     pub async fn execute(&self, context: &mut Context) -> anyhow::Result<()> {
-        let step = match context.step() {
-            Some(s) => s,
+        let (ty, mut keyword, value) = match context.step() {
+            Some(s) => (s.ty, s.keyword.clone(), s.value.clone()),
             None => anyhow::bail!("Step dispatch outside of step context"),
         };
+        // Say which type an `And` step is, so it's clear which step attribute to implement it with
+        match context.component().step_type_keyword() {
+            Some(type_keyword) if type_keyword != keyword.trim() => {
+                keyword = format!("{} ({})", keyword.trim(), type_keyword);
+            }
+            _ => (),
+        }
+        self.execute_text(context, ty, &keyword, &value).await
+    }
 
-        // Normalize step to English
-        let mut line = String::from(match step.ty {
-            StepType::Given => "Given ",
-            StepType::When => "When ",
-            StepType::Then => "Then ",
-        });
-        line.push_str(step.value.as_str());
-
+    /// Execute arbitrary step text in the context of the current step, as if it were written in
+    /// place of the current step. `keyword` is only used for messages.
+    pub async fn execute_text(
+        &self,
+        context: &mut Context,
+        ty: StepType,
+        keyword: &str,
+        text: &str,
+    ) -> anyhow::Result<()> {
+        let value = context.expand(text);
+        let (matched, offsets) = if self.normalize_text {
+            let (matched, offsets) = normalize_with_offsets(&value);
+            (matched, Some(offsets))
+        } else {
+            (value.clone(), None)
+        };
+        let line = Self::normalize(ty, &matched);
         let matches: Vec<_> = self.regexes.matches(&line).into_iter().collect();
 
         if matches.is_empty() {
-            let what = format!("{} {}", &step.keyword, &step.value);
-            Err(Error::NoMatch { what }.into())
-        } else if matches.len() > 1 {
-            let what = format!("{} {}", &step.keyword, &step.value);
-            let locations = matches
-                .into_iter()
-                .map(|i| self.steps[i].location().clone())
-                .collect();
-            Err(Error::MultipleMatches { what, locations }.into())
+            let what = format!("{} {}", keyword, text);
+            let other = if self.strict_keywords {
+                self.find_other_keyword(ty, &matched)
+            } else {
+                None
+            };
+            match other {
+                Some(other) => Err(Error::KeywordMismatch {
+                    what,
+                    keyword: other.keyword().unwrap_or(ty),
+                    location: other.location().clone(),
+                }
+                .into()),
+                None => Err(Error::NoMatch { what }.into()),
+            }
         } else {
-            let i = matches[0];
-            let captures = match self.steps[i].regex().captures(&line) {
+            let i = match matches[..] {
+                [i] => i,
+                _ => match self.resolve(context, &matches)? {
+                    Some(i) => i,
+                    None => {
+                        let what = format!("{} {}", keyword, text);
+                        let locations = matches
+                            .into_iter()
+                            .map(|i| self.steps[i].location().clone())
+                            .collect();
+                        return Err(Error::MultipleMatches { what, locations }.into());
+                    }
+                },
+            };
+            let regex = self.steps[i].regex();
+            let captures = match regex.captures(&line) {
                 Some(c) => c,
                 None => return Err(Error::BadParameters.into()),
             };
 
+            // Normalizing is only for matching. Put the arguments back as they were written, and
+            // match again so that the implementation gets ordinary captures.
+            let prefix = line.len() - matched.len();
+            let restored = offsets.map(|o| restore_captures(&line, prefix, &captures, &value, &o));
+            let (line, captures) = match restored
+                .as_deref()
+                .and_then(|r| Some((r, regex.captures(r)?)))
+            {
+                Some(found) => found,
+                None => (line.as_str(), captures),
+            };
+
+            // Steps run from inside other steps don't replace the match of the outer step
+            let outcome = context.outcome_mut();
+            if outcome.kind() == ComponentKind::Step && outcome.step_match.is_none() {
+                outcome.step_match = Some(StepMatch::new(self.steps[i], line, &captures));
+            }
+
             self.execute_step(self.steps[i], context, &captures).await
         }
     }
 
+    /// Choose between several matching implementations using the crates or modules named by
+    /// `@use-steps(...)` tags, then those given to [`Self::with_step_priority`]. The first name
+    /// that provides exactly one of the matches wins.
+    fn resolve(&self, context: &Context, matches: &[usize]) -> anyhow::Result<Option<usize>> {
+        let mut preferred = use_steps_tags(context)?;
+        preferred.extend(self.step_priority.iter().cloned());
+
+        for name in preferred {
+            let mut provided = matches
+                .iter()
+                .filter(|&&i| self.steps[i].location().provided_by(&name));
+            match (provided.next(), provided.next()) {
+                (Some(&i), None) => return Ok(Some(i)),
+                (Some(_), Some(_)) => return Ok(None),
+                (None, _) => (),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Normalize a step to English
+    fn normalize(ty: StepType, value: &str) -> String {
+        let mut line = String::from(match ty {
+            StepType::Given => "Given ",
+            StepType::When => "When ",
+            StepType::Then => "Then ",
+        });
+        line.push_str(value);
+        line
+    }
+
+    /// Find a step implementation that would have matched under a different keyword
+    fn find_other_keyword(
+        &self,
+        ty: StepType,
+        value: &str,
+    ) -> Option<&'static dyn StepImplementation> {
+        [StepType::Given, StepType::When, StepType::Then]
+            .iter()
+            .filter(|&&other| other != ty)
+            .map(|&other| Self::normalize(other, value))
+            .find_map(|line| {
+                let matches = self.regexes.matches(&line);
+                match matches.iter().count() {
+                    1 => matches.iter().next().map(|i| self.steps[i]),
+                    _ => None,
+                }
+            })
+    }
+
     fn execute_step<'a>(
         &self,
         step: &'static dyn StepImplementation,
@@ -136,3 +504,177 @@ impl Vocab {
 }
 
 inventory::collect!(&'static dyn StepImplementation);
+
+/// Builder for a [`Vocab`] with a subset of the registered steps. With no rules, every step is
+/// included.
+///
+/// ```ignore
+/// let vocab = Vocab::builder()
+///     .include_module("checkout_steps")
+///     .include_module("shared_steps::http")
+///     .exclude_pattern("legacy")?
+///     .build()?;
+/// ```
+#[derive(Default)]
+pub struct VocabBuilder {
+    modules: Vec<String>,
+    excluded: Vec<Regex>,
+    extra: Vec<&'static dyn StepImplementation>,
+}
+
+impl VocabBuilder {
+    /// Include steps implemented in a crate or module, as with [`Location::provided_by`]. Once a
+    /// module is included, steps from modules that weren't are left out.
+    pub fn include_module<S: Into<String>>(mut self, name: S) -> Self {
+        self.modules.push(name.into());
+        self
+    }
+
+    /// Leave out steps whose pattern, as a regular expression, contains a match for `pattern`
+    pub fn exclude_pattern(mut self, pattern: &str) -> Result<Self, regex::Error> {
+        self.excluded.push(Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Add steps that weren't registered with the step macros, such as those from a
+    /// [`crate::plugin`]. They are subject to the same rules as registered steps.
+    pub fn steps<I>(mut self, steps: I) -> Self
+    where
+        I: IntoIterator<Item = &'static dyn StepImplementation>,
+    {
+        self.extra.extend(steps);
+        self
+    }
+
+    /// Does a step pass the rules?
+    fn includes(&self, step: &dyn StepImplementation) -> bool {
+        let location = step.location();
+        let pattern = step.regex().as_str();
+        (self.modules.is_empty() || self.modules.iter().any(|m| location.provided_by(m)))
+            && !self.excluded.iter().any(|re| re.is_match(pattern))
+    }
+
+    /// Create the [`Vocab`]
+    pub fn build(self) -> Result<Vocab, regex::Error> {
+        let steps = inventory::iter::<&'static dyn StepImplementation>
+            .into_iter()
+            .copied()
+            .chain(self.extra.iter().copied())
+            .filter(|s| self.includes(*s))
+            .collect();
+        Vocab::from_steps(steps)
+    }
+}
+
+/// Crates or modules named by `@use-steps(...)` tags, nearest tag first
+#[cfg(feature = "tags")]
+fn use_steps_tags(context: &Context) -> anyhow::Result<Vec<String>> {
+    let mut names = vec![];
+    for args in crate::tags::parse_tags("use-steps", context.tags()) {
+        for arg in args?.0 {
+            match arg {
+                crate::tags::TagArg::Text(name) => names.push(name),
+                crate::tags::TagArg::Number(_) => {
+                    anyhow::bail!(
+                        "@use-steps takes crate or module names, e.g. @use-steps(my_steps)"
+                    )
+                }
+            }
+        }
+    }
+    Ok(names)
+}
+
+#[cfg(not(feature = "tags"))]
+fn use_steps_tags(_context: &Context) -> anyhow::Result<Vec<String>> {
+    Ok(vec![])
+}
+
+/// Canonicalize text pasted from a word processor, so that it matches step patterns written in
+/// plain ASCII: curly quotes become straight quotes, non-breaking and other unusual spaces become
+/// ordinary spaces, and runs of whitespace collapse to a single space. Leading and trailing
+/// whitespace is removed.
+///
+/// Steps are matched against the normalized text, but their arguments are captured from the text
+/// as written.
+pub fn normalize_text(text: &str) -> String {
+    normalize_with_offsets(text).0
+}
+
+/// As [`normalize_text`], along with the range of `text` that each byte of the result came from
+fn normalize_with_offsets(text: &str) -> (String, Vec<Range<usize>>) {
+    let trimmed = text.trim();
+    let base = text.len() - text.trim_start().len();
+    let mut out = String::with_capacity(trimmed.len());
+    let mut offsets = Vec::with_capacity(trimmed.len());
+    let mut space = false;
+    for (i, c) in trimmed.char_indices() {
+        let source = base + i..base + i + c.len_utf8();
+        let c = match c {
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' => '\'',
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' => '"',
+            c if c.is_whitespace() => ' ',
+            c => c,
+        };
+        // Zero-width spaces aren't whitespace according to Unicode, but are just as invisible
+        if c == '\u{200B}' || c == '\u{FEFF}' || (c == ' ' && space) {
+            continue;
+        }
+        space = c == ' ';
+        out.push(c);
+        offsets.extend(std::iter::repeat(source).take(c.len_utf8()));
+    }
+    (out, offsets)
+}
+
+/// Replace each argument captured from `line`, a normalized step after `prefix` bytes of keyword,
+/// with the text it came from in `value`. `offsets` are from [`normalize_with_offsets`].
+fn restore_captures(
+    line: &str,
+    prefix: usize,
+    captures: &Captures,
+    value: &str,
+    offsets: &[Range<usize>],
+) -> String {
+    let mut groups: Vec<_> = captures
+        .iter()
+        .skip(1)
+        .flatten()
+        .filter(|m| m.start() >= prefix && m.start() < m.end())
+        .collect();
+    groups.sort_by_key(|m| m.start());
+
+    let mut restored = String::with_capacity(line.len());
+    let mut end = 0;
+    for m in groups {
+        // Nested groups are restored along with the group around them
+        if m.start() < end {
+            continue;
+        }
+        restored.push_str(&line[end..m.start()]);
+        let start = offsets[m.start() - prefix].start;
+        let stop = offsets[m.end() - prefix - 1].end;
+        restored.push_str(&value[start..stop]);
+        end = m.end();
+    }
+    restored.push_str(&line[end..]);
+    restored
+}
+
+/// Split a step's keyword from its text, as for [`Vocab::execute_text`]. `And`, `But`, and a
+/// missing keyword keep the type of the current step. Returns the type, keyword, and text.
+///
+/// Only English keywords are understood. See [`Vocab::split_keyword`] for others.
+pub fn split_keyword(text: &str, current: StepType) -> (StepType, &str, &str) {
+    let (first, rest) = match text.split_once(char::is_whitespace) {
+        Some((first, rest)) => (first, rest.trim_start()),
+        None => (text, ""),
+    };
+    match first.to_lowercase().as_str() {
+        "given" => (StepType::Given, first, rest),
+        "when" => (StepType::When, first, rest),
+        "then" => (StepType::Then, first, rest),
+        "and" | "but" | "*" => (current, first, rest),
+        _ => (current, "*", text),
+    }
+}
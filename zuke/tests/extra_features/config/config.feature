@@ -0,0 +1,3 @@
+Feature: A feature named in a config file
+    Scenario: Runs as many times as the config file says
+        Given a step that returns nothing
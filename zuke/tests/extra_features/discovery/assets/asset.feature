@@ -0,0 +1,3 @@
+Feature: A feature among assets
+    Scenario: A scenario in A feature among assets
+        Given a step that returns nothing
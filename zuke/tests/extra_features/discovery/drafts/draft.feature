@@ -0,0 +1,3 @@
+Feature: A draft feature
+    Scenario: A scenario in A draft feature
+        Given a step that returns nothing
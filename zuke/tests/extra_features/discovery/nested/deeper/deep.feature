@@ -0,0 +1,3 @@
+Feature: A deeply nested feature
+    Scenario: A scenario in A deeply nested feature
+        Given a step that returns nothing
@@ -0,0 +1,3 @@
+Feature: A nested feature
+    Scenario: A scenario in A nested feature
+        Given a step that returns nothing
@@ -0,0 +1,3 @@
+Feature: A top level feature
+    Scenario: A scenario in A top level feature
+        Given a step that returns nothing
@@ -0,0 +1,7 @@
+Feature: Distributed duplicates
+
+    Scenario: Same name
+        Given a step that returns nothing
+
+    Scenario: Same name
+        Given a step that return Err from anyhow::Result
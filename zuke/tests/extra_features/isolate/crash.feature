@@ -0,0 +1,7 @@
+Feature: Crash isolation
+
+    Scenario: A scenario that crashes
+        Given a step that aborts the process
+
+    Scenario: A scenario that survives
+        Given a step that returns nothing
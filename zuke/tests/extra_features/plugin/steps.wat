@@ -0,0 +1,27 @@
+;; Steps for plugin.feature, in the guest interface described in src/plugin.rs
+(module
+  (memory (export "memory") 1)
+  (global $next (mut i32) (i32.const 4096))
+
+  (data (i32.const 1024) "[{\"pattern\":\"a plugin step that passes\",\"keyword\":\"given\",\"function\":\"passes\"},{\"pattern\":\"a plugin step that fails\",\"keyword\":null,\"function\":\"fails\"},{\"pattern\":\"a plugin step that never returns\",\"keyword\":\"when\",\"function\":\"spin\"}]")
+  (data (i32.const 2048) "{\"verdict\":\"failed\",\"message\":\"Plugins can fail\"}")
+
+  ;; A bump allocator; each step runs in a fresh instance
+  (func (export "zuke_alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $next))
+    (global.set $next (i32.add (global.get $next) (local.get $len)))
+    (local.get $ptr))
+
+  (func (export "zuke_steps") (result i64)
+    (i64.const 4398046511338))
+
+  (func (export "passes") (param i32 i32) (result i64)
+    (i64.const 0))
+
+  (func (export "fails") (param i32 i32) (result i64)
+    (i64.const 8796093022257))
+
+  (func (export "spin") (param i32 i32) (result i64)
+    (loop $forever (br $forever))
+    (i64.const 0)))
@@ -0,0 +1,7 @@
+Feature: Quiet
+
+    Scenario: Passes quietly
+        Given a step that returns nothing
+
+    Scenario: Fails quietly
+        Given a step that return Err from anyhow::Result
@@ -0,0 +1,16 @@
+#!/bin/sh
+# A step server for remote.feature, talking over stdin and stdout. Its one step is "a step from",
+# then its first argument. Before answering a step, it answers a request Zuke has already had an
+# answer to, which Zuke should ignore.
+while read -r line; do
+    id=$(echo "$line" | sed 's/.*"id":\([0-9][0-9]*\).*/\1/')
+    case "$line" in
+    *'"step_definitions"'*)
+        echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"name\":\"shell\",\"steps\":[{\"id\":\"step\",\"pattern\":\"a step from $1\",\"keyword\":null}]}}"
+        ;;
+    *)
+        echo "{\"jsonrpc\":\"2.0\",\"id\":$((id - 1)),\"result\":{\"verdict\":\"failed\",\"message\":\"A stale answer\"}}"
+        echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"verdict\":\"passed\"}}"
+        ;;
+    esac
+done
@@ -0,0 +1,7 @@
+Feature: Scenarios that would clash if they ran at once
+
+    Scenario: The first scenario to use the counter
+        When I use the counter "lanes" for a moment
+
+    Scenario: The second scenario to use the counter
+        When I use the counter "lanes" for a moment
@@ -0,0 +1,16 @@
+@ordered
+Feature: Ordered scenarios run one at a time
+
+    Scenario: The first scenario to use the counter
+        When I use the counter "ordered" for a moment
+
+    Scenario: The second scenario to use the counter
+        When I use the counter "ordered" for a moment
+
+    Rule: Rules inherit the tag
+
+        Scenario: The third scenario to use the counter
+            When I use the counter "ordered" for a moment
+
+        Scenario: The fourth scenario to use the counter
+            When I use the counter "ordered" for a moment
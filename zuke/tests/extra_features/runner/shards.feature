@@ -0,0 +1,31 @@
+Feature: Scenarios to split into shards
+
+    Scenario: Picks the apple
+        Given a word with a double vowel "moon"
+
+    Scenario: Picks the banana
+        Given a word with a double vowel "moon"
+
+    Scenario: Picks the cherry
+        Given a word with a double vowel "moon"
+
+    Scenario: Picks the damson
+        Given a word with a double vowel "moon"
+
+    Scenario: Picks the elder
+        Given a word with a double vowel "moon"
+
+    Scenario: Picks the fig
+        Given a word with a double vowel "moon"
+
+    Scenario: Picks the grape
+        Given a word with a double vowel "moon"
+
+    Scenario: Picks the huckleberry
+        Given a word with a double vowel "moon"
+
+    Scenario: Picks the kiwi
+        Given a word with a double vowel "moon"
+
+    Scenario: Picks the lemon
+        Given a word with a double vowel "moon"
@@ -0,0 +1,4 @@
+Feature: A slow step
+
+    Scenario: A scenario with a slow step
+        When I use the counter "slow" for a moment
@@ -0,0 +1,13 @@
+Feature: Scheduling
+
+    Scenario: Fast and passing
+        Given a step that returns nothing
+
+    Scenario: Slow and passing
+        Given a step that returns nothing
+
+    Scenario: Failed last time
+        Given a step that returns nothing
+
+    Scenario: New since last time
+        Given a step that returns nothing
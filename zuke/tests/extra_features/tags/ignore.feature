@@ -0,0 +1,8 @@
+Feature: We can ignore scenarios with a tag plugin
+
+    Scenario: This scenario runs
+        Given a step that returns nothing
+
+    @ignore
+    Scenario: This scenario doesn't run
+        Then I shouldn't get here
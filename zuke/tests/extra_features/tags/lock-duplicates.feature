@@ -0,0 +1,13 @@
+Feature: Scenarios with the same name lock resources separately
+
+    @lock-shared(duplicate)
+    Scenario: A scenario that shares a resource
+        When I wait for 2 scenarios to say "duplicate"
+
+    @lock-shared(duplicate)
+    Scenario: A scenario that shares a resource
+        When I wait for 2 scenarios to say "duplicate"
+
+    @lock(duplicate) @timeout(10s)
+    Scenario: A scenario that locks the resource afterwards
+        When I use the counter "duplicate lock" for a moment
@@ -0,0 +1,17 @@
+Feature: Scenarios can lock resources
+
+    @lock(counter)
+    Scenario: The first scenario to use the counter
+        When I use the counter "locked" for a moment
+
+    @lock(counter)
+    Scenario: The second scenario to use the counter
+        When I use the counter "locked" for a moment
+
+    @lock-shared(reader)
+    Scenario: The first scenario to share a resource
+        When I wait for 2 scenarios to say "shared"
+
+    @lock-shared(reader)
+    Scenario: The second scenario to share a resource
+        When I wait for 2 scenarios to say "shared"
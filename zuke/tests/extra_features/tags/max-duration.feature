@@ -0,0 +1,9 @@
+Feature: Scenarios can have a maximum duration
+
+    @max-duration(10s)
+    Scenario: This scenario is quick enough
+        When I use the counter "quick enough" for a moment
+
+    @max-duration(10ms)
+    Scenario: This scenario takes too long
+        When I use the counter "too long" for a moment
@@ -0,0 +1,13 @@
+Feature: Scenarios with the same name take slots separately
+
+    @max-parallel(duplicate, 2)
+    Scenario: A scenario that takes a slot
+        When I wait for 2 scenarios to say "duplicate slot"
+
+    @max-parallel(duplicate, 2)
+    Scenario: A scenario that takes a slot
+        When I wait for 2 scenarios to say "duplicate slot"
+
+    @max-parallel(duplicate, 1) @timeout(10s)
+    Scenario: A scenario that runs alone afterwards
+        When I use the counter "duplicate slot" for a moment
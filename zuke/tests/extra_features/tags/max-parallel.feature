@@ -0,0 +1,25 @@
+Feature: Scenarios can limit how many run at once
+
+    @max-parallel(gpu, 2)
+    Scenario: The first scenario to use the GPU
+        When I use the counter "gpu" for a moment, with at most 2 users
+
+    @max-parallel(gpu, 2)
+    Scenario: The second scenario to use the GPU
+        When I use the counter "gpu" for a moment, with at most 2 users
+
+    @max-parallel(gpu, 2)
+    Scenario: The third scenario to use the GPU
+        When I use the counter "gpu" for a moment, with at most 2 users
+
+    @max-parallel(1)
+    Rule: One scenario at a time
+
+        Scenario: The first scenario in a rule limited to one at a time
+            When I use the counter "serial rule" for a moment
+
+        Scenario: The second scenario in a rule limited to one at a time
+            When I use the counter "serial rule" for a moment
+
+        Scenario: The third scenario in a rule limited to one at a time
+            When I use the counter "serial rule" for a moment
@@ -0,0 +1,7 @@
+Feature: Scenarios without locks run in parallel
+
+    Scenario: The first scenario to use the counter
+        When I use the counter "unlocked" for a moment
+
+    Scenario: The second scenario to use the counter
+        When I use the counter "unlocked" for a moment
@@ -0,0 +1,6 @@
+Feature: We can pause scenarios when running interactively
+
+    @pause
+    Scenario: This scenario would pause with --interactive
+        Given a step that returns nothing
+        Then I will move the world
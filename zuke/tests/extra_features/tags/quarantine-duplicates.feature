@@ -0,0 +1,7 @@
+Feature: Flaky scenarios with the same name can be quarantined one at a time
+
+    Scenario: Same name
+        Given a step that panics
+
+    Scenario: Same name
+        Given a step that panics
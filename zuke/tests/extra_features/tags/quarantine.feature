@@ -0,0 +1,14 @@
+Feature: Flaky scenarios can be quarantined
+
+    Scenario: This scenario is fine
+        Given a step that returns nothing
+
+    @quarantined
+    Scenario: This scenario is tagged as flaky
+        Given a step that panics
+
+    Scenario: This scenario is listed as flaky
+        Given a step that panics
+
+    Scenario: This scenario is broken
+        Given a step that panics
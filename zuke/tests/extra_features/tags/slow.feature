@@ -0,0 +1,8 @@
+Feature: We can skip slow scenarios
+
+    Scenario: This scenario runs
+        Given a step that returns nothing
+
+    @slow
+    Scenario: This scenario only runs with --include-slow
+        Given a step that returns nothing
@@ -0,0 +1,9 @@
+Feature: Scenarios can time out
+
+    Scenario: This scenario has no time limit
+        Given a step that returns nothing
+
+    @timeout-0.1
+    Scenario: This scenario takes too long
+        When I pause forever
+        Then I shouldn't get here
@@ -0,0 +1,12 @@
+Feature: We can mark scenarios as works in progress
+
+    Scenario: This scenario is finished
+        Given a step that returns nothing
+
+    @wip
+    Scenario: This scenario is still being worked on
+        Then I shouldn't get here
+
+    @wip
+    Scenario: This scenario is finished, but still tagged
+        Given a step that returns nothing
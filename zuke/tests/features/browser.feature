@@ -0,0 +1,28 @@
+@needs-browser
+Feature: A browser can be driven over WebDriver
+
+    # These don't need a WebDriver server. The steps fail before they'd use one.
+
+    Scenario: Browser steps fail clearly when there's no WebDriver server
+        Given a zuke sub-instance
+        When I add "--config tests/extra_features/browser/zuke.toml" to the command line
+        And I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Visits a page
+                    Given I am on http://example.com/
+            """
+        And I run the tests
+        Then the step "I am on http://example.com/" failed mentioning "Could not start a WebDriver session at http://127.0.0.1:9"
+
+    Scenario: A browser timeout too long to keep is an error
+        Given a zuke sub-instance
+        When I add "--config tests/extra_features/browser/too-long.toml" to the command line
+        And I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Visits a page
+                    Given I am on http://example.com/
+            """
+        And I run the tests
+        Then the step "I am on http://example.com/" failed mentioning "Bad setting browser-timeout"
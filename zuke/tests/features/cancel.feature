@@ -11,3 +11,31 @@ Feature: Steps can be canceled
         And I run the tests
         And I cancel the tests
         Then the tests were canceled
+
+    Scenario: Scenario can be aborted when cleanup hangs
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Never cleans up
+                    Given a fixture that never tears down
+                    When I pause forever
+            """
+        And I run the tests
+        And I cancel the tests
+        And I abort the tests
+        Then the tests were canceled
+        And the scenario "Never cleans up" failed mentioning "Test run aborted"
+
+    Scenario: Blocking steps can check for cancellation
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Polls until canceled
+                    When I poll for cancellation
+                    Then I shouldn't get here
+            """
+        And I run the tests
+        And I cancel the tests
+        Then the tests were canceled
@@ -23,6 +23,14 @@ Feature: Steps can capture arguments
     Scenario: Regex steps will fail on conversion errors
         Given a regex step that expects the color zlurple
 
+    Scenario: Regex steps can capture an optional integer
+        Given a regex step that expects number 100 to be present
+        And a regex step that expects number to be absent
+
+    Scenario: Regex steps can capture an optional &str
+        Given a regex step that expects str "foo" to be present
+        And a regex step that expects str to be absent
+
     Scenario: The name 'context' is not reserved
         Given a regex step that captures "foo" using the name context
 
@@ -53,3 +61,16 @@ Feature: Steps can capture arguments
 
     Scenario: The name '_context' is not reserved in basic steps
         Given a step that captures "foo" using the name _context
+
+    Scenario: Basic steps can capture a list
+        Given a step that expects a list of colors red, green, blue
+
+    Scenario: Basic steps can capture a list with a custom separator
+        Given a step that expects a space separated list of colors red green  blue
+
+    Scenario: Basic steps can capture a list of &str
+        Given a step that expects a list of strs foo, bar
+
+    @expect-fail
+    Scenario: Basic steps will fail on conversion errors in lists
+        Given a step that expects a list of colors red, zlurple, blue
@@ -0,0 +1,122 @@
+Feature: Zuke can read settings from a config file
+
+    Scenario: Config files can add features and set options
+        Given a zuke sub-instance
+        When I add "--config tests/extra_features/config/zuke.toml" to the command line
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/1 passing features
+        And there are 3/3 passing steps
+
+    Scenario: The command line takes precedence over the config file
+        Given a zuke sub-instance
+        When I add "--config tests/extra_features/config/zuke.toml --repeat 2" to the command line
+        And I run the tests
+        Then the tests complete successfully
+        And there are 2/2 passing steps
+
+    Scenario: A missing config file is an error
+        Given a zuke sub-instance
+        When I add "--config tests/extra_features/config/missing.toml" to the command line
+        Then running the tests fails mentioning "missing.toml"
+
+    Scenario: Fixtures can read typed settings from the builder
+        Given a zuke sub-instance
+        When I give the sub-instance the greeting setting "hi"
+        And I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Reads a setting
+                    Then the greeting setting is "hi"
+            """
+        And I run the tests
+        Then the tests complete successfully
+
+    Scenario: Fixtures can read typed settings from the config file
+        Given a zuke sub-instance
+        When I add "--config tests/extra_features/config/zuke.toml" to the command line
+        And I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Reads settings
+                    Then the greeting from the config file is "hello"
+                    And the server from the config file is "http://localhost:8080" with a timeout of 30
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And there are 2/2 passing features
+
+    Scenario: Building without any features is an error
+        Given a zuke sub-instance
+        Then running the tests fails mentioning "No features to test"
+
+    Scenario: Asking for a reporter that doesn't exist is an error
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Does nothing
+            """
+        And I add "--reporter nonsense" to the command line
+        Then running the tests fails mentioning "No such reporter nonsense"
+
+    Scenario: Options that replace a custom runner are an error
+        Given a zuke sub-instance
+        When I use a sequential runner
+        And I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Does nothing
+            """
+        And I add "--workers 2" to the command line
+        Then running the tests fails mentioning "--workers can't be used with a custom runner"
+
+    Scenario: A profile chooses scenarios and fixtures
+        Given a zuke sub-instance
+        When I set up the sub-instance's "smoke" profile to run scenarios tagged "@smoke"
+        And I set up the sub-instance's "smoke" profile to use a fake backend
+        And I add "--profile smoke" to the command line
+        And I add the feature source
+            """
+            Feature: An inline feature
+                @smoke
+                Scenario: A quick check
+                    Then the backend is "fake"
+                    And the profile is "smoke"
+
+                Scenario: A thorough check
+                    Then the backend is "real"
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/2 passing scenarios
+        And there are 1/2 skipped scenarios
+
+    Scenario: Profiles only apply when chosen
+        Given a zuke sub-instance
+        When I set up the sub-instance's "smoke" profile to run scenarios tagged "@smoke"
+        And I set up the sub-instance's "smoke" profile to use a fake backend
+        And I add the feature source
+            """
+            Feature: An inline feature
+                @smoke
+                Scenario: A quick check
+                    Then the backend is "real"
+
+                Scenario: A thorough check
+                    Then the backend is "real"
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And there are 2/2 passing scenarios
+
+    Scenario: A profile that isn't set up is an error
+        Given a zuke sub-instance
+        When I set up the sub-instance's "smoke" profile to run scenarios tagged "@smoke"
+        And I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Does nothing
+            """
+        And I add "--profile smoek" to the command line
+        Then running the tests fails mentioning "No such profile smoek. Available profiles: smoke"
@@ -0,0 +1,19 @@
+@needs-dashboard
+Feature: The dashboard shows the test run as it happens
+
+    Scenario: The dashboard shows the final state, however quickly the run ends
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Passes
+                    Given a step that returns nothing
+                    And a step that returns nothing
+                Scenario: Passes too
+                    Given a step that returns nothing
+                Scenario: Fails
+                    Given a step that panics
+            """
+        And I serve the dashboard
+        And I run the tests
+        Then the dashboard shows the finished run, with 2 passing and 1 failing scenarios
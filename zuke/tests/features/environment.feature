@@ -0,0 +1,58 @@
+Feature: Command line options can be set with environment variables
+
+    # Each scenario uses its own prefix, because the environment is shared between all of them.
+
+    Scenario: Options can be set from the environment
+        Given a zuke sub-instance
+        When I read options from environment variables starting with "ZUKETEST1_"
+        And I set the environment variable "ZUKETEST1_REPEAT" to "3"
+        And I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Runs three times
+                    Given a step that returns nothing
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And there are 3/3 passing steps
+
+    Scenario: Flags can be set from the environment
+        Given a zuke sub-instance
+        When I read options from environment variables starting with "ZUKETEST2_"
+        And I set the environment variable "ZUKETEST2_STRICT" to "true"
+        And I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Not finished
+                    Given a step that is pending
+            """
+        And I run the tests
+        Then the tests fail
+
+    Scenario: The command line takes precedence over the environment
+        Given a zuke sub-instance
+        When I read options from environment variables starting with "ZUKETEST3_"
+        And I set the environment variable "ZUKETEST3_REPEAT" to "3"
+        And I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Runs twice
+                    Given a step that returns nothing
+            """
+        And I add "--repeat 2" to the command line
+        And I run the tests
+        Then the tests complete successfully
+        And there are 2/2 passing steps
+
+    Scenario: Unknown options in the environment are ignored, with a warning
+        Given a zuke sub-instance
+        When I read options from environment variables starting with "ZUKETEST4_"
+        And I set the environment variable "ZUKETEST4_NOT_AN_OPTION" to "1"
+        And I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Does nothing
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And the test run has a warning mentioning "ZUKETEST4_NOT_AN_OPTION"
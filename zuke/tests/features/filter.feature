@@ -50,6 +50,18 @@ Feature: We can include and exclude items from the command line
         And there are 1/2 passing rules
         And there are 3/6 passing scenarios
 
+    Scenario: --hide-excluded counts excluded components as deselected
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/null/null_items.feature"
+        And I add "--exclude 'rule with empty scenarios' --hide-excluded" to the command line
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/1 passing features
+        And there are 1/1 passing rules
+        And there are 1/1 deselected rules
+        And there are 3/3 passing scenarios
+        And there are 3/3 deselected scenarios
+
     Scenario: Zuke can exclude select features
         Given a zuke sub-instance
         When I add the path "tests/extra_features/null/null_items.feature"
@@ -60,3 +72,55 @@ Feature: We can include and exclude items from the command line
         And there are 1/2 passing features
         And there are 0/2 passing rules
         And there are 0/6 passing scenarios
+
+    Scenario: Filters without --name include select scenarios, as with cargo test
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/null/null_items.feature"
+        And I add "outline" to the command line
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/1 passing features
+        And there are 1/2 passing rules
+        And there are 4/6 passing scenarios
+
+    Scenario: --owner runs only what the owner owns
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: A feature with owners
+                @owner(team-levers, team-fulcrums)
+                Scenario: Owned by two teams
+                    Given a step that returns nothing
+
+                @owner(team-gears)
+                Scenario: Owned by another team
+                    Given a step that returns nothing
+
+                Scenario: Owned by nobody
+                    Given a step that returns nothing
+            """
+        And I add "--owner team-fulcrums" to the command line
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/3 passing scenarios
+        And there are 2/3 skipped scenarios
+
+    Scenario: Owners are parsed like other tag arguments
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            @owner("team-levers")
+            Feature: A feature with owners
+                @owner("team-fulcrums",team-gears)
+                Scenario: Owned by three teams
+                    Given a step that returns nothing
+
+                @owner(team-fulcrums
+                Scenario: Owned by the feature's team, with a broken tag
+                    Given a step that returns nothing
+            """
+        And I add "--owner team-fulcrums" to the command line
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/2 passing scenarios
+        And there are 1/2 skipped scenarios
@@ -29,3 +29,9 @@ Feature: Fixtures can have scenario, feature and global scope
         Given a counter fixture with global scope, that should be 4 on teardown
         When I increment the global counter
 
+
+    Scenario: Fixtures can subscribe to events in their scope
+        Given a fixture that counts finished steps in its scenario
+        And a step that returns nothing
+        And a step that returns nothing
+        Then 3 steps have finished in this scenario
@@ -0,0 +1,270 @@
+Feature: Zuke comes with ready-made fixtures
+
+    Scenario: Scenarios get a temporary directory that is removed afterwards
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Uses a temporary directory
+                    Given a temporary directory remembered as "first"
+                    When I write "hello" to the scratch file "{tempdir}/greeting.txt"
+                    Then the scratch file "{tempdir}/greeting.txt" contains "hello"
+                    And the text "{not-an-expansion}" is unchanged
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And there are 4/4 passing steps
+        And the temporary directory remembered as "first" was removed
+
+    Scenario: Each scenario gets its own temporary directory
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Writes a file
+                    Given a temporary directory remembered as "second"
+                    When I write "hello" to the scratch file "{tempdir}/greeting.txt"
+
+                Scenario: Doesn't see the other file
+                    Given a temporary directory remembered as "third"
+                    Then the scratch file "{tempdir}/greeting.txt" contains "hello"
+            """
+        And I run the tests
+        Then the tests fail
+        And there are 1/2 passing scenarios
+        And the temporary directory remembered as "second" was removed
+        And the temporary directory remembered as "third" was removed
+
+    Scenario: Scenarios can run commands and check their output
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: A command that succeeds
+                    When I run `cargo --version`
+                    Then the exit code is 0
+                    And stdout contains "cargo"
+
+                Scenario: A command that fails
+                    When I run `cargo --zuke-no-such-option`
+                    Then the exit code is 3
+            """
+        And I run the tests
+        Then the tests fail
+        And there are 1/2 passing scenarios
+        And the step "the exit code is 3" failed mentioning "Expected exit code 3"
+
+    Scenario: Running a program that doesn't exist fails the step
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: A missing program
+                    When I run `zuke-no-such-program`
+            """
+        And I run the tests
+        Then the tests fail
+        And the step "I run `zuke-no-such-program`" failed mentioning "Could not run zuke-no-such-program"
+
+    Scenario: Filesystem steps work in the scenario's temporary directory
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Files are created and checked
+                    Given a file "config/settings.ini" containing
+                        ```
+                        [server]
+                        port = 8080
+                        ```
+                    And an empty file "empty.txt"
+                    And a directory "logs"
+                    Then the file "config/settings.ini" exists
+                    And the file "{tempdir}/logs" exists
+                    And the file "missing.txt" does not exist
+                    And the file "config/settings.ini" contains "port = 8080"
+                    And the file "config/settings.ini" contains
+                        ```
+                        [server]
+                        ```
+
+                Scenario: A missing file fails
+                    Then the file "empty.txt" exists
+            """
+        And I run the tests
+        Then the tests fail
+        And there are 1/2 passing scenarios
+        And the step "the file "empty.txt" exists" failed mentioning "does not exist"
+
+    Scenario: Steps can remember values for later steps
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Values are remembered
+                    Given I remember "hello" as "greeting"
+                    Then the remembered value "greeting" is "hello"
+                    And the text "{saved:greeting}" is "hello"
+
+                Scenario: Values aren't shared between scenarios
+                    Then the remembered value "greeting" is "hi"
+            """
+        And I run the tests
+        Then the tests fail
+        And there are 1/2 passing scenarios
+        And the step "the remembered value "greeting" is "hi"" failed mentioning "Nothing was remembered"
+
+    Scenario: Fixtures are set up for real unless replaced
+        Then the backend is "real"
+
+    Scenario: A sub-instance can replace a fixture with a fake
+        Given a zuke sub-instance
+        When I replace the backend with a fake in the sub-instance
+        And I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Uses the backend
+                    Then the backend is "fake"
+            """
+        And I run the tests
+        Then the tests complete successfully
+
+    Scenario: Fixtures can collect artifacts when a step fails
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Fails on camera
+                    Given a camera is watching
+                    And a step that panics
+            """
+        And I run the tests
+        Then the tests fail
+        And the step "a step that panics" has a "Snapshot" artifact
+        And the step "a camera is watching" has no artifacts
+
+    Scenario: Fixtures that are slow to collect artifacts are given up on at the deadline
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                @timeout(1s)
+                Scenario: Fails on a stuck camera
+                    Given a stuck camera is watching
+                    And a step that panics
+            """
+        And I run the tests
+        Then the tests fail
+        And the step "a step that panics" has a warning mentioning "Gave up collecting failure artifacts"
+
+    Scenario: The time is real unless the clock is mocked
+        Then the time is the real time
+
+    Scenario: The clock can be set and moved
+        Given the time is "2021-06-01T12:00:00Z"
+        When 3 hours pass
+        Then the time is "2021-06-01T15:00:00Z"
+        When 1 day passes
+        Then the time is "2021-06-02T15:00:00Z"
+        And the scenario started in real time
+
+    Scenario: Steps can pick random values
+        Given I pick a random name as "name"
+        And I pick a random email as "email"
+        And I pick a random UUID as "id"
+        And I pick a random number from -2 to 2 as "number"
+        Then the remembered value "name" matches "[A-Z][a-z]+ [A-Z][a-z]+"
+        And the remembered value "email" matches "[a-z]+\.[a-z]+\.\d{4}@example\.com"
+        And the remembered value "id" matches "[0-9a-f]{8}-[0-9a-f]{4}-4[0-9a-f]{3}-[89ab][0-9a-f]{3}-[0-9a-f]{12}"
+        And the remembered value "number" matches "-?[0-2]"
+
+    Scenario: A random number needs its bounds in order
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Picks backward
+                    Given I pick a random number from 3 to 1 as "number"
+            """
+        And I run the tests
+        Then the tests fail
+        And the step "I pick a random number from 3 to 1 as "number"" failed mentioning "3 is more than 1"
+
+    Scenario: Random values are the same when the seed is, even for scenarios with the same name
+        Then running this twice with the seed 42 warns about the same values, all different
+            """
+            Feature: An inline feature
+                Scenario: Signing up
+                    Given I pick a random email as "email"
+                    Then I warn about the remembered value "email"
+                Scenario: Signing up
+                    Given I pick a random email as "email"
+                    Then I warn about the remembered value "email"
+                Scenario: Signing in
+                    Given I pick a random email as "email"
+                    Then I warn about the remembered value "email"
+            """
+
+    Scenario: Checks can be retried until they pass
+        Then a counter eventually reaches 3
+        And within 5s the attempt counter reaches 3
+        And the remembered value "attempts" is "3"
+
+    Scenario: Retried steps fail after a timeout
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Never gets there
+                    Then within 0.2s the attempt counter reaches 1000
+            """
+        And I run the tests
+        Then the tests fail
+        And the step "within 0.2s the attempt counter reaches 1000" failed mentioning "Still failing after"
+
+    Scenario: A retry timeout too long to keep fails the step
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Waits forever
+                    Then within 99999999999999999999999s the attempt counter reaches 1
+            """
+        And I run the tests
+        Then the tests fail
+        And the step "within 99999999999999999999999s the attempt counter reaches 1" failed mentioning "Bad timeout"
+
+    Scenario: Steps can capture errors for later steps to check
+        When I parse "twelve" as a number
+        Then it fails mentioning "invalid digit"
+        When I parse "12" as a number
+        Then it does not fail
+
+    Scenario: Any step can be expected to fail
+        Given a step that panics, expecting an error
+        Then it fails mentioning "PANIC!"
+
+    Scenario: A step expected to fail must fail
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Doesn't fail
+                    When I parse "12" as a number, expecting an error
+            """
+        And I run the tests
+        Then the tests fail
+        And the step "I parse "12" as a number, expecting an error" failed mentioning "Expected an error"
+
+    Scenario: A misspelled step expected to fail still fails the scenario
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Misspelled
+                    When I prase "twelve" as a number, expecting an error
+                    Then it fails mentioning "twelve"
+            """
+        And I run the tests
+        Then the tests fail
+        And the step "I prase "twelve" as a number, expecting an error" failed mentioning "No implementation found"
@@ -26,3 +26,58 @@ Feature: Before/after hooks can be implemented as functions
 
     Scenario: Exclude inherited tags
         Then the NonInheritedFixture fixture is not present
+
+    @step-failures-are-warnings
+    Scenario: After step hooks can inspect and override the step outcome
+        Given a step that panics
+
+    @hook-order
+    Scenario: Hooks run in the order given
+        Then the hooks ran in order
+
+    Scenario: After hooks with the same order unwind before hooks
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                @hook-nesting
+                Scenario: Has nested hooks
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And after hooks with the same order ran in the reverse of before hooks
+
+    Scenario: Hook failures name the hook that failed
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                @failing-hook
+                Scenario: Fails in a hook
+            """
+        And I run the tests
+        Then the scenario "Fails in a hook" failed mentioning "failing_hook"
+
+    Scenario: Every failing after hook is reported
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                @failing-after-hooks
+                Scenario: Fails in two hooks
+                    Given a step that panics
+            """
+        And I run the tests
+        Then the scenario "Fails in two hooks" failed mentioning "first_failing_after_hook"
+        And the scenario "Fails in two hooks" failed mentioning "second_failing_after_hook"
+        And the step "a step that panics" failed mentioning "PANIC!"
+
+    Scenario: before_all hooks can share computed state
+        Then the dataset from before_all has 3 records
+        And global state can't be set from a step
+
+    Scenario: Name filters can limit hooks to scenarios that match
+        Then the NamedFixture fixture is present
+
+    Scenario: Name filters can skip scenarios that don't
+        Then the NamedFixture fixture is not present
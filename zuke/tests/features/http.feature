@@ -0,0 +1,44 @@
+@needs-http
+Feature: HTTP APIs can be tested
+
+    Background:
+        Given a zuke sub-instance
+        And a local HTTP server for the sub-instance
+
+    Scenario: Responses can be checked
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Lists items
+                    When I GET items
+                    Then the response status is 200
+                    And the response JSON at /items/0/name equals "widget"
+            """
+        And I run the tests
+        Then the tests complete successfully
+
+    Scenario: A docstring is sent as the request body
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Echoes an item
+                    When I POST echo
+                        ```
+                        {"name": "gadget"}
+                        ```
+                    Then the response status is 201
+                    And the response JSON at /name equals "gadget"
+            """
+        And I run the tests
+        Then the tests complete successfully
+
+    Scenario: A wrong status fails the step
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Asks for something missing
+                    When I GET missing
+                    Then the response status is 200
+            """
+        And I run the tests
+        Then the step "the response status is 200" failed mentioning "Expected status 200, got 404"
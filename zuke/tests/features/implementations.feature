@@ -49,3 +49,231 @@ Feature: Steps may be defined in a variety of ways
     @expect-fail
     Scenario: Multiply-implemented steps cause errors
         Given a step that is implemented twice
+
+    Scenario: Multiply-implemented steps report where they are implemented
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Ambiguous step
+                    Given a step that is implemented twice
+            """
+        And I run the tests
+        Then the step "a step that is implemented twice" failed mentioning "implementations.rs"
+
+    Scenario: Multiply-implemented steps can be found before running
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Doesn't use the conflicting step
+                    Given a step that returns nothing
+            """
+        And I add "--check" to the command line
+        Then running the tests fails mentioning "a step that is implemented twice"
+
+    Scenario: Pending steps don't fail the test run
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Not finished
+                    Given a step that is pending
+                    And a step that returns nothing
+            """
+        And I run the tests
+        Then the tests do not fail
+        And there are 1/1 pending scenarios
+        And there are 1/2 skipped steps
+
+    Scenario: Pending steps fail the test run in strict mode
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Not finished
+                    Given a step that is pending
+            """
+        And I add "--strict" to the command line
+        And I run the tests
+        Then the tests fail
+        And there are 1/1 pending scenarios
+
+    Scenario: Custom errors can choose their own verdict
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Synchronous
+                    Given a step that returns a custom error that skips
+                Scenario: Asynchronous
+                    Given an async step that returns a custom error that skips
+            """
+        And I run the tests
+        Then the tests do not fail
+        And there are 2/2 skipped scenarios
+
+    Scenario: Context added to a StepError keeps its verdict
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: With context
+                    Given a step that skips with context
+            """
+        And I run the tests
+        Then the tests do not fail
+        And the step "a step that skips with context" was skipped mentioning "while checking support"
+
+    Scenario: Steps used with the wrong keyword don't match
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Wrong keyword
+                    Given I will move the world
+            """
+        And I run the tests
+        Then the step "I will move the world" failed mentioning "No implementation found"
+
+    Scenario: Steps used with the wrong keyword are reported in strict keyword mode
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Wrong keyword
+                    Given I will move the world
+            """
+        And I add "--strict-keywords" to the command line
+        And I run the tests
+        Then the step "I will move the world" failed mentioning "implemented as a Then step"
+
+    Scenario: A * step has the type of the step before it
+        Given a place to stand
+        Then I will move the world
+        * I will move the world
+
+    Scenario: Messages say which type an And step has
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Unimplemented
+                    Given a place to stand
+                    And a step nobody wrote
+            """
+        And I run the tests
+        Then the step "a step nobody wrote" failed mentioning "And (Given) a step nobody wrote"
+
+    Scenario: Step text from a word processor is normalized before matching
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Pasted text
+                    Given a step that quotes “smart”  text
+            """
+        And I run the tests
+        Then the tests complete successfully
+
+    Scenario: Arguments are passed on as written, not normalized
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Pasted text
+                    Given a step that keeps “it’s  here”  as written
+            """
+        And I run the tests
+        Then the tests complete successfully
+
+    Scenario: Step text normalization can be turned off
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Pasted text
+                    Given a step that quotes “smart”  text
+            """
+        And I add "--no-normalize-text" to the command line
+        And I run the tests
+        Then the tests fail
+
+    Scenario: Steps can run other steps
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Nested steps pass
+                    Given a step that runs other steps
+                Scenario: Nested steps keep their verdict
+                    Given a step that runs a pending step
+                Scenario: Nested steps can't recurse forever
+                    Given a step that runs itself
+            """
+        And I run the tests
+        Then the tests fail
+        And there are 1/3 passing scenarios
+        And there are 1/3 pending scenarios
+        And the step "a step that runs itself" failed mentioning "nested more than 32 deep"
+
+    Scenario: Comparisons can fail with a diff
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Lists differ
+                    Given a step that expects unequal lists to be equal
+            """
+        And I run the tests
+        Then the tests fail
+        And the step "a step that expects unequal lists to be equal" failed with this diff
+            """
+            Values are not equal
+            - expected
+            + actual
+            [
+            1,
+            2,
+            -     4,
+            +     3,
+            ]
+            """
+
+    Scenario: assert_eq_diff! can describe what was compared
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Lists differ
+                    Given a step that compares unequal lists with assert_eq_diff
+            """
+        And I run the tests
+        Then the tests fail
+        And the step "a step that compares unequal lists with assert_eq_diff" failed mentioning "Wrong fruit for lunch"
+        And the step "a step that compares unequal lists with assert_eq_diff" failed mentioning "+     "banana","
+
+    Scenario: Steps know where they are in their scenario
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Background:
+                    Given this is step 1 of 3, from the background
+
+                Scenario: Counts background steps
+                    Given this is step 2 of 3, from the scenario
+                    And this is step 3 of 3, from the scenario
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And there are 3/3 passing steps
+
+    Scenario: Steps can be methods that share a struct
+        Given I put apples in my cart
+        And I also put pears in my cart
+        Then my cart holds apples, pears
+        And steps without self can still use the context
+
+    Scenario: Each scenario gets its own instance of a steps struct
+        Then my cart is empty
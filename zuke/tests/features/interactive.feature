@@ -0,0 +1,23 @@
+Feature: Failed steps can be debugged interactively
+
+    Background:
+        Given a zuke sub-instance
+        When I add "--debug" to the command line
+        And I add the feature source
+            """
+            Feature: An inline feature
+                @debug-hooks
+                Scenario: Fails the first time
+                    Given a step that passes when retried after its step hooks
+            """
+
+    Scenario: A retried step runs its step hooks again
+        When I answer the sub-instance's debug prompts with "retry"
+        And I run the tests
+        Then the tests complete successfully
+
+    Scenario: Continuing keeps the failure
+        When I answer the sub-instance's debug prompts with "help, continue, retry"
+        And I run the tests
+        Then the tests fail
+        And the step "a step that passes when retried after its step hooks" failed mentioning "The first attempt fails"
@@ -0,0 +1,23 @@
+Feature: Zuke can read features from Markdown files
+
+    Scenario: Gherkin code blocks are features
+        Given a zuke sub-instance
+        When I add the markdown path "tests/extra_features/markdown"
+        And I run the tests
+        Then the tests complete successfully
+        And there are 2/2 passing features
+        And there are 2/2 passing scenarios
+
+    Scenario: Features from one Markdown file have their own keys
+        Given a zuke sub-instance
+        When I add the markdown path "tests/extra_features/markdown/README.md"
+        And I run the tests
+        Then the features have unique keys and ids
+        And the scenarios have unique keys and ids
+
+    Scenario: Line numbers refer to the Markdown file
+        Given a zuke sub-instance
+        When I add the markdown path "tests/extra_features/markdown/README.md"
+        And I run the tests
+        Then the step "a step that returns nothing" is on line 14
+        And the step "I will move the world" is on line 22
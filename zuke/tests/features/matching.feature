@@ -28,3 +28,59 @@ Feature: Step implementations can match in a variety of ways
     @expect-fail
     Scenario: Regex expressions are anchored to the end
         Given a word with a double vowel "book" blah
+
+    Scenario: Ambiguous steps say which module implemented them
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Ambiguous
+                    Given a step implemented in two modules
+            """
+        And I run the tests
+        Then the step "a step implemented in two modules" failed mentioning "(in main::matches)"
+        And the step "a step implemented in two modules" failed mentioning "(in main::implementations)"
+
+    @use-steps(main::matches)
+    Scenario: Tags can choose between ambiguous steps
+        Given a step implemented in two modules
+
+    Scenario: A priority list can choose between ambiguous steps
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Ambiguous
+                    Given a step implemented in two modules
+            """
+        And I prefer steps from "other_crate, main::matches"
+        And I run the tests
+        Then the tests complete successfully
+
+    Scenario: A Zuke instance can use only some of the registered steps
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Mixed modules
+                    Given a regex step that returns nothing
+                    And a step that returns nothing
+            """
+        And I only use steps from "main::matches"
+        And I run the tests
+        Then the tests fail
+        And there are 1/2 passing steps
+        And the step "a step that returns nothing" failed mentioning "No implementation found"
+
+    Scenario: A Zuke instance can leave out steps by pattern
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Excluded step
+                    Given a word with a double vowel "book"
+            """
+        And I leave out steps matching "double vowel"
+        And I run the tests
+        Then the tests fail
+        And the step "a word with a double vowel "book"" failed mentioning "No implementation found"
@@ -0,0 +1,26 @@
+@needs-messaging
+Feature: Messages can be published and received through Kafka
+
+    # These don't need Kafka. The steps fail before they'd connect.
+
+    Scenario: A bad pattern fails the step
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Waits for a message
+                    Then a message matching "[unclosed" is received on topic "orders" within 1s
+            """
+        And I run the tests
+        Then the step "a message matching "[unclosed" is received on topic "orders" within 1s" failed mentioning "Bad regular expression"
+
+    Scenario: A timeout too long to keep fails the step
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Waits for a message
+                    Then no message matching "refund" is received on topic "orders" within 99999999999999999999999s
+            """
+        And I run the tests
+        Then the step "no message matching "refund" is received on topic "orders" within 99999999999999999999999s" failed mentioning "Bad timeout"
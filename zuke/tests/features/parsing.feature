@@ -26,6 +26,54 @@ Feature: Zuke can parse files
         And there are 2/2 passing rules
         And there are 8/8 passing scenarios
 
+    Scenario: Zuke can parse files matching a glob pattern
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/null/null_*.feature"
+        And I run the tests
+        Then the tests complete successfully
+        And there are 2/2 passing features
+        And there are 8/8 passing scenarios
+
+    Scenario: Glob patterns can search directories
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/**/null.feature"
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/1 passing features
+
+    Scenario: Directories are searched for features, skipping what .zukeignore lists
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/discovery"
+        And I run the tests
+        Then the tests complete successfully
+        And there are 4/4 passing features
+
+    Scenario: Directory searches can be limited in depth and skip more
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/discovery"
+        And I ignore features matching "drafts"
+        And I search for features at most 1 directories deep
+        And I run the tests
+        Then the tests complete successfully
+        And there are 2/2 passing features
+
+    Scenario: Directories can be searched for other extensions
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/discovery"
+        And I search for features with the extension "feature"
+        And I search for features with the extension "gherkin"
+        And I run the tests
+        Then the tests complete successfully
+        And there are 5/5 passing features
+        And in the scenario "A scenario in A feature with another extension", the step "a step that returns nothing" has the verdict "passed"
+
+    Scenario: A glob pattern that matches nothing is an error
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/null/nothing*.feature"
+        And I run the tests
+        Then the tests fail
+        And there are 0/1 passing features
+
     Scenario: Zuke can parse a source string
         Given a zuke sub-instance
         When I add the feature source
@@ -53,3 +101,115 @@ Feature: Zuke can parse files
         And there are 4/4 passing features
         And there are 2/2 passing rules
         And there are 10/10 passing scenarios
+
+    Scenario: Zuke can parse features in other languages
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            # language: fr
+            Fonctionnalité: Une fonctionnalité
+                Scénario: Déplacer le monde
+                    Soit a lever long enough
+                    Et a place to stand
+                    Alors I will move the world
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/1 passing scenarios
+        And there are 3/3 passing steps
+
+    Scenario: Steps can be written in other languages
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            # language: fr
+            Fonctionnalité: Une fonctionnalité
+                Scénario: Déplacer le monde
+                    Étant donné un levier assez long
+                    Alors je déplacerai le monde
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And there are 2/2 passing steps
+
+    Scenario: Steps can run steps with keywords in other languages
+        Given I move the world in French
+
+    Scenario: Feature files can start with front matter
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            ---
+            owner: team-payments
+            tags: [payments, database]
+            timeout: 100ms
+            ---
+            Feature: A feature with front matter
+                Scenario: This scenario takes too long
+                    When I pause forever
+
+                Scenario: This scenario is quick
+                    Given a step that returns nothing
+            """
+        And I run the tests
+        Then there are 1/2 passing scenarios
+        And the step "I pause forever" failed mentioning "Timed out"
+        And there are 2 scenarios tagged "database"
+        And the feature "A feature with front matter" is owned by "team-payments"
+
+    Scenario: Feature files can start with zuke comments
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            # zuke: owner: team-refunds
+            # zuke: language: fr
+            # zuke: tags: @refunds
+            Fonctionnalité: Une fonctionnalité
+                Scénario: Déplacer le monde
+                    Soit a lever long enough
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1 scenarios tagged "refunds"
+        And the feature "Une fonctionnalité" is owned by "team-refunds"
+
+    Scenario: Zuke can set the default language
+        Given a zuke sub-instance
+        When I set the language to "fr"
+        And I add the feature source
+            """
+            Fonctionnalité: Une fonctionnalité
+                Scénario: Déplacer le monde
+                    Soit a lever long enough
+                    Et a place to stand
+                    Alors I will move the world
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/1 passing scenarios
+        And there are 3/3 passing steps
+
+    Scenario: Rules and scenarios with the same name have their own keys
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: Duplicate names
+                Scenario: Same name
+                    Given a step that returns nothing
+
+                Scenario: Same name
+                    Given a step that returns nothing
+
+                Rule: Same rule
+                    Scenario: Same name
+                        Given a step that returns nothing
+
+                Rule: Same rule
+                    Scenario: Same name
+                        Given a step that returns nothing
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And the rules have unique keys and ids
+        And the scenarios have unique keys and ids
+        And the steps have unique keys and ids
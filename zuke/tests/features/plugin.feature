@@ -0,0 +1,32 @@
+@needs-wasm
+Feature: Steps can be loaded from WebAssembly plugins
+
+    Scenario: Plugin steps run and fail like other steps
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Passes
+                    Given a plugin step that passes
+                Scenario: Fails
+                    When a plugin step that fails
+            """
+        And I add "--plugin tests/extra_features/plugin/steps.wat" to the command line
+        And I run the tests
+        Then the tests fail
+        And there are 1/2 passing scenarios
+        And the step "a plugin step that fails" failed mentioning "Plugins can fail"
+
+    Scenario: A plugin step that never returns is stopped when it times out
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                @timeout(1s)
+                Scenario: Spins
+                    When a plugin step that never returns
+            """
+        And I add "--plugin tests/extra_features/plugin/steps.wat" to the command line
+        And I run the tests
+        Then the tests fail
+        And the step "a plugin step that never returns" failed mentioning "Timed out"
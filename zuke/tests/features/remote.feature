@@ -0,0 +1,31 @@
+Feature: Steps can be implemented by a remote step server
+
+    Scenario: Remote steps run, save values, and fail like other steps
+        Given a remote step server
+        And a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Greeting
+                    Given a remote step that greets world
+                    Then the remote step saved "hello world"
+                Scenario: Refusal
+                    Given a remote step that greets moon
+            """
+        And I add "--remote-steps tcp://{saved:remote server}" to the command line
+        And I run the tests
+        Then the tests fail
+        And there are 1/2 passing scenarios
+        And the step "a remote step that greets moon" failed mentioning "I only greet the world"
+
+    Scenario: A step server can be a child process, with quoted arguments
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: From a child process
+                    Given a step from the shell server
+            """
+        And I add "--remote-steps "sh tests/extra_features/remote/server.sh 'the shell server'"" to the command line
+        And I run the tests
+        Then the tests complete successfully
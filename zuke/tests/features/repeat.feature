@@ -0,0 +1,61 @@
+Feature: Scenarios can be run more than once
+
+    Scenario: Each scenario runs the requested number of times
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Runs three times
+                    Given a step that returns nothing
+            """
+        And I add "--repeat 3" to the command line
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/1 passing scenarios
+        And there are 3/3 passing steps
+
+    Scenario: An iteration that fails doesn't stop the rest
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Fails the first time
+                    Given a step that fails the first time
+                    And a step that returns nothing
+            """
+        And I add "--repeat 3" to the command line
+        And I add a reporter that only wants iterations
+        And I run the tests
+        Then there are 1/1 failed scenarios
+        And there are 4/6 passing steps
+        And there are 1/6 failed steps
+        And there are 1/6 skipped steps
+        And the reporter only got 3 iterations
+
+    Scenario: Repeating until failure stops at the first failure
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Fails the first time
+                    Given a step that panics
+            """
+        And I add "--repeat-until-failure 3" to the command line
+        And I run the tests
+        Then there are 1/1 failed scenarios
+        And there are 1/1 failed steps
+
+    Scenario: --bench times each scenario, after warming up
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: A feature to benchmark
+                Scenario: A quick scenario
+                    Given a step that returns nothing
+            """
+        And I add "--bench 3 --bench-warmup 2" to the command line
+        And I capture the bench output as JSON
+        And I run the tests
+        Then the tests complete successfully
+        And there are 5/5 passing steps
+        And the bench results for "A quick scenario" have 3 timed runs
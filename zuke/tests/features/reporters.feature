@@ -0,0 +1,222 @@
+Feature: Reporters can be configured
+
+    Background:
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Passes
+                    Given a lever long enough
+                        | length     |
+                        | Archimedes |
+                Scenario: Fails
+                    Given a place to stand
+                    And a step that panics
+            """
+
+    Scenario: The quiet plain reporter only prints failures
+        When I capture the plain output at "quiet" verbosity
+        And I run the tests
+        Then the plain output contains "Scenario: Fails"
+        And the plain output contains "a place to stand"
+        And the plain output contains "1 scenarios passed, 1 failed"
+        And the plain output does not contain "Scenario: Passes"
+
+    Scenario: The plain reporter only prints steps of scenarios that didn't pass
+        When I capture the plain output at "normal" verbosity
+        And I run the tests
+        Then the plain output contains "Scenario: Passes"
+        And the plain output contains "a place to stand"
+        And the plain output does not contain "a lever long enough"
+
+    Scenario: The verbose plain reporter prints every step
+        When I capture the plain output at "verbose" verbosity
+        And I run the tests
+        Then the plain output contains "a lever long enough"
+        And the plain output does not contain "| Archimedes |"
+
+    Scenario: The very verbose plain reporter prints tables and docstrings
+        When I capture the plain output at "very verbose" verbosity
+        And I run the tests
+        Then the plain output contains "| Archimedes |"
+
+    Scenario: The plain reporter prints warnings
+        When I add the feature source
+            """
+            Feature: A warning feature
+                Scenario: Warns
+                    Given a step that warns twice
+            """
+        And I capture the plain output at "verbose" verbosity
+        And I run the tests
+        Then the plain output contains "Warning: The lever is bending more"
+        And the plain output contains "1 scenarios with warnings"
+
+    Scenario: The plain reporter counts expected failures and unexpected passes
+        When I add the feature source
+            """
+            Feature: An expected failure feature
+                @expect-fail
+                Scenario: Fails as expected
+                    Given a step that panics
+                @expect-fail
+                Scenario: Passes unexpectedly
+                    Given a place to stand
+            """
+        And I capture the plain output at "quiet" verbosity
+        And I run the tests
+        Then the plain output contains "2 scenarios passed, 2 failed, 0 skipped, 1 expected failures, 1 unexpected passes"
+
+    Scenario: Quiet on success, the plain reporter prints only the summary when the run passes
+        When I add "--name ^Passes$" to the command line
+        And I capture the plain output, quiet on success, saving artifacts on failure
+        And I run the tests
+        Then the tests complete successfully
+        And the plain output contains "1 scenarios passed"
+        And the plain output does not contain "Scenario:"
+        And no artifacts were saved
+
+    Scenario: Quiet on success, the plain reporter prints everything when the run fails
+        When I add the feature source
+            """
+            Feature: A camera feature
+                Scenario: Takes a snapshot
+                    Given a camera is watching
+                    And a step that panics
+            """
+        And I capture the plain output, quiet on success, saving artifacts on failure
+        And I run the tests
+        Then the plain output contains "Scenario: Passes"
+        And the plain output contains "a lever long enough"
+        And the saved artifact "report.txt" contains "| Archimedes |"
+        And the saved artifact "report.txt" contains "1 scenarios passed, 2 failed"
+        And the saved artifact "001-Snapshot.txt" contains "Failed at: a step that panics"
+
+    Scenario: The plain reporter groups failures by owner
+        When I add the feature source
+            """
+            Feature: An owned feature
+                @owner(team-levers)
+                Scenario: Breaks for its owner
+                    Given a step that panics
+            """
+        And I capture the plain output at "quiet" verbosity
+        And I run the tests
+        Then the plain output contains "Failures by owner:"
+        And the plain output contains "team-levers (1)"
+        And the plain output contains "(no owner) (1)"
+
+    Scenario: The plain reporter can show the type of And steps
+        When I capture the plain output at "normal" verbosity
+        And I add "--show-step-types" to the command line
+        And I run the tests
+        Then the plain output contains "And (Given) a step that panics"
+        And the plain output contains "Given a place to stand"
+
+    Scenario: The verbose plain reporter says how the tests were run
+        When I capture the plain output at "verbose" verbosity
+        And I set up the sub-instance's "smoke" profile to use a fake backend
+        And I add "--profile smoke" to the command line
+        And I run the tests
+        Then the plain output contains ", profile smoke"
+        And the plain output contains "Command line: arg0 --profile smoke"
+
+    Scenario: The verbose plain reporter shows the random seed
+        When I capture the plain output at "verbose" verbosity
+        And I add "--seed 1234" to the command line
+        And I run the tests
+        Then the plain output contains ", seed 1234"
+
+    Scenario: The OpenMetrics reporter counts scenarios
+        When I capture the OpenMetrics output
+        And I run the tests
+        Then the OpenMetrics output has these lines
+            """
+            zuke_scenarios{verdict="passed"} 1
+            zuke_scenarios{verdict="failed"} 1
+            zuke_feature_scenarios{feature="An inline feature",verdict="failed"} 1
+            zuke_scenario_duration_seconds_count 2
+            zuke_run_passed 0
+            # EOF
+            """
+
+    Scenario: A reporter that can't write its output doesn't hold up the others
+        When I add a plain reporter whose output can't be written
+        And I capture the plain output at "quiet" verbosity
+        And I run the tests
+        Then there are 1/2 failed scenarios
+        And the plain output contains "1 scenarios passed, 1 failed"
+
+    Scenario: A reporter that's stuck writing its output doesn't hold up the others
+        When I add a plain reporter whose output blocks forever
+        And I capture the plain output at "quiet" verbosity
+        And I run the tests
+        Then the plain output soon contains "1 scenarios passed, 1 failed"
+
+    Scenario: A reporter that can't write its output can fall back to another
+        When I add a plain reporter whose output can't be written, falling back to the plain output
+        And I run the tests
+        Then the plain output contains "Scenario: Fails"
+        And the plain output contains "1 scenarios passed, 1 failed"
+
+    Scenario: A recorded run can be replayed to reporters later
+        When I record the run
+        And I run the tests
+        And I replay the recording to the plain output
+        Then the plain output contains "Scenario: Fails"
+        And the plain output contains "a lever long enough"
+        And the plain output contains "1 scenarios passed, 1 failed"
+
+    Scenario: A recorded run with pruned outcomes and repeated names can be replayed
+        When I add the feature source
+            """
+            Feature: Repeated names
+                Background:
+                    Given a lever long enough
+                Scenario: Same
+                    Given a place to stand
+                Scenario: Same
+                    Given a step that panics
+            """
+        And I add "--prune-outcomes" to the command line
+        And I record the run
+        And I run the tests
+        And I replay the recording to the plain output
+        Then the plain output contains "2 scenarios passed, 2 failed"
+
+    Scenario: Reporters only get the events they ask for
+        When I add a reporter that only wants finished scenarios
+        And I run the tests
+        Then the reporter only got 2 finished scenarios
+
+    Scenario: The badge reporter writes a shields.io endpoint
+        When I capture the badge output with the label "nightly"
+        And I run the tests
+        Then the badge output is the JSON
+            """
+            {"schemaVersion": 1, "label": "nightly", "message": "1/2 passed", "color": "orange"}
+            """
+
+    Scenario: The Allure reporter writes a result for each scenario
+        When I write Allure results
+        And I run the tests
+        Then there are 2 Allure results
+        And the Allure result for "Passes" has status "passed"
+        And the Allure result for "Fails" has status "failed"
+        And the Allure result for "Passes" has a "Table" attachment
+        And the Allure results are in a container
+
+    Scenario: Allure results for scenarios with the same name have different history IDs
+        When I add the feature source
+            """
+            Feature: Repeated names
+                Scenario: Same
+                    Given a lever long enough
+                Scenario: Same
+                    Given a place to stand
+            """
+        And I write Allure results
+        And I run the tests
+        Then there are 4 Allure results
+        And the Allure results have different history IDs
+        And the Allure results are in a container
@@ -0,0 +1,286 @@
+Feature: Custom runners can be built from the standard parts
+
+    Scenario: A sequential runner runs everything
+        Given a zuke sub-instance
+        When I use a sequential runner
+        And I add the path "tests/extra_features/null/null_items.feature"
+        And I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: A scenario with steps
+                    Given a step that returns nothing
+                    Then I will move the world
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And there are 2/2 passing features
+        And there are 2/2 passing rules
+        And there are 7/7 passing scenarios
+        And there are 2/2 passing steps
+
+    Scenario: Isolated scenarios survive a crashing step
+        When I run this test binary with `--isolate-scenarios --workers 2 --config tests/extra_features/isolate/zuke.toml --name ^Crash\sisolation$`
+        Then the exit code is 101
+        And stdout contains "1 scenarios passed, 1 failed"
+        And stdout contains "The worker exited while running the scenario"
+
+    Scenario: Workers run scenarios with the same name
+        When I run this test binary with `--workers 2 --config tests/extra_features/distributed/zuke.toml --name ^Distributed\sduplicates$`
+        Then the exit code is 101
+        And stdout contains "1 scenarios passed, 1 failed"
+
+    Scenario: Workers report each iteration of a repeated scenario
+        When I run this test binary with `--workers 2 --repeat 2 --reporter events --config tests/extra_features/distributed/zuke.toml --name ^Distributed\sduplicates$`
+        Then the exit code is 101
+        And stdout contains "iteration_finished"
+
+    Scenario: cargo test -q runs the plain reporter quietly
+        When I run this test binary with `-q --config tests/extra_features/quiet/zuke.toml --name ^Quiet$`
+        Then the exit code is 101
+        And stdout contains "Scenario: Fails quietly"
+        And stdout contains "1 scenarios passed, 1 failed"
+        And stdout does not contain "Passes quietly"
+
+    Scenario: Listening gives up if no workers connect
+        When I run this test binary with `--listen 127.0.0.1:0 --listen-timeout 1s --config tests/extra_features/distributed/zuke.toml --name ^Distributed\sduplicates$`
+        Then the exit code is 101
+        And stdout contains "No workers connected"
+
+    Scenario: By default, scenarios start in file order
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/schedule/schedule.feature"
+        And I add "--baseline tests/extra_features/schedule/baseline.json" to the command line
+        And I record the order scenarios start in
+        And I run the tests
+        Then the scenarios started in this order
+            """
+            Fast and passing
+            Slow and passing
+            Failed last time
+            New since last time
+            """
+
+    Scenario: With failed-first, scenarios that failed before start first
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/schedule/schedule.feature"
+        And I add "--baseline tests/extra_features/schedule/baseline.json" to the command line
+        And I add "--schedule failed-first" to the command line
+        And I record the order scenarios start in
+        And I run the tests
+        Then the scenarios started in this order
+            """
+            Failed last time
+            Fast and passing
+            Slow and passing
+            New since last time
+            """
+
+    Scenario: With slowest-first, scenarios that were slow before start first
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/schedule/schedule.feature"
+        And I add "--baseline tests/extra_features/schedule/baseline.json" to the command line
+        And I add "--schedule slowest-first" to the command line
+        And I record the order scenarios start in
+        And I run the tests
+        Then the scenarios started in this order
+            """
+            Slow and passing
+            Failed last time
+            Fast and passing
+            New since last time
+            """
+
+    Scenario: Scenarios of an @ordered feature run one at a time
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/runner/ordered.feature"
+        And I record the order scenarios start in
+        And I run the tests
+        Then the tests complete successfully
+        And the scenarios started in this order
+            """
+            The first scenario to use the counter
+            The second scenario to use the counter
+            The third scenario to use the counter
+            The fourth scenario to use the counter
+            """
+
+    Scenario: --lane-size limits how many scenarios of a feature run at once
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/runner/lanes.feature"
+        And I add "--lane-size 1" to the command line
+        And I run the tests
+        Then the tests complete successfully
+        And there are 2/2 passing scenarios
+
+    Scenario: --warn-slow-step flags steps that pass, but take too long
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/runner/slow.feature"
+        And I add "--warn-slow-step 10ms" to the command line
+        And I run the tests
+        Then the tests complete successfully
+        And the step "I use the counter "slow" for a moment" passed with warnings mentioning "Slow step"
+
+    Scenario: Steps can warn without stopping
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Warns
+                    Given a step that warns twice
+                    Then I will move the world
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And the step "a step that warns twice" has 2 warnings
+        And there are 2/2 passing steps
+
+    Scenario: Step outcomes say which implementation matched, and what it captured
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Matches
+                    When I use the counter "matched" for a moment, with at most 2 users
+                    Then nothing implements this step
+            """
+        And I run the tests
+        Then the step "I use the counter "matched" for a moment, with at most 2 users" was matched in "tags.rs" with the arguments "matched, 2"
+        And the step "nothing implements this step" has no step match
+
+    Scenario: Steps faster than --warn-slow-step pass as usual
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/runner/slow.feature"
+        And I add "--warn-slow-step 10s" to the command line
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/1 passing steps
+
+    Scenario: A duration too long to keep is an error
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/runner/slow.feature"
+        And I add "--warn-slow-step 99999999999999999999999h" to the command line
+        Then running the tests fails mentioning "Bad --warn-slow-step"
+
+    Scenario: Shards split the scenarios between them
+        Then splitting "tests/extra_features/runner/shards.feature" into 3 shards runs each scenario exactly once
+
+    Scenario: A shard outside the count is an error
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/runner/shards.feature"
+        And I add "--shard 4/3" to the command line
+        Then running the tests fails mentioning "Bad --shard"
+
+    Scenario: --prune-outcomes keeps only failures, but counts everything
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: A passing scenario
+                    Given a step that returns nothing
+
+                Scenario: A failing scenario
+                    Given a step that panics
+            """
+        And I add "--prune-outcomes" to the command line
+        And I run the tests
+        Then the tests fail
+        And there are 1/2 passing scenarios
+        And there are 1/2 failed steps
+        And the step "a step that panics" failed mentioning "PANIC!"
+        And the outcome of the scenario "A passing scenario" was pruned
+
+    Scenario: --prune-outcomes counts every step of a repeated scenario
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: A repeated scenario
+                    Given a step that returns nothing
+            """
+        And I add "--prune-outcomes --repeat 3" to the command line
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/1 passing scenarios
+        And there are 3/3 passing steps
+        And the outcome of the scenario "A repeated scenario" was pruned
+
+    Scenario: Outcomes can be serialized as JSON
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: A passing scenario
+                    Given a step that returns nothing
+
+                Scenario: A failing scenario
+                    Given a step that panics
+            """
+        And I run the tests
+        Then the outcome as JSON has the scenario "A passing scenario" with the verdict "passed"
+        And the outcome as JSON has the scenario "A failing scenario" with the verdict "failed"
+
+    Scenario: Components skipped because of a parent say why
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            @fail
+            Feature: A broken feature
+                Scenario: Never runs
+                    Given a step that returns nothing
+                Rule: A rule
+                    Scenario: Never runs either
+                        Given a place to stand
+            """
+        And I run the tests
+        Then the tests fail
+        And the scenario "Never runs" was skipped because "feature "A broken feature" failed"
+        And the scenario "Never runs either" was skipped because "feature "A broken feature" failed"
+        And the step "a step that returns nothing" was skipped because "feature "A broken feature" failed"
+
+    Scenario: Outcomes can be queried by tag, verdict, and step text
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                @fast
+                Scenario: Moves the world
+                    Given a place to stand
+                    Then I will move the world
+                @fast @fragile
+                Scenario: Breaks the lever
+                    Given a place to stand
+                    And a step that panics
+            """
+        And I run the tests
+        Then there are 2 scenarios tagged "@fast"
+        And the only "failed" scenario tagged "fast" is "Breaks the lever"
+        And in the scenario "Breaks the lever", the step "And a step that panics" has the verdict "failed"
+        And in the scenario "Moves the world", the step "a place to stand" has the verdict "passed"
+        And there is 1 passing and 1 failed scenario, with 4 steps
+
+    Scenario: Tagged features can go to their own runner
+        Given a zuke sub-instance
+        When I run features tagged "serial" with a sequential runner
+        And I add the feature source
+            """
+            @serial
+            Feature: Shares a counter
+                Scenario: First user
+                    When I use the counter "partitioned" for a moment
+                Scenario: Second user
+                    When I use the counter "partitioned" for a moment
+                Scenario: Third user
+                    When I use the counter "partitioned" for a moment
+            """
+        And I add the feature source
+            """
+            Feature: Runs concurrently
+                Scenario: One side
+                    When I wait for 2 scenarios to say "partitioned"
+                Scenario: Other side
+                    When I wait for 2 scenarios to say "partitioned"
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And there are 2/2 passing features
+        And there are 5/5 passing scenarios
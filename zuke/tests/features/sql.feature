@@ -0,0 +1,68 @@
+@needs-sql
+Feature: SQL databases can be seeded and checked
+
+    Background:
+        Given a zuke sub-instance
+        When I add "--config tests/extra_features/sql/zuke.toml" to the command line
+
+    Scenario: An in-memory database is the same in every step
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Seeds and checks a table
+                    When I execute the SQL
+                        ```
+                        CREATE TABLE orders (id INTEGER, item TEXT)
+                        ```
+                    Given the table orders contains
+                        | id | item  |
+                        | 1  | apple |
+                        | 2  | pear  |
+                    Then the table orders contains exactly
+                        | item  | id |
+                        | pear  | 2  |
+                        | apple | 1  |
+            """
+        And I run the tests
+        Then the tests complete successfully
+
+    Scenario: Semicolons in strings don't split statements
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Runs a script
+                    When I execute the SQL
+                        ```
+                        CREATE TABLE notes (body TEXT);
+                        INSERT INTO notes VALUES ('first; second');
+                        ```
+                    Then the query "SELECT body FROM notes" returns
+                        | body          |
+                        | first; second |
+            """
+        And I run the tests
+        Then the tests complete successfully
+
+    Scenario: A column can be named NULL
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Queries a NULL
+                    Then the query "SELECT NULL, 'x' AS name" returns
+                        | NULL | name |
+                        | NULL | x    |
+            """
+        And I run the tests
+        Then the tests complete successfully
+
+    Scenario: A mismatch names the cell
+        When I add the feature source
+            """
+            Feature: An inline feature
+                Scenario: Queries a number
+                    Then the query "SELECT 1 AS n" returns
+                        | n |
+                        | 2 |
+            """
+        And I run the tests
+        Then the step "the query "SELECT 1 AS n" returns" failed mentioning "Row 1, column n"
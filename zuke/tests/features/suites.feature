@@ -0,0 +1,38 @@
+Feature: Several suites can be run as one
+
+    Scenario: Features from every suite are reported together
+        When I add the suite "levers" with the feature source
+            """
+            Feature: Levers
+                Scenario: Long enough
+                    Given a lever long enough
+                Scenario: Somewhere to stand
+                    Given a place to stand
+            """
+        And I add the suite "panics" with the feature source
+            """
+            Feature: Panics
+                Scenario: Panics
+                    Given a step that panics
+            """
+        And I run the suites
+        Then the combined run has 2 features
+        And the combined run has 2 passing and 1 failing scenarios
+        And the combined run's verdict is "failed"
+
+    Scenario: Suites with the same feature paths have different keys
+        When I add the suite "first" with the feature source "shared.feature"
+            """
+            Feature: Shared
+                Scenario: Same
+                    Given a lever long enough
+            """
+        And I add the suite "second" with the feature source "shared.feature"
+            """
+            Feature: Shared
+                Scenario: Same
+                    Given a lever long enough
+            """
+        And I run the suites
+        Then the combined run has 2 features
+        And the combined run's scenarios have unique keys
@@ -21,3 +21,191 @@ Feature: Default tags work as expected
         And I run the tests
         Then there are 0/1 skipped features
         And there are 2/3 skipped scenarios
+
+    Scenario: Default tags can be turned off
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/skip.feature"
+        And I turn off the default tags
+        And I run the tests
+        Then there are 0/3 skipped scenarios
+        And there are 2/3 failed scenarios
+
+    Scenario: Tag plugins can add new tags
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/ignore.feature"
+        And I add the ignore tag plugin
+        And I run the tests
+        Then there are 1/2 passing scenarios
+        And there are 1/2 skipped scenarios
+
+    Scenario: Slow scenarios are skipped by default
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/slow.feature"
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/2 passing scenarios
+        And there are 1/2 skipped scenarios
+
+    Scenario: Slow scenarios run with --include-slow
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/slow.feature"
+        And I add "--include-slow" to the command line
+        And I run the tests
+        Then the tests complete successfully
+        And there are 2/2 passing scenarios
+
+    Scenario: Scenarios fail when they run past their timeout
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/timeout.feature"
+        And I run the tests
+        Then there are 1/2 passing scenarios
+        And there are 1/2 failed scenarios
+        And the step "I pause forever" failed mentioning "Timed out"
+        And there are 1/3 skipped steps
+
+    Scenario: Works in progress are skipped by default
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/wip.feature"
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/3 passing scenarios
+        And there are 2/3 skipped scenarios
+
+    Scenario: Only works in progress run with --wip, and they shouldn't pass
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/wip.feature"
+        And I add "--wip" to the command line
+        And I run the tests
+        Then the tests fail
+        And there are 1/3 skipped scenarios
+        And there are 2/3 failed scenarios
+        And the scenario "This scenario is finished, but still tagged" failed mentioning "remove the @wip tag"
+
+    Scenario: Pauses are ignored unless running interactively
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/pause.feature"
+        And I run the tests
+        Then the tests complete successfully
+        And there are 2/2 passing steps
+
+    Scenario: Scenarios that lock the same resource take turns
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/lock.feature"
+        And I run the tests
+        Then the tests complete successfully
+        And there are 4/4 passing scenarios
+
+    Scenario: Scenarios with the same name release their own locks
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/lock-duplicates.feature"
+        And I run the tests
+        Then the tests complete successfully
+        And there are 3/3 passing scenarios
+
+    Scenario: Scenarios that don't lock a resource can run at the same time
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/no-lock.feature"
+        And I run the tests
+        Then the tests fail
+        And there are 1/2 failed scenarios
+
+    Scenario: Scenarios can limit how many of them run at once
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/max-parallel.feature"
+        And I run the tests
+        Then the tests complete successfully
+        And there are 6/6 passing scenarios
+
+    Scenario: Scenarios with the same name give back their own slots
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/max-parallel-duplicates.feature"
+        And I run the tests
+        Then the tests complete successfully
+        And there are 3/3 passing scenarios
+
+    Scenario: Tags can take arguments, such as a duration for @timeout
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                @timeout(100ms)
+                Scenario: This scenario takes too long
+                    When I pause forever
+
+                @timeout("1m")
+                Scenario: This scenario has plenty of time
+                    Given a step that returns nothing
+            """
+        And I run the tests
+        Then there are 1/2 passing scenarios
+        And the step "I pause forever" failed mentioning "Timed out"
+
+    Scenario: Tags with bad arguments fail the scenario
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                @timeout(soon)
+                Scenario: This scenario has a bad timeout
+                    Given a step that returns nothing
+                @timeout(99999999999999999999999)
+                Scenario: This scenario has a timeout too long to keep
+                    Given a step that returns nothing
+            """
+        And I run the tests
+        Then the scenario "This scenario has a bad timeout" failed mentioning "@timeout takes a duration"
+        And the scenario "This scenario has a timeout too long to keep" failed mentioning "@timeout takes a duration"
+
+    Scenario: Quarantined scenarios fail with warnings
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/quarantine.feature"
+        And I capture the plain output at "quiet" verbosity
+        And I run the tests
+        Then there are 2/4 passing scenarios
+        And there are 2/4 failed scenarios
+        And the plain output contains "Quarantined failures:"
+        And the plain output contains "::This scenario is tagged as flaky: "
+
+    Scenario: Scenarios can be quarantined with a file
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/quarantine.feature"
+        And I add "--quarantine tests/extra_features/tags/quarantine.txt" to the command line
+        And I run the tests
+        Then there are 3/4 passing scenarios
+        And there are 1/4 failed scenarios
+
+    Scenario: Quarantining one scenario doesn't quarantine another with the same name
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/quarantine-duplicates.feature"
+        And I add "--quarantine tests/extra_features/tags/quarantine-duplicates.txt" to the command line
+        And I run the tests
+        Then there are 1/2 passing scenarios
+        And there are 1/2 failed scenarios
+
+    Scenario: Scenarios fail when they take longer than their @max-duration
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/max-duration.feature"
+        And I run the tests
+        Then there are 1/2 passing scenarios
+        And there are 1/2 failed scenarios
+        And the scenario "This scenario takes too long" failed mentioning "@max-duration"
+
+    Scenario: Scenarios without steps are timed by the real clock
+        Given a zuke sub-instance
+        When I add the feature source
+            """
+            Feature: An inline feature
+                @max-duration(10s) @an-hour-on-the-mock-clock
+                Scenario: Has no steps
+            """
+        And I run the tests
+        Then the tests complete successfully
+        And there are 1/1 passing scenarios
+
+    Scenario: Scenarios past their @max-duration can warn instead
+        Given a zuke sub-instance
+        When I add the path "tests/extra_features/tags/max-duration.feature"
+        And I add "--max-duration-warns" to the command line
+        And I run the tests
+        Then the tests complete successfully
+        And there are 2/2 passing scenarios
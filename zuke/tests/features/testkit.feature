@@ -0,0 +1,20 @@
+Feature: Test kit
+    Step libraries can test their steps on their own, without a test run
+
+    Scenario: A step runs on its own
+        When I test the step "Given a place to stand" on its own
+        Then the tested step has the verdict "passed"
+
+    Scenario: A step that isn't defined is reported
+        When I test the step "Given a place to sit" on its own
+        Then the tested step has the verdict "failed"
+
+    Scenario: Fixtures can be replaced with fakes
+        When I test the step "Then there are 5 widgets" with 5 fake widgets
+        Then the tested step has the verdict "passed"
+        When I test the step "Then there are 5 widgets" on its own
+        Then the tested step has the verdict "failed"
+
+    Scenario: Hooks from the hook macros run around the steps
+        When I test the step "Given a place to stand" on its own
+        Then the test kit ran the feature hooks
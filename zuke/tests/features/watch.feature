@@ -0,0 +1,20 @@
+Feature: --watch notices changed files
+
+    Scenario: Changed and new feature files are noticed once
+        Given a watched directory with the file "a.feature"
+        When I change the file "a.feature"
+        And I change the file "sub/b.feature"
+        And I change the file "notes.txt"
+        Then the watcher sees changes to "a.feature, sub/b.feature"
+        And the watcher sees no changes
+
+    Scenario: Unchanged files aren't noticed
+        Given a watched directory with the file "a.feature"
+        Then the watcher sees no changes
+
+    Scenario: Changed Rust sources are noticed, but aren't features
+        Given a watched directory with the file "src/lib.rs"
+        When I change the file "src/lib.rs"
+        And I change the file "tests/steps.rs"
+        Then the watcher sees a change to Rust sources, but no features
+        And the watcher sees no changes
@@ -1,3 +1,4 @@
+use async_trait::async_trait;
 use futures::future::pending;
 use zuke::*;
 
@@ -5,3 +6,29 @@ use zuke::*;
 async fn pause_forever() {
     let () = pending().await;
 }
+
+struct HangingFixture;
+
+#[async_trait]
+impl Fixture for HangingFixture {
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn teardown(&mut self, _context: &mut Context) -> anyhow::Result<()> {
+        pending().await
+    }
+}
+
+#[given("a fixture that never tears down")]
+async fn never_tears_down(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<HangingFixture>().await
+}
+
+#[when("I poll for cancellation")]
+fn poll_for_cancellation(context: &mut Context) -> anyhow::Result<()> {
+    loop {
+        context.check_canceled()?;
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+}
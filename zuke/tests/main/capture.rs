@@ -105,3 +105,43 @@ async fn expects_foo_context_basic(context: &str) {
 async fn expects_foo_context_unused_basic(_context: &str) {
     assert_eq!(_context, "foo")
 }
+
+#[given(
+    regex,
+    r#"a regex step that expects number(?: (?P<num>\d+))? to be (?P<expected>present|absent)"#
+)]
+async fn expects_optional_hundred(num: Option<u32>, expected: String) {
+    match expected.as_str() {
+        "present" => assert_eq!(num, Some(100)),
+        _ => assert_eq!(num, None),
+    }
+}
+
+#[given(
+    regex,
+    r#"a regex step that expects str(?: "(?P<what>.*)")? to be (?P<expected>present|absent)"#
+)]
+async fn expects_optional_foo_str(what: Option<&str>, expected: String) {
+    match expected.as_str() {
+        "present" => assert_eq!(what, Some("foo")),
+        _ => assert_eq!(what, None),
+    }
+}
+
+#[given("a step that expects a list of colors {colors}")]
+async fn expects_color_list(colors: Vec<Color>) {
+    assert_eq!(colors, vec![Color::Red, Color::Green, Color::Blue]);
+}
+
+#[given(
+    "a step that expects a space separated list of colors {colors}",
+    separator = " "
+)]
+async fn expects_space_separated_color_list(colors: Vec<Color>) {
+    assert_eq!(colors, vec![Color::Red, Color::Green, Color::Blue]);
+}
+
+#[given("a step that expects a list of strs {what}")]
+async fn expects_str_list(what: Vec<&str>) {
+    assert_eq!(what, vec!["foo", "bar"]);
+}
@@ -0,0 +1,28 @@
+//! Scenarios tagged `@needs-<feature>` are skipped unless zuke was built with that cargo feature,
+//! since their steps aren't compiled in otherwise
+use zuke::*;
+
+const CARGO_FEATURES: &[(&str, bool)] = &[
+    ("http", cfg!(feature = "http")),
+    ("sql", cfg!(feature = "sql")),
+    ("browser", cfg!(feature = "browser")),
+    ("messaging", cfg!(feature = "messaging")),
+    ("wasm", cfg!(feature = "wasm")),
+    ("dashboard", cfg!(feature = "dashboard")),
+];
+
+#[before_scenario]
+async fn skip_without_cargo_feature(context: &mut Context) -> anyhow::Result<()> {
+    for tag in context.tags() {
+        let name = match tag.strip_prefix("needs-") {
+            Some(name) => name,
+            None => continue,
+        };
+        match CARGO_FEATURES.iter().find(|(n, _)| *n == name) {
+            Some((_, true)) => (),
+            Some((_, false)) => skip!("Needs the {} feature", name),
+            None => anyhow::bail!("Unknown cargo feature {:?}", name),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use crate::sub_instance::SubInstance;
+use async_std::net::TcpStream;
+use async_trait::async_trait;
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use serde_json::Value;
+use zuke::reporter::DashboardReporter;
+use zuke::*;
+
+/// Where a sub-instance's dashboard is served
+pub struct Dashboard {
+    address: String,
+}
+
+#[async_trait]
+impl Fixture for Dashboard {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        // A port that was free a moment ago
+        let port = std::net::TcpListener::bind("127.0.0.1:0")?
+            .local_addr()?
+            .port();
+        Ok(Self {
+            address: format!("127.0.0.1:{}", port),
+        })
+    }
+}
+
+#[when("I serve the dashboard")]
+async fn when_i_serve_the_dashboard(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<Dashboard>().await?;
+    let address = context.fixture::<Dashboard>().await.address.clone();
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance
+        .builder()
+        .reporter(DashboardReporter::new(address));
+    Ok(())
+}
+
+/// The dashboard's latest state, from `/state`
+async fn dashboard_state(context: &mut Context) -> anyhow::Result<Value> {
+    context.fixture_mut::<SubInstance>().await.outcome().await;
+    let address = context.fixture::<Dashboard>().await.address.clone();
+
+    let mut stream = TcpStream::connect(&address).await?;
+    let request = format!(
+        "GET /state HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        address
+    );
+    stream.write_all(request.as_bytes()).await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    let body = match response.split_once("\r\n\r\n") {
+        Some((_, body)) => body,
+        None => anyhow::bail!("Bad response: {:?}", response),
+    };
+    Ok(serde_json::from_str(body)?)
+}
+
+#[then(
+    "the dashboard shows the finished run, with {passed} passing and {failed} failing scenarios"
+)]
+async fn then_the_dashboard_shows(
+    context: &mut Context,
+    passed: u64,
+    failed: u64,
+) -> anyhow::Result<()> {
+    let state = dashboard_state(context).await?;
+    anyhow::ensure!(state["finished"] == true, "Not finished: {:#}", state);
+    anyhow::ensure!(
+        state["scenarios"]["passed"] == passed && state["scenarios"]["failed"] == failed,
+        "Wrong counts: {:#}",
+        state["scenarios"]
+    );
+    Ok(())
+}
@@ -1,5 +1,9 @@
+use async_std::task;
 use async_trait::async_trait;
+use futures::stream::StreamExt;
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use zuke::*;
 
 struct ScenarioCounter {
@@ -118,3 +122,45 @@ async fn inc_global_counter(context: &mut Context) -> anyhow::Result<()> {
     counter.count.fetch_add(1, Ordering::Relaxed);
     Ok(())
 }
+
+/// Counts steps that finish in its scenario, as they finish
+struct StepWatcher {
+    finished: Arc<AtomicU32>,
+}
+
+#[async_trait]
+impl Fixture for StepWatcher {
+    async fn setup(context: &mut Context) -> anyhow::Result<Self> {
+        let finished = Arc::new(AtomicU32::new(0));
+        let mut events = context.subscribe_events(Self::SCOPE);
+        let count = finished.clone();
+        task::spawn(async move {
+            while let Some(event) = events.next().await {
+                if let Event::Finished(outcome) = event {
+                    if outcome.kind() == ComponentKind::Step {
+                        count.fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            }
+        });
+        Ok(Self { finished })
+    }
+}
+
+#[given("a fixture that counts finished steps in its scenario")]
+async fn watch_steps(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<StepWatcher>().await
+}
+
+#[then("{n} steps have finished in this scenario")]
+async fn check_finished_steps(context: &mut Context, n: u32) {
+    let watcher = context.fixture::<StepWatcher>().await;
+    // Events are delivered in the background, so give them a moment
+    for _ in 0..100 {
+        if watcher.finished.load(Ordering::SeqCst) >= n {
+            break;
+        }
+        task::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(watcher.finished.load(Ordering::SeqCst), n);
+}
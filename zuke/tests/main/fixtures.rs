@@ -0,0 +1,247 @@
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use zuke::fixtures::TempDir;
+use zuke::flag::Flag;
+use zuke::reporter::Collect;
+use zuke::{given, then, when, Artifact, Context, FailureArtifactProvider, Fixture};
+use zuke::{CancelMethod, ComponentKind, ZukeBuilder};
+
+/// A fixture that tests would rather not use for real
+pub struct Backend {
+    pub name: &'static str,
+}
+
+#[async_trait]
+impl Fixture for Backend {
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Backend { name: "real" })
+    }
+}
+
+/// Stands in for [`Backend`]
+pub struct FakeBackend;
+
+#[async_trait]
+impl Fixture for FakeBackend {
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(FakeBackend)
+    }
+}
+
+impl From<FakeBackend> for Backend {
+    fn from(_fake: FakeBackend) -> Self {
+        Backend { name: "fake" }
+    }
+}
+
+/// Takes a snapshot when something fails, like a browser taking a screenshot
+pub struct Camera;
+
+#[async_trait]
+impl Fixture for Camera {
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Camera)
+    }
+
+    fn artifact_provider(&self) -> Option<&dyn FailureArtifactProvider> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl FailureArtifactProvider for Camera {
+    async fn failure_artifacts(&self, context: &mut Context) -> anyhow::Result<Vec<Artifact>> {
+        let text = format!("Failed at: {}", context.component().name());
+        Ok(vec![Artifact::text("Snapshot", text)])
+    }
+}
+
+/// A camera whose snapshots never come back
+pub struct StuckCamera;
+
+#[async_trait]
+impl Fixture for StuckCamera {
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(StuckCamera)
+    }
+
+    fn artifact_provider(&self) -> Option<&dyn FailureArtifactProvider> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl FailureArtifactProvider for StuckCamera {
+    async fn failure_artifacts(&self, _context: &mut Context) -> anyhow::Result<Vec<Artifact>> {
+        futures::future::pending().await
+    }
+}
+
+lazy_static! {
+    /// Temporary directories by name, so that we can check on them after the scenario ends
+    static ref TEMPDIRS: Mutex<HashMap<String, PathBuf>> = Mutex::new(HashMap::new());
+}
+
+#[given(r#"a temporary directory remembered as "{name}""#)]
+async fn a_temporary_directory(context: &mut Context, name: String) -> anyhow::Result<()> {
+    context.use_fixture::<TempDir>().await?;
+    let path = context.fixture::<TempDir>().await.path().to_path_buf();
+    assert!(path.is_dir());
+    TEMPDIRS.lock().insert(name, path);
+    Ok(())
+}
+
+#[when(r#"I write "{text}" to the scratch file "{path}""#)]
+fn write_scratch_file(_context: &mut Context, text: String, path: String) -> anyhow::Result<()> {
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+#[then(r#"the scratch file "{path}" contains "{text}""#)]
+fn scratch_file_contains(_context: &mut Context, path: String, text: String) -> anyhow::Result<()> {
+    assert_eq!(std::fs::read_to_string(path)?, text);
+    Ok(())
+}
+
+#[then(r#"the text "{text}" is unchanged"#)]
+fn text_is_unchanged(_context: &mut Context, text: String) {
+    assert_eq!(text, "{not-an-expansion}");
+}
+
+#[then(r#"the text "{text}" is "{expected}""#)]
+fn text_is(_context: &mut Context, text: String, expected: String) {
+    assert_eq!(text, expected);
+}
+
+#[then(r#"the temporary directory remembered as "{name}" was removed"#)]
+fn temporary_directory_removed(_context: &mut Context, name: String) {
+    let path = TEMPDIRS.lock().get(&name).cloned();
+    let path = path.expect("no such temporary directory");
+    assert!(!path.exists(), "{} still exists", path.display());
+}
+
+#[then(r#"the backend is "{name}""#)]
+async fn the_backend_is(context: &mut Context, name: String) -> anyhow::Result<()> {
+    context.use_fixture::<Backend>().await?;
+    assert_eq!(context.fixture::<Backend>().await.name, name);
+    Ok(())
+}
+
+#[given("a camera is watching")]
+async fn a_camera_is_watching(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<Camera>().await
+}
+
+#[given("a stuck camera is watching")]
+async fn a_stuck_camera_is_watching(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<StuckCamera>().await
+}
+
+#[then("the time is the real time")]
+async fn the_time_is_real(context: &mut Context) {
+    let difference = chrono::Utc::now() - context.now().await;
+    assert!(difference.num_seconds().abs() < 60, "{} away", difference);
+}
+
+#[then("the scenario started in real time")]
+fn the_scenario_started_in_real_time(context: &mut Context) {
+    let difference = chrono::Utc::now() - context.outcome().started;
+    assert!(difference.num_seconds().abs() < 60, "{} away", difference);
+}
+
+#[then(r#"the remembered value "{name}" matches "{pattern}""#)]
+fn remembered_value_matches(context: &mut Context, name: String, pattern: String) {
+    let value = context.recall(&name).expect("nothing remembered");
+    let pattern = regex::Regex::new(&format!("^(?:{})$", pattern)).unwrap();
+    assert!(
+        pattern.is_match(value),
+        "{:?} doesn't match {}",
+        value,
+        pattern
+    );
+}
+
+#[then(r#"I warn about the remembered value "{name}""#)]
+fn warn_about_remembered_value(context: &mut Context, name: String) {
+    let value = context
+        .recall(&name)
+        .expect("nothing remembered")
+        .to_string();
+    context.warn(value);
+}
+
+/// Run the docstring as a feature with `--seed {seed}`, and return every warning from its steps,
+/// sorted
+async fn step_warnings_with_seed(context: &mut Context, seed: u64) -> anyhow::Result<Vec<String>> {
+    let source = context.step().unwrap().docstring.clone();
+    let source = source.ok_or_else(|| anyhow::anyhow!("Expected a docstring"))?;
+
+    let mut builder = ZukeBuilder::new();
+    let (collect, outcome) = Collect::new();
+    builder
+        .cancel_method(CancelMethod::Shared(Flag::new()))
+        .feature_source("<source>", source)
+        .reporter(collect);
+    let args = ["arg0".to_string(), "--seed".to_string(), seed.to_string()];
+    let _ = builder
+        .build_with_app_from(clap::App::new("zuke-sub-instance"), args)?
+        .run()
+        .await;
+
+    let mut warnings: Vec<String> = outcome
+        .await?
+        .query()
+        .kind(ComponentKind::Step)
+        .all()
+        .iter()
+        .flat_map(|step| step.warnings.iter().map(|w| w.to_string()))
+        .collect();
+    warnings.sort();
+    Ok(warnings)
+}
+
+#[then("running this twice with the seed {seed} warns about the same values, all different")]
+async fn same_seed_same_values(context: &mut Context, seed: u64) -> anyhow::Result<()> {
+    let first = step_warnings_with_seed(context, seed).await?;
+    let second = step_warnings_with_seed(context, seed).await?;
+    assert_eq!(first, second);
+
+    let mut distinct = first.clone();
+    distinct.dedup();
+    assert!(distinct.len() > 1, "Only {:?}", first);
+    assert_eq!(distinct, first);
+    Ok(())
+}
+
+#[then("the attempt counter reaches {count}")]
+fn attempt_counter_reaches(context: &mut Context, count: u32) -> anyhow::Result<()> {
+    let attempts = context.recall("attempts").map_or(Ok(0), str::parse)? + 1;
+    context.remember("attempts", attempts.to_string());
+    anyhow::ensure!(attempts >= count, "Only {} attempts", attempts);
+    Ok(())
+}
+
+#[then("a counter eventually reaches {count}")]
+async fn counter_eventually_reaches(context: &mut Context, count: u32) -> anyhow::Result<()> {
+    let counter = Mutex::new(0);
+    let timeout = std::time::Duration::from_secs(5);
+    let interval = std::time::Duration::from_millis(10);
+    let attempts = context
+        .eventually(timeout, interval, || async {
+            let mut counter = counter.lock();
+            *counter += 1;
+            anyhow::ensure!(*counter >= count, "Only {} attempts", *counter);
+            Ok(*counter)
+        })
+        .await?;
+    assert_eq!(attempts, count);
+    Ok(())
+}
+
+#[when(r#"I parse "{text}" as a number"#)]
+fn parse_number(context: &mut Context, text: String) {
+    context.capture_error(text.parse::<i32>());
+}
@@ -1,4 +1,6 @@
 use async_trait::async_trait;
+use lazy_static::lazy_static;
+use std::sync::Mutex;
 use zuke::*;
 
 struct TaggedFixture;
@@ -96,3 +98,149 @@ async fn check_and_not(context: &mut Context) {
 async fn check_or(context: &mut Context) {
     context.fixture::<OrFixture>().await;
 }
+
+#[after_step("@step-failures-are-warnings")]
+async fn step_failures_are_warnings(context: &mut Context) {
+    let outcome = context.outcome_mut();
+    if outcome.verdict == Verdict::Failed {
+        outcome.verdict = Verdict::PassedWithWarnings;
+    }
+}
+
+#[derive(Default)]
+struct HookOrder {
+    order: Mutex<Vec<&'static str>>,
+}
+
+#[async_trait]
+impl Fixture for HookOrder {
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self::default())
+    }
+}
+
+#[before_scenario("@hook-order", order = 10)]
+async fn hook_order_last(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<HookOrder>().await?;
+    let fixture = context.fixture::<HookOrder>().await;
+    fixture.order.lock().unwrap().push("last");
+    Ok(())
+}
+
+#[before_scenario("@hook-order", order = -10)]
+async fn hook_order_first(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<HookOrder>().await?;
+    let fixture = context.fixture::<HookOrder>().await;
+    fixture.order.lock().unwrap().push("first");
+    Ok(())
+}
+
+#[before_scenario("@hook-order")]
+async fn hook_order_middle(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<HookOrder>().await?;
+    let fixture = context.fixture::<HookOrder>().await;
+    fixture.order.lock().unwrap().push("middle");
+    Ok(())
+}
+
+#[then("the hooks ran in order")]
+async fn check_hook_order(context: &mut Context) {
+    let fixture = context.fixture::<HookOrder>().await;
+    assert_eq!(*fixture.order.lock().unwrap(), ["first", "middle", "last"]);
+}
+
+lazy_static! {
+    /// Hooks that ran for @hook-nesting, in order
+    static ref NESTING: Mutex<Vec<&'static str>> = Mutex::new(vec![]);
+}
+
+#[before_scenario("@hook-nesting")]
+async fn nesting_before_a(_context: &mut Context) -> anyhow::Result<()> {
+    NESTING.lock().unwrap().push("a");
+    Ok(())
+}
+
+#[before_scenario("@hook-nesting")]
+async fn nesting_before_b(_context: &mut Context) -> anyhow::Result<()> {
+    NESTING.lock().unwrap().push("b");
+    Ok(())
+}
+
+#[after_scenario("@hook-nesting")]
+async fn nesting_after_a(_context: &mut Context) -> anyhow::Result<()> {
+    NESTING.lock().unwrap().push("a");
+    Ok(())
+}
+
+#[after_scenario("@hook-nesting")]
+async fn nesting_after_b(_context: &mut Context) -> anyhow::Result<()> {
+    NESTING.lock().unwrap().push("b");
+    Ok(())
+}
+
+#[then("after hooks with the same order ran in the reverse of before hooks")]
+async fn check_hook_nesting(_context: &mut Context) {
+    let nesting = NESTING.lock().unwrap();
+    assert_eq!(nesting.len(), 4, "{:?}", nesting);
+    assert_eq!(nesting[0], nesting[3], "{:?}", nesting);
+    assert_eq!(nesting[1], nesting[2], "{:?}", nesting);
+}
+
+#[before_scenario("@failing-hook")]
+async fn failing_hook(_context: &mut Context) -> anyhow::Result<()> {
+    anyhow::bail!("this hook always fails");
+}
+
+#[after_scenario("@failing-after-hooks")]
+async fn first_failing_after_hook(_context: &mut Context) -> anyhow::Result<()> {
+    anyhow::bail!("the first after hook failed");
+}
+
+#[after_scenario("@failing-after-hooks")]
+async fn second_failing_after_hook(_context: &mut Context) -> anyhow::Result<()> {
+    anyhow::bail!("the second after hook failed");
+}
+
+struct NamedFixture;
+
+#[async_trait]
+impl Fixture for NamedFixture {
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+}
+
+#[before_scenario(feature = "Before/after hooks .*", scenario = "Name filters .* match")]
+async fn named_fixture(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<NamedFixture>().await
+}
+
+#[then("the NamedFixture fixture is present")]
+async fn check_named(context: &mut Context) {
+    context.fixture::<NamedFixture>().await;
+}
+
+#[then("the NamedFixture fixture is not present")]
+async fn check_not_named(context: &mut Context) {
+    assert!(context.try_fixture::<NamedFixture>().await.is_none());
+}
+
+struct Dataset(Vec<&'static str>);
+
+#[before_all]
+async fn load_dataset(context: &mut Context) -> anyhow::Result<()> {
+    context.set_state(Dataset(vec!["apple", "banana", "cherry"]))?;
+    Ok(())
+}
+
+#[then("the dataset from before_all has {n} records")]
+async fn check_dataset(context: &mut Context, n: usize) {
+    let dataset = context.state::<Dataset>().expect("No dataset");
+    assert_eq!(dataset.0.len(), n);
+}
+
+#[then("global state can't be set from a step")]
+async fn check_state_read_only(context: &mut Context) {
+    let result = context.set_state(Dataset(vec![]));
+    assert!(matches!(result, Err(StateError::NotGlobal(_))));
+}
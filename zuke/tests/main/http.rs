@@ -0,0 +1,108 @@
+use crate::sub_instance::SubInstance;
+use async_std::io::BufReader;
+use async_std::net::{TcpListener, TcpStream};
+use async_std::task;
+use async_trait::async_trait;
+use futures::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+use zuke::fixtures::TempDir;
+use zuke::*;
+
+/// A small HTTP API for a sub-instance to test, serving these:
+///
+/// - `GET /api/items`: A JSON list of one item, named "widget"
+/// - `POST /api/echo`: The request body, with status 201
+///
+/// Anything else is 404.
+pub struct HttpServer {
+    address: String,
+    server: Option<task::JoinHandle<()>>,
+}
+
+#[async_trait]
+impl Fixture for HttpServer {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?.to_string();
+        let server = task::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                task::spawn(async move {
+                    let _ = serve(stream).await;
+                });
+            }
+        });
+        Ok(Self {
+            address,
+            server: Some(server),
+        })
+    }
+
+    async fn teardown(&mut self, _context: &mut Context) -> anyhow::Result<()> {
+        if let Some(server) = self.server.take() {
+            server.cancel().await;
+        }
+        Ok(())
+    }
+}
+
+/// Answer one request
+async fn serve(stream: TcpStream) -> anyhow::Result<()> {
+    let mut reader = BufReader::new(stream.clone());
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).await?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse()?;
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+
+    let target: Vec<_> = request_line.split_whitespace().take(2).collect();
+    let (status, body) = match target.as_slice() {
+        ["GET", "/api/items"] => ("200 OK", br#"{"items":[{"name":"widget"}]}"#.to_vec()),
+        ["POST", "/api/echo"] => ("201 Created", body),
+        _ => ("404 Not Found", b"Not found".to_vec()),
+    };
+
+    let mut stream = stream;
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n",
+        status,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&body).await?;
+    Ok(())
+}
+
+#[given("a local HTTP server for the sub-instance")]
+async fn a_local_http_server(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<HttpServer>().await?;
+    context.use_fixture::<TempDir>().await?;
+    let address = context.fixture::<HttpServer>().await.address.clone();
+    let config = context.fixture::<TempDir>().await.join("zuke.toml");
+    std::fs::write(
+        &config,
+        format!("[settings]\nbase-url = \"http://{}/api/\"\n", address),
+    )?;
+
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.args.push(String::from("--config"));
+    sub_instance
+        .args
+        .push(config.to_string_lossy().into_owned());
+    Ok(())
+}
@@ -1,5 +1,7 @@
 use anyhow;
-use zuke::{given, then};
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicBool, Ordering};
+use zuke::{given, steps, then, Context, Fixture, Scope, StepOrigin};
 
 #[given("a step that returns nothing")]
 #[given("a lever long enough")]
@@ -13,6 +15,20 @@ fn given_a_lever() {}
 #[then("I will move the world without blocking")]
 async fn given_a_lever_async() {}
 
+#[given(
+    "Étant donné un levier assez long",
+    keyword = "Étant donné",
+    keyword = "Soit"
+)]
+#[then("Alors je déplacerai le monde", keyword = "Alors")]
+fn un_levier() {}
+
+#[given("I move the world in French")]
+async fn move_the_world_in_french(context: &mut Context) -> anyhow::Result<()> {
+    context.run_step("Soit un levier assez long").await?;
+    context.run_step("Alors je déplacerai le monde").await
+}
+
 #[given("a step that returns Ok from anyhow::Result")]
 fn returns_anyhow_ok() -> anyhow::Result<()> {
     Ok(())
@@ -49,6 +65,33 @@ fn panics() {
     panic!("PANIC!");
 }
 
+/// Whether "a step that fails the first time" has run yet in this test run
+#[derive(Default)]
+struct FailedOnce(AtomicBool);
+
+#[async_trait]
+impl Fixture for FailedOnce {
+    const SCOPE: Scope = Scope::Global;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self::default())
+    }
+}
+
+#[given("a step that fails the first time")]
+async fn fails_the_first_time(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<FailedOnce>().await?;
+    if !context
+        .fixture::<FailedOnce>()
+        .await
+        .0
+        .swap(true, Ordering::SeqCst)
+    {
+        anyhow::bail!("Failing the first time");
+    }
+    Ok(())
+}
+
 #[given("a step that return Err from anyhow::Result")]
 fn err_anyhow() -> anyhow::Result<()> {
     anyhow::bail!("error!");
@@ -64,3 +107,148 @@ fn multiple_1() {}
 
 #[given("a step that is implemented twice")]
 fn multiple_2() {}
+
+#[given("a step that is pending")]
+fn is_pending() -> anyhow::Result<()> {
+    zuke::pending!("Not finished yet");
+}
+
+#[derive(Debug)]
+struct NotSupported;
+
+impl std::fmt::Display for NotSupported {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "not supported here")
+    }
+}
+
+impl std::error::Error for NotSupported {}
+
+impl From<NotSupported> for zuke::StepError {
+    fn from(e: NotSupported) -> Self {
+        zuke::StepError::skip_with_reason(e)
+    }
+}
+
+#[given("a step that returns a custom error that skips")]
+fn err_custom_skip() -> Result<(), NotSupported> {
+    Err(NotSupported)
+}
+
+#[given("a step that skips with context")]
+fn skip_with_context() -> anyhow::Result<()> {
+    use anyhow::Context as _;
+    Err(zuke::StepError::skip_with_message("not supported here")).context("while checking support")
+}
+
+#[given("an async step that returns a custom error that skips")]
+async fn err_custom_skip_async() -> Result<(), NotSupported> {
+    Err(NotSupported)
+}
+
+#[given("a step that runs other steps")]
+async fn runs_other_steps(context: &mut Context) -> anyhow::Result<()> {
+    context
+        .run_step("Given a step that returns nothing")
+        .await?;
+    context.run_step("Then I will move the world").await?;
+    Ok(())
+}
+
+#[given("a step that runs a pending step")]
+async fn runs_pending_step(context: &mut Context) -> anyhow::Result<()> {
+    context.run_step("a step that is pending").await?;
+    panic!("Shouldn't get here");
+}
+
+#[given("a step that runs itself")]
+async fn runs_itself(context: &mut Context) -> anyhow::Result<()> {
+    context.run_step("Given a step that runs itself").await
+}
+
+#[given("a step that compares unequal lists with assert_eq_diff")]
+fn unequal_assert_eq_diff() -> anyhow::Result<()> {
+    let fruit = vec!["apple", "banana", "cherry"];
+    zuke::assert_eq_diff!(
+        fruit,
+        vec!["apple", "blueberry", "cherry"],
+        "Wrong fruit for {}",
+        "lunch"
+    );
+    Ok(())
+}
+
+#[given("a step that expects unequal lists to be equal")]
+fn unequal_expect(context: &mut Context) -> anyhow::Result<()> {
+    context.expect(vec![1, 2, 3]).to_equal(vec![1, 2, 4])?;
+    Ok(())
+}
+
+#[given("this is step {index} of {count}, from the {origin}")]
+fn step_position(context: &mut Context, index: usize, count: usize, origin: String) {
+    let component = context.component();
+    assert_eq!(component.step_index().map(|i| i + 1), Some(index));
+    assert_eq!(component.step_count(), Some(count));
+    let expected = match origin.as_str() {
+        "background" => StepOrigin::Background,
+        "scenario" => StepOrigin::Scenario,
+        _ => panic!("Unknown origin {:?}", origin),
+    };
+    assert_eq!(component.step_origin(), Some(expected));
+}
+
+#[given(r#"a step that quotes "{text}" text"#)]
+fn quotes_text(text: String) {
+    assert_eq!(text, "smart");
+}
+
+#[given(r#"a step that keeps "{text}" as written"#)]
+fn keeps_text(text: String) {
+    assert_eq!(text, "it\u{2019}s  here");
+}
+
+#[given("a step implemented in {place}")]
+fn implemented_in_implementations(place: String) {
+    panic!(
+        "Used the implementation for {:?} from the wrong module",
+        place
+    );
+}
+
+/// Steps implemented as methods. Each scenario gets its own cart.
+#[derive(Default)]
+struct Cart {
+    items: Vec<String>,
+}
+
+#[steps]
+impl Cart {
+    #[given("I put {item} in my cart")]
+    #[given("I also put {item} in my cart")]
+    async fn put(&mut self, item: String) {
+        self.items.push(item);
+    }
+
+    #[then("my cart holds {items}")]
+    fn holds(&self, items: Vec<String>) -> anyhow::Result<()> {
+        zuke::assert_eq_diff!(self.items, items);
+        Ok(())
+    }
+
+    #[then("my cart is empty")]
+    fn is_empty(&mut self) -> anyhow::Result<()> {
+        anyhow::ensure!(self.items.is_empty(), "Cart holds {:?}", self.items);
+        Ok(())
+    }
+
+    #[then("steps without self can still use the context")]
+    fn without_self(context: &mut Context) {
+        assert!(context.scenario().is_some());
+    }
+}
+
+#[given("a step that warns twice")]
+fn warns_twice(context: &mut Context) {
+    context.warn("The lever is bending");
+    context.warn("The lever is bending more");
+}
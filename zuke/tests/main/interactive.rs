@@ -0,0 +1,51 @@
+use crate::sub_instance::SubInstance;
+use async_trait::async_trait;
+use std::sync::Mutex;
+use zuke::interactive::DebugInput;
+use zuke::*;
+
+#[when(r#"I answer the sub-instance's debug prompts with "{commands}""#)]
+async fn when_i_answer_debug_prompts(context: &mut Context, commands: String) {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let commands: Vec<_> = commands.split(", ").collect();
+    sub_instance.builder().setting(DebugInput::new(commands));
+}
+
+/// The step hooks that ran in a @debug-hooks scenario, in order
+#[derive(Default)]
+struct StepHookLog(Mutex<Vec<&'static str>>);
+
+#[async_trait]
+impl Fixture for StepHookLog {
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self::default())
+    }
+}
+
+async fn log_step_hook(context: &mut Context, hook: &'static str) -> anyhow::Result<()> {
+    context.use_fixture::<StepHookLog>().await?;
+    let log = context.fixture::<StepHookLog>().await;
+    log.0.lock().unwrap().push(hook);
+    Ok(())
+}
+
+#[before_step("@debug-hooks")]
+async fn debug_before_step(context: &mut Context) -> anyhow::Result<()> {
+    log_step_hook(context, "before").await
+}
+
+#[after_step("@debug-hooks")]
+async fn debug_after_step(context: &mut Context) -> anyhow::Result<()> {
+    log_step_hook(context, "after").await
+}
+
+#[given("a step that passes when retried after its step hooks")]
+async fn passes_when_retried(context: &mut Context) -> anyhow::Result<()> {
+    let log = context.fixture::<StepHookLog>().await;
+    let log = log.0.lock().unwrap();
+    match log.as_slice() {
+        ["before"] => anyhow::bail!("The first attempt fails"),
+        ["before", "after", "before"] => Ok(()),
+        hooks => anyhow::bail!("Unexpected step hooks {:?}", hooks),
+    }
+}
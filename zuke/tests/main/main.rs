@@ -1,16 +1,24 @@
-use async_std::task::block_on;
-use zuke::Zuke;
-
 mod cancel;
 mod capture;
+mod cargo_features;
 mod concurrent;
+#[cfg(feature = "dashboard")]
+mod dashboard;
 mod fixture_scope;
+mod fixtures;
 mod hooks;
+mod http;
 mod implementations;
+mod interactive;
 mod matches;
+mod remote;
+mod reporters;
+mod runner;
+mod settings;
 mod sub_instance;
+mod suites;
+mod tags;
+mod testkit;
+mod watch;
 
-fn main() -> anyhow::Result<()> {
-    let zuke = Zuke::builder().feature_path("tests/features").build()?;
-    block_on(zuke.run())
-}
+zuke::main!();
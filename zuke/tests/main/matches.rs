@@ -4,3 +4,6 @@ use zuke::given;
 #[given("a step with special characters...")]
 #[given(regex, r#"a word with a double vowel ".*(aa|ee|ii|oo|uu).*""#)]
 fn do_nothing() {}
+
+#[given("a step implemented in two modules")]
+fn implemented_in_matches() {}
@@ -0,0 +1,58 @@
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+use zuke::{given, Context};
+
+/// Answer one JSON-RPC request from Zuke
+fn answer(request: &Value) -> Value {
+    let params = &request["params"];
+    let result = match request["method"].as_str() {
+        Some("step_definitions") => json!({
+            "name": "test_server",
+            "steps": [
+                {"id": "greet", "pattern": r"a remote step that greets (?P<name>\w+)", "keyword": "given"},
+                {"id": "check", "pattern": r#"the remote step saved "(?P<value>.*)""#, "keyword": "then"},
+            ],
+        }),
+        Some("invoke") => match (params["id"].as_str(), params["args"]["name"].as_str()) {
+            (Some("greet"), Some("world")) => json!({
+                "verdict": "passed",
+                "remember": {"greeting": "hello world"},
+            }),
+            (Some("greet"), _) => json!({
+                "verdict": "failed",
+                "message": "I only greet the world",
+            }),
+            _ if params["saved"]["greeting"] == params["args"]["value"] => {
+                json!({"verdict": "passed"})
+            }
+            _ => json!({"verdict": "failed", "message": "Nothing was saved"}),
+        },
+        _ => {
+            return json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "error": {"code": -32601, "message": "Method not found"},
+            })
+        }
+    };
+    json!({"jsonrpc": "2.0", "id": request["id"], "result": result})
+}
+
+#[given("a remote step server")]
+fn given_a_remote_step_server(context: &mut Context) -> anyhow::Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    context.remember("remote server", listener.local_addr()?.to_string());
+
+    thread::spawn(move || -> anyhow::Result<()> {
+        let (stream, _) = listener.accept()?;
+        let mut writer = stream.try_clone()?;
+        for line in BufReader::new(stream).lines() {
+            let response = answer(&serde_json::from_str(&line?)?);
+            writeln!(writer, "{}", response)?;
+        }
+        Ok(())
+    });
+    Ok(())
+}
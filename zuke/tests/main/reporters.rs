@@ -0,0 +1,540 @@
+use crate::sub_instance::SubInstance;
+use async_broadcast as broadcast;
+use async_std::task;
+use async_trait::async_trait;
+use futures::io::AsyncWrite;
+use futures::stream::StreamExt;
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+use std::time::{Duration, Instant};
+use zuke::fixtures::TempDir;
+use zuke::reporter::{
+    AllureReporter, BadgeReporter, BenchReporter, OpenMetricsReporter, PlainReporter, Reporter,
+    Supervised, Verbosity,
+};
+use zuke::{
+    replay, then, when, Component, ComponentKind, Context, Event, EventFilter, Fixture, Scope,
+};
+
+/// Output from a sub-instance's reporters, by name, such as "plain" for the plain reporter
+#[derive(Default)]
+pub struct CapturedOutput(Mutex<HashMap<String, Output>>);
+
+/// One reporter's output
+#[derive(Clone, Default)]
+pub struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Output {
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock()).to_string()
+    }
+}
+
+#[async_trait]
+impl Fixture for CapturedOutput {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self::default())
+    }
+}
+
+/// Where to capture the output named `name`
+async fn capture(context: &mut Context, name: &str) -> anyhow::Result<Output> {
+    context.use_fixture::<CapturedOutput>().await?;
+    let captured = context.fixture::<CapturedOutput>().await;
+    let output = captured
+        .0
+        .lock()
+        .entry(name.to_string())
+        .or_default()
+        .clone();
+    Ok(output)
+}
+
+/// The output named `name`, so far
+async fn output_so_far(context: &mut Context, name: &str) -> anyhow::Result<Output> {
+    let captured = match context.try_fixture::<CapturedOutput>().await {
+        Some(captured) => captured,
+        None => anyhow::bail!("No output was captured"),
+    };
+    let output = captured.0.lock().get(name).cloned();
+    output.ok_or_else(|| anyhow::anyhow!("The {} output wasn't captured", name))
+}
+
+/// The output named `name`, once the reporters are done
+async fn captured_output(context: &mut Context, name: &str) -> anyhow::Result<String> {
+    // Reporters are done once the outcome is available
+    context.fixture_mut::<SubInstance>().await.outcome().await;
+    Ok(output_so_far(context, name).await?.text())
+}
+
+#[then(r#"the {name} output contains "{text}""#)]
+async fn then_output_contains(
+    context: &mut Context,
+    name: String,
+    text: String,
+) -> anyhow::Result<()> {
+    let output = captured_output(context, &name).await?;
+    anyhow::ensure!(output.contains(&text), "{:?} not in:\n{}", text, output);
+    Ok(())
+}
+
+#[then(r#"the {name} output does not contain "{text}""#)]
+async fn then_output_lacks(
+    context: &mut Context,
+    name: String,
+    text: String,
+) -> anyhow::Result<()> {
+    let output = captured_output(context, &name).await?;
+    anyhow::ensure!(!output.contains(&text), "{:?} in:\n{}", text, output);
+    Ok(())
+}
+
+#[then(r#"the {name} output soon contains "{text}""#)]
+async fn then_output_soon_contains(
+    context: &mut Context,
+    name: String,
+    text: String,
+) -> anyhow::Result<()> {
+    // Without waiting for every reporter to finish
+    let output = output_so_far(context, &name).await?;
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !output.text().contains(&text) {
+        anyhow::ensure!(Instant::now() < deadline, "{:?} never written", text);
+        task::sleep(Duration::from_millis(10)).await;
+    }
+    Ok(())
+}
+
+#[then("the {name} output has these lines")]
+async fn then_output_has_lines(context: &mut Context, name: String) -> anyhow::Result<()> {
+    let expected = match &context.step().unwrap().docstring {
+        Some(d) => d.clone(),
+        None => anyhow::bail!("Expected a docstring"),
+    };
+    let output = captured_output(context, &name).await?;
+    for line in expected.lines() {
+        anyhow::ensure!(
+            output.lines().any(|l| l == line),
+            "{:?} not in:\n{}",
+            line,
+            output
+        );
+    }
+    Ok(())
+}
+
+#[then("the {name} output is the JSON")]
+async fn then_output_is_json(context: &mut Context, name: String) -> anyhow::Result<()> {
+    let expected: Value = match &context.step().unwrap().docstring {
+        Some(d) => serde_json::from_str(d)?,
+        None => anyhow::bail!("Expected a docstring"),
+    };
+    let actual: Value = serde_json::from_str(&captured_output(context, &name).await?)?;
+    assert_eq!(actual, expected);
+    Ok(())
+}
+
+#[when(r#"I capture the plain output at "{level}" verbosity"#)]
+async fn when_i_capture_plain_output(context: &mut Context, level: String) -> anyhow::Result<()> {
+    let verbosity = match level.as_str() {
+        "quiet" => Verbosity::Quiet,
+        "normal" => Verbosity::Normal,
+        "verbose" => Verbosity::Verbose,
+        "very verbose" => Verbosity::VeryVerbose,
+        _ => anyhow::bail!("Unknown verbosity {:?}", level),
+    };
+
+    let output = capture(context, "plain").await?;
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance
+        .builder()
+        .reporter(PlainReporter::from(output).with_verbosity(verbosity));
+    Ok(())
+}
+
+/// Output that can never be written, like a full disk
+pub struct BrokenOutput;
+
+impl Write for BrokenOutput {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::Other, "Disk full"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other, "Disk full"))
+    }
+}
+
+/// Output that never finishes a write, like a pipe that's never read
+pub struct StuckOutput;
+
+impl AsyncWrite for StuckOutput {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut TaskContext,
+        _buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Pending
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut TaskContext) -> Poll<io::Result<()>> {
+        Poll::Pending
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut TaskContext) -> Poll<io::Result<()>> {
+        Poll::Pending
+    }
+}
+
+#[when("I add a plain reporter whose output blocks forever")]
+async fn when_i_add_stuck_reporter(context: &mut Context) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance
+        .builder()
+        .reporter(PlainReporter::<StuckOutput>::from(StuckOutput));
+    Ok(())
+}
+
+#[when("I add a plain reporter whose output can't be written")]
+async fn when_i_add_broken_reporter(context: &mut Context) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance
+        .builder()
+        .reporter(PlainReporter::from(BrokenOutput));
+    Ok(())
+}
+
+#[when("I add a plain reporter whose output can't be written, falling back to the plain output")]
+async fn when_i_add_broken_reporter_with_fallback(context: &mut Context) -> anyhow::Result<()> {
+    let output = capture(context, "plain").await?;
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().reporter(
+        Supervised::new(Box::new(PlainReporter::from(BrokenOutput)))
+            .with_name("broken")
+            .with_fallback(Some(Box::new(PlainReporter::from(output)))),
+    );
+    Ok(())
+}
+
+#[when("I capture the plain output, quiet on success, saving artifacts on failure")]
+async fn when_i_capture_plain_output_quiet_on_success(context: &mut Context) -> anyhow::Result<()> {
+    let output = capture(context, "plain").await?;
+    context.use_fixture::<TempDir>().await?;
+    let dir = context.fixture::<TempDir>().await.join("artifacts");
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().reporter(
+        PlainReporter::from(output)
+            .with_quiet_on_success(true)
+            .with_artifacts_dir(Some(dir)),
+    );
+    Ok(())
+}
+
+#[then(r#"the saved artifact "{name}" contains "{text}""#)]
+async fn then_saved_artifact_contains(
+    context: &mut Context,
+    name: String,
+    text: String,
+) -> anyhow::Result<()> {
+    context.fixture_mut::<SubInstance>().await.outcome().await;
+    let path = context
+        .fixture::<TempDir>()
+        .await
+        .join("artifacts")
+        .join(name);
+    let contents = fs::read_to_string(&path)?;
+    anyhow::ensure!(contents.contains(&text), "{:?} not in:\n{}", text, contents);
+    Ok(())
+}
+
+#[then("no artifacts were saved")]
+async fn then_no_artifacts_saved(context: &mut Context) -> anyhow::Result<()> {
+    context.fixture_mut::<SubInstance>().await.outcome().await;
+    let path = context.fixture::<TempDir>().await.join("artifacts");
+    anyhow::ensure!(!path.exists(), "{} exists", path.display());
+    Ok(())
+}
+
+#[when("I capture the OpenMetrics output")]
+async fn when_i_capture_metrics(context: &mut Context) -> anyhow::Result<()> {
+    let output = capture(context, "OpenMetrics").await?;
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance
+        .builder()
+        .reporter(OpenMetricsReporter::from(output));
+    Ok(())
+}
+
+#[when(r#"I capture the badge output with the label "{label}""#)]
+async fn when_i_capture_badge(context: &mut Context, label: String) -> anyhow::Result<()> {
+    let output = capture(context, "badge").await?;
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance
+        .builder()
+        .reporter(BadgeReporter::from(output).with_label(Some(label)));
+    Ok(())
+}
+
+#[when("I capture the bench output as JSON")]
+async fn when_i_capture_bench(context: &mut Context) -> anyhow::Result<()> {
+    let output = capture(context, "bench").await?;
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance
+        .builder()
+        .reporter(BenchReporter::from(output).with_json(true));
+    Ok(())
+}
+
+#[then(r#"the bench results for "{name}" have {runs} timed runs"#)]
+async fn then_bench_runs(context: &mut Context, name: String, runs: u64) -> anyhow::Result<()> {
+    let results: Value = serde_json::from_str(&captured_output(context, "bench").await?)?;
+    let result = results
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|r| r["key"].as_str().map_or(false, |k| k.ends_with(&name)))
+        .ok_or_else(|| anyhow::anyhow!("No results for {:?} in {}", name, results))?;
+    assert_eq!(result["runs"], runs);
+    assert_eq!(result["failed"], 0);
+    for stat in ["mean_ms", "median_ms", "p95_ms", "min_ms", "max_ms"] {
+        anyhow::ensure!(result[stat].is_f64(), "No {} in {}", stat, result);
+    }
+    Ok(())
+}
+
+/// Events seen by a reporter that only wants some of them
+#[derive(Clone)]
+pub struct FilteredEvents(Arc<Mutex<Vec<String>>>, EventFilter);
+
+#[async_trait]
+impl Fixture for FilteredEvents {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self(Default::default(), EventFilter::none()))
+    }
+}
+
+#[async_trait]
+impl Reporter for FilteredEvents {
+    async fn report(
+        self: Box<Self>,
+        _global: Arc<Component>,
+        mut events: broadcast::Receiver<Event>,
+    ) -> anyhow::Result<()> {
+        while let Some(event) = events.next().await {
+            let seen = match &event {
+                Event::Started(c) => format!("started {}", c.kind()),
+                Event::Finished(o) => format!("finished {}", o.kind()),
+                Event::HookFinished(h) => format!("hook {}", h.component.kind()),
+                Event::IterationFinished(o) => format!("iteration {}", o.kind()),
+            };
+            self.0.lock().push(seen);
+        }
+        Ok(())
+    }
+
+    fn events(&self) -> EventFilter {
+        self.1
+    }
+}
+
+async fn add_filtered_reporter(context: &mut Context, filter: EventFilter) -> anyhow::Result<()> {
+    context.use_fixture::<FilteredEvents>().await?;
+    let mut reporter = context.fixture::<FilteredEvents>().await.clone();
+    reporter.1 = filter;
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().reporter(reporter);
+    Ok(())
+}
+
+#[when("I add a reporter that only wants finished scenarios")]
+async fn when_i_add_filtered_reporter(context: &mut Context) -> anyhow::Result<()> {
+    add_filtered_reporter(
+        context,
+        EventFilter::none().finished(&[ComponentKind::Scenario]),
+    )
+    .await
+}
+
+#[when("I add a reporter that only wants iterations")]
+async fn when_i_add_iterations_reporter(context: &mut Context) -> anyhow::Result<()> {
+    add_filtered_reporter(context, EventFilter::none().iterations(true)).await
+}
+
+async fn filtered_events(context: &mut Context) -> Vec<String> {
+    context.fixture_mut::<SubInstance>().await.outcome().await;
+    context.fixture::<FilteredEvents>().await.0.lock().clone()
+}
+
+#[then("the reporter only got {count} finished scenarios")]
+async fn then_filtered_reporter_got(context: &mut Context, count: usize) -> anyhow::Result<()> {
+    let expected = vec![String::from("finished scenario"); count];
+    assert_eq!(filtered_events(context).await, expected);
+    Ok(())
+}
+
+#[then("the reporter only got {count} iterations")]
+async fn then_iterations_reporter_got(context: &mut Context, count: usize) -> anyhow::Result<()> {
+    let expected = vec![String::from("iteration scenario"); count];
+    assert_eq!(filtered_events(context).await, expected);
+    Ok(())
+}
+
+#[when("I record the run")]
+async fn when_i_record_the_run(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<TempDir>().await?;
+    let path = context.fixture::<TempDir>().await.join("run.zuke");
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.args.push("--record".into());
+    sub_instance.args.push(path.to_string_lossy().to_string());
+    Ok(())
+}
+
+#[when("I replay the recording to the plain output")]
+async fn when_i_replay_the_recording(context: &mut Context) -> anyhow::Result<()> {
+    context.fixture_mut::<SubInstance>().await.outcome().await;
+    let output = capture(context, "plain").await?;
+    let path = context.fixture::<TempDir>().await.join("run.zuke");
+
+    let reporter = PlainReporter::from(output).with_verbosity(Verbosity::Verbose);
+    replay(path, vec![Box::new(reporter)]).await
+}
+
+#[when("I write Allure results")]
+async fn when_i_write_allure_results(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<TempDir>().await?;
+    let dir = context.fixture::<TempDir>().await.join("allure-results");
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().reporter(AllureReporter::new(dir));
+    Ok(())
+}
+
+async fn allure_dir(context: &mut Context) -> PathBuf {
+    context.fixture_mut::<SubInstance>().await.outcome().await;
+    context.fixture::<TempDir>().await.join("allure-results")
+}
+
+/// The `*-result.json` files written by the Allure reporter
+async fn allure_results(context: &mut Context) -> anyhow::Result<Vec<Value>> {
+    let mut results = vec![];
+    for entry in fs::read_dir(allure_dir(context).await)? {
+        let path = entry?.path();
+        if path.to_string_lossy().ends_with("-result.json") {
+            results.push(serde_json::from_slice(&fs::read(path)?)?);
+        }
+    }
+    Ok(results)
+}
+
+async fn allure_result(context: &mut Context, name: &str) -> anyhow::Result<Value> {
+    allure_results(context)
+        .await?
+        .into_iter()
+        .find(|r| r["name"] == name)
+        .ok_or_else(|| anyhow::anyhow!("No Allure result for {:?}", name))
+}
+
+#[then("there are {count} Allure results")]
+async fn then_allure_results(context: &mut Context, count: usize) -> anyhow::Result<()> {
+    let results = allure_results(context).await?;
+    anyhow::ensure!(
+        results.len() == count,
+        "Expected {} results, but found {}",
+        count,
+        results.len()
+    );
+    Ok(())
+}
+
+#[then(r#"the Allure result for "{name}" has status "{status}""#)]
+async fn then_allure_status(
+    context: &mut Context,
+    name: String,
+    status: String,
+) -> anyhow::Result<()> {
+    let result = allure_result(context, &name).await?;
+    anyhow::ensure!(
+        result["status"] == status.as_str(),
+        "Wrong status in {:#}",
+        result
+    );
+    Ok(())
+}
+
+#[then(r#"the Allure result for "{name}" has a "{attachment}" attachment"#)]
+async fn then_allure_attachment(
+    context: &mut Context,
+    name: String,
+    attachment: String,
+) -> anyhow::Result<()> {
+    let result = allure_result(context, &name).await?;
+    let source = result["steps"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|s| s["attachments"].as_array().into_iter().flatten())
+        .find(|a| a["name"] == attachment.as_str())
+        .and_then(|a| a["source"].as_str())
+        .ok_or_else(|| anyhow::anyhow!("No {:?} attachment in {:#}", attachment, result))?
+        .to_string();
+    let dir = allure_dir(context).await;
+    anyhow::ensure!(dir.join(&source).is_file(), "{} wasn't written", source);
+    Ok(())
+}
+
+#[then("the Allure results have different history IDs")]
+async fn then_allure_history_ids(context: &mut Context) -> anyhow::Result<()> {
+    let results = allure_results(context).await?;
+    let ids: Vec<_> = results.iter().map(|r| r["historyId"].clone()).collect();
+    for (i, id) in ids.iter().enumerate() {
+        anyhow::ensure!(!ids[..i].contains(id), "{} is used more than once", id);
+    }
+    Ok(())
+}
+
+#[then("the Allure results are in a container")]
+async fn then_allure_container(context: &mut Context) -> anyhow::Result<()> {
+    let dir = allure_dir(context).await;
+    let mut children = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.to_string_lossy().ends_with("-container.json") {
+            let container: Value = serde_json::from_slice(&fs::read(path)?)?;
+            children.extend(
+                container["children"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default(),
+            );
+        }
+    }
+    for result in allure_results(context).await? {
+        anyhow::ensure!(
+            children.contains(&result["uuid"]),
+            "{} isn't in a container",
+            result["name"]
+        );
+    }
+    Ok(())
+}
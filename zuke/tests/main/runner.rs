@@ -0,0 +1,225 @@
+use crate::sub_instance::SubInstance;
+use async_broadcast as broadcast;
+use async_trait::async_trait;
+use futures::channel::mpsc;
+use futures::stream::StreamExt;
+use std::sync::{Arc, Mutex};
+use zuke::fixtures::Command;
+use zuke::flag::Flag;
+use zuke::parts::{self, RunResult};
+use zuke::reporter::Collect;
+use zuke::*;
+
+/// A runner that runs one scenario at a time, built from the standard parts
+struct SequentialRunner;
+
+#[async_trait]
+impl Runner for SequentialRunner {
+    async fn run(
+        self: Box<Self>,
+        global: Arc<Component>,
+        mut features: mpsc::Receiver<Outcome>,
+        events: broadcast::Sender<Event>,
+    ) {
+        let mut open = OpenContext::new_global(global);
+        open.set_events(events.clone());
+        if parts::begin_run(&mut open, &events).await.is_err() {
+            return;
+        }
+
+        let mut outcomes = vec![];
+        while let Some(feature) = features.next().await {
+            match run_feature(open.with_feature(feature), &events).await {
+                Ok(o) => outcomes.push(o),
+                Err(_) => return,
+            }
+        }
+
+        let _ = parts::finish_run(open, outcomes, false, &events).await;
+    }
+}
+
+/// Run a feature, one rule or scenario at a time
+async fn run_feature(mut open: OpenContext, events: &broadcast::Sender<Event>) -> RunResult {
+    parts::begin_group(&mut open, events).await?;
+
+    let mut outcomes = vec![];
+    for rule in open.with_rules().unwrap() {
+        outcomes.push(run_rule(rule, events).await?);
+    }
+    for scenario in open.with_scenarios().unwrap() {
+        outcomes.push(parts::run_scenario(scenario, events).await?);
+    }
+
+    parts::finish_group(open, outcomes, events).await
+}
+
+/// Run a rule, one scenario at a time
+async fn run_rule(mut open: OpenContext, events: &broadcast::Sender<Event>) -> RunResult {
+    parts::begin_group(&mut open, events).await?;
+
+    let mut outcomes = vec![];
+    for scenario in open.with_scenarios().unwrap() {
+        outcomes.push(parts::run_scenario(scenario, events).await?);
+    }
+
+    parts::finish_group(open, outcomes, events).await
+}
+
+#[when("I use a sequential runner")]
+async fn when_i_use_a_sequential_runner(context: &mut Context) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().runner(SequentialRunner);
+    Ok(())
+}
+
+#[when(r#"I run features tagged "{tag}" with a sequential runner"#)]
+async fn when_i_run_tagged_features_sequentially(
+    context: &mut Context,
+    tag: String,
+) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance
+        .builder()
+        .runner_for_tag(tag, zuke::SequentialRunner::new());
+    Ok(())
+}
+
+#[given("a step that aborts the process")]
+fn given_a_step_that_aborts_the_process() {
+    std::process::abort();
+}
+
+#[when("I run this test binary with `{args}`")]
+async fn when_i_run_this_test_binary(context: &mut Context, args: String) -> anyhow::Result<()> {
+    let mut argv = vec![std::env::current_exe()?.into_os_string()];
+    argv.extend(args.split_whitespace().map(Into::into));
+    context.use_fixture::<Command>().await?;
+    context.fixture_mut::<Command>().await.run(argv).await?;
+    Ok(())
+}
+
+#[then(r#"stdout does not contain "{text}""#)]
+async fn then_stdout_lacks(context: &mut Context, text: String) -> anyhow::Result<()> {
+    let command = context.fixture::<Command>().await;
+    let output = command.output().expect("The command didn't finish");
+    anyhow::ensure!(
+        !output.stdout.contains(&text),
+        "{:?} in stdout:\n{}",
+        text,
+        output.stdout
+    );
+    Ok(())
+}
+
+/// Names of scenarios in a sub-instance, in the order they started
+#[derive(Default)]
+struct StartOrder(Arc<Mutex<Vec<String>>>);
+
+#[async_trait]
+impl Fixture for StartOrder {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self::default())
+    }
+}
+
+#[async_trait]
+impl Reporter for StartOrder {
+    async fn report(
+        self: Box<Self>,
+        _global: Arc<Component>,
+        mut events: broadcast::Receiver<Event>,
+    ) -> anyhow::Result<()> {
+        while let Some(event) = events.next().await {
+            if let Event::Started(component) = event {
+                if component.kind() == ComponentKind::Scenario {
+                    self.0.lock().unwrap().push(component.name().to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[when("I record the order scenarios start in")]
+async fn when_i_record_the_start_order(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<StartOrder>().await?;
+    let names = context.fixture::<StartOrder>().await.0.clone();
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().reporter(StartOrder(names));
+    Ok(())
+}
+
+#[then("the scenarios started in this order")]
+async fn then_the_scenarios_started_in_order(context: &mut Context) -> anyhow::Result<()> {
+    let expected: Vec<_> = match &context.step().unwrap().docstring {
+        Some(text) => text.lines().map(str::trim).map(String::from).collect(),
+        None => anyhow::bail!("Expected a docstring"),
+    };
+    context.fixture_mut::<SubInstance>().await.outcome().await;
+    let names = context
+        .fixture::<StartOrder>()
+        .await
+        .0
+        .lock()
+        .unwrap()
+        .clone();
+    assert_eq!(names, expected);
+    Ok(())
+}
+
+/// Run the features at `path`, with extra `args`, and return the names of the scenarios that
+/// passed. Excluded scenarios don't pass.
+async fn passing_scenarios(path: &str, args: &[String]) -> anyhow::Result<Vec<String>> {
+    let mut builder = ZukeBuilder::new();
+    let (collect, outcome) = Collect::new();
+    builder
+        .cancel_method(CancelMethod::Shared(Flag::new()))
+        .feature_path(path)
+        .reporter(collect);
+    let args = std::iter::once(String::from("arg0")).chain(args.iter().cloned());
+    let _ = builder
+        .build_with_app_from(clap::App::new("zuke-sub-instance"), args)?
+        .run()
+        .await;
+
+    let names = outcome
+        .await?
+        .query()
+        .kind(ComponentKind::Scenario)
+        .all()
+        .iter()
+        .filter(|scenario| scenario.passed())
+        .map(|scenario| scenario.component().name().to_string())
+        .collect();
+    Ok(names)
+}
+
+#[then(r#"splitting "{path}" into {count} shards runs each scenario exactly once"#)]
+async fn shards_split_the_run(
+    _context: &mut Context,
+    path: String,
+    count: u64,
+) -> anyhow::Result<()> {
+    let mut everything = passing_scenarios(&path, &[]).await?;
+    everything.sort();
+    anyhow::ensure!(everything.len() > 1, "Only {:?} to split", everything);
+
+    let mut sharded = vec![];
+    for index in 1..=count {
+        let args = [String::from("--shard"), format!("{}/{}", index, count)];
+        let shard = passing_scenarios(&path, &args).await?;
+        assert!(
+            shard.len() < everything.len(),
+            "Shard {} ran everything",
+            index
+        );
+        sharded.extend(shard);
+    }
+    // Any scenario in two shards, or in none, makes these differ
+    sharded.sort();
+    assert_eq!(sharded, everything);
+    Ok(())
+}
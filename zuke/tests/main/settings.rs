@@ -0,0 +1,41 @@
+use serde::Deserialize;
+use zuke::{then, Context};
+
+/// A setting given to the builder
+pub struct Greeting(pub String);
+
+/// A setting from the config file
+#[derive(Deserialize)]
+struct Server {
+    url: String,
+    timeout: u32,
+}
+
+#[then(r#"the greeting setting is "{expected}""#)]
+fn the_greeting_is(context: &mut Context, expected: String) {
+    let greeting = context.options().get_extension::<Greeting>();
+    assert_eq!(greeting.map(|g| g.0.as_str()), Some(expected.as_str()));
+}
+
+#[then(r#"the greeting from the config file is "{expected}""#)]
+fn the_config_greeting_is(context: &mut Context, expected: String) -> anyhow::Result<()> {
+    let greeting: Option<String> = context.options().setting_as("greeting")?;
+    assert_eq!(greeting, Some(expected));
+    Ok(())
+}
+
+#[then(r#"the server from the config file is "{url}" with a timeout of {timeout}"#)]
+fn the_config_server_is(context: &mut Context, url: String, timeout: u32) -> anyhow::Result<()> {
+    let server: Server = match context.options().setting_as("server")? {
+        Some(s) => s,
+        None => anyhow::bail!("No server setting"),
+    };
+    assert_eq!(server.url, url);
+    assert_eq!(server.timeout, timeout);
+    Ok(())
+}
+
+#[then(r#"the profile is "{name}""#)]
+fn the_profile_is(context: &mut Context, name: String) {
+    assert!(context.options().is_profile(&name));
+}
@@ -1,5 +1,8 @@
+use crate::fixtures::{Backend, FakeBackend};
+use crate::settings::Greeting;
 use async_std::task;
 use async_trait::async_trait;
+use std::ffi::OsString;
 use std::sync::Arc;
 use zuke::flag::Flag;
 use zuke::reporter::Collect;
@@ -17,6 +20,7 @@ pub struct SubInstance {
     pub args: Vec<String>,
     result: State,
     cancel: Flag,
+    abort: Flag,
 }
 
 #[async_trait]
@@ -25,19 +29,23 @@ impl Fixture for SubInstance {
 
     async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
         let cancel = Flag::new();
+        let abort = Flag::new();
         let mut builder = ZukeBuilder::new();
         builder.cancel_method(CancelMethod::Shared(cancel.clone()));
+        builder.abort_flag(abort.clone());
 
         Ok(Self {
             builder: Some(builder),
             args: vec!["arg0".into()],
             result: State::Building,
             cancel,
+            abort,
         })
     }
 
     async fn teardown(&mut self, _context: &mut Context) -> anyhow::Result<()> {
         self.cancel.set();
+        self.abort.set();
         Ok(())
     }
 }
@@ -96,6 +104,10 @@ impl SubInstance {
     pub fn cancel(&self) {
         self.cancel.set();
     }
+
+    pub fn abort(&self) {
+        self.abort.set();
+    }
 }
 
 #[given("a zuke sub-instance")]
@@ -111,6 +123,39 @@ async fn when_i_add_the_path(context: &mut Context, path: String) -> anyhow::Res
     Ok(())
 }
 
+#[when(r#"I search for features with the extension "{extension}""#)]
+async fn when_i_search_for_extension(
+    context: &mut Context,
+    extension: String,
+) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().feature_extension(extension);
+    Ok(())
+}
+
+#[when(r#"I ignore features matching "{pattern}""#)]
+async fn when_i_ignore_features(context: &mut Context, pattern: String) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().ignore_features(pattern);
+    Ok(())
+}
+
+#[when("I search for features at most {depth} directories deep")]
+async fn when_i_limit_depth(context: &mut Context, depth: usize) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().max_feature_depth(depth);
+    Ok(())
+}
+
+#[when(r#"I add the markdown path "{path}""#)]
+async fn when_i_add_the_markdown_path(context: &mut Context, path: String) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance
+        .builder()
+        .parser(MarkdownParser::from_path(path));
+    Ok(())
+}
+
 #[when("I add the feature source")]
 async fn when_i_add_feature_source(context: &mut Context) -> anyhow::Result<()> {
     let source = match &context.step().unwrap().docstring {
@@ -123,6 +168,121 @@ async fn when_i_add_feature_source(context: &mut Context) -> anyhow::Result<()>
     Ok(())
 }
 
+#[when(r#"I set the language to "{language}""#)]
+async fn when_i_set_the_language(context: &mut Context, language: String) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().language(language);
+    Ok(())
+}
+
+#[when(r#"I read options from environment variables starting with "{prefix}""#)]
+async fn when_i_set_the_env_prefix(context: &mut Context, prefix: String) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().env_prefix(prefix);
+    Ok(())
+}
+
+#[when(r#"I give the sub-instance the greeting setting "{greeting}""#)]
+async fn when_i_add_a_setting(context: &mut Context, greeting: String) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().setting(Greeting(greeting));
+    Ok(())
+}
+
+#[when("I replace the backend with a fake in the sub-instance")]
+async fn when_i_replace_the_backend(context: &mut Context) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance
+        .builder()
+        .replace_fixture::<Backend, FakeBackend>();
+    Ok(())
+}
+
+#[when(r#"I set up the sub-instance's "{name}" profile to run scenarios tagged "{tag}""#)]
+async fn when_i_set_up_profile_tags(
+    context: &mut Context,
+    name: String,
+    tag: String,
+) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().profile(name).tagged(tag);
+    Ok(())
+}
+
+#[when(r#"I set up the sub-instance's "{name}" profile to use a fake backend"#)]
+async fn when_i_set_up_profile_fakes(context: &mut Context, name: String) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance
+        .builder()
+        .profile(name)
+        .replace_fixture::<Backend, FakeBackend>();
+    Ok(())
+}
+
+/// Environment variables a scenario set, with the values they had before. They're put back when
+/// the scenario ends.
+#[derive(Default)]
+struct EnvVars(Vec<(String, Option<OsString>)>);
+
+#[async_trait]
+impl Fixture for EnvVars {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self::default())
+    }
+
+    async fn teardown(&mut self, _context: &mut Context) -> anyhow::Result<()> {
+        for (name, value) in self.0.drain(..).rev() {
+            match value {
+                Some(value) => std::env::set_var(name, value),
+                None => std::env::remove_var(name),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[when(r#"I set the environment variable "{name}" to "{value}""#)]
+async fn when_i_set_an_env_var(
+    context: &mut Context,
+    name: String,
+    value: String,
+) -> anyhow::Result<()> {
+    context.use_fixture::<EnvVars>().await?;
+    let previous = std::env::var_os(&name);
+    std::env::set_var(&name, value);
+    context
+        .fixture_mut::<EnvVars>()
+        .await
+        .0
+        .push((name, previous));
+    Ok(())
+}
+
+#[when(r#"I prefer steps from "{names}""#)]
+async fn when_i_prefer_steps(context: &mut Context, names: Vec<String>) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().step_priority(names);
+    Ok(())
+}
+
+#[when(r#"I only use steps from "{module}""#)]
+async fn when_i_only_use_steps_from(context: &mut Context, module: String) -> anyhow::Result<()> {
+    let vocab = Vocab::builder().include_module(module).build()?;
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().vocab(vocab);
+    Ok(())
+}
+
+#[when(r#"I leave out steps matching "{pattern}""#)]
+async fn when_i_leave_out_steps(context: &mut Context, pattern: String) -> anyhow::Result<()> {
+    let vocab = Vocab::builder().exclude_pattern(&pattern)?.build()?;
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().vocab(vocab);
+    Ok(())
+}
+
 #[when(r#"I add "{args}" to the command line"#)]
 async fn when_i_add_args(context: &mut Context, args: String) -> anyhow::Result<()> {
     let sub_instance = context.fixture_mut::<SubInstance>().await;
@@ -136,6 +296,19 @@ async fn when_i_run_the_tests(context: &mut Context) -> anyhow::Result<()> {
     sub_instance.run()
 }
 
+#[then(r#"running the tests fails mentioning "{text}""#)]
+async fn running_the_tests_fails(context: &mut Context, text: String) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    match sub_instance.run() {
+        Ok(()) => panic!("Tests ran successfully"),
+        Err(e) => {
+            let msg = format!("{:#}", e);
+            assert!(msg.contains(&text), "{:?} not found in {:?}", text, msg);
+        }
+    }
+    Ok(())
+}
+
 #[then("the tests complete successfully")]
 async fn the_tests_complete_successfully(context: &mut Context) -> anyhow::Result<()> {
     let sub_instance = context.fixture_mut::<SubInstance>().await;
@@ -144,7 +317,23 @@ async fn the_tests_complete_successfully(context: &mut Context) -> anyhow::Resul
     Ok(())
 }
 
-#[then(regex, r#"there are (?P<num>\d+)/(?P<total>\d+) (?P<stat>passing|failed|skipped) (?P<what>features|rules|scenarios|steps)"#)]
+#[then("the tests fail")]
+async fn the_tests_fail(context: &mut Context) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    assert!(outcome.failed(), "Outcome passed:\n{:#?}", outcome);
+    Ok(())
+}
+
+#[then("the tests do not fail")]
+async fn the_tests_do_not_fail(context: &mut Context) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    assert!(!outcome.failed(), "Outcome failed:\n{:#?}", outcome);
+    Ok(())
+}
+
+#[then(regex, r#"there are (?P<num>\d+)/(?P<total>\d+) (?P<stat>passing|failed|skipped|pending|deselected) (?P<what>features|rules|scenarios|steps)"#)]
 async fn check_stats(
     context: &mut Context,
     num: usize,
@@ -176,6 +365,8 @@ async fn check_stats(
         "passing" => stat_row.passed,
         "failed" => stat_row.failed,
         "skipped" => stat_row.skipped,
+        "pending" => stat_row.pending,
+        "deselected" => stat_row.deselected,
         _ => panic!("Unexpected stat"),
     };
 
@@ -184,6 +375,396 @@ async fn check_stats(
     Ok(())
 }
 
+#[then(
+    regex,
+    r#"the (?P<what>features|rules|scenarios|steps) have unique keys and ids"#
+)]
+async fn unique_keys(context: &mut Context, what: String) -> anyhow::Result<()> {
+    let kind = match what.as_str() {
+        "features" => ComponentKind::Feature,
+        "rules" => ComponentKind::Rule,
+        "scenarios" => ComponentKind::Scenario,
+        "steps" => ComponentKind::Step,
+        _ => panic!("Unexpected kind"),
+    };
+
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let all = outcome.query().kind(kind).all();
+    let keys: std::collections::HashSet<_> = all.iter().map(|o| o.component().key()).collect();
+    let ids: std::collections::HashSet<_> = all.iter().map(|o| o.component().id()).collect();
+    assert_eq!(keys.len(), all.len(), "Duplicate {} keys: {:?}", what, keys);
+    assert_eq!(ids.len(), all.len(), "Duplicate {} ids", what);
+    Ok(())
+}
+
+fn assert_failed_mentioning(outcome: Arc<Outcome>, kind: ComponentKind, name: &str, text: &str) {
+    let component = outcome.query().kind(kind).named(name).one();
+    assert!(component.failed(), "{} did not fail: {}", kind, component);
+    let reason = match &component.reason {
+        Some(r) => format!("{:#}", r),
+        None => panic!("{} has no reason", kind),
+    };
+    assert!(
+        reason.contains(text),
+        "{:?} not found in {:?}",
+        text,
+        reason
+    );
+}
+
+#[then(r#"the scenario "{name}" failed mentioning "{text}""#)]
+async fn scenario_failed_mentioning(
+    context: &mut Context,
+    name: String,
+    text: String,
+) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    assert_failed_mentioning(outcome, ComponentKind::Scenario, &name, &text);
+    Ok(())
+}
+
+#[then(
+    regex,
+    r#"the (?P<kind>scenario|step) "(?P<name>.*)" was skipped because "(?P<text>.*)""#
+)]
+async fn skipped_because(
+    context: &mut Context,
+    kind: String,
+    name: String,
+    text: String,
+) -> anyhow::Result<()> {
+    let kind = match kind.as_str() {
+        "scenario" => ComponentKind::Scenario,
+        _ => ComponentKind::Step,
+    };
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let component = outcome.query().kind(kind).named(&name).one();
+    assert_eq!(component.verdict, Verdict::Skipped);
+    let cause = component.skip_cause.as_ref().expect("No skip cause");
+    assert!(
+        cause.to_string().contains(&text),
+        "{:?} not in {:?}",
+        text,
+        cause.to_string()
+    );
+    Ok(())
+}
+
+#[then(r#"the step "{name}" was skipped mentioning "{text}""#)]
+async fn step_skipped_mentioning(
+    context: &mut Context,
+    name: String,
+    text: String,
+) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let step = outcome.query().kind(ComponentKind::Step).named(&name).one();
+    assert_eq!(step.verdict, Verdict::Skipped);
+    let reason = format!("{:#}", step.reason.as_ref().expect("No reason"));
+    assert!(reason.contains(&text), "{:?} not in {:?}", text, reason);
+    Ok(())
+}
+
+#[then(r#"the step "{name}" is on line {line}"#)]
+async fn step_is_on_line(context: &mut Context, name: String, line: usize) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let found = outcome.query().kind(ComponentKind::Step).named(&name).one();
+    let step = found.component().step().unwrap();
+    assert_eq!(step.position.line, line);
+    Ok(())
+}
+
+#[then(r#"the step "{name}" failed mentioning "{text}""#)]
+async fn step_failed_mentioning(
+    context: &mut Context,
+    name: String,
+    text: String,
+) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    assert_failed_mentioning(outcome, ComponentKind::Step, &name, &text);
+    Ok(())
+}
+
+#[then(r#"the step "{name}" was matched in "{file}" with the arguments "{args}""#)]
+async fn step_matched_with(
+    context: &mut Context,
+    name: String,
+    file: String,
+    args: String,
+) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let found = outcome.query().kind(ComponentKind::Step).named(&name).one();
+    let step_match = match &found.step_match {
+        Some(m) => m,
+        None => anyhow::bail!("{:?} has no step match", name),
+    };
+    anyhow::ensure!(
+        step_match.location.path.ends_with(&file),
+        "Matched at {}",
+        step_match.location
+    );
+    let actual: Vec<_> = step_match
+        .args
+        .iter()
+        .map(|a| a.value.as_deref().unwrap_or(""))
+        .collect();
+    assert_eq!(actual.join(", "), args);
+    Ok(())
+}
+
+#[then(r#"the step "{name}" has no step match"#)]
+async fn step_has_no_match(context: &mut Context, name: String) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let found = outcome.query().kind(ComponentKind::Step).named(&name).one();
+    assert!(found.step_match.is_none(), "{:?}", found.step_match);
+    Ok(())
+}
+
+#[then(r#"the step "{name}" has {count} warnings"#)]
+async fn step_has_warnings(
+    context: &mut Context,
+    name: String,
+    count: usize,
+) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let step = outcome.query().kind(ComponentKind::Step).named(&name).one();
+    assert_eq!(step.verdict, Verdict::PassedWithWarnings, "{}", step);
+    assert_eq!(step.warnings.len(), count, "{:?}", step.warnings);
+    Ok(())
+}
+
+#[then(r#"the step "{name}" has a "{artifact}" artifact"#)]
+async fn step_has_artifact(
+    context: &mut Context,
+    name: String,
+    artifact: String,
+) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let step = outcome.query().kind(ComponentKind::Step).named(&name).one();
+    assert!(
+        step.artifacts.iter().any(|a| a.name == artifact),
+        "{:?}",
+        step.artifacts
+    );
+    Ok(())
+}
+
+#[then(r#"the step "{name}" has no artifacts"#)]
+async fn step_has_no_artifacts(context: &mut Context, name: String) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let step = outcome.query().kind(ComponentKind::Step).named(&name).one();
+    assert!(step.artifacts.is_empty(), "{:?}", step.artifacts);
+    Ok(())
+}
+
+#[then(r#"the step "{name}" has a warning mentioning "{text}""#)]
+async fn step_has_warning(context: &mut Context, name: String, text: String) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let step = outcome.query().kind(ComponentKind::Step).named(&name).one();
+    assert!(
+        step.warnings
+            .iter()
+            .any(|w| format!("{:#}", w).contains(&text)),
+        "{:?} not found in {:?}",
+        text,
+        step.warnings
+    );
+    Ok(())
+}
+
+#[then(r#"the test run has a warning mentioning "{text}""#)]
+async fn run_has_warning(context: &mut Context, text: String) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    assert!(
+        outcome
+            .warnings
+            .iter()
+            .any(|w| format!("{:#}", w).contains(&text)),
+        "{:?} not found in {:?}",
+        text,
+        outcome.warnings
+    );
+    Ok(())
+}
+
+#[then(r#"the step "{name}" passed with warnings mentioning "{text}""#)]
+async fn step_passed_with_warnings(
+    context: &mut Context,
+    name: String,
+    text: String,
+) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let step = outcome.query().kind(ComponentKind::Step).named(&name).one();
+    assert_eq!(step.verdict, Verdict::PassedWithWarnings, "{}", step);
+    let reason = format!("{:#}", step.reason.as_ref().expect("step has no reason"));
+    assert!(
+        reason.contains(&text),
+        "{:?} not found in {:?}",
+        text,
+        reason
+    );
+    Ok(())
+}
+
+#[then(r#"the feature "{name}" is owned by "{owner}""#)]
+async fn feature_owned_by(
+    context: &mut Context,
+    name: String,
+    owner: String,
+) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let feature = outcome
+        .query()
+        .kind(ComponentKind::Feature)
+        .named(&name)
+        .one();
+    assert_eq!(
+        feature.component().metadata().owner.as_deref(),
+        Some(owner.as_str())
+    );
+    Ok(())
+}
+
+#[then(r#"there are {count} scenarios tagged "{tag}""#)]
+async fn scenarios_tagged(context: &mut Context, count: usize, tag: String) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let found = outcome.query().kind(ComponentKind::Scenario).tagged(&tag);
+    assert_eq!(
+        found.count(),
+        count,
+        "Wrong number of scenarios tagged {}",
+        tag
+    );
+    Ok(())
+}
+
+#[then(r#"the only "{verdict}" scenario tagged "{tag}" is "{name}""#)]
+async fn only_scenario_tagged(
+    context: &mut Context,
+    verdict: String,
+    tag: String,
+    name: String,
+) -> anyhow::Result<()> {
+    let verdict: Verdict = verdict.parse()?;
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let scenario = outcome
+        .query()
+        .kind(ComponentKind::Scenario)
+        .tagged(&tag)
+        .verdict(verdict)
+        .one();
+    assert_eq!(scenario.component().name(), name);
+    Ok(())
+}
+
+#[then(r#"in the scenario "{name}", the step "{text}" has the verdict "{verdict}""#)]
+async fn step_in_scenario(
+    context: &mut Context,
+    name: String,
+    text: String,
+    verdict: String,
+) -> anyhow::Result<()> {
+    let verdict: Verdict = verdict.parse()?;
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let scenario = outcome
+        .query()
+        .kind(ComponentKind::Scenario)
+        .named(&name)
+        .one();
+    let step = scenario.step(&text).expect("No such step in the scenario");
+    assert_eq!(step.verdict, verdict, "{}", step);
+    Ok(())
+}
+
+#[then("there is 1 passing and 1 failed scenario, with 4 steps")]
+async fn one_passing_one_failed(context: &mut Context) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    assert_counts!(
+        outcome,
+        Scenario {
+            passed: 1,
+            failed: 1,
+        },
+        Step { total: 4 },
+    );
+    Ok(())
+}
+
+#[then(r#"the step "{name}" failed with this diff"#)]
+async fn step_failed_with_diff(context: &mut Context, name: String) -> anyhow::Result<()> {
+    let expected: Vec<_> = match &context.step().unwrap().docstring {
+        Some(text) => text.lines().map(str::trim).map(String::from).collect(),
+        None => anyhow::bail!("Expected a docstring"),
+    };
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let step = outcome.query().kind(ComponentKind::Step).named(&name).one();
+    assert!(step.failed(), "Step did not fail: {}", step);
+    let diff = match step.diff() {
+        Some(diff) => diff.to_string(),
+        None => panic!("Step has no diff: {}", step),
+    };
+    let actual: Vec<_> = diff.lines().map(str::trim).map(String::from).collect();
+    assert_eq!(actual, expected);
+    Ok(())
+}
+
+#[then(r#"the outcome as JSON has the scenario "{name}" with the verdict "{verdict}""#)]
+async fn json_outcome_has_scenario(
+    context: &mut Context,
+    name: String,
+    verdict: String,
+) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let json: serde_json::Value = serde_json::from_str(&outcome.to_json()?)?;
+
+    let mut outcomes = vec![&json];
+    let mut found = vec![];
+    while let Some(o) = outcomes.pop() {
+        if o["component"]["kind"] == "scenario" && o["component"]["name"] == name.as_str() {
+            found.push(o);
+        }
+        outcomes.extend(o["children"].as_array().into_iter().flatten());
+    }
+
+    assert_eq!(
+        found.len(),
+        1,
+        "Expected exactly one scenario named {:?}",
+        name
+    );
+    assert_eq!(found[0]["verdict"], verdict.as_str());
+    Ok(())
+}
+
+#[then(r#"the outcome of the scenario "{name}" was pruned"#)]
+async fn scenario_was_pruned(context: &mut Context, name: String) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    let outcome = sub_instance.outcome().await;
+    let found = outcome.query().kind(ComponentKind::Scenario).named(&name);
+    assert_eq!(found.count(), 0, "The scenario {:?} was kept", name);
+    Ok(())
+}
+
 #[when("I cancel the tests")]
 async fn when_i_cancel_the_tests(context: &mut Context) -> anyhow::Result<()> {
     let sub_instance = context.fixture_mut::<SubInstance>().await;
@@ -191,6 +772,13 @@ async fn when_i_cancel_the_tests(context: &mut Context) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[when("I abort the tests")]
+async fn when_i_abort_the_tests(context: &mut Context) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.abort();
+    Ok(())
+}
+
 #[then("the tests were canceled")]
 async fn the_tests_were_canceled(context: &mut Context) -> anyhow::Result<()> {
     let sub_instance = context.fixture_mut::<SubInstance>().await;
@@ -0,0 +1,120 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use zuke::reporter::Collect;
+use zuke::*;
+
+/// Several suites, run as one by a [`SuiteRunner`]
+pub struct Suites {
+    runner: SuiteRunner,
+    outcome: Option<Arc<Outcome>>,
+}
+
+#[async_trait]
+impl Fixture for Suites {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        let mut runner = SuiteRunner::new();
+        runner.cancel_method(CancelMethod::Manual);
+        Ok(Self {
+            runner,
+            outcome: None,
+        })
+    }
+}
+
+impl Suites {
+    fn outcome(&self) -> anyhow::Result<&Arc<Outcome>> {
+        self.outcome
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("The suites haven't run"))
+    }
+}
+
+#[when(r#"I add the suite "{name}" with the feature source"#)]
+async fn when_i_add_a_suite(context: &mut Context, name: String) -> anyhow::Result<()> {
+    let filename = format!("<{}>", name);
+    add_suite(context, name, filename).await
+}
+
+#[when(r#"I add the suite "{name}" with the feature source "{filename}""#)]
+async fn when_i_add_a_suite_from(
+    context: &mut Context,
+    name: String,
+    filename: String,
+) -> anyhow::Result<()> {
+    add_suite(context, name, filename).await
+}
+
+async fn add_suite(context: &mut Context, name: String, filename: String) -> anyhow::Result<()> {
+    let source = match &context.step().unwrap().docstring {
+        Some(s) => s.clone(),
+        None => anyhow::bail!("Expected a docstring"),
+    };
+    let mut builder = ZukeBuilder::new();
+    builder.feature_source(filename, source);
+
+    context.use_fixture::<Suites>().await?;
+    let suites = context.fixture_mut::<Suites>().await;
+    suites.runner.suite(name, builder);
+    Ok(())
+}
+
+#[when("I run the suites")]
+async fn when_i_run_the_suites(context: &mut Context) -> anyhow::Result<()> {
+    let suites = context.fixture_mut::<Suites>().await;
+    let (collect, out) = Collect::new();
+    suites.runner.reporter(collect);
+    let _ = suites.runner.run_from(["suites"]).await;
+    suites.outcome = Some(out.await?);
+    Ok(())
+}
+
+#[then("the combined run has {count} features")]
+async fn then_combined_features(context: &mut Context, count: usize) -> anyhow::Result<()> {
+    let suites = context.fixture::<Suites>().await;
+    let outcome = suites.outcome()?;
+    assert_eq!(outcome.kind(), ComponentKind::Global);
+    assert_eq!(outcome.children.len(), count);
+    Ok(())
+}
+
+#[then("the combined run has {passed} passing and {failed} failing scenarios")]
+async fn then_combined_scenarios(
+    context: &mut Context,
+    passed: usize,
+    failed: usize,
+) -> anyhow::Result<()> {
+    let suites = context.fixture::<Suites>().await;
+    let stats = suites.outcome()?.stats();
+    let stat = stats
+        .get(&ComponentKind::Scenario)
+        .cloned()
+        .unwrap_or_default();
+    assert_eq!((stat.passed, stat.failed), (passed, failed));
+    Ok(())
+}
+
+#[then("the combined run's scenarios have unique keys")]
+async fn then_combined_unique_keys(context: &mut Context) -> anyhow::Result<()> {
+    let suites = context.fixture::<Suites>().await;
+    let keys = suites
+        .outcome()?
+        .query()
+        .kind(ComponentKind::Scenario)
+        .all()
+        .iter()
+        .map(|o| o.component.key())
+        .collect::<Vec<_>>();
+    let unique = keys.iter().collect::<std::collections::HashSet<_>>();
+    assert_eq!(unique.len(), keys.len(), "Duplicate keys in {:?}", keys);
+    Ok(())
+}
+
+#[then(r#"the combined run's verdict is "{verdict}""#)]
+async fn then_combined_verdict(context: &mut Context, verdict: String) -> anyhow::Result<()> {
+    let verdict: Verdict = verdict.parse()?;
+    let suites = context.fixture::<Suites>().await;
+    assert_eq!(suites.outcome()?.verdict, verdict);
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use crate::sub_instance::SubInstance;
+use async_std::task;
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use zuke::{before_scenario, when, Context, Fixture, MockClock, Scope};
+
+lazy_static! {
+    /// How many scenarios are using each counter
+    static ref COUNTERS: Mutex<HashMap<String, usize>> = Mutex::new(HashMap::new());
+}
+
+/// A tag plugin that skips scenarios tagged `@ignore`
+pub struct Ignore;
+
+#[async_trait]
+impl Fixture for Ignore {
+    const SCOPE: Scope = Scope::Global;
+
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Self)
+    }
+
+    async fn before(&self, context: &mut Context) -> anyhow::Result<()> {
+        if context.component().step().is_none() && context.tags().any(|t| t == "ignore") {
+            zuke::skip!();
+        }
+        Ok(())
+    }
+}
+
+#[when("I turn off the default tags")]
+async fn when_i_turn_off_default_tags(context: &mut Context) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().with_default_tags(false);
+    Ok(())
+}
+
+#[when("I add the ignore tag plugin")]
+async fn when_i_add_the_ignore_plugin(context: &mut Context) -> anyhow::Result<()> {
+    let sub_instance = context.fixture_mut::<SubInstance>().await;
+    sub_instance.builder().tag_plugin::<Ignore, _>("ignore");
+    Ok(())
+}
+
+#[when(r#"I use the counter "{name}" for a moment"#)]
+async fn when_i_use_the_counter(name: String) -> anyhow::Result<()> {
+    use_counter(name, 1).await
+}
+
+#[when(r#"I use the counter "{name}" for a moment, with at most {max} users"#)]
+async fn when_i_share_the_counter(name: String, max: usize) -> anyhow::Result<()> {
+    use_counter(name, max).await
+}
+
+async fn use_counter(name: String, max: usize) -> anyhow::Result<()> {
+    let users = {
+        let mut counters = COUNTERS.lock().unwrap();
+        let users = counters.entry(name.clone()).or_default();
+        *users += 1;
+        *users
+    };
+
+    task::sleep(Duration::from_millis(100)).await;
+    *COUNTERS.lock().unwrap().get_mut(&name).unwrap() -= 1;
+
+    if users > max {
+        anyhow::bail!("The counter {:?} is already in use", name);
+    }
+    Ok(())
+}
+
+/// Put the scenario's mock clock an hour ahead, as a scenario with no steps might
+#[before_scenario("@an-hour-on-the-mock-clock")]
+async fn an_hour_on_the_mock_clock(context: &mut Context) -> anyhow::Result<()> {
+    context.use_fixture::<MockClock>().await?;
+    let clock = context.fixture::<MockClock>().await;
+    clock.advance(chrono::Duration::hours(1));
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use anyhow::Context as _;
+use async_trait::async_trait;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use zuke::testkit::StepTester;
+use zuke::{before_feature, then, when, Context, Fixture, Verdict};
+
+/// How many times a feature named as the test kit names its own has started
+static STEP_TEST_FEATURES: AtomicUsize = AtomicUsize::new(0);
+
+#[before_feature(feature = "Step test")]
+async fn count_step_test_features(_context: &mut Context) {
+    STEP_TEST_FEATURES.fetch_add(1, Ordering::SeqCst);
+}
+
+/// A fixture to fake in tests of the test kit
+struct Widgets(u32);
+
+#[async_trait]
+impl Fixture for Widgets {
+    async fn setup(_context: &mut Context) -> anyhow::Result<Self> {
+        Ok(Widgets(0))
+    }
+}
+
+#[then("there are {count} widgets")]
+async fn there_are_widgets(context: &mut Context, count: u32) -> anyhow::Result<()> {
+    context.use_fixture::<Widgets>().await?;
+    assert_eq!(context.fixture::<Widgets>().await.0, count);
+    Ok(())
+}
+
+async fn test_step(context: &mut Context, tester: StepTester, text: &str) -> anyhow::Result<()> {
+    let before = STEP_TEST_FEATURES.load(Ordering::SeqCst);
+    let outcome = tester.step(text).run().await?;
+    let step = outcome.step(text).context("The step didn't run")?;
+    context.remember("tested verdict", step.verdict.to_string());
+    // Other tests of the test kit may run at the same time, so this is at least 1 if ours ran
+    let hooked = STEP_TEST_FEATURES.load(Ordering::SeqCst) - before;
+    context.remember("feature hooks", hooked.to_string());
+    Ok(())
+}
+
+#[when(r#"I test the step "{text}" on its own"#)]
+async fn test_step_on_its_own(context: &mut Context, text: String) -> anyhow::Result<()> {
+    test_step(context, StepTester::new(), &text).await
+}
+
+#[when(r#"I test the step "{text}" with {count} fake widgets"#)]
+async fn test_step_with_widgets(
+    context: &mut Context,
+    text: String,
+    count: u32,
+) -> anyhow::Result<()> {
+    let tester = StepTester::new().fixture(Widgets(count));
+    test_step(context, tester, &text).await
+}
+
+#[then(r#"the tested step has the verdict "{verdict}""#)]
+fn tested_step_verdict(context: &mut Context, verdict: String) -> anyhow::Result<()> {
+    let verdict: Verdict = verdict.parse()?;
+    assert_eq!(
+        context.recall("tested verdict"),
+        Some(verdict.to_string().as_str())
+    );
+    Ok(())
+}
+
+#[then("the test kit ran the feature hooks")]
+fn test_kit_ran_feature_hooks(context: &mut Context) -> anyhow::Result<()> {
+    let hooked: usize = context
+        .recall("feature hooks")
+        .context("No step tested")?
+        .parse()?;
+    assert!(hooked > 0, "The feature hooks didn't run");
+    Ok(())
+}
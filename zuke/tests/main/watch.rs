@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zuke::fixtures::TempDir;
+use zuke::harness::Watcher;
+use zuke::*;
+
+/// A watcher on a scenario's temporary directory, for features and for Rust sources
+struct WatchedDir {
+    dir: PathBuf,
+    watcher: Watcher,
+}
+
+#[async_trait]
+impl Fixture for WatchedDir {
+    const SCOPE: Scope = Scope::Scenario;
+
+    async fn setup(context: &mut Context) -> anyhow::Result<Self> {
+        context.use_fixture::<TempDir>().await?;
+        let dir = context.fixture::<TempDir>().await.path().to_path_buf();
+        let watcher = Watcher::new(vec![dir.clone()], Some(dir.clone()));
+        Ok(Self { dir, watcher })
+    }
+}
+
+/// Add a line to a file in the directory, creating it if needed. The file's size changes, so
+/// the change is seen even within the resolution of its modification time.
+fn change_file(dir: &Path, name: &str) -> anyhow::Result<()> {
+    let path = dir.join(name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "# changed")?;
+    Ok(())
+}
+
+#[given(r#"a watched directory with the file "{name}""#)]
+async fn a_watched_directory(context: &mut Context, name: String) -> anyhow::Result<()> {
+    context.use_fixture::<TempDir>().await?;
+    let dir = context.fixture::<TempDir>().await.path().to_path_buf();
+    change_file(&dir, &name)?;
+    context.use_fixture::<WatchedDir>().await
+}
+
+#[when(r#"I change the file "{name}""#)]
+async fn i_change_the_file(context: &mut Context, name: String) -> anyhow::Result<()> {
+    let watched = context.fixture::<WatchedDir>().await;
+    change_file(&watched.dir, &name)
+}
+
+#[then(r#"the watcher sees changes to "{names}""#)]
+async fn the_watcher_sees_changes(context: &mut Context, names: String) {
+    let watched = context.fixture_mut::<WatchedDir>().await;
+    let expected: Vec<_> = names.split(", ").map(|n| watched.dir.join(n)).collect();
+    let changes = watched.watcher.changes();
+    assert_eq!(changes.features, expected);
+    assert!(!changes.rust, "{:?}", changes);
+}
+
+#[then("the watcher sees no changes")]
+async fn the_watcher_sees_no_changes(context: &mut Context) {
+    let watched = context.fixture_mut::<WatchedDir>().await;
+    let changes = watched.watcher.changes();
+    assert_eq!(changes, Default::default());
+}
+
+#[then("the watcher sees a change to Rust sources, but no features")]
+async fn the_watcher_sees_rust_changes(context: &mut Context) {
+    let watched = context.fixture_mut::<WatchedDir>().await;
+    let changes = watched.watcher.changes();
+    assert!(changes.rust, "{:?}", changes);
+    assert!(changes.features.is_empty(), "{:?}", changes);
+}